@@ -0,0 +1,43 @@
+// Compares the single-pass SNBT parser (`Tag::parse`) with the parser that tokenizes the
+// whole input first (`Tag::parse_tokenized`).
+// Run with `cargo bench --bench snbt`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rustnbt::{snbt::Dialect, tag::Tag};
+use std::fmt::Write;
+
+/// Builds SNBT shaped like a structure file with `block_count` blocks.
+fn structure(block_count: usize) -> String {
+	let mut snbt = String::from("{\n\tDataVersion: 3953,\n\tsize: [I; 16, 16, 16],\n\tpalette: [\n");
+	for i in 0..16 {
+		writeln!(snbt, "\t\t{{Name: \"minecraft:block_{i}\", Properties: {{facing: \"north\", waterlogged: \"false\"}}}},").unwrap();
+	}
+	snbt.push_str("\t],\n\tblocks: [\n");
+	for i in 0..block_count {
+		writeln!(
+			snbt,
+			"\t\t{{pos: [I; {}, {}, {}], state: {}, nbt: {{id: 'minecraft:chest', Items: [{{Slot: {}b, id: \"minecraft:stone\", count: 64}}], Lock: \"\", Weight: {:?}d}}}},",
+			i % 16, (i / 16) % 16, i / 256, i % 16, i % 27, i as f64 / 7.0,
+		).unwrap();
+	}
+	snbt.push_str("\t],\n\tentities: []\n}\n");
+	snbt
+}
+
+fn parse(c: &mut Criterion) {
+	let mut group = c.benchmark_group("snbt_parse");
+	for block_count in [100, 10_000] {
+		let source = structure(block_count);
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::new("single_pass", block_count), &source, |b, source| {
+			b.iter(|| Tag::parse_dialect(source, Dialect::Modern).unwrap())
+		});
+		group.bench_with_input(BenchmarkId::new("tokenized", block_count), &source, |b, source| {
+			b.iter(|| Tag::parse_tokenized(source, Dialect::Modern).unwrap())
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
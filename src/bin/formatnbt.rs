@@ -1,11 +1,841 @@
-#![allow(unused)]
+// formatnbt: inspect, convert and edit NBT files from the command line.
+// Build with `cargo build --features cli`.
+//
+// Every command reads a file, or stdin when the input is `-` or omitted.
+// Binary NBT and SNBT are both accepted, and gzip, zlib and LZ4 compression is detected automatically.
+// Like diff(1), the exit code is 1 when `diff` finds differences or `validate` finds invalid files,
+// and 2 when a command fails.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use rustnbt::{
-	*,
-	tag::*,
+	Map,
+	NbtError,
+	compression::{self, Compression},
+	diff::{diff, DiffOptions},
+	format::{self, Indentation, SpaceCount},
+	io::{read_named_tag, write_named_tag},
+	path::{PathError, TagPath},
+	snbt::{Dialect, ParseError},
+	codegen,
+	schema::{Schema, SchemaError},
+	tag::{ListTag, Tag, TagID},
 };
+use std::io::{IsTerminal, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "formatnbt", version, about = "Inspect, convert and edit Minecraft NBT files.")]
+struct Cli {
+	/// Parse SNBT with the grammar used before Minecraft 1.21.5.
+	#[arg(long, global = true)]
+	legacy: bool,
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Print a file as SNBT.
+	Print {
+		/// The file to read, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+		#[command(flatten)]
+		style: Style,
+	},
+	/// Convert SNBT into binary NBT.
+	Compile {
+		/// The SNBT file to read, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+		/// Where to write the binary NBT. Defaults to stdout.
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+		/// `gzip`, `zlib`, `lz4` or `none`.
+		#[arg(short, long, default_value_t = Compression::Gzip)]
+		compression: Compression,
+		/// The name of the root tag.
+		#[arg(long, default_value = "")]
+		name: String,
+	},
+	/// Print the tag at a path, such as `Data.Player.Inventory[0].id`.
+	Get {
+		path: TagPath,
+		/// The file to read, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+		#[command(flatten)]
+		style: Style,
+	},
+	/// Replace the tag at a path with an SNBT value.
+	/// The file keeps its format and compression, and is rewritten in place unless `--output` is given.
+	Set {
+		path: TagPath,
+		/// The new value as SNBT, such as `64b` or `{id: "minecraft:stone"}`.
+		value: String,
+		/// The file to edit, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+		/// Where to write the result. Defaults to the input file, or stdout for stdin.
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+	},
+	/// Check that files contain well-formed NBT or SNBT.
+	Validate {
+		/// The files to check, or `-` for stdin.
+		#[arg(default_value = "-")]
+		inputs: Vec<PathBuf>,
+	},
+	/// Summarize the contents of a file.
+	Stats {
+		/// The file to read, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+	},
+	/// Show the differences between two files as a unified diff annotated with tag paths.
+	Diff {
+		before: PathBuf,
+		after: PathBuf,
+		/// Don't report compounds whose keys are in a different order.
+		#[arg(long)]
+		ignore_order: bool,
+		/// Skip the tag at this path. Can be given more than once.
+		#[arg(long = "ignore", value_name = "PATH")]
+		ignore_paths: Vec<TagPath>,
+		/// Treat Float and Double values that differ by no more than this as equal.
+		#[arg(long, default_value_t = 0.0)]
+		epsilon: f64,
+		/// When to color the output.
+		#[arg(long, value_enum, default_value_t = Color::Auto)]
+		color: Color,
+	},
+	/// Infer a schema from sample files, and print it as SNBT or as Rust types.
+	Infer {
+		/// The files to read. Directories are searched recursively, and files that can't be read are skipped.
+		#[arg(required = true)]
+		inputs: Vec<PathBuf>,
+		/// Print Rust types with EncodeNbt and DecodeNbt implementations instead, naming the root type NAME.
+		#[arg(long, value_name = "NAME")]
+		rust: Option<String>,
+		#[command(flatten)]
+		style: Style,
+	},
+	/// Generate Rust types with EncodeNbt and DecodeNbt implementations from a sample file or a schema.
+	Codegen {
+		/// The file to read, or `-` for stdin.
+		#[arg(default_value = "-")]
+		input: PathBuf,
+		/// Read the input as a schema, in the format printed by `infer`, instead of as a sample.
+		#[arg(long)]
+		schema: bool,
+		/// The name of the root type. Defaults to the name of the input file.
+		#[arg(long)]
+		name: Option<String>,
+	},
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Color {
+	/// Color the output when stdout is a terminal and `NO_COLOR` isn't set.
+	Auto,
+	Always,
+	Never,
+}
+
+impl Color {
+	fn enabled(self) -> bool {
+		match self {
+			Color::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+			Color::Always => true,
+			Color::Never => false,
+		}
+	}
+}
+
+#[derive(Args)]
+struct Style {
+	/// Print everything on a single line.
+	#[arg(long)]
+	compact: bool,
+	/// Indent with this many spaces instead of tabs.
+	#[arg(long, value_name = "SPACES")]
+	indent: Option<usize>,
+}
+
+impl Style {
+	fn format(&self, tag: &Tag) -> String {
+		let indentation = match self.indent {
+			Some(count) => Indentation::spaces(SpaceCount::Exact(count)),
+			None => Indentation::tabs(),
+		};
+		let mut text = String::new();
+		format::write_tag(&mut text, tag, self.compact, indentation).expect("writing to a String can't fail");
+		text
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+enum CliError {
+	#[error("{input}: {source}")]
+	Io { input: String, source: std::io::Error },
+	#[error("{input}: {source}")]
+	Nbt { input: String, source: NbtError },
+	#[error("{input}: {source}")]
+	Snbt { input: String, source: ParseError },
+	#[error("{input}: {source}")]
+	Schema { input: String, source: SchemaError },
+	#[error("{input}: {message}")]
+	Invalid { input: String, message: String },
+	#[error("{0}")]
+	Path(#[from] PathError),
+	#[error("Invalid value. {0}")]
+	Value(ParseError),
+	#[error("None of the inputs could be read.")]
+	NoSamples,
+}
+
+/// How a file was stored, so that it can be written back the same way.
+#[derive(Clone, Copy)]
+enum Format {
+	Binary,
+	Snbt,
+}
+
+/// A file that was read by one of the commands.
+struct Document {
+	name: String,
+	tag: Tag,
+	format: Format,
+	compression: Compression,
+	/// The size of the file as stored.
+	stored_size: usize,
+	/// The size of the file after decompression.
+	size: usize,
+}
+
+fn label(input: &Path) -> String {
+	if is_stdin(input) {
+		"<stdin>".to_owned()
+	} else {
+		input.display().to_string()
+	}
+}
+
+fn is_stdin(input: &Path) -> bool {
+	input.as_os_str() == "-"
+}
+
+fn read_input(input: &Path) -> Result<Vec<u8>, CliError> {
+	let mut data = Vec::new();
+	let result = if is_stdin(input) {
+		std::io::stdin().lock().read_to_end(&mut data).map(|_| ())
+	} else {
+		std::fs::File::open(input).and_then(|mut file| file.read_to_end(&mut data)).map(|_| ())
+	};
+	result.map(|_| data).map_err(|source| CliError::Io { input: label(input), source })
+}
+
+fn write_output(output: &Path, data: &[u8]) -> Result<(), CliError> {
+	let result = if is_stdin(output) {
+		std::io::stdout().lock().write_all(data)
+	} else {
+		std::fs::write(output, data)
+	};
+	result.map_err(|source| CliError::Io { input: label(output), source })
+}
+
+fn load(input: &Path, dialect: Dialect) -> Result<Document, CliError> {
+	let stored = read_input(input)?;
+	let (compression, data) = compression::decompress(&stored)
+		.map_err(|source| CliError::Io { input: label(input), source })?;
+	// Binary NBT starts with the ID of the root tag, which is never a printable character.
+	if matches!(data.first(), Some(1..=12)) {
+		let mut reader = data.as_slice();
+		let (name, tag) = read_named_tag(&mut reader)
+			.map_err(|source| CliError::Nbt { input: label(input), source })?;
+		if !reader.is_empty() {
+			return Err(CliError::Invalid {
+				input: label(input),
+				message: format!("Found {} unexpected byte(s) after the root tag.", reader.len()),
+			});
+		}
+		return Ok(Document { name, tag, format: Format::Binary, compression, stored_size: stored.len(), size: data.len() });
+	}
+	let source = String::from_utf8(data).map_err(|_| CliError::Invalid {
+		input: label(input),
+		message: "The file is neither binary NBT nor UTF-8 SNBT.".to_owned(),
+	})?;
+	let tag = Tag::parse_dialect(&source, dialect)
+		.map_err(|source| CliError::Snbt { input: label(input), source })?;
+	Ok(Document { name: String::new(), tag, format: Format::Snbt, compression, stored_size: stored.len(), size: source.len() })
+}
+
+/// Lists the files in `inputs`, searching directories recursively in a stable order.
+fn collect_files(inputs: Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<(), CliError> {
+	for input in inputs {
+		if input.is_dir() {
+			let mut entries = std::fs::read_dir(&input)
+				.and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>())
+				.map_err(|source| CliError::Io { input: label(&input), source })?;
+			entries.sort();
+			collect_files(entries, files)?;
+		} else {
+			files.push(input);
+		}
+	}
+	Ok(())
+}
+
+fn save(document: &Document, output: &Path) -> Result<(), CliError> {
+	let data = match document.format {
+		Format::Binary => {
+			let mut data = Vec::new();
+			write_named_tag(&mut data, &document.tag, &document.name)
+				.map_err(|source| CliError::Nbt { input: label(output), source })?;
+			data
+		}
+		Format::Snbt => format!("{}\n", document.tag).into_bytes(),
+	};
+	let data = document.compression.compress(&data)
+		.map_err(|source| CliError::Io { input: label(output), source })?;
+	write_output(output, &data)
+}
+
+fn run(cli: Cli) -> Result<ExitCode, CliError> {
+	let dialect = if cli.legacy { Dialect::Legacy } else { Dialect::Modern };
+	match cli.command {
+		Command::Print { input, style } => {
+			let document = load(&input, dialect)?;
+			println!("{}", style.format(&document.tag));
+		}
+		Command::Compile { input, output, compression, name } => {
+			let source = read_input(&input)?;
+			let source = String::from_utf8(source).map_err(|_| CliError::Invalid {
+				input: label(&input),
+				message: "SNBT must be UTF-8.".to_owned(),
+			})?;
+			let tag = Tag::parse_dialect(&source, dialect)
+				.map_err(|source| CliError::Snbt { input: label(&input), source })?;
+			let document = Document { name, tag, format: Format::Binary, compression, stored_size: 0, size: 0 };
+			save(&document, output.as_deref().unwrap_or(Path::new("-")))?;
+		}
+		Command::Get { path, input, style } => {
+			let document = load(&input, dialect)?;
+			println!("{}", style.format(&path.get(&document.tag)?));
+		}
+		Command::Set { path, value, input, output } => {
+			let value = Tag::parse_dialect(&value, dialect).map_err(CliError::Value)?;
+			let mut document = load(&input, dialect)?;
+			path.set(&mut document.tag, value)?;
+			save(&document, output.as_deref().unwrap_or(&input))?;
+		}
+		Command::Validate { inputs } => {
+			let mut valid = true;
+			for input in inputs {
+				match load(&input, dialect) {
+					Ok(document) => println!("{}: ok ({})", label(&input), describe(&document)),
+					Err(error) => {
+						eprintln!("error: {error}");
+						valid = false;
+					}
+				}
+			}
+			if !valid {
+				return Ok(ExitCode::FAILURE);
+			}
+		}
+		Command::Stats { input } => {
+			let document = load(&input, dialect)?;
+			print!("{}", Stats::of(&document.tag).report(&label(&input), &document));
+		}
+		Command::Diff { before, after, ignore_order, ignore_paths, epsilon, color } => {
+			let options = DiffOptions { ignore_order, ignore_paths, epsilon };
+			let (before_tag, after_tag) = (load(&before, dialect)?.tag, load(&after, dialect)?.tag);
+			if !diff(&before_tag, &after_tag, &options).is_empty() {
+				print!("{}", render_diff(&label(&before), &label(&after), &before_tag, &after_tag, &options, color.enabled()));
+				return Ok(ExitCode::from(1));
+			}
+		}
+		Command::Infer { inputs, rust, style } => {
+			let mut files = Vec::new();
+			collect_files(inputs, &mut files)?;
+			let mut samples = Vec::new();
+			for file in files {
+				match load(&file, dialect) {
+					Ok(document) => samples.push(document.tag),
+					Err(error) => eprintln!("warning: skipping {error}"),
+				}
+			}
+			if samples.is_empty() {
+				return Err(CliError::NoSamples);
+			}
+			let schema = Schema::infer(&samples);
+			match rust {
+				Some(name) => print!("{}", codegen::rust_source(&schema, &name)),
+				None => println!("{}", style.format(&schema.to_tag())),
+			}
+		}
+		Command::Codegen { input, schema, name } => {
+			let document = load(&input, dialect)?;
+			let name = name
+				.or_else(|| (!is_stdin(&input)).then(|| input.file_stem()).flatten().map(|stem| stem.to_string_lossy().into_owned()))
+				.unwrap_or_else(|| "Root".to_owned());
+			let source = if schema {
+				let schema = Schema::from_tag(&document.tag)
+					.map_err(|source| CliError::Schema { input: label(&input), source })?;
+				codegen::rust_source(&schema, &name)
+			} else {
+				codegen::rust_source_for_sample(&document.tag, &name)
+			};
+			print!("{source}");
+		}
+	}
+	Ok(ExitCode::SUCCESS)
+}
+
+/// A line of pretty-printed SNBT, with the path of the tag that it belongs to.
+struct Line {
+	text: String,
+	path: TagPath,
+}
+
+/// Pretty-prints `tag` like `print` does, keeping track of the tag on each line.
+fn render_lines(tag: &Tag) -> Vec<Line> {
+	let mut lines = Vec::new();
+	render_tag(&mut lines, tag, &TagPath::root(), 0, String::new(), "");
+	lines
+}
+
+/// Renders `tag` at `depth`, starting its first line with `prefix`, such as `key : `,
+/// and ending its last line with `suffix`.
+fn render_tag(lines: &mut Vec<Line>, tag: &Tag, path: &TagPath, depth: u32, prefix: String, suffix: &str) {
+	let indentation = Indentation::tabs().level(depth);
+	let separator = |index: usize, len: usize| if index + 1 == len { "" } else { "," };
+	match tag {
+		Tag::Compound(map) if !map.is_empty() => {
+			lines.push(Line { text: format!("{indentation}{prefix}{{"), path: path.clone() });
+			for (index, (key, value)) in map.iter().enumerate() {
+				let mut prefix = String::new();
+				// Writing to a String can't fail.
+				let _ = format::write_identifier(&mut prefix, key);
+				render_tag(lines, value, &path.key(key.as_str()), depth + 1, prefix + " : ", separator(index, map.len()));
+			}
+			lines.push(Line { text: format!("{indentation}}}{suffix}"), path: path.clone() });
+		}
+		Tag::List(list) if !list.is_empty() => {
+			lines.push(Line { text: format!("{indentation}{prefix}["), path: path.clone() });
+			for index in 0..list.len() {
+				let value = list.get(index).expect("index is below the length of the list");
+				render_tag(lines, &value, &path.index(index as isize), depth + 1, String::new(), separator(index, list.len()));
+			}
+			lines.push(Line { text: format!("{indentation}]{suffix}"), path: path.clone() });
+		}
+		// Everything else is printed whole, which puts each element of an array on its own line.
+		tag => {
+			let mut text = format!("{indentation}{prefix}");
+			let _ = format::write_tag(&mut text, tag, false, indentation);
+			text += suffix;
+			lines.extend(text.lines().map(|line| Line { text: line.to_owned(), path: path.clone() }));
+		}
+	}
+}
+
+/// Returns `after` with the differences that `options` ignores replaced by the parts of `before`,
+/// so that comparing the lines of `before` and the result only shows the changes that [diff] reports.
+fn align(before: &Tag, after: &Tag, options: &DiffOptions, path: &TagPath) -> Tag {
+	let ignored = |path: &TagPath| options.ignore_paths.contains(path);
+	if ignored(path) {
+		return before.clone();
+	}
+	// Ignored elements of lists and arrays are taken from `before`, and dropped if only `after` has them.
+	// Those can only be at the end, so dropping them doesn't move any other elements.
+	let elements = |before_len: usize, after_len: usize, take: &mut dyn FnMut(bool, usize)| {
+		for index in 0..before_len.max(after_len) {
+			if ignored(&path.index(index as isize)) {
+				if index < before_len {
+					take(true, index);
+				}
+			} else if index < after_len {
+				take(false, index);
+			}
+		}
+	};
+	macro_rules! align_array {
+		($before:expr, $after:expr, $wrap:path) => {{
+			let mut array = Vec::new();
+			elements($before.len(), $after.len(), &mut |keep_before, index| {
+				array.push(if keep_before { $before[index] } else { $after[index] });
+			});
+			$wrap(array)
+		}};
+	}
+	match (before, after) {
+		(Tag::Float(a), Tag::Float(b)) if (*a as f64 - *b as f64).abs() <= options.epsilon => before.clone(),
+		(Tag::Double(a), Tag::Double(b)) if (a - b).abs() <= options.epsilon => before.clone(),
+		(Tag::ByteArray(before), Tag::ByteArray(after)) => align_array!(before, after, Tag::ByteArray),
+		(Tag::IntArray(before), Tag::IntArray(after)) => align_array!(before, after, Tag::IntArray),
+		(Tag::LongArray(before), Tag::LongArray(after)) => align_array!(before, after, Tag::LongArray),
+		(Tag::List(before_list), Tag::List(after_list))
+			if before_list.id() == after_list.id() || before_list.is_empty() || after_list.is_empty() =>
+		{
+			let mut tags = Vec::new();
+			elements(before_list.len(), after_list.len(), &mut |keep_before, index| {
+				let (before, after) = (before_list.get(index), after_list.get(index));
+				tags.push(match (keep_before, before, after) {
+					(true, Some(before), _) => before,
+					(false, Some(before), Some(after)) => align(&before, &after, options, &path.index(index as isize)),
+					(_, _, after) => after.expect("elements only takes indices that after has"),
+				});
+			});
+			Tag::List(ListTag::from_tags(tags).unwrap_or_else(ListTag::from_mixed))
+		}
+		(Tag::Compound(before), Tag::Compound(after)) => {
+			let kept = |key: &String| ignored(&path.key(key.as_str()));
+			// Ignored keys that were added are dropped, and ignored keys that were removed are put back.
+			let mut keys: Vec<&String> = if options.ignore_order {
+				before.keys()
+					.filter(|key| after.contains_key(key.as_str()) || kept(key))
+					.chain(after.keys().filter(|key| !before.contains_key(key.as_str()) && !kept(key)))
+					.collect()
+			} else {
+				after.keys().filter(|key| before.contains_key(key.as_str()) || !kept(key)).collect()
+			};
+			if !options.ignore_order {
+				let removed = before.keys().enumerate().filter(|(_, key)| !after.contains_key(key.as_str()) && kept(key));
+				for (position, key) in removed.collect::<Vec<_>>() {
+					// Put the key back after the key that came before it.
+					let at = before.keys().take(position).rev()
+						.find_map(|previous| keys.iter().position(|&key| key == previous))
+						.map_or(0, |index| index + 1);
+					keys.insert(at, key);
+				}
+			}
+			let mut map = Map::new();
+			for key in keys {
+				let value = match (before.get(key.as_str()), after.get(key.as_str())) {
+					(Some(before), Some(after)) => align(before, after, options, &path.key(key.as_str())),
+					(Some(value), None) | (None, Some(value)) => value.clone(),
+					(None, None) => unreachable!("keys only has keys from one of the compounds"),
+				};
+				map.insert(key.clone(), value);
+			}
+			Tag::Compound(map)
+		}
+		_ => after.clone(),
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Edit {
+	/// A line that both files have, by its index in each.
+	Keep(usize, usize),
+	/// A line of the first file that was removed.
+	Delete(usize),
+	/// A line of the second file that was inserted.
+	Insert(usize),
+}
+
+/// The shortest list of edits that turns `old` into `new`, using the linear-space variant of Myers' algorithm,
+/// so that large files that are very different don't need a trace of every step.
+fn edit_script(old: &[Line], new: &[Line]) -> Vec<Edit> {
+	let mut edits = Vec::new();
+	edit_range(old, new, 0..old.len(), 0..new.len(), &mut edits);
+	// Within each run of changes, deletions come before insertions, as in other diff tools.
+	for run in edits.split_mut(|edit| matches!(edit, Edit::Keep(..))) {
+		run.sort_by_key(|edit| matches!(edit, Edit::Insert(_)));
+	}
+	edits
+}
+
+/// Adds the edits that turn `old[a]` into `new[b]`, splitting the ranges at a point on a shortest path until one side is empty.
+fn edit_range(old: &[Line], new: &[Line], mut a: Range<usize>, mut b: Range<usize>, edits: &mut Vec<Edit>) {
+	while !a.is_empty() && !b.is_empty() && old[a.start].text == new[b.start].text {
+		edits.push(Edit::Keep(a.start, b.start));
+		a.start += 1;
+		b.start += 1;
+	}
+	let mut suffix = 0;
+	while suffix < a.len() && suffix < b.len() && old[a.end - 1 - suffix].text == new[b.end - 1 - suffix].text {
+		suffix += 1;
+	}
+	let (a_end, b_end) = (a.end - suffix, b.end - suffix);
+	if a.start == a_end {
+		edits.extend((b.start..b_end).map(Edit::Insert));
+	} else if b.start == b_end {
+		edits.extend((a.start..a_end).map(Edit::Delete));
+	} else {
+		let (x, y) = middle_snake(&old[a.start..a_end], &new[b.start..b_end]);
+		edit_range(old, new, a.start..a.start + x, b.start..b.start + y, edits);
+		edit_range(old, new, a.start + x..a_end, b.start + y..b_end, edits);
+	}
+	edits.extend((0..suffix).map(|index| Edit::Keep(a_end + index, b_end + index)));
+}
+
+/// How many steps [middle_snake] searches from each end before giving up, which bounds the time spent on files that are very different.
+const MAX_STEPS: isize = 4096;
+
+/// A point where a shortest path from the start of both sides to their end can be split,
+/// found by searching forwards from the start and backwards from the end until the searches meet.
+/// The sides must differ in their first and last lines.
+fn middle_snake(old: &[Line], new: &[Line]) -> (usize, usize) {
+	let (n, m) = (old.len() as isize, new.len() as isize);
+	let max_d = (n + m + 1) / 2;
+	let offset = max_d + 1;
+	// The furthest x reached on each diagonal, counting from the start forwards and from the end backwards, or -1.
+	let mut forward = vec![-1isize; 2 * offset as usize + 1];
+	let mut backward = forward.clone();
+	forward[offset as usize + 1] = 0;
+	backward[offset as usize + 1] = 0;
+	let delta = n - m;
+	// Whether the searches meet during a forward step.
+	let odd = delta % 2 != 0;
+	// How many diagonals at each end of the range have run off the edges, and are skipped.
+	let (mut forward_start, mut forward_end, mut backward_start, mut backward_end) = (0, 0, 0, 0);
+	let furthest = |v: &[isize], k: isize, d: isize| {
+		let at = |k: isize| v[(offset + k) as usize];
+		if k == -d || (k != d && at(k - 1) < at(k + 1)) { at(k + 1) } else { at(k - 1) + 1 }
+	};
+	for d in 0..=max_d.min(MAX_STEPS) {
+		for k in (-d + forward_start..=d - forward_end).step_by(2) {
+			let mut x = furthest(&forward, k, d);
+			let mut y = x - k;
+			while x < n && y < m && old[x as usize].text == new[y as usize].text {
+				x += 1;
+				y += 1;
+			}
+			forward[(offset + k) as usize] = x;
+			if x > n {
+				forward_end += 2;
+			} else if y > m {
+				forward_start += 2;
+			} else if odd {
+				let other = offset + delta - k;
+				if (0..backward.len() as isize).contains(&other) && backward[other as usize] != -1 && x >= n - backward[other as usize] {
+					return (x as usize, y as usize);
+				}
+			}
+		}
+		for k in (-d + backward_start..=d - backward_end).step_by(2) {
+			let mut x = furthest(&backward, k, d);
+			let mut y = x - k;
+			while x < n && y < m && old[(n - x - 1) as usize].text == new[(m - y - 1) as usize].text {
+				x += 1;
+				y += 1;
+			}
+			backward[(offset + k) as usize] = x;
+			if x > n {
+				backward_end += 2;
+			} else if y > m {
+				backward_start += 2;
+			} else if !odd {
+				let other = delta - k;
+				let index = offset + other;
+				if (0..forward.len() as isize).contains(&index) && forward[index as usize] != -1 && forward[index as usize] >= n - x {
+					let x = forward[index as usize];
+					return (x as usize, (x - other) as usize);
+				}
+			}
+		}
+	}
+	// The searches always meet within `max_d` steps. If that is too many, replacing everything is still a valid path.
+	(old.len(), 0)
+}
+
+/// The number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Prints the lines of `before` and `after` as a unified diff. Each hunk header names the path of the first tag that changed in it.
+fn render_diff(before_label: &str, after_label: &str, before: &Tag, after: &Tag, options: &DiffOptions, color: bool) -> String {
+	use std::fmt::Write;
+	let paint = |code: &str, text: &str| if color {
+		format!("\x1b[{code}m{text}\x1b[0m")
+	} else {
+		text.to_owned()
+	};
+	let old = render_lines(before);
+	let new = render_lines(&align(before, after, options, &TagPath::root()));
+	let edits = edit_script(&old, &new);
+	let mut output = String::new();
+	// Writing to a String can't fail.
+	let _ = writeln!(output, "{}", paint("1", &format!("--- {before_label}")));
+	let _ = writeln!(output, "{}", paint("1", &format!("+++ {after_label}")));
+	let changed: Vec<usize> = (0..edits.len()).filter(|&index| !matches!(edits[index], Edit::Keep(..))).collect();
+	let mut rest = changed.as_slice();
+	while let Some(&first) = rest.first() {
+		// A hunk continues while the next change is close enough for their context lines to touch.
+		let mut last = first;
+		while let Some(&next) = rest.get(1).filter(|&&next| next - last <= 2 * CONTEXT + 1) {
+			last = next;
+			rest = &rest[1..];
+		}
+		rest = &rest[1..];
+		let hunk = &edits[first.saturating_sub(CONTEXT)..(last + CONTEXT + 1).min(edits.len())];
+		// The lines before the hunk in each file.
+		let (old_start, new_start) = edits[..first.saturating_sub(CONTEXT)].iter().fold((0, 0), |(old, new), edit| match edit {
+			Edit::Keep(..) => (old + 1, new + 1),
+			Edit::Delete(_) => (old + 1, new),
+			Edit::Insert(_) => (old, new + 1),
+		});
+		let old_len = hunk.iter().filter(|edit| !matches!(edit, Edit::Insert(_))).count();
+		let new_len = hunk.iter().filter(|edit| !matches!(edit, Edit::Delete(_))).count();
+		// Like diff(1), an empty range starts at the line before it.
+		let range = |start: usize, len: usize| format!("{},{len}", if len == 0 { start } else { start + 1 });
+		let path = match edits[first] {
+			Edit::Delete(index) => &old[index].path,
+			Edit::Insert(index) | Edit::Keep(_, index) => &new[index].path,
+		};
+		let path = if path.is_root() { "<root>".to_owned() } else { path.to_string() };
+		let header = format!("@@ -{} +{} @@", range(old_start, old_len), range(new_start, new_len));
+		let _ = writeln!(output, "{} {path}", paint("36", &header));
+		for edit in hunk {
+			let _ = match *edit {
+				Edit::Keep(index, _) => writeln!(output, " {}", old[index].text),
+				Edit::Delete(index) => writeln!(output, "{}", paint("31", &format!("-{}", old[index].text))),
+				Edit::Insert(index) => writeln!(output, "{}", paint("32", &format!("+{}", new[index].text))),
+			};
+		}
+	}
+	output
+}
+
+fn describe(document: &Document) -> String {
+	let format = match document.format {
+		Format::Binary => "binary",
+		Format::Snbt => "SNBT",
+	};
+	format!("{format}, {} compression", document.compression)
+}
+
+/// Counts gathered by walking a whole tag tree.
+#[derive(Default)]
+struct Stats {
+	/// Number of tags of each type, indexed by [TagID] value.
+	counts: [usize; 13],
+	max_depth: usize,
+	/// Total elements stored in byte, int and long arrays.
+	array_elements: usize,
+	/// Total UTF-8 bytes of strings and compound keys.
+	string_bytes: usize,
+	/// The list or array with the most elements.
+	largest: Option<(TagPath, TagID, usize)>,
+}
+
+impl Stats {
+	fn of(tag: &Tag) -> Self {
+		let mut stats = Stats::default();
+		stats.tag(tag, &TagPath::root(), 0);
+		stats
+	}
+
+	fn count(&mut self, id: TagID, amount: usize, depth: usize) {
+		self.counts[id as usize] += amount;
+		if amount > 0 {
+			self.max_depth = self.max_depth.max(depth);
+		}
+	}
+
+	fn collection(&mut self, path: &TagPath, id: TagID, len: usize) {
+		if self.largest.as_ref().map_or(true, |(_, _, largest)| len > *largest) {
+			self.largest = Some((path.clone(), id, len));
+		}
+	}
+
+	fn tag(&mut self, tag: &Tag, path: &TagPath, depth: usize) {
+		self.count(tag.id(), 1, depth);
+		match tag {
+			Tag::String(value) => self.string_bytes += value.len(),
+			Tag::ByteArray(array) => self.array(path, TagID::ByteArray, array.len()),
+			Tag::IntArray(array) => self.array(path, TagID::IntArray, array.len()),
+			Tag::LongArray(array) => self.array(path, TagID::LongArray, array.len()),
+			Tag::List(list) => self.list(list, path, depth),
+			Tag::Compound(map) => self.compound(map, path, depth),
+			_ => (),
+		}
+	}
+
+	fn array(&mut self, path: &TagPath, id: TagID, len: usize) {
+		self.array_elements += len;
+		self.collection(path, id, len);
+	}
+
+	fn compound(&mut self, map: &Map, path: &TagPath, depth: usize) {
+		for (key, tag) in map.iter() {
+			self.string_bytes += key.len();
+			self.tag(tag, &path.key(key.as_str()), depth + 1);
+		}
+	}
+
+	fn list(&mut self, list: &ListTag, path: &TagPath, depth: usize) {
+		self.collection(path, TagID::List, list.len());
+		let depth = depth + 1;
+		match list {
+			ListTag::Empty => (),
+			ListTag::String(values) => {
+				self.count(TagID::String, values.len(), depth);
+				self.string_bytes += values.iter().map(String::len).sum::<usize>();
+			}
+			ListTag::ByteArray(arrays) => arrays.iter().enumerate().for_each(|(index, array)| {
+				self.count(TagID::ByteArray, 1, depth);
+				self.array(&path.index(index as isize), TagID::ByteArray, array.len());
+			}),
+			ListTag::IntArray(arrays) => arrays.iter().enumerate().for_each(|(index, array)| {
+				self.count(TagID::IntArray, 1, depth);
+				self.array(&path.index(index as isize), TagID::IntArray, array.len());
+			}),
+			ListTag::LongArray(arrays) => arrays.iter().enumerate().for_each(|(index, array)| {
+				self.count(TagID::LongArray, 1, depth);
+				self.array(&path.index(index as isize), TagID::LongArray, array.len());
+			}),
+			ListTag::List(lists) => lists.iter().enumerate().for_each(|(index, list)| {
+				self.count(TagID::List, 1, depth);
+				self.list(list, &path.index(index as isize), depth);
+			}),
+			ListTag::Compound(maps) => maps.iter().enumerate().for_each(|(index, map)| {
+				self.count(TagID::Compound, 1, depth);
+				self.compound(map, &path.index(index as isize), depth);
+			}),
+			// The remaining lists hold primitives.
+			list => self.count(list.id(), list.len(), depth),
+		}
+	}
+
+	fn report(&self, input: &str, document: &Document) -> String {
+		use std::fmt::Write;
+		let mut report = String::new();
+		let total: usize = self.counts.iter().sum();
+		// Writing to a String can't fail.
+		let _ = writeln!(report, "file:          {input} ({})", describe(document));
+		let _ = writeln!(report, "stored size:   {} bytes", document.stored_size);
+		let _ = writeln!(report, "size:          {} bytes", document.size);
+		let _ = writeln!(report, "root:          {:?} ({})", document.name, document.tag.id().title());
+		let _ = writeln!(report, "tags:          {total}");
+		let _ = writeln!(report, "max depth:     {}", self.max_depth);
+		let _ = writeln!(report, "string bytes:  {}", self.string_bytes);
+		let _ = writeln!(report, "array values:  {}", self.array_elements);
+		if let Some((path, id, len)) = &self.largest {
+			let path = if path.is_root() { "<root>".to_owned() } else { path.to_string() };
+			let _ = writeln!(report, "largest:       {path} ({}, {len} elements)", id.title());
+		}
+		let _ = writeln!(report, "by type:");
+		for id in (1..=12u8).filter_map(|id| TagID::try_from(id).ok()) {
+			let count = self.counts[id as usize];
+			if count > 0 {
+				let _ = writeln!(report, "  {:<12} {count}", id.title());
+			}
+		}
+		report
+	}
+}
 
-use std::fs::*;
+fn main() -> ExitCode {
+	match run(Cli::parse()) {
+		Ok(code) => code,
+		Err(error) => {
+			eprintln!("error: {error}");
+			ExitCode::from(2)
+		}
+	}
+}
 
-fn main() {
-	// program input
-}
\ No newline at end of file
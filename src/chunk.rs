@@ -0,0 +1,750 @@
+// A typed view of chunk NBT, as stored in region files, from Minecraft 1.13 onwards.
+// https://minecraft.wiki/w/Chunk_format
+//
+// Chunks changed shape twice since block states got palettes in 1.13:
+// packed data stopped spanning longs in 1.16, and 1.18 removed the `Level` compound,
+// renamed most keys, and gave every section its own biome palette.
+// Keys that aren't modeled are kept, so that a chunk can be decoded, edited and encoded again.
+
+use crate::{
+	Map,
+	MapType,
+	codegen::{self, DecodeError},
+	packed::{Layout, PackedArray},
+	path::PathSegment,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+	},
+};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The first data version with block state palettes (17w47a, Minecraft 1.13). Older chunks aren't supported.
+pub const PALETTE_VERSION: i32 = 1451;
+/// The first data version where packed entries don't span longs (20w17a, Minecraft 1.16).
+pub const ALIGNED_VERSION: i32 = 2529;
+/// The first data version without the `Level` compound (21w43a, Minecraft 1.18).
+pub const FLAT_VERSION: i32 = 2844;
+
+/// The number of blocks in a section.
+const SECTION_BLOCKS: usize = 4096;
+/// The number of biome cells in a section since 1.18, which are 4×4×4 blocks each.
+const SECTION_BIOMES: usize = 64;
+/// The number of columns in a chunk, which is the length of a heightmap.
+const COLUMNS: usize = 256;
+
+/// The layout of packed data in chunks of `data_version`.
+pub fn layout(data_version: i32) -> Layout {
+	if data_version >= ALIGNED_VERSION {
+		Layout::Aligned
+	} else {
+		Layout::Spanning
+	}
+}
+
+/// A block and its properties, such as `minecraft:oak_stairs[facing=east,half=bottom]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BlockState {
+	pub name: String,
+	pub properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+	pub fn new<S: Into<String>>(name: S) -> Self {
+		Self { name: name.into(), properties: BTreeMap::new() }
+	}
+
+	/// `minecraft:air`, which fills new sections.
+	pub fn air() -> Self {
+		Self::new("minecraft:air")
+	}
+
+	/// Returns this block state with `key` set to `value`.
+	pub fn with<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+		self.properties.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn property(&self, key: &str) -> Option<&str> {
+		self.properties.get(key).map(String::as_str)
+	}
+}
+
+impl Display for BlockState {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)?;
+		if !self.properties.is_empty() {
+			let properties: Vec<String> = self.properties.iter().map(|(key, value)| format!("{key}={value}")).collect();
+			write!(f, "[{}]", properties.join(","))?;
+		}
+		Ok(())
+	}
+}
+
+/// A block state string that isn't `name` or `name[key=value,...]`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid block state `{0}`.")]
+pub struct InvalidBlockState(pub String);
+
+impl FromStr for BlockState {
+	type Err = InvalidBlockState;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || InvalidBlockState(s.to_owned());
+		let (name, properties) = match s.split_once('[') {
+			Some((name, properties)) => (name, Some(properties.strip_suffix(']').ok_or_else(invalid)?)),
+			None => (s, None),
+		};
+		if name.is_empty() || name.contains(']') {
+			return Err(invalid());
+		}
+		let mut state = BlockState::new(name);
+		for property in properties.into_iter().flat_map(|properties| properties.split(',')).filter(|property| !property.is_empty()) {
+			let (key, value) = property.split_once('=').ok_or_else(invalid)?;
+			state.properties.insert(key.to_owned(), value.to_owned());
+		}
+		Ok(state)
+	}
+}
+
+impl EncodeNbt for BlockState {
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		map.insert("Name".to_owned(), Tag::String(self.name));
+		if !self.properties.is_empty() {
+			let properties = self.properties.into_iter().map(|(key, value)| (key, Tag::String(value))).collect();
+			map.insert("Properties".to_owned(), Tag::Compound(properties));
+		}
+		Tag::Compound(map)
+	}
+}
+
+impl DecodeNbt for BlockState {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let name = codegen::required(&mut map, "Name", codegen::value)?;
+		let properties = codegen::optional(&mut map, "Properties", |tag| {
+			codegen::compound(tag)?.into_iter()
+				.map(|(key, value)| codegen::value(value)
+					.map(|value| (key.clone(), value))
+					.map_err(|error| error.within(PathSegment::Key(key))))
+				.collect::<Result<BTreeMap<_, _>, _>>()
+		})?;
+		Ok(Self { name, properties: properties.unwrap_or_default() })
+	}
+}
+
+/// A fixed number of values stored as indices into a palette, like the blocks and biomes of a section.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paletted<T> {
+	palette: Vec<T>,
+	/// Indices into `palette`, or `None` if every entry is the first one.
+	data: Option<PackedArray>,
+	len: usize,
+	/// The fewest bits per index, which is 4 for blocks.
+	min_bits: u8,
+	layout: Layout,
+}
+
+impl<T: Clone + PartialEq> Paletted<T> {
+	/// Creates `len` entries of `value`.
+	pub fn filled(len: usize, value: T, min_bits: u8, layout: Layout) -> Self {
+		Self { palette: vec![value], data: None, len, min_bits, layout }
+	}
+
+	/// Wraps a palette and packed indices into it. Without `data`, every entry is the first one in the palette.
+	pub fn from_parts(palette: Vec<T>, data: Option<PackedArray>, len: usize, min_bits: u8, layout: Layout) -> Result<Self, DecodeError> {
+		if palette.is_empty() {
+			return Err(DecodeError::invalid("The palette is empty."));
+		}
+		if let Some(data) = &data {
+			if data.len() != len {
+				return Err(DecodeError::invalid(format!("Expected {len} entries, found {}.", data.len())));
+			}
+			if let Some((index, value)) = data.iter().enumerate().find(|&(_, value)| value >= palette.len() as u64) {
+				return Err(DecodeError::invalid(format!("Entry {index} is {value}, but the palette only has {} entries.", palette.len())));
+			}
+		}
+		Ok(Self { palette, data, len, min_bits, layout })
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The values that entries refer to. Some of them may be unused.
+	pub fn palette(&self) -> &[T] {
+		&self.palette
+	}
+
+	/// The packed indices into the palette, or `None` if every entry is the first one.
+	pub fn data(&self) -> Option<&PackedArray> {
+		self.data.as_ref()
+	}
+
+	pub fn get(&self, index: usize) -> Option<&T> {
+		match &self.data {
+			Some(data) => data.get(index).map(|index| &self.palette[index as usize]),
+			None if index < self.len => Some(&self.palette[0]),
+			None => None,
+		}
+	}
+
+	/// Replaces the entry at `index`, adding `value` to the palette and widening the indices if needed.
+	/// Returns the old value.
+	/// # Panics
+	/// Panics if `index` is out of bounds.
+	pub fn set(&mut self, index: usize, value: T) -> T {
+		let old = self.get(index)
+			.unwrap_or_else(|| panic!("index {index} is out of bounds for a palette of length {}", self.len))
+			.clone();
+		if old == value {
+			return old;
+		}
+		let palette_index = match self.palette.iter().position(|entry| entry == &value) {
+			Some(palette_index) => palette_index,
+			None => {
+				self.palette.push(value);
+				self.palette.len() - 1
+			}
+		};
+		let bits = self.bits();
+		let data = match &mut self.data {
+			Some(data) => {
+				if data.bits() < bits {
+					data.resize_bits(bits).expect("indices fit in the bits that the palette needs");
+				}
+				data
+			}
+			None => self.data.insert(PackedArray::new(self.len, bits, self.layout).expect("bits are between 1 and 64")),
+		};
+		data.set(index, palette_index as u64);
+		old
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+		(0..self.len).map(|index| self.get(index).expect("index is below the length"))
+	}
+
+	/// Removes palette entries that aren't used, and narrows the indices to match.
+	pub fn compact(&mut self) {
+		let Some(data) = &self.data else {
+			self.palette.truncate(1);
+			return;
+		};
+		let mut used = vec![false; self.palette.len()];
+		for index in data.iter() {
+			used[index as usize] = true;
+		}
+		// The new index of every palette entry that is used.
+		let mut remap = vec![0u64; self.palette.len()];
+		let mut palette = Vec::new();
+		for (index, entry) in self.palette.iter().enumerate() {
+			if used[index] {
+				remap[index] = palette.len() as u64;
+				palette.push(entry.clone());
+			}
+		}
+		let indices = data.iter().map(|index| remap[index as usize]).collect::<Vec<_>>();
+		self.palette = palette;
+		self.data = match self.palette.len() {
+			1 => None,
+			_ => Some(PackedArray::from_values(indices, self.bits(), self.layout).expect("indices fit in the bits that the palette needs")),
+		};
+	}
+
+	/// The bits per index that the palette needs.
+	fn bits(&self) -> u8 {
+		PackedArray::bits_needed(self.palette.len().saturating_sub(1) as u64).max(self.min_bits)
+	}
+
+	/// The packed indices, creating them if every entry is the first one.
+	pub(crate) fn packed(&self) -> PackedArray {
+		match &self.data {
+			Some(data) => data.clone(),
+			None => PackedArray::new(self.len, self.bits(), self.layout).expect("bits are between 1 and 64"),
+		}
+	}
+}
+
+/// Decodes packed palette indices, whose width is decided by the palette, but may be wider.
+fn decode_indices(tag: Tag, len: usize, palette_len: usize, min_bits: u8, layout: Layout) -> Result<PackedArray, DecodeError> {
+	let longs: Vec<i64> = codegen::value(tag)?;
+	let bits = PackedArray::bits_needed(palette_len.saturating_sub(1) as u64).max(min_bits);
+	let bits = match PackedArray::longs_needed(len, bits, layout) {
+		Ok(expected) if expected == longs.len() => bits,
+		_ => PackedArray::bits_for_longs(len, longs.len(), layout)
+			.ok_or_else(|| DecodeError::invalid(format!("{} longs can't hold {len} entries.", longs.len())))?,
+	};
+	PackedArray::from_longs(longs, len, bits, layout).map_err(|error| DecodeError::invalid(error.to_string()))
+}
+
+/// Decodes a palette and the indices under `data_key`, which may be missing if the palette has one entry.
+pub(crate) fn decode_paletted<T: Clone + PartialEq>(palette: Vec<T>, data: Option<Tag>, len: usize, min_bits: u8, layout: Layout, data_key: &str) -> Result<Paletted<T>, DecodeError> {
+	let within = |error: DecodeError| error.within(PathSegment::Key(data_key.to_owned()));
+	let data = data.map(|data| decode_indices(data, len, palette.len(), min_bits, layout)).transpose().map_err(within)?;
+	Paletted::from_parts(palette, data, len, min_bits, layout).map_err(within)
+}
+
+/// Decodes a `{palette, data}` compound, used for blocks and biomes since 1.18.
+fn decode_container<T: Clone + PartialEq>(tag: Tag, decode: impl Fn(Tag) -> Result<T, DecodeError>, len: usize, min_bits: u8, layout: Layout) -> Result<Paletted<T>, DecodeError> {
+	let mut map = codegen::compound(tag)?;
+	let palette = codegen::required(&mut map, "palette", |tag| codegen::list(tag, &decode))?;
+	let data = codegen::optional(&mut map, "data", codegen::any)?;
+	decode_paletted(palette, data, len, min_bits, layout, "data")
+}
+
+/// Encodes a `{palette, data}` compound, leaving out the indices when the palette has one entry.
+fn encode_container(palette: Vec<Tag>, data: Option<&PackedArray>) -> Tag {
+	let mut container = Map::new();
+	let needs_data = palette.len() > 1;
+	container.insert("palette".to_owned(), Tag::List(ListTag::from_mixed(palette)));
+	if let Some(data) = data.filter(|_| needs_data) {
+		container.insert("data".to_owned(), Tag::from(data.clone()));
+	}
+	Tag::Compound(container)
+}
+
+/// A 16×16×16 part of a chunk.
+#[derive(Clone, Debug)]
+pub struct Section {
+	/// The height of the section, in sections, so that its lowest blocks are at `y * 16`.
+	pub y: i8,
+	/// The block states, indexed by `y << 8 | z << 4 | x`.
+	/// `None` for sections that only hold light, such as those above and below the world.
+	pub blocks: Option<Paletted<BlockState>>,
+	/// Biome names in 4×4×4 cells, indexed by `y << 4 | z << 2 | x`. Only used since 1.18.
+	pub biomes: Option<Paletted<String>>,
+	/// Keys that aren't modeled, such as `BlockLight` and `SkyLight`.
+	pub other: Map,
+}
+
+impl Section {
+	/// Creates a section filled with air, and with plains if `data_version` has biomes in sections.
+	pub fn new(y: i8, data_version: i32) -> Self {
+		let layout = layout(data_version);
+		Self {
+			y,
+			blocks: Some(Paletted::filled(SECTION_BLOCKS, BlockState::air(), 4, layout)),
+			biomes: (data_version >= FLAT_VERSION).then(|| Paletted::filled(SECTION_BIOMES, "minecraft:plains".to_owned(), 1, layout)),
+			other: Map::new(),
+		}
+	}
+
+	/// Returns the block at section coordinates, which are all in `0..16`.
+	pub fn block(&self, x: usize, y: usize, z: usize) -> Option<&BlockState> {
+		self.blocks.as_ref()?.get(block_index(x, y, z))
+	}
+
+	/// Replaces the block at section coordinates, returning the old one.
+	/// Sections without blocks are filled with air first.
+	/// # Panics
+	/// Panics if a coordinate isn't in `0..16`.
+	pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: BlockState, data_version: i32) -> BlockState {
+		self.blocks
+			.get_or_insert_with(|| Paletted::filled(SECTION_BLOCKS, BlockState::air(), 4, layout(data_version)))
+			.set(block_index(x, y, z), state)
+	}
+
+	fn decode(nbt: Tag, data_version: i32) -> Result<Self, DecodeError> {
+		let layout = layout(data_version);
+		let mut map = codegen::compound(nbt)?;
+		let y = codegen::required(&mut map, "Y", codegen::value)?;
+		let (blocks, biomes) = if data_version >= FLAT_VERSION {
+			let blocks = codegen::optional(&mut map, "block_states", |tag| decode_container(tag, BlockState::decode_nbt, SECTION_BLOCKS, 4, layout))?;
+			let biomes = codegen::optional(&mut map, "biomes", |tag| decode_container(tag, codegen::value::<String>, SECTION_BIOMES, 1, layout))?;
+			(blocks, biomes)
+		} else {
+			let palette = codegen::optional(&mut map, "Palette", |tag| codegen::list(tag, BlockState::decode_nbt))?;
+			let blocks = match palette {
+				Some(palette) => {
+					let data = codegen::required(&mut map, "BlockStates", codegen::any)?;
+					Some(decode_paletted(palette, Some(data), SECTION_BLOCKS, 4, layout, "BlockStates")?)
+				}
+				None => None,
+			};
+			(blocks, None)
+		};
+		Ok(Self { y, blocks, biomes, other: map })
+	}
+
+	fn encode(self, data_version: i32) -> Tag {
+		let mut map = Map::new();
+		map.insert("Y".to_owned(), Tag::Byte(self.y));
+		if data_version >= FLAT_VERSION {
+			if let Some(blocks) = &self.blocks {
+				let palette = blocks.palette.iter().cloned().map(BlockState::encode_nbt).collect();
+				map.insert("block_states".to_owned(), encode_container(palette, blocks.data()));
+			}
+			if let Some(biomes) = &self.biomes {
+				let palette = biomes.palette.iter().cloned().map(Tag::String).collect();
+				map.insert("biomes".to_owned(), encode_container(palette, biomes.data()));
+			}
+		} else if let Some(blocks) = &self.blocks {
+			// Chunks before 1.18 always have indices, even when the palette has one entry.
+			let palette: Vec<Map> = blocks.palette.iter().cloned()
+				.map(|state| match state.encode_nbt() {
+					Tag::Compound(map) => map,
+					_ => unreachable!("block states encode as compounds"),
+				})
+				.collect();
+			map.insert("Palette".to_owned(), Tag::List(ListTag::from(palette)));
+			map.insert("BlockStates".to_owned(), Tag::from(blocks.packed()));
+		}
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+fn block_index(x: usize, y: usize, z: usize) -> usize {
+	assert!(x < 16 && y < 16 && z < 16, "section coordinates ({x}, {y}, {z}) must be in 0..16");
+	y << 8 | z << 4 | x
+}
+
+/// The biome of a column or cell, which was a numeric ID before 1.18.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Biome<'a> {
+	Id(i32),
+	Name(&'a str),
+}
+
+/// A 16×16 column of the world.
+#[derive(Clone, Debug)]
+pub struct Chunk {
+	pub data_version: i32,
+	/// The position of the chunk, in chunks.
+	pub x: i32,
+	pub z: i32,
+	/// How far generation got, such as `minecraft:full`. Before 1.18 it has no namespace.
+	pub status: Option<String>,
+	/// The sections, in the order they were stored. Chunks that haven't been generated far may have none.
+	pub sections: Option<Vec<Section>>,
+	/// Heightmaps by name, such as `MOTION_BLOCKING`, with one entry per column indexed by `z << 4 | x`.
+	pub heightmaps: Option<MapType<String, PackedArray>>,
+	pub block_entities: Option<Vec<Map>>,
+	/// Entities were stored in chunks until 1.17, and are still stored in chunks that aren't fully generated.
+	pub entities: Option<Vec<Map>>,
+	/// Numeric biome IDs from before 1.18, either per column (256) or in 4×4×4 cells (1024, since 1.15).
+	pub legacy_biomes: Option<Vec<i32>>,
+	/// Keys that aren't modeled. Before 1.18, these are the keys in `Level`.
+	pub other: Map,
+	/// Keys next to `Level` before 1.18, other than `DataVersion`.
+	pub root: Map,
+}
+
+impl Chunk {
+	/// The section at section height `y`.
+	pub fn section(&self, y: i8) -> Option<&Section> {
+		self.sections.iter().flatten().find(|section| section.y == y)
+	}
+
+	pub fn section_mut(&mut self, y: i8) -> Option<&mut Section> {
+		self.sections.iter_mut().flatten().find(|section| section.y == y)
+	}
+
+	/// Returns the block at `x` and `z` in `0..16` and absolute height `y`.
+	/// `None` means that there is no section with blocks at that height.
+	/// # Panics
+	/// Panics if `x` or `z` isn't in `0..16`.
+	pub fn block_at(&self, x: usize, y: i32, z: usize) -> Option<&BlockState> {
+		let section = self.section(section_y(y)?)?;
+		section.block(x, (y & 15) as usize, z)
+	}
+
+	/// Replaces the block at `x` and `z` in `0..16` and absolute height `y`, returning the old one.
+	/// A missing section is created, filled with air.
+	/// # Panics
+	/// Panics if `x` or `z` isn't in `0..16`, or `y` is outside the heights that sections can have.
+	pub fn set_block(&mut self, x: usize, y: i32, z: usize, state: BlockState) -> BlockState {
+		let section_y = section_y(y).unwrap_or_else(|| panic!("height {y} is outside the heights that sections can have"));
+		let data_version = self.data_version;
+		let sections = self.sections.get_or_insert_with(Vec::new);
+		let index = match sections.iter().position(|section| section.y == section_y) {
+			Some(index) => index,
+			None => {
+				let index = sections.iter().position(|section| section.y > section_y).unwrap_or(sections.len());
+				sections.insert(index, Section::new(section_y, data_version));
+				index
+			}
+		};
+		sections[index].set_block(x, (y & 15) as usize, z, state, data_version)
+	}
+
+	/// Returns the biome at `x` and `z` in `0..16` and absolute height `y`.<br>
+	/// Before 1.18, `y` is ignored for 2D biomes, and 3D biomes are assumed to start at height 0.
+	/// # Panics
+	/// Panics if `x` or `z` isn't in `0..16`.
+	pub fn biome_at(&self, x: usize, y: i32, z: usize) -> Option<Biome<'_>> {
+		assert!(x < 16 && z < 16, "column ({x}, {z}) must be in 0..16");
+		if let Some(biomes) = &self.legacy_biomes {
+			let index = match biomes.len() {
+				COLUMNS => z << 4 | x,
+				_ => (y.clamp(0, 255) as usize >> 2) << 4 | (z >> 2) << 2 | x >> 2,
+			};
+			return biomes.get(index).copied().map(Biome::Id);
+		}
+		let section = self.section(section_y(y)?)?;
+		let index = ((y & 15) as usize >> 2) << 4 | (z >> 2) << 2 | x >> 2;
+		section.biomes.as_ref()?.get(index).map(|name| Biome::Name(name))
+	}
+
+	fn decode_body(mut map: Map, data_version: i32) -> Result<Self, DecodeError> {
+		let flat = data_version >= FLAT_VERSION;
+		let layout = layout(data_version);
+		let x = codegen::required(&mut map, "xPos", codegen::value)?;
+		let z = codegen::required(&mut map, "zPos", codegen::value)?;
+		let status = codegen::optional(&mut map, "Status", codegen::value)?;
+		let sections = codegen::optional(&mut map, if flat { "sections" } else { "Sections" }, |tag| {
+			codegen::list(tag, |tag| Section::decode(tag, data_version))
+		})?;
+		let heightmaps = codegen::optional(&mut map, "Heightmaps", |tag| {
+			codegen::compound(tag)?.into_iter()
+				.map(|(key, tag)| {
+					let within = |error: DecodeError| error.within(PathSegment::Key(key.clone()));
+					let longs: Vec<i64> = codegen::value(tag).map_err(within)?;
+					let bits = PackedArray::bits_for_longs(COLUMNS, longs.len(), layout)
+						.ok_or_else(|| within(DecodeError::invalid(format!("{} longs can't hold a heightmap.", longs.len()))))?;
+					let heightmap = PackedArray::from_longs(longs, COLUMNS, bits, layout).map_err(|error| within(DecodeError::invalid(error.to_string())))?;
+					Ok((key, heightmap))
+				})
+				.collect::<Result<MapType<_, _>, _>>()
+		})?;
+		let block_entities = codegen::optional(&mut map, if flat { "block_entities" } else { "TileEntities" }, |tag| {
+			codegen::list(tag, codegen::compound)
+		})?;
+		let entities = codegen::optional(&mut map, if flat { "entities" } else { "Entities" }, |tag| codegen::list(tag, codegen::compound))?;
+		let legacy_biomes = match flat {
+			true => None,
+			false => codegen::optional(&mut map, "Biomes", codegen::value)?,
+		};
+		Ok(Self {
+			data_version,
+			x,
+			z,
+			status,
+			sections,
+			heightmaps,
+			block_entities,
+			entities,
+			legacy_biomes,
+			other: map,
+			root: Map::new(),
+		})
+	}
+}
+
+/// The section height that holds blocks at absolute height `y`.
+fn section_y(y: i32) -> Option<i8> {
+	i8::try_from(y >> 4).ok()
+}
+
+impl DecodeNbt for Chunk {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut root = codegen::compound(nbt)?;
+		let data_version = codegen::required(&mut root, "DataVersion", |tag| {
+			let data_version: i32 = codegen::value(tag)?;
+			match data_version < PALETTE_VERSION {
+				true => Err(DecodeError::invalid(format!("Data version {data_version} is from before Minecraft 1.13, which isn't supported."))),
+				false => Ok(data_version),
+			}
+		})?;
+		if data_version >= FLAT_VERSION {
+			return Chunk::decode_body(root, data_version);
+		}
+		let mut chunk = codegen::required(&mut root, "Level", |tag| Chunk::decode_body(codegen::compound(tag)?, data_version))?;
+		chunk.root = root;
+		Ok(chunk)
+	}
+}
+
+impl EncodeNbt for Chunk {
+	fn encode_nbt(self) -> Tag {
+		let flat = self.data_version >= FLAT_VERSION;
+		let data_version = self.data_version;
+		let list = |maps: Vec<Map>| Tag::List(ListTag::from(maps));
+		let mut body = Map::new();
+		if flat {
+			body.insert("DataVersion".to_owned(), Tag::Int(data_version));
+		}
+		body.insert("xPos".to_owned(), Tag::Int(self.x));
+		body.insert("zPos".to_owned(), Tag::Int(self.z));
+		if let Some(status) = self.status {
+			body.insert("Status".to_owned(), Tag::String(status));
+		}
+		if let Some(sections) = self.sections {
+			let sections = sections.into_iter().map(|section| section.encode(data_version)).collect();
+			body.insert(if flat { "sections" } else { "Sections" }.to_owned(), Tag::List(ListTag::from_mixed(sections)));
+		}
+		if let Some(heightmaps) = self.heightmaps {
+			let heightmaps = heightmaps.into_iter().map(|(key, heightmap)| (key, Tag::from(heightmap))).collect();
+			body.insert("Heightmaps".to_owned(), Tag::Compound(heightmaps));
+		}
+		if let Some(block_entities) = self.block_entities {
+			body.insert(if flat { "block_entities" } else { "TileEntities" }.to_owned(), list(block_entities));
+		}
+		if let Some(entities) = self.entities {
+			body.insert(if flat { "entities" } else { "Entities" }.to_owned(), list(entities));
+		}
+		if let Some(biomes) = self.legacy_biomes.filter(|_| !flat) {
+			body.insert("Biomes".to_owned(), Tag::IntArray(biomes));
+		}
+		body.extend(self.other);
+		if flat {
+			return Tag::Compound(body);
+		}
+		let mut root = Map::new();
+		root.insert("DataVersion".to_owned(), Tag::Int(data_version));
+		root.insert("Level".to_owned(), Tag::Compound(body));
+		root.extend(self.root);
+		Tag::Compound(root)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::diff::{diff, DiffOptions};
+
+	fn stone() -> BlockState {
+		BlockState::new("minecraft:stone")
+	}
+
+	/// A chunk with one section whose palette is air and stone, and stone at the origin.
+	fn chunk_source(data_version: i32) -> String {
+		let (data, heightmap) = match layout(data_version) {
+			// 4 bits per block, and 9 bits per column.
+			Layout::Aligned => ("1L", "[L; 1L".to_owned() + &", 0L".repeat(36) + "]"),
+			Layout::Spanning => ("1L", "[L; 1L".to_owned() + &", 0L".repeat(35) + "]"),
+		};
+		let zeros = ", 0L".repeat(255);
+		if data_version >= FLAT_VERSION {
+			format!(r#"{{
+				DataVersion: {data_version}, xPos: 3, zPos: -2, yPos: -4, Status: "minecraft:full", LastUpdate: 100L,
+				sections: [
+					{{Y: 0b, block_states: {{palette: [{{Name: "minecraft:air"}}, {{Name: "minecraft:stone"}}], data: [L; {data}{zeros}]}},
+						biomes: {{palette: ["minecraft:plains"]}}, SkyLight: [B; 1b]}},
+					{{Y: 5b, SkyLight: [B; 2b]}},
+				],
+				Heightmaps: {{MOTION_BLOCKING: {heightmap}}},
+				block_entities: [{{id: "minecraft:chest", x: 48, y: 0, z: -32}}],
+			}}"#)
+		} else {
+			format!(r#"{{
+				DataVersion: {data_version}, Custom: 1b,
+				Level: {{
+					xPos: 3, zPos: -2, Status: "full", LastUpdate: 100L,
+					Sections: [
+						{{Y: 0b, Palette: [{{Name: "minecraft:air"}}, {{Name: "minecraft:stone"}}], BlockStates: [L; {data}{zeros}], SkyLight: [B; 1b]}},
+						{{Y: 5b, SkyLight: [B; 2b]}},
+					],
+					Heightmaps: {{MOTION_BLOCKING: {heightmap}}},
+					TileEntities: [{{id: "minecraft:chest", x: 48, y: 0, z: -32}}],
+					Entities: [],
+					Biomes: [I; {}],
+				}},
+			}}"#, vec!["1"; 1024].join(", "))
+		}
+	}
+
+	#[test]
+	fn decode_test() {
+		let options = DiffOptions { ignore_order: true, ..Default::default() };
+		for data_version in [1976, 2586, 3465] {
+			let tag = Tag::parse(chunk_source(data_version)).unwrap();
+			let chunk = Chunk::decode_nbt(tag.clone()).unwrap();
+			assert_eq!((chunk.x, chunk.z, chunk.sections.as_ref().map(Vec::len)), (3, -2, Some(2)));
+			assert_eq!(chunk.block_at(0, 0, 0), Some(&stone()));
+			assert_eq!(chunk.block_at(1, 0, 0), Some(&BlockState::air()));
+			assert_eq!(chunk.block_at(0, 80, 0), None);
+			assert_eq!(chunk.heightmaps.as_ref().unwrap()["MOTION_BLOCKING"].get(0), Some(1));
+			assert_eq!(chunk.block_entities.as_ref().map(Vec::len), Some(1));
+			assert!(chunk.other.contains_key("LastUpdate"));
+			assert!(chunk.section(5).unwrap().other.contains_key("SkyLight"));
+			let biome = if data_version >= FLAT_VERSION { Biome::Name("minecraft:plains") } else { Biome::Id(1) };
+			assert_eq!(chunk.biome_at(5, 5, 5), Some(biome));
+			// Nothing is lost when the chunk is encoded again.
+			assert!(diff(&tag, &chunk.encode_nbt(), &options).is_empty(), "{data_version}");
+		}
+
+		// Chunks that have barely been generated lack most keys, and encoding doesn't add them.
+		for source in [r#"{DataVersion: 3465, xPos: 0, zPos: 0, Status: "minecraft:empty"}"#, "{DataVersion: 1976, Level: {xPos: 0, zPos: 0}}"] {
+			let tag = Tag::parse(source).unwrap();
+			let chunk = Chunk::decode_nbt(tag.clone()).unwrap();
+			assert!(chunk.sections.is_none() && chunk.heightmaps.is_none() && chunk.block_entities.is_none());
+			assert!(diff(&tag, &chunk.encode_nbt(), &options).is_empty(), "{source}");
+		}
+	}
+
+	#[test]
+	fn edit_test() {
+		for data_version in [1976, 2586, 3465] {
+			let mut chunk = Chunk::decode_nbt(Tag::parse(chunk_source(data_version)).unwrap()).unwrap();
+			let chest = BlockState::new("minecraft:chest").with("facing", "north");
+			assert_eq!(chunk.set_block(15, 15, 15, chest.clone()), BlockState::air());
+			assert_eq!(chunk.set_block(0, 0, 0, BlockState::air()), stone());
+			// A new section is created, and sections that only hold light get blocks.
+			assert_eq!(chunk.set_block(1, -20, 2, stone()), BlockState::air());
+			assert_eq!(chunk.set_block(1, 80, 2, stone()), BlockState::air());
+			assert_eq!(chunk.sections.iter().flatten().map(|section| section.y).collect::<Vec<_>>(), [-2, 0, 5]);
+			// Enough block states to widen the indices past 4 bits.
+			for index in 0..20 {
+				chunk.set_block(index % 16, 1, index / 16, BlockState::new(format!("minecraft:block_{index}")));
+			}
+
+			let chunk = Chunk::decode_nbt(chunk.encode_nbt()).unwrap();
+			assert_eq!(chunk.block_at(15, 15, 15), Some(&chest));
+			assert_eq!(chunk.block_at(0, 0, 0), Some(&BlockState::air()));
+			assert_eq!(chunk.block_at(1, -20, 2), Some(&stone()));
+			assert_eq!(chunk.block_at(1, 80, 2), Some(&stone()));
+			assert_eq!(chunk.block_at(3, 1, 1).map(|state| state.name.as_str()), Some("minecraft:block_19"));
+			assert_eq!(chunk.section(0).unwrap().blocks.as_ref().unwrap().data().unwrap().bits(), 5);
+			assert_eq!(chest.to_string(), "minecraft:chest[facing=north]");
+			assert_eq!("minecraft:chest[facing=north]".parse(), Ok(chest));
+		}
+	}
+
+	#[test]
+	fn block_state_test() {
+		let stairs: BlockState = "minecraft:oak_stairs[half=bottom,facing=east]".parse().unwrap();
+		assert_eq!(stairs.property("facing"), Some("east"));
+		assert_eq!(stairs.to_string(), "minecraft:oak_stairs[facing=east,half=bottom]");
+		assert_eq!("stone[]".parse(), Ok(BlockState::new("stone")));
+		for invalid in ["", "[a=b]", "stone[a=b", "stone[a]", "stone]"] {
+			assert_eq!(invalid.parse::<BlockState>(), Err(InvalidBlockState(invalid.to_owned())));
+		}
+	}
+
+	#[test]
+	fn paletted_test() {
+		let mut paletted = Paletted::filled(64, "a", 1, Layout::Aligned);
+		assert!(paletted.data().is_none());
+		paletted.set(3, "b");
+		paletted.set(4, "c");
+		assert_eq!(paletted.data().unwrap().bits(), 2);
+		paletted.set(4, "a");
+		paletted.compact();
+		assert_eq!(paletted.palette(), ["a", "b"]);
+		assert_eq!(paletted.data().unwrap().bits(), 1);
+		assert_eq!(paletted.iter().filter(|&&value| value == "b").count(), 1);
+		paletted.set(3, "a");
+		paletted.compact();
+		assert_eq!((paletted.palette(), paletted.data()), (&["a"][..], None));
+
+		let error = Chunk::decode_nbt(Tag::parse(r#"{DataVersion: 3465, xPos: 0, zPos: 0, sections: [{Y: 0b, block_states: {palette: [{Name: "a"}], data: [L; 1L]}}]}"#).unwrap()).unwrap_err();
+		assert_eq!(error.to_string(), "sections[0].block_states.data: 1 longs can't hold 4096 entries.");
+		let error = Chunk::decode_nbt(Tag::parse("{DataVersion: 1343, Level: {}}").unwrap()).unwrap_err();
+		assert_eq!(error.to_string(), "DataVersion: Data version 1343 is from before Minecraft 1.13, which isn't supported.");
+	}
+}
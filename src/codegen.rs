@@ -0,0 +1,640 @@
+// Generates Rust types with EncodeNbt/DecodeNbt implementations from a Schema.
+//
+// The generated code calls the helper functions at the top of this module, so that it stays
+// short enough to read and edit by hand. The typed models in this crate, such as chunk, use them too.
+
+use crate::{
+	Map,
+	path::{PathSegment, TagPath},
+	schema::{CompoundSchema, Field, ListSchema, Schema},
+	tag::{
+		ListTag,
+		NbtType,
+		Tag,
+		TagID,
+	},
+};
+use std::fmt::Write;
+
+/// Failure to decode a generated type, or one of the typed models in this crate, from NBT.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}: {kind}", if path.is_root() { "<root>".to_owned() } else { path.to_string() })]
+pub struct DecodeError {
+	/// Where the error occurred, relative to the tag that was being decoded.
+	pub path: TagPath,
+	pub kind: DecodeErrorKind,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+	#[error("Expected {}, found {}.", .expected.title(), .found.title())]
+	WrongType { expected: TagID, found: TagID },
+	#[error("Required key is missing.")]
+	MissingKey,
+	#[error("Key is not allowed.")]
+	UnknownKey,
+	/// None of the options of an enum accepted the tag, and none of them were for its type.
+	#[error("Expected one of {}, found {}.", .expected.iter().map(|id| id.title()).collect::<Vec<_>>().join(", "), .found.title())]
+	NotOneOf { expected: Vec<TagID>, found: TagID },
+	/// The tag has the right type, but its value doesn't make sense.
+	#[error("{0}")]
+	Invalid(String),
+}
+
+impl DecodeError {
+	pub fn new(kind: DecodeErrorKind) -> Self {
+		Self { path: TagPath::root(), kind }
+	}
+
+	/// A [DecodeErrorKind::Invalid] error.
+	pub fn invalid<S: Into<String>>(message: S) -> Self {
+		Self::new(DecodeErrorKind::Invalid(message.into()))
+	}
+
+	/// Moves the error below `segment`, as errors travel up from nested tags.
+	pub fn within(mut self, segment: PathSegment) -> Self {
+		self.path = self.path.prefixed(segment);
+		self
+	}
+}
+
+/// Decodes a tag that is directly represented by `T`, such as `i32` for Int or `Vec<i64>` for LongArray.
+pub fn value<T: NbtType + TryFrom<Tag, Error = ()>>(tag: Tag) -> Result<T, DecodeError> {
+	let found = tag.id();
+	T::try_from(tag).map_err(|_| DecodeError::new(DecodeErrorKind::WrongType { expected: T::ID, found }))
+}
+
+/// Accepts any tag.
+pub fn any(tag: Tag) -> Result<Tag, DecodeError> {
+	Ok(tag)
+}
+
+/// Decodes a Compound.
+pub fn compound(tag: Tag) -> Result<Map, DecodeError> {
+	value(tag)
+}
+
+/// Decodes a List, using `decode` for every element. Heterogeneous lists are unwrapped (see [ListTag::from_mixed]).
+pub fn list<T>(tag: Tag, decode: impl Fn(Tag) -> Result<T, DecodeError>) -> Result<Vec<T>, DecodeError> {
+	value::<ListTag>(tag)?
+		.into_mixed()
+		.into_iter()
+		.enumerate()
+		.map(|(index, tag)| decode(tag).map_err(|error| error.within(PathSegment::Index(index as isize))))
+		.collect()
+}
+
+/// Encodes a List, which is heterogeneous if the elements have different types (see [ListTag::from_mixed]).
+pub fn encode_list<I: IntoIterator<Item = Tag>>(tags: I) -> Tag {
+	Tag::List(ListTag::from_mixed(tags.into_iter().collect()))
+}
+
+fn take(map: &mut Map, key: &str) -> Option<Tag> {
+	// Keeps the order of the keys that are left, which become the unknown keys of a struct.
+	#[cfg(feature = "preserve_order")]
+	return map.shift_remove(key);
+	#[cfg(not(feature = "preserve_order"))]
+	return map.remove(key);
+}
+
+/// Removes `key` from `map` and decodes it.
+pub fn required<T>(map: &mut Map, key: &str, decode: impl FnOnce(Tag) -> Result<T, DecodeError>) -> Result<T, DecodeError> {
+	let within = |error: DecodeError| error.within(PathSegment::Key(key.to_owned()));
+	let tag = take(map, key).ok_or_else(|| within(DecodeError::new(DecodeErrorKind::MissingKey)))?;
+	decode(tag).map_err(within)
+}
+
+/// Removes `key` from `map` and decodes it if it was present.
+pub fn optional<T>(map: &mut Map, key: &str, decode: impl FnOnce(Tag) -> Result<T, DecodeError>) -> Result<Option<T>, DecodeError> {
+	take(map, key)
+		.map(|tag| decode(tag).map_err(|error| error.within(PathSegment::Key(key.to_owned()))))
+		.transpose()
+}
+
+/// A function that decodes a generated type, or one of the types that it contains.
+pub type Decoder<T> = fn(Tag) -> Result<T, DecodeError>;
+
+/// Decodes a tag with the first of `options` that accepts it.<br>
+/// If none do, the error is the one from an option for the tag's type, if there is one.
+pub fn one_of<T>(tag: Tag, options: &[Decoder<T>]) -> Result<T, DecodeError> {
+	let found = tag.id();
+	let mut expected = Vec::new();
+	let mut error = None;
+	for decode in options {
+		match decode(tag.clone()) {
+			Ok(value) => return Ok(value),
+			Err(DecodeError { path, kind: DecodeErrorKind::WrongType { expected: id, .. } }) if path.is_root() => expected.push(id),
+			Err(other) => {
+				error.get_or_insert(other);
+			}
+		}
+	}
+	Err(error.unwrap_or_else(|| DecodeError::new(DecodeErrorKind::NotOneOf { expected, found })))
+}
+
+/// Fails if `map` has any keys left after the known keys were removed.
+pub fn deny_unknown(map: Map) -> Result<(), DecodeError> {
+	match map.into_iter().next() {
+		Some((key, _)) => Err(DecodeError::new(DecodeErrorKind::UnknownKey).within(PathSegment::Key(key))),
+		None => Ok(()),
+	}
+}
+
+/// Generates Rust source code with a type named `name` for tags that match `schema`.<br>
+/// Compounds become structs that implement [EncodeNbt](crate::tag::EncodeNbt) and
+/// [DecodeNbt](crate::tag::DecodeNbt), optional keys become `Option` fields, and compounds
+/// that allow unknown keys keep them in an `other` field so that nothing is lost when a value is
+/// decoded and encoded again. [Schema::OneOf] becomes an enum with a variant for every option,
+/// and [Schema::Any] is kept as a [Tag]. Bounds aren't checked by the generated code.
+/// ```
+/// # use rustnbt::{codegen, schema::Schema};
+/// let schema = Schema::compound()
+///     .required("id", Schema::string())
+///     .optional("Count", Schema::byte())
+///     .into();
+/// let source = codegen::rust_source(&schema, "Item");
+/// assert!(source.contains("pub struct Item {"));
+/// assert!(source.contains("pub count: Option<i8>,"));
+/// ```
+pub fn rust_source(schema: &Schema, name: &str) -> String {
+	let mut generator = Generator::default();
+	let root = generator.resolve(&shape(schema), name, "");
+	if !matches!(&root, Type::Named(type_name) if type_name == &pascal_case(name)) {
+		generator.items.insert(0, format!("pub type {} = {};\n", pascal_case(name), root.name()));
+	}
+	let mut source = String::from(concat!(
+		"// Generated by rustnbt.\n",
+		"#[allow(unused_imports)]\n",
+		"use rustnbt::{\n",
+		"\tMap,\n",
+		"\tcodegen::{self, DecodeError},\n",
+		"\ttag::{DecodeNbt, EncodeNbt, Tag},\n",
+		"};\n",
+	));
+	for item in generator.items {
+		source.push('\n');
+		source.push_str(&item);
+	}
+	source
+}
+
+/// Generates Rust source code with a type named `name` for tags that look like `sample`.<br>
+/// Every key of a compound is required, and lists get the type of their first element.
+/// [Schema::infer] and [rust_source] can learn from more than one sample.
+pub fn rust_source_for_sample(sample: &Tag, name: &str) -> String {
+	rust_source(&Schema::infer([sample]), name)
+}
+
+/// `schema` without bounds, which the generated code doesn't check,
+/// so that compounds that only differ in their bounds share a struct.
+fn shape(schema: &Schema) -> Schema {
+	match schema {
+		Schema::Any => Schema::Any,
+		Schema::Tag(schema) => Schema::of(schema.id()),
+		Schema::List(list) => Schema::list(shape(&list.element)).into(),
+		Schema::Compound(compound) => Schema::Compound(CompoundSchema {
+			fields: compound.fields.iter()
+				.map(|(key, field)| (key.clone(), Field { schema: shape(&field.schema), required: field.required }))
+				.collect(),
+			allow_unknown: compound.allow_unknown,
+		}),
+		Schema::OneOf(options) => Schema::OneOf(options.iter().map(shape).collect()),
+	}
+}
+
+/// The Rust type that represents a [Schema].
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+	/// A type that [value] can decode.
+	Direct(&'static str),
+	Tag,
+	List(Box<Type>),
+	/// A generated struct or enum.
+	Named(String),
+}
+
+impl Type {
+	fn name(&self) -> String {
+		match self {
+			Type::Direct(name) => (*name).to_owned(),
+			Type::Tag => "Tag".to_owned(),
+			Type::List(element) => format!("Vec<{}>", element.name()),
+			Type::Named(name) => name.clone(),
+		}
+	}
+
+	/// An expression for a function that decodes this type from a [Tag].
+	fn decoder(&self) -> String {
+		match self {
+			Type::Direct(_) => "codegen::value".to_owned(),
+			Type::Tag => "codegen::any".to_owned(),
+			Type::List(_) => format!("|tag| {}", self.decode("tag")),
+			Type::Named(name) => format!("{name}::decode_nbt"),
+		}
+	}
+
+	/// An expression that decodes the [Tag] `tag` as this type.
+	fn decode(&self, tag: &str) -> String {
+		match self {
+			Type::List(element) => format!("codegen::list({tag}, {})", element.decoder()),
+			_ => format!("{}({tag})", self.decoder()),
+		}
+	}
+
+	/// An expression that encodes `value` as a [Tag].
+	fn encoder(&self, value: &str) -> String {
+		match self {
+			Type::Direct(_) => format!("Tag::from({value})"),
+			Type::Tag => value.to_owned(),
+			Type::List(element) if **element == Type::Tag => format!("codegen::encode_list({value})"),
+			Type::List(element) => format!("codegen::encode_list({value}.into_iter().map(|value| {}))", element.encoder("value")),
+			Type::Named(_) => format!("{value}.encode_nbt()"),
+		}
+	}
+}
+
+#[derive(Default)]
+struct Generator {
+	/// Type definitions, in the order they were named.
+	items: Vec<String>,
+	/// Every schema that has a struct or enum, so that identical schemas share one.
+	named: Vec<(String, Schema)>,
+}
+
+/// Names that generated types can't use, because the generated code refers to them.
+const RESERVED_TYPES: [&str; 11] = ["Self", "Tag", "Map", "Vec", "Option", "Result", "String", "Box", "DecodeError", "EncodeNbt", "DecodeNbt"];
+
+impl Generator {
+	/// Returns the type for `schema`, generating any structs and enums that it needs.
+	/// `hint` is the key where the schema was found, and `parent` is the name of the enclosing struct.
+	fn resolve(&mut self, schema: &Schema, hint: &str, parent: &str) -> Type {
+		match schema {
+			Schema::Any => Type::Tag,
+			Schema::OneOf(options) if options.is_empty() => Type::Tag,
+			Schema::OneOf(options) if options.len() == 1 => self.resolve(&options[0], hint, parent),
+			Schema::OneOf(options) => Type::Named(self.one_of(schema, options, hint, parent)),
+			Schema::Tag(schema) => Type::Direct(match schema.id() {
+				TagID::Byte => "i8",
+				TagID::Short => "i16",
+				TagID::Int => "i32",
+				TagID::Long => "i64",
+				TagID::Float => "f32",
+				TagID::Double => "f64",
+				TagID::ByteArray => "Vec<i8>",
+				TagID::String => "String",
+				TagID::IntArray => "Vec<i32>",
+				TagID::LongArray => "Vec<i64>",
+				TagID::List | TagID::Compound => unreachable!("TagSchema is never for a List or Compound"),
+			}),
+			Schema::List(ListSchema { element, .. }) => Type::List(Box::new(self.resolve(element, &singular(hint), parent))),
+			Schema::Compound(compound) if compound.fields.is_empty() => Type::Direct("Map"),
+			Schema::Compound(compound) => Type::Named(self.compound(schema, compound, hint, parent)),
+		}
+	}
+
+	fn is_taken(&self, name: &str) -> bool {
+		RESERVED_TYPES.contains(&name) || self.named.iter().any(|(taken, _)| taken == name)
+	}
+
+	/// Picks an unused type name for `hint`, prefixing it with `parent` if it is taken.
+	fn type_name(&self, hint: &str, parent: &str) -> String {
+		let name = pascal_case(hint);
+		if !self.is_taken(&name) {
+			return name;
+		}
+		let name = format!("{parent}{name}");
+		let mut candidate = name.clone();
+		let mut number = 2;
+		while self.is_taken(&candidate) {
+			candidate = format!("{name}{number}");
+			number += 1;
+		}
+		candidate
+	}
+
+	/// The name of the type that was already generated for `schema`.
+	fn existing(&self, schema: &Schema) -> Option<String> {
+		self.named.iter().find(|(_, existing)| existing == schema).map(|(name, _)| name.clone())
+	}
+
+	/// Names a new type for `schema`, and reserves the index of its definition in `items`,
+	/// so that types are defined before the types they contain.
+	fn reserve(&mut self, schema: &Schema, hint: &str, parent: &str) -> (String, usize) {
+		let name = self.type_name(hint, parent);
+		self.named.push((name.clone(), schema.clone()));
+		self.items.push(String::new());
+		(name, self.items.len() - 1)
+	}
+
+	/// Generates an enum for `options`, returning its name.
+	fn one_of(&mut self, schema: &Schema, options: &[Schema], hint: &str, parent: &str) -> String {
+		if let Some(name) = self.existing(schema) {
+			return name;
+		}
+		let (name, index) = self.reserve(schema, hint, parent);
+		let mut variants: Vec<(String, Type)> = Vec::new();
+		for option in options {
+			let base = option.id().map_or("Other", TagID::title).to_owned();
+			let mut variant = base.clone();
+			let mut number = 2;
+			while variants.iter().any(|(taken, _)| taken == &variant) {
+				variant = format!("{base}{number}");
+				number += 1;
+			}
+			let variant_type = self.resolve(option, &format!("{name}{variant}"), &name);
+			variants.push((variant, variant_type));
+		}
+
+		let mut code = String::new();
+		let _ = writeln!(code, "#[derive(Clone, Debug)]");
+		let _ = writeln!(code, "pub enum {name} {{");
+		for (variant, variant_type) in &variants {
+			let _ = writeln!(code, "\t{variant}({}),", variant_type.name());
+		}
+		let _ = writeln!(code, "}}\n");
+
+		let _ = writeln!(code, "impl EncodeNbt for {name} {{");
+		let _ = writeln!(code, "\tfn encode_nbt(self) -> Tag {{");
+		let _ = writeln!(code, "\t\tmatch self {{");
+		for (variant, variant_type) in &variants {
+			let _ = writeln!(code, "\t\t\tSelf::{variant}(value) => {},", variant_type.encoder("value"));
+		}
+		let _ = writeln!(code, "\t\t}}");
+		let _ = writeln!(code, "\t}}");
+		let _ = writeln!(code, "}}\n");
+
+		let _ = writeln!(code, "impl DecodeNbt for {name} {{");
+		let _ = writeln!(code, "\ttype Error = DecodeError;\n");
+		let _ = writeln!(code, "\tfn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {{");
+		let _ = writeln!(code, "\t\tcodegen::one_of(nbt, &[");
+		for (variant, variant_type) in &variants {
+			let _ = writeln!(code, "\t\t\t|tag| {}.map(Self::{variant}),", variant_type.decode("tag"));
+		}
+		let _ = writeln!(code, "\t\t])");
+		let _ = writeln!(code, "\t}}");
+		let _ = writeln!(code, "}}");
+
+		self.items[index] = code;
+		name
+	}
+
+	/// Generates a struct for `compound`, returning its name.
+	fn compound(&mut self, schema: &Schema, compound: &CompoundSchema, hint: &str, parent: &str) -> String {
+		if let Some(name) = self.existing(schema) {
+			return name;
+		}
+		let (name, index) = self.reserve(schema, hint, parent);
+
+		let mut keys: Vec<&String> = compound.fields.keys().collect();
+		if !cfg!(feature = "preserve_order") {
+			// Keeps the output the same from one run to the next.
+			keys.sort();
+		}
+		let mut field_names: Vec<String> = Vec::new();
+		let mut fields = Vec::new();
+		for key in keys {
+			let field = &compound.fields[key];
+			let mut field_name = snake_case(key);
+			let base = field_name.clone();
+			let mut number = 2;
+			while field_names.contains(&field_name) || (compound.allow_unknown && field_name == "other") {
+				field_name = format!("{base}_{number}");
+				number += 1;
+			}
+			field_names.push(field_name.clone());
+			let field_type = self.resolve(&field.schema, key, &name);
+			fields.push((key, field_name, field_type, field.required));
+		}
+
+		let mut code = String::new();
+		// Writing to a String can't fail.
+		let _ = writeln!(code, "#[derive(Clone, Debug)]");
+		let _ = writeln!(code, "pub struct {name} {{");
+		for (_, field_name, field_type, required) in &fields {
+			if *required {
+				let _ = writeln!(code, "\tpub {field_name}: {},", field_type.name());
+			} else {
+				let _ = writeln!(code, "\tpub {field_name}: Option<{}>,", field_type.name());
+			}
+		}
+		if compound.allow_unknown {
+			let _ = writeln!(code, "\t/// Keys that aren't listed above.");
+			let _ = writeln!(code, "\tpub other: Map,");
+		}
+		let _ = writeln!(code, "}}\n");
+
+		let _ = writeln!(code, "impl EncodeNbt for {name} {{");
+		let _ = writeln!(code, "\tfn encode_nbt(self) -> Tag {{");
+		let _ = writeln!(code, "\t\tlet mut map = Map::new();");
+		for (key, field_name, field_type, required) in &fields {
+			if *required {
+				let _ = writeln!(code, "\t\tmap.insert({key:?}.to_owned(), {});", field_type.encoder(&format!("self.{field_name}")));
+			} else {
+				let _ = writeln!(code, "\t\tif let Some(value) = self.{field_name} {{");
+				let _ = writeln!(code, "\t\t\tmap.insert({key:?}.to_owned(), {});", field_type.encoder("value"));
+				let _ = writeln!(code, "\t\t}}");
+			}
+		}
+		if compound.allow_unknown {
+			let _ = writeln!(code, "\t\tmap.extend(self.other);");
+		}
+		let _ = writeln!(code, "\t\tTag::Compound(map)");
+		let _ = writeln!(code, "\t}}");
+		let _ = writeln!(code, "}}\n");
+
+		let _ = writeln!(code, "impl DecodeNbt for {name} {{");
+		let _ = writeln!(code, "\ttype Error = DecodeError;\n");
+		let _ = writeln!(code, "\tfn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {{");
+		let _ = writeln!(code, "\t\tlet mut map = codegen::compound(nbt)?;");
+		let _ = writeln!(code, "\t\tlet value = Self {{");
+		for (key, field_name, field_type, required) in &fields {
+			let function = if *required { "required" } else { "optional" };
+			let _ = writeln!(code, "\t\t\t{field_name}: codegen::{function}(&mut map, {key:?}, {})?,", field_type.decoder());
+		}
+		if compound.allow_unknown {
+			let _ = writeln!(code, "\t\t\tother: map,");
+		}
+		let _ = writeln!(code, "\t\t}};");
+		if !compound.allow_unknown {
+			let _ = writeln!(code, "\t\tcodegen::deny_unknown(map)?;");
+		}
+		let _ = writeln!(code, "\t\tOk(value)");
+		let _ = writeln!(code, "\t}}");
+		let _ = writeln!(code, "}}");
+
+		self.items[index] = code;
+		name
+	}
+}
+
+/// Splits a key into words at separators and case changes.
+/// The `minecraft:` namespace is dropped, since nearly every key would have it.
+fn words(key: &str) -> Vec<String> {
+	let key = key.strip_prefix("minecraft:").unwrap_or(key);
+	let chars: Vec<char> = key.chars().collect();
+	let mut words = Vec::new();
+	let mut word = String::new();
+	for (index, &c) in chars.iter().enumerate() {
+		if !c.is_alphanumeric() {
+			if !word.is_empty() {
+				words.push(std::mem::take(&mut word));
+			}
+			continue;
+		}
+		if let Some(&previous) = index.checked_sub(1).map(|index| &chars[index]) {
+			let next = chars.get(index + 1).copied();
+			// "dataVersion" splits before "V", and "UUIDMost" splits before "M".
+			let boundary = c.is_uppercase() && (previous.is_lowercase() || previous.is_numeric()
+				|| (previous.is_uppercase() && next.is_some_and(char::is_lowercase)));
+			if boundary && !word.is_empty() {
+				words.push(std::mem::take(&mut word));
+			}
+		}
+		word.push(c);
+	}
+	if !word.is_empty() {
+		words.push(word);
+	}
+	words
+}
+
+const KEYWORDS: [&str; 51] = [
+	"as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+	"let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+	"trait", "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box",
+	"do", "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Converts a key into a field name, such as `DataVersion` into `data_version`.
+fn snake_case(key: &str) -> String {
+	let name = words(key).join("_").to_lowercase();
+	let name = match name.chars().next() {
+		None => "field".to_owned(),
+		Some(first) if first.is_numeric() => format!("_{name}"),
+		Some(_) => name,
+	};
+	match name.as_str() {
+		// These can't be raw identifiers.
+		"self" | "super" | "crate" => format!("{name}_"),
+		keyword if KEYWORDS.contains(&keyword) => format!("r#{name}"),
+		_ => name,
+	}
+}
+
+/// Converts a key into a type name, such as `block_entities` into `BlockEntities`.
+fn pascal_case(key: &str) -> String {
+	let name: String = words(key).iter().map(|word| {
+		let mut chars = word.chars();
+		let first = chars.next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default();
+		first + &chars.as_str().to_lowercase()
+	}).collect();
+	match name.chars().next() {
+		None => "Compound".to_owned(),
+		Some(first) if first.is_numeric() => format!("T{name}"),
+		Some(_) => name,
+	}
+}
+
+/// Guesses the singular of an English plural, to name the elements of lists.
+fn singular(key: &str) -> String {
+	if let Some(stem) = key.strip_suffix("ies") {
+		format!("{stem}y")
+	} else if key.len() > 3 && key.ends_with('s') && !key.ends_with("ss") && !key.ends_with("us") {
+		key[..key.len() - 1].to_owned()
+	} else {
+		key.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn names_test() {
+		assert_eq!(snake_case("DataVersion"), "data_version");
+		assert_eq!(snake_case("UUIDMost"), "uuid_most");
+		assert_eq!(snake_case("minecraft:custom_data"), "custom_data");
+		assert_eq!(snake_case("xTile"), "x_tile");
+		assert_eq!(snake_case("type"), "r#type");
+		assert_eq!(snake_case("self"), "self_");
+		assert_eq!(snake_case("2nd"), "_2nd");
+		assert_eq!(snake_case(""), "field");
+		assert_eq!(pascal_case("block_entities"), "BlockEntities");
+		assert_eq!(pascal_case("UUID"), "Uuid");
+		assert_eq!(singular("Entities"), "Entity");
+		assert_eq!(singular("Items"), "Item");
+		assert_eq!(singular("Pos"), "Pos");
+		assert_eq!(singular("Status"), "Status");
+	}
+
+	#[test]
+	fn source_test() {
+		let inventory = Schema::list(Schema::compound()
+			.required("Slot", Schema::byte())
+			.required("id", Schema::string())
+			.optional("tag", Schema::compound()));
+		let schema = Schema::compound()
+			.required("DataVersion", Schema::int())
+			.required("Inventory", inventory.clone())
+			.required("Items", inventory)
+			.optional("Pos", Schema::list(Schema::double()))
+			.required("type", Schema::one_of(vec![Schema::int().into(), Schema::string().into(), Schema::compound().required("id", Schema::string()).into()]))
+			.required("Tag", Schema::compound().required("a", Schema::long_array()).deny_unknown())
+			.into();
+		let source = rust_source(&schema, "player");
+		for expected in [
+			"pub struct Player {",
+			"\tpub data_version: i32,",
+			"\tpub inventory: Vec<Inventory>,",
+			"\tpub items: Vec<Inventory>,",
+			"\tpub pos: Option<Vec<f64>>,",
+			"\tpub r#type: Type,",
+			"\tpub tag: PlayerTag,",
+			"\t/// Keys that aren't listed above.\n\tpub other: Map,\n}",
+			"pub struct Inventory {",
+			"\tpub tag: Option<Map>,",
+			"pub struct PlayerTag {\n\tpub a: Vec<i64>,\n}",
+			"\t\tmap.insert(\"Inventory\".to_owned(), codegen::encode_list(self.inventory.into_iter().map(|value| value.encode_nbt())));",
+			"\t\tif let Some(value) = self.pos {\n\t\t\tmap.insert(\"Pos\".to_owned(), codegen::encode_list(value.into_iter().map(|value| Tag::from(value))));\n\t\t}",
+			"\t\t\tinventory: codegen::required(&mut map, \"Inventory\", |tag| codegen::list(tag, Inventory::decode_nbt))?,",
+			"\t\tcodegen::deny_unknown(map)?;",
+			"pub enum Type {\n\tInt(i32),\n\tString(String),\n\tCompound(TypeCompound),\n}",
+			"\t\t\tSelf::Compound(value) => value.encode_nbt(),",
+			"\t\tcodegen::one_of(nbt, &[\n\t\t\t|tag| codegen::value(tag).map(Self::Int),",
+			"\t\t\t|tag| TypeCompound::decode_nbt(tag).map(Self::Compound),",
+			"pub struct TypeCompound {\n\tpub id: String,",
+		] {
+			assert!(source.contains(expected), "{expected}\n\n{source}");
+		}
+		assert!(rust_source(&Schema::list(Schema::compound().required("a", Schema::byte())).into(), "Sections")
+			.contains("pub type Sections = Vec<Section>;"));
+		let source = rust_source_for_sample(&Tag::parse("{Items: [{id: \"a\"}], Pos: [I; 1, 2, 3], self: {}}").unwrap(), "Chest");
+		for expected in ["\tpub items: Vec<Item>,", "\tpub pos: Vec<i32>,", "\tpub self_: Map,", "pub struct Item {\n\tpub id: String,"] {
+			assert!(source.contains(expected), "{expected}\n\n{source}");
+		}
+	}
+
+	#[test]
+	fn helpers_test() {
+		let mut map = match Tag::parse("{a: 1, b: [[1b], [2b, 3s]], c: {}}").unwrap() {
+			Tag::Compound(map) => map,
+			_ => unreachable!(),
+		};
+		assert_eq!(required::<i32>(&mut map, "a", value), Ok(1));
+		assert_eq!(optional::<i32>(&mut map, "a", value), Ok(None));
+		let error = required(&mut map, "b", |tag| list(tag, |tag| list::<i8>(tag, value))).unwrap_err();
+		assert_eq!(error.to_string(), "b[1][1]: Expected Byte, found Short.");
+		assert_eq!(required::<i8>(&mut map, "x", value).unwrap_err().to_string(), "x: Required key is missing.");
+		assert_eq!(deny_unknown(map).unwrap_err().to_string(), "c: Key is not allowed.");
+		assert!(matches!(encode_list([Tag::Int(1), Tag::string("a")]), Tag::List(ListTag::Compound(_))));
+		let options: [Decoder<Vec<i8>>; 2] = [
+			|tag| value::<i8>(tag).map(|value| vec![value]),
+			|tag| list(tag, value),
+		];
+		assert_eq!(one_of(Tag::Byte(1), &options), Ok(vec![1]));
+		assert_eq!(one_of(Tag::parse("[1b, 2b]").unwrap(), &options), Ok(vec![1, 2]));
+		assert_eq!(one_of(Tag::parse("[1b, 2s]").unwrap(), &options).unwrap_err().to_string(), "[1]: Expected Byte, found Short.");
+		assert_eq!(one_of(Tag::Int(1), &options).unwrap_err().to_string(), "<root>: Expected one of Byte, List, found Int.");
+	}
+}
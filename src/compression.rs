@@ -0,0 +1,261 @@
+// Minecraft stores most standalone NBT files (level.dat, player data, structures) gzipped,
+// while region files store zlib streams. Some tools write raw, uncompressed NBT.
+// Since Minecraft 1.20.5, servers can store region chunks with LZ4, using the block stream format of lz4-java,
+// where every block of up to 64 KiB has its own header and checksum.
+
+use flate2::{
+	Compression as Level,
+	read::{GzDecoder, ZlibDecoder},
+	write::{GzEncoder, ZlibEncoder},
+};
+use std::fmt::Display;
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+/// The compression applied to a stream of NBT data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Compression {
+	/// Raw, uncompressed data.
+	None,
+	/// Gzip (RFC 1952). This is what Minecraft uses for standalone `.dat` and `.nbt` files.
+	#[default]
+	Gzip,
+	/// Zlib (RFC 1950).
+	Zlib,
+	/// The LZ4 block stream of lz4-java, which Minecraft can use for region files.
+	Lz4,
+}
+
+impl Compression {
+	/// Guesses the compression of `data` from its first bytes.<br>
+	/// Anything that doesn't start with a gzip or zlib header is assumed to be uncompressed.
+	pub fn detect(data: &[u8]) -> Compression {
+		match data {
+			[0x1f, 0x8b, ..] => Compression::Gzip,
+			[b'L', b'Z', b'4', b'B', b'l', b'o', b'c', b'k', ..] => Compression::Lz4,
+			// CMF must say "deflate" (low nibble 8), and CMF/FLG must be a multiple of 31.
+			[cmf, flg, ..] if cmf & 0x0f == 8 && u16::from_be_bytes([*cmf, *flg]) % 31 == 0 => Compression::Zlib,
+			_ => Compression::None,
+		}
+	}
+
+	/// Decompresses all of `data`.
+	pub fn decompress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+		let mut buffer = Vec::new();
+		match self {
+			Compression::None => buffer.extend_from_slice(data),
+			Compression::Gzip => { GzDecoder::new(data).read_to_end(&mut buffer)?; }
+			Compression::Zlib => { ZlibDecoder::new(data).read_to_end(&mut buffer)?; }
+			Compression::Lz4 => lz4::decompress(data, &mut buffer)?,
+		}
+		Ok(buffer)
+	}
+
+	/// Compresses all of `data` using the default compression level.
+	pub fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+		match self {
+			Compression::None => Ok(data.to_vec()),
+			Compression::Gzip => {
+				let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+				encoder.write_all(data)?;
+				encoder.finish()
+			}
+			Compression::Zlib => {
+				let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+				encoder.write_all(data)?;
+				encoder.finish()
+			}
+			Compression::Lz4 => Ok(lz4::compress(data)),
+		}
+	}
+
+	/// The lowercase name of this compression, as accepted by [Compression::from_str].
+	pub const fn name(self) -> &'static str {
+		match self {
+			Compression::None => "none",
+			Compression::Gzip => "gzip",
+			Compression::Zlib => "zlib",
+			Compression::Lz4 => "lz4",
+		}
+	}
+}
+
+/// Detects the compression of `data` and decompresses it.<br>
+/// Falls back to treating `data` as uncompressed if it only looked compressed
+/// and fails to decompress, because plain data can start with bytes that resemble a zlib header.
+pub fn decompress(data: &[u8]) -> std::io::Result<(Compression, Vec<u8>)> {
+	match Compression::detect(data) {
+		Compression::Zlib => match Compression::Zlib.decompress(data) {
+			Ok(buffer) => Ok((Compression::Zlib, buffer)),
+			Err(_) => Ok((Compression::None, data.to_vec())),
+		},
+		compression => Ok((compression, compression.decompress(data)?)),
+	}
+}
+
+impl Display for Compression {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown compression `{0}`. Expected `none`, `gzip`, `zlib` or `lz4`.")]
+pub struct UnknownCompression(pub String);
+
+impl FromStr for Compression {
+	type Err = UnknownCompression;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		match name.to_ascii_lowercase().as_str() {
+			"none" | "raw" => Ok(Compression::None),
+			"gzip" | "gz" => Ok(Compression::Gzip),
+			"zlib" => Ok(Compression::Zlib),
+			"lz4" => Ok(Compression::Lz4),
+			_ => Err(UnknownCompression(name.to_owned())),
+		}
+	}
+}
+
+/// The block stream format of lz4-java's `LZ4BlockOutputStream`.
+mod lz4 {
+	use super::*;
+
+	const MAGIC: &[u8] = b"LZ4Block";
+	const BLOCK_SIZE: usize = 1 << 16;
+	/// The compression level in the low nibble of a block's token, which follows from the block size.
+	const LEVEL: u8 = 16 - 10;
+	const RAW: u8 = 0x10;
+	const LZ4: u8 = 0x20;
+	const SEED: u32 = 0x9747b28c;
+	/// The magic, token, compressed length, original length and checksum.
+	const HEADER_SIZE: usize = MAGIC.len() + 13;
+
+	fn checksum(data: &[u8]) -> u32 {
+		let mut hasher = twox_hash::XxHash32::with_seed(SEED);
+		hasher.write(data);
+		hasher.finish() as u32 & 0x0fff_ffff
+	}
+
+	fn invalid(message: &str) -> std::io::Error {
+		std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid LZ4 block: {message}"))
+	}
+
+	fn write_block(output: &mut Vec<u8>, token: u8, block: &[u8], original_len: usize, checksum: u32) {
+		output.extend_from_slice(MAGIC);
+		output.push(token | LEVEL);
+		output.extend_from_slice(&(block.len() as u32).to_le_bytes());
+		output.extend_from_slice(&(original_len as u32).to_le_bytes());
+		output.extend_from_slice(&checksum.to_le_bytes());
+		output.extend_from_slice(block);
+	}
+
+	pub(super) fn compress(data: &[u8]) -> Vec<u8> {
+		let mut output = Vec::new();
+		for block in data.chunks(BLOCK_SIZE) {
+			let compressed = lz4_flex::block::compress(block);
+			// Blocks that don't get smaller are stored as they are.
+			match compressed.len() < block.len() {
+				true => write_block(&mut output, LZ4, &compressed, block.len(), checksum(block)),
+				false => write_block(&mut output, RAW, block, block.len(), checksum(block)),
+			}
+		}
+		write_block(&mut output, RAW, &[], 0, 0);
+		output
+	}
+
+	pub(super) fn decompress(mut data: &[u8], buffer: &mut Vec<u8>) -> std::io::Result<()> {
+		// Streams end with an empty block, but lz4-java also accepts streams that just stop.
+		while !data.is_empty() {
+			let header = data.get(..HEADER_SIZE).ok_or_else(|| invalid("the header is cut off"))?;
+			if &header[..MAGIC.len()] != MAGIC {
+				return Err(invalid("the magic is missing"));
+			}
+			let token = header[MAGIC.len()];
+			let int = |at: usize| u32::from_le_bytes(header[MAGIC.len() + at..MAGIC.len() + at + 4].try_into().unwrap()) as usize;
+			let (compressed_len, original_len, expected) = (int(1), int(5), int(9) as u32);
+			if original_len == 0 {
+				return Ok(());
+			}
+			// Check the lengths before allocating anything, since they may be corrupt.
+			if original_len > 1 << (10 + (token & 0x0f)) {
+				return Err(invalid("the block is larger than its level allows"));
+			}
+			if token & 0xf0 == LZ4 && compressed_len > original_len {
+				return Err(invalid("the compressed data is longer than the original"));
+			}
+			let block = data.get(HEADER_SIZE..HEADER_SIZE + compressed_len).ok_or_else(|| invalid("the data is cut off"))?;
+			let start = buffer.len();
+			match token & 0xf0 {
+				RAW => buffer.extend_from_slice(block),
+				LZ4 => {
+					buffer.resize(start + original_len, 0);
+					let len = lz4_flex::block::decompress_into(block, &mut buffer[start..]).map_err(|error| invalid(&error.to_string()))?;
+					buffer.truncate(start + len);
+				}
+				_ => return Err(invalid("unknown compression method")),
+			}
+			if buffer.len() - start != original_len || checksum(&buffer[start..]) != expected {
+				return Err(invalid("the checksum doesn't match"));
+			}
+			data = &data[HEADER_SIZE + compressed_len..];
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip_test() {
+		let mut data = Vec::new();
+		crate::io::write_named_tag(&mut data, &crate::tag::Tag::string("Hello, world!"), "root").unwrap();
+		for compression in [Compression::None, Compression::Gzip, Compression::Zlib, Compression::Lz4] {
+			let compressed = compression.compress(&data).unwrap();
+			assert_eq!(Compression::detect(&compressed), compression);
+			assert_eq!(decompress(&compressed).unwrap(), (compression, data.clone()));
+			assert_eq!(compression.name().parse::<Compression>(), Ok(compression));
+		}
+		// "x^" looks like a zlib header, but this is plain SNBT.
+		assert_eq!(Compression::detect(b"x^2"), Compression::Zlib);
+		assert_eq!(decompress(b"x^2").unwrap(), (Compression::None, b"x^2".to_vec()));
+	}
+
+	#[test]
+	fn lz4_test() {
+		// Several blocks, one of which can't be compressed.
+		let mut data = vec![b'a'; 150_000];
+		let mut state = 1u32;
+		data.extend((0..70_000).map(|_| {
+			state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+			(state >> 16) as u8
+		}));
+		let compressed = Compression::Lz4.compress(&data).unwrap();
+		assert!(compressed.len() < 80_000);
+		assert_eq!(Compression::Lz4.decompress(&compressed).unwrap(), data);
+		// An empty stream is only the end block.
+		assert_eq!(Compression::Lz4.compress(&[]).unwrap().len(), 21);
+
+		let mut corrupt = compressed.clone();
+		corrupt[30] ^= 1;
+		assert!(Compression::Lz4.decompress(&corrupt).is_err());
+		assert!(Compression::Lz4.decompress(&compressed[..100]).is_err());
+
+		// Headers with impossible lengths are rejected before anything is allocated.
+		let header = |token: u8, compressed_len: u32, original_len: u32| {
+			let mut header = b"LZ4Block".to_vec();
+			header.push(token);
+			header.extend_from_slice(&compressed_len.to_le_bytes());
+			header.extend_from_slice(&original_len.to_le_bytes());
+			header.extend_from_slice(&[0; 4]);
+			header.extend_from_slice(&[0; 16]);
+			header
+		};
+		let error = |data: &[u8]| Compression::Lz4.decompress(data).unwrap_err().to_string();
+		assert_eq!(error(&header(0x26, 16, u32::MAX)), "Invalid LZ4 block: the block is larger than its level allows");
+		assert_eq!(error(&header(0x26, 16, 8)), "Invalid LZ4 block: the compressed data is longer than the original");
+	}
+}
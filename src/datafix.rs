@@ -0,0 +1,363 @@
+// Upgrading NBT across data versions, like Minecraft's DataFixerUpper.
+//
+// A fix is registered for a context, such as items, at the data version that introduced the change it makes.
+// Upgrading data from one version to another runs every fix in between, one version at a time.
+// A change made across several snapshots is one fix over the range of their versions, which also runs for data
+// saved inside the range, at the first version after it. Such data may already have part of the change.
+// Upgrading in several steps runs the same fixes in the same order as upgrading at once,
+// except that a fix over a range runs again in each step that starts inside it.
+// Contexts can be nested, such as items in the inventory of an entity, so that at each version,
+// the fixes of the outer data run before the fixes of the data inside it.
+
+use crate::{
+	Map,
+	codegen::DecodeError,
+	tag::{ListTag, Tag, TagID},
+};
+use std::ops::RangeInclusive;
+
+/// The kind of data that a fix applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FixContext {
+	Chunk,
+	Entity,
+	Item,
+	BlockEntity,
+	/// Data of other kinds, such as the NBT of a mod or plugin.
+	Custom(&'static str),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FixError {
+	#[error("Fix `{name}` for {context:?} at version {version} failed: {source}")]
+	Failed { name: String, context: FixContext, version: i32, source: DecodeError },
+	#[error("Fix `{name}` for {context:?} at version {version} replaced a Compound with {}.", .found.title())]
+	NotACompound { name: String, context: FixContext, version: i32, found: TagID },
+	#[error("Data can't be downgraded from version {from} to {to}.")]
+	Downgrade { from: i32, to: i32 },
+	#[error("The data has no DataVersion.")]
+	NoDataVersion,
+}
+
+type FixFn = Box<dyn Fn(&mut Tag) -> Result<(), DecodeError> + Send + Sync>;
+
+struct Fix {
+	context: FixContext,
+	versions: RangeInclusive<i32>,
+	name: String,
+	apply: FixFn,
+}
+
+impl Fix {
+	/// The version that the fix runs at when data saved at `from` is upgraded, which is the first of its versions after `from`.
+	fn step(&self, from: i32) -> Option<i32> {
+		let step = from.saturating_add(1).max(*self.versions.start());
+		self.versions.contains(&step).then_some(step)
+	}
+}
+
+/// Data of `child` inside data of `parent`, under any of `paths`.
+struct Nesting {
+	parent: FixContext,
+	child: FixContext,
+	paths: Vec<Vec<String>>,
+}
+
+/// A set of fixes, and where contexts are nested in each other.
+/// ```
+/// # use rustnbt::{datafix::{DataFixer, FixContext}, tag::Tag};
+/// let mut fixer = DataFixer::standard();
+/// fixer.register(FixContext::Item, 3000, "rename ruby", |item| {
+///     if let Tag::Compound(item) = item {
+///         if matches!(item.get("id"), Some(Tag::String(id)) if id == "mymod:ruby") {
+///             item.insert("id".to_owned(), Tag::string("mymod:red_gem"));
+///         }
+///     }
+///     Ok(())
+/// });
+/// let mut entity = Tag::parse(r#"{id: "minecraft:zombie", HandItems: [{id: "mymod:ruby", Count: 1b}, {}]}"#).unwrap();
+/// fixer.upgrade(FixContext::Entity, &mut entity, 2586, 3465).unwrap();
+/// assert!(entity.to_string().contains("mymod:red_gem"));
+/// ```
+#[derive(Default)]
+pub struct DataFixer {
+	fixes: Vec<Fix>,
+	nestings: Vec<Nesting>,
+}
+
+impl std::fmt::Debug for DataFixer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DataFixer")
+			.field("fixes", &self.fixes.iter().map(|fix| (fix.context, &fix.versions, &fix.name)).collect::<Vec<_>>())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Every compound under `path`, descending into every element of the lists along the way.
+fn compounds_at<'a>(map: &'a mut Map, path: &[String], found: &mut Vec<&'a mut Map>) {
+	let Some((key, rest)) = path.split_first() else {
+		found.push(map);
+		return;
+	};
+	match map.get_mut(key) {
+		Some(Tag::Compound(map)) => compounds_at(map, rest, found),
+		Some(Tag::List(ListTag::Compound(maps))) => {
+			for map in maps {
+				// Empty compounds are empty slots, such as in `HandItems`.
+				if !map.is_empty() {
+					compounds_at(map, rest, found);
+				}
+			}
+		}
+		_ => {}
+	}
+}
+
+impl DataFixer {
+	/// Creates a fixer without fixes or nested contexts.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a fixer without fixes, that knows where block entities, entities and items are nested in
+	/// chunks, entities, block entities and items, in both the old and new layouts of each.
+	pub fn standard() -> Self {
+		let mut fixer = Self::new();
+		fixer
+			.nest(FixContext::Chunk, FixContext::BlockEntity, &["block_entities", "Level.TileEntities"])
+			.nest(FixContext::Chunk, FixContext::Entity, &["entities", "Level.Entities", "Entities"])
+			.nest(FixContext::Entity, FixContext::Entity, &["Passengers"])
+			.nest(FixContext::Entity, FixContext::Item, &["Item", "Items", "HandItems", "ArmorItems", "Inventory", "EnderItems"])
+			.nest(FixContext::BlockEntity, FixContext::Item, &["Item", "Items", "RecordItem", "item"])
+			.nest(FixContext::Item, FixContext::Item, &["tag.BlockEntityTag.Items", "components.minecraft:container.item"]);
+		fixer
+	}
+
+	/// Registers a fix for data of `context` saved before `version`, which runs when the data is upgraded to `version` or later.
+	/// Fixes at the same version run in the order they were registered.
+	/// For a change made across several versions, use [DataFixer::register_range].
+	pub fn register<S, F>(&mut self, context: FixContext, version: i32, name: S, fix: F) -> &mut Self
+	where
+		S: Into<String>,
+		F: Fn(&mut Tag) -> Result<(), DecodeError> + Send + Sync + 'static,
+	{
+		self.register_range(context, version..=version, name, fix)
+	}
+
+	/// Registers a fix for a change made across `versions`, for data of `context` saved before the last of them.
+	/// The fix runs at the first of `versions` that the data is upgraded past, so for data saved inside the range,
+	/// it runs at the version after, and must accept data that already has part of the change.
+	pub fn register_range<S, F>(&mut self, context: FixContext, versions: RangeInclusive<i32>, name: S, fix: F) -> &mut Self
+	where
+		S: Into<String>,
+		F: Fn(&mut Tag) -> Result<(), DecodeError> + Send + Sync + 'static,
+	{
+		self.fixes.push(Fix { context, versions, name: name.into(), apply: Box::new(fix) });
+		self
+	}
+
+	/// Declares that compounds of `child` are found in compounds of `parent` under any of `paths`.
+	/// Paths are keys separated by `.`, and lists along a path are searched element by element.
+	pub fn nest(&mut self, parent: FixContext, child: FixContext, paths: &[&str]) -> &mut Self {
+		let paths = paths.iter().map(|path| path.split('.').map(str::to_owned).collect()).collect();
+		self.nestings.push(Nesting { parent, child, paths });
+		self
+	}
+
+	/// The names and versions of the fixes that upgrading data of `context` from `from` to `to` runs directly,
+	/// not counting fixes for nested contexts.
+	pub fn fixes(&self, context: FixContext, from: i32, to: i32) -> Vec<(i32, &str)> {
+		let mut fixes: Vec<_> = self.fixes.iter()
+			.filter(|fix| fix.context == context)
+			.filter_map(|fix| Some((fix.step(from)?, fix.name.as_str())))
+			.filter(|&(version, _)| version <= to)
+			.collect();
+		fixes.sort_by_key(|&(version, _)| version);
+		fixes
+	}
+
+	/// Upgrades data of `context` saved at version `from` to version `to`.
+	/// If a fix fails, the data is left partly upgraded.
+	pub fn upgrade(&self, context: FixContext, tag: &mut Tag, from: i32, to: i32) -> Result<(), FixError> {
+		if from > to {
+			return Err(FixError::Downgrade { from, to });
+		}
+		let mut versions: Vec<i32> = self.fixes.iter()
+			.filter_map(|fix| fix.step(from))
+			.filter(|&version| version <= to)
+			.collect();
+		versions.sort_unstable();
+		versions.dedup();
+		for version in versions {
+			self.apply(context, tag, from, version, false)?;
+		}
+		Ok(())
+	}
+
+	/// Upgrades a compound with a `DataVersion`, such as a chunk, to version `to`, and updates its `DataVersion`.
+	pub fn upgrade_versioned(&self, context: FixContext, tag: &mut Tag, to: i32) -> Result<(), FixError> {
+		let Tag::Compound(map) = tag else {
+			return Err(FixError::NoDataVersion);
+		};
+		let Some(&Tag::Int(from)) = map.get("DataVersion") else {
+			return Err(FixError::NoDataVersion);
+		};
+		self.upgrade(context, tag, from, to)?;
+		if let Tag::Compound(map) = tag {
+			map.insert("DataVersion".to_owned(), Tag::Int(to));
+		}
+		Ok(())
+	}
+
+	/// Runs the fixes of `context` at `version` for data saved at `from`, then the fixes of nested contexts.
+	/// Nested data must stay a compound, so that it can be put back.
+	fn apply(&self, context: FixContext, tag: &mut Tag, from: i32, version: i32, nested: bool) -> Result<(), FixError> {
+		for fix in self.fixes.iter().filter(|fix| fix.context == context && fix.step(from) == Some(version)) {
+			(fix.apply)(tag).map_err(|source| FixError::Failed { name: fix.name.clone(), context, version, source })?;
+			if nested && !matches!(tag, Tag::Compound(_)) {
+				return Err(FixError::NotACompound { name: fix.name.clone(), context, version, found: tag.id() });
+			}
+		}
+		let Tag::Compound(map) = tag else {
+			return Ok(());
+		};
+		for nesting in self.nestings.iter().filter(|nesting| nesting.parent == context) {
+			for path in &nesting.paths {
+				let mut children = Vec::new();
+				compounds_at(map, path, &mut children);
+				for child in children {
+					let mut child_tag = Tag::Compound(std::mem::take(child));
+					let result = self.apply(nesting.child, &mut child_tag, from, version, true);
+					if let Tag::Compound(map) = child_tag {
+						*child = map;
+					}
+					result?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::diff::{diff, DiffOptions};
+	use crate::path::TagPath;
+
+	/// Appends `name` to the `log` string of a compound.
+	fn log(name: &'static str) -> impl Fn(&mut Tag) -> Result<(), DecodeError> + Send + Sync {
+		move |tag| {
+			if let Tag::Compound(map) = tag {
+				let log = match map.get("log") {
+					Some(Tag::String(log)) => format!("{log},{name}"),
+					_ => name.to_owned(),
+				};
+				map.insert("log".to_owned(), Tag::String(log));
+			}
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn order_test() {
+		let mut fixer = DataFixer::standard();
+		fixer
+			.register(FixContext::Chunk, 20, "chunk 20", log("c20"))
+			.register(FixContext::Item, 10, "item 10", log("i10"))
+			.register(FixContext::Item, 20, "item 20", log("i20"))
+			.register(FixContext::BlockEntity, 10, "chest 10", log("b10"))
+			.register(FixContext::Item, 10, "item 10 again", log("i10b"))
+			.register(FixContext::Chunk, 30, "chunk 30", log("c30"));
+		assert_eq!(fixer.fixes(FixContext::Item, 10, 30), [(20, "item 20")]);
+
+		let mut chunk = Tag::parse(r#"{DataVersion: 5, block_entities: [
+			{id: "minecraft:chest", Items: [{id: "minecraft:shulker_box", tag: {BlockEntityTag: {Items: [{id: "minecraft:dirt"}]}}}]},
+		]}"#).unwrap();
+		fixer.upgrade_versioned(FixContext::Chunk, &mut chunk, 25).unwrap();
+		let get = |path: &str| path.parse::<TagPath>().unwrap().get(&chunk).unwrap().to_string();
+		assert_eq!(get("DataVersion"), "25");
+		assert_eq!(get("log"), "\"c20\"");
+		assert_eq!(get("block_entities[0].log"), "\"b10\"");
+		assert_eq!(get("block_entities[0].Items[0].log"), "\"i10,i10b,i20\"");
+		assert_eq!(get("block_entities[0].Items[0].tag.BlockEntityTag.Items[0].log"), "\"i10,i10b,i20\"");
+
+		assert!(matches!(fixer.upgrade(FixContext::Chunk, &mut chunk, 30, 20), Err(FixError::Downgrade { from: 30, to: 20 })));
+		assert!(matches!(fixer.upgrade_versioned(FixContext::Chunk, &mut Tag::Int(0), 30), Err(FixError::NoDataVersion)));
+	}
+
+	#[test]
+	fn error_test() {
+		let mut fixer = DataFixer::standard();
+		fixer
+			.register(FixContext::Item, 2, "fail", |_| Err(DecodeError::invalid("Unknown item.")))
+			.register(FixContext::Item, 3, "replace", |tag| {
+				*tag = Tag::Int(0);
+				Ok(())
+			});
+		let mut entity = Tag::parse(r#"{Item: {id: "minecraft:stone"}}"#).unwrap();
+		let error = fixer.upgrade(FixContext::Entity, &mut entity, 1, 2).unwrap_err();
+		assert_eq!(error.to_string(), "Fix `fail` for Item at version 2 failed: <root>: Unknown item.");
+		let error = fixer.upgrade(FixContext::Entity, &mut entity, 2, 3).unwrap_err();
+		assert_eq!(error.to_string(), "Fix `replace` for Item at version 3 replaced a Compound with Int.");
+		// Custom contexts have no nesting unless it is declared.
+		let mut custom = Tag::parse(r#"{Item: {id: "minecraft:stone"}}"#).unwrap();
+		fixer.upgrade(FixContext::Custom("backpack"), &mut custom, 1, 3).unwrap();
+		fixer.nest(FixContext::Custom("backpack"), FixContext::Item, &["Item"]);
+		assert!(fixer.upgrade(FixContext::Custom("backpack"), &mut custom, 1, 3).is_err());
+	}
+
+	#[test]
+	fn steps_test() {
+		let mut fixer = DataFixer::standard();
+		fixer
+			.register(FixContext::Item, 10, "item 10", log("i10"))
+			// Renames `count` over versions 15 to 20, so data from in between may already have `Count`.
+			.register_range(FixContext::Item, 15..=20, "rename count", |tag| {
+				if let Tag::Compound(map) = tag {
+					if let Some(count) = map.remove("count") {
+						map.insert("Count".to_owned(), count);
+					}
+				}
+				Ok(())
+			})
+			.register(FixContext::Item, 30, "item 30", log("i30"));
+		let source = r#"{DataVersion: 5, Item: {id: "minecraft:stone", count: 1b}}"#;
+		let mut at_once = Tag::parse(source).unwrap();
+		fixer.upgrade_versioned(FixContext::Entity, &mut at_once, 30).unwrap();
+		let mut in_steps = Tag::parse(source).unwrap();
+		for to in [12, 17, 20, 30] {
+			fixer.upgrade_versioned(FixContext::Entity, &mut in_steps, to).unwrap();
+		}
+		let same = |a: &Tag, b: &Tag| diff(a, b, &DiffOptions { ignore_order: true, ..Default::default() }).is_empty();
+		assert!(same(&at_once, &in_steps));
+		assert!(same(&at_once, &Tag::parse(r#"{DataVersion: 30, Item: {id: "minecraft:stone", Count: 1b, log: "i10,i30"}}"#).unwrap()));
+
+		let mut halfway = Tag::parse(r#"{DataVersion: 17, Item: {id: "minecraft:stone", Count: 1b, log: "i10"}}"#).unwrap();
+		fixer.upgrade_versioned(FixContext::Entity, &mut halfway, 30).unwrap();
+		assert!(same(&halfway, &at_once));
+	}
+
+	#[test]
+	fn range_test() {
+		let mut fixer = DataFixer::standard();
+		fixer
+			.register(FixContext::Item, 10, "item 10", log("i10"))
+			.register(FixContext::Item, 17, "item 17", log("i17"))
+			.register_range(FixContext::Item, 15..=20, "range", log("r"));
+		// The range fix runs at its first version, or right after the data's version if that is inside the range.
+		assert_eq!(fixer.fixes(FixContext::Item, 5, 30), [(10, "item 10"), (15, "range"), (17, "item 17")]);
+		assert_eq!(fixer.fixes(FixContext::Item, 5, 16), [(10, "item 10"), (15, "range")]);
+		// Fixes that end up at the same version run in the order they were registered.
+		assert_eq!(fixer.fixes(FixContext::Item, 16, 30), [(17, "item 17"), (17, "range")]);
+		assert_eq!(fixer.fixes(FixContext::Item, 19, 30), [(20, "range")]);
+		assert_eq!(fixer.fixes(FixContext::Item, 20, 30), []);
+
+		for (from, log) in [(5, "i10,r,i17"), (12, "r,i17"), (16, "i17,r"), (19, "r"), (20, "")] {
+			let mut entity = Tag::parse(format!(r#"{{DataVersion: {from}, Item: {{id: "minecraft:stone"}}}}"#)).unwrap();
+			fixer.upgrade_versioned(FixContext::Entity, &mut entity, 30).unwrap();
+			let found = TagPath::root().key("Item").key("log").get(&entity).map(|tag| tag.to_string()).unwrap_or_default();
+			assert_eq!(found, if log.is_empty() { String::new() } else { format!("\"{log}\"") }, "{from}");
+		}
+	}
+}
@@ -0,0 +1,278 @@
+// Structural comparison of NBT trees.
+// Changes are reported with the TagPath where they occur, so that a migration
+// that went wrong can be traced to the exact keys that were added, removed or changed.
+
+use crate::{
+	Map,
+	path::TagPath,
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+
+/// Options for [diff].
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+	/// Don't report compounds whose keys are in a different order.<br>
+	/// Key order is only known with the `preserve_order` feature, so reordering is never reported without it.
+	pub ignore_order: bool,
+	/// Subtrees that are skipped entirely. Indices must be written as non-negative numbers.
+	pub ignore_paths: Vec<TagPath>,
+	/// Float and Double values that differ by no more than this are considered equal.
+	pub epsilon: f64,
+}
+
+/// A single difference between two NBT trees.
+#[derive(Clone, Debug)]
+pub enum Change {
+	/// `value` only exists in the second tree.
+	Added { path: TagPath, value: Tag },
+	/// `value` only exists in the first tree.
+	Removed { path: TagPath, value: Tag },
+	/// The value at `path` is different, or has a different type.
+	Changed { path: TagPath, before: Tag, after: Tag },
+	/// Both compounds have the same keys in common, but in a different order.
+	/// Only the common keys are listed.
+	Reordered { path: TagPath, before: Vec<String>, after: Vec<String> },
+}
+
+impl Change {
+	/// The path of the tag that changed.
+	pub fn path(&self) -> &TagPath {
+		match self {
+			Change::Added { path, .. } => path,
+			Change::Removed { path, .. } => path,
+			Change::Changed { path, .. } => path,
+			Change::Reordered { path, .. } => path,
+		}
+	}
+}
+
+/// Compares `before` with `after`, returning every [Change] in the order they occur in the trees.<br>
+/// Lists and arrays are compared element by element.
+/// ```
+/// # use rustnbt::{compound, diff::{diff, Change, DiffOptions}, tag::Tag};
+/// let before = compound!{ ("Health", 20.0f32), ("Name", "Steve") };
+/// let after = compound!{ ("Health", 18.5f32), ("Name", "Steve") };
+/// let changes = diff(&before, &after, &DiffOptions::default());
+/// assert!(matches!(&changes[..], [Change::Changed { path, .. }] if path.to_string() == "Health"));
+/// ```
+pub fn diff(before: &Tag, after: &Tag, options: &DiffOptions) -> Vec<Change> {
+	let mut differ = Differ { options, changes: Vec::new() };
+	let root = TagPath::root();
+	if !differ.ignored(&root) {
+		differ.tag(&root, before, after);
+	}
+	differ.changes
+}
+
+struct Differ<'a> {
+	options: &'a DiffOptions,
+	changes: Vec<Change>,
+}
+
+impl Differ<'_> {
+	fn ignored(&self, path: &TagPath) -> bool {
+		self.options.ignore_paths.iter().any(|ignored| ignored == path)
+	}
+
+	fn changed(&mut self, path: &TagPath, before: Tag, after: Tag) {
+		self.changes.push(Change::Changed { path: path.clone(), before, after });
+	}
+
+	fn tag(&mut self, path: &TagPath, before: &Tag, after: &Tag) {
+		match (before, after) {
+			(Tag::Compound(before), Tag::Compound(after)) => self.compound(path, before, after),
+			(Tag::List(before), Tag::List(after)) => self.list(path, before, after),
+			(Tag::ByteArray(before), Tag::ByteArray(after)) => self.array(path, before, after, Tag::Byte),
+			(Tag::IntArray(before), Tag::IntArray(after)) => self.array(path, before, after, Tag::Int),
+			(Tag::LongArray(before), Tag::LongArray(after)) => self.array(path, before, after, Tag::Long),
+			(before, after) => {
+				if !self.same_value(before, after) {
+					self.changed(path, before.clone(), after.clone());
+				}
+			}
+		}
+	}
+
+	/// Compares two tags that aren't containers.
+	fn same_value(&self, before: &Tag, after: &Tag) -> bool {
+		let epsilon = self.options.epsilon;
+		match (before, after) {
+			(Tag::Byte(a), Tag::Byte(b)) => a == b,
+			(Tag::Short(a), Tag::Short(b)) => a == b,
+			(Tag::Int(a), Tag::Int(b)) => a == b,
+			(Tag::Long(a), Tag::Long(b)) => a == b,
+			(Tag::Float(a), Tag::Float(b)) => a.to_bits() == b.to_bits() || (*a as f64 - *b as f64).abs() <= epsilon,
+			(Tag::Double(a), Tag::Double(b)) => a.to_bits() == b.to_bits() || (a - b).abs() <= epsilon,
+			(Tag::String(a), Tag::String(b)) => a == b,
+			_ => false,
+		}
+	}
+
+	fn array<T: PartialEq + Copy>(&mut self, path: &TagPath, before: &[T], after: &[T], wrap: fn(T) -> Tag) {
+		self.sequence(path, before, after, |differ, path, &before, &after| {
+			if before != after {
+				differ.changed(path, wrap(before), wrap(after));
+			}
+		}, |&value| wrap(value));
+	}
+
+	fn compound(&mut self, path: &TagPath, before: &Map, after: &Map) {
+		for (key, value) in before.iter() {
+			let path = path.key(key.as_str());
+			if self.ignored(&path) {
+				continue;
+			}
+			match after.get(key) {
+				Some(other) => self.tag(&path, value, other),
+				None => self.changes.push(Change::Removed { path, value: value.clone() }),
+			}
+		}
+		for (key, value) in after.iter().filter(|(key, _)| !before.contains_key(key.as_str())) {
+			let path = path.key(key.as_str());
+			if !self.ignored(&path) {
+				self.changes.push(Change::Added { path, value: value.clone() });
+			}
+		}
+		if !self.options.ignore_order && cfg!(feature = "preserve_order") {
+			let common = |map: &Map, other: &Map| map.keys()
+				.filter(|key| other.contains_key(key.as_str()))
+				.cloned()
+				.collect::<Vec<_>>();
+			let (before_order, after_order) = (common(before, after), common(after, before));
+			if before_order != after_order {
+				self.changes.push(Change::Reordered { path: path.clone(), before: before_order, after: after_order });
+			}
+		}
+	}
+
+	fn list(&mut self, path: &TagPath, before: &ListTag, after: &ListTag) {
+		match (before, after) {
+			(ListTag::Compound(before), ListTag::Compound(after)) => self.sequence(path, before, after, Self::compound, |map| Tag::Compound(map.clone())),
+			(ListTag::List(before), ListTag::List(after)) => self.sequence(path, before, after, Self::list, |list| Tag::List(list.clone())),
+			(ListTag::ByteArray(before), ListTag::ByteArray(after)) => self.sequence(path, before, after, |differ, path, before, after| differ.array(path, before, after, Tag::Byte), |array| Tag::ByteArray(array.clone())),
+			(ListTag::IntArray(before), ListTag::IntArray(after)) => self.sequence(path, before, after, |differ, path, before, after| differ.array(path, before, after, Tag::Int), |array| Tag::IntArray(array.clone())),
+			(ListTag::LongArray(before), ListTag::LongArray(after)) => self.sequence(path, before, after, |differ, path, before, after| differ.array(path, before, after, Tag::Long), |array| Tag::LongArray(array.clone())),
+			(before, after) if before.id() != after.id() && !before.is_empty() && !after.is_empty() => {
+				self.changed(path, Tag::List(before.clone()), Tag::List(after.clone()));
+			}
+			// Lists of primitives, and lists where one side is empty.
+			(before, after) => {
+				for index in 0..before.len().max(after.len()) {
+					let path = path.index(index as isize);
+					if self.ignored(&path) {
+						continue;
+					}
+					match (before.get(index), after.get(index)) {
+						(Some(before), Some(after)) => self.tag(&path, &before, &after),
+						(Some(value), None) => self.changes.push(Change::Removed { path, value }),
+						(None, Some(value)) => self.changes.push(Change::Added { path, value }),
+						(None, None) => unreachable!("index is below the length of one of the lists"),
+					}
+				}
+			}
+		}
+	}
+
+	/// Compares two sequences element by element, using `to_tag` to report elements that were added or removed.
+	fn sequence<T>(
+		&mut self,
+		path: &TagPath,
+		before: &[T],
+		after: &[T],
+		compare: impl Fn(&mut Self, &TagPath, &T, &T),
+		to_tag: impl Fn(&T) -> Tag,
+	) {
+		for index in 0..before.len().max(after.len()) {
+			let path = path.index(index as isize);
+			if self.ignored(&path) {
+				continue;
+			}
+			match (before.get(index), after.get(index)) {
+				(Some(before), Some(after)) => compare(self, &path, before, after),
+				(Some(value), None) => self.changes.push(Change::Removed { path, value: to_tag(value) }),
+				(None, Some(value)) => self.changes.push(Change::Added { path, value: to_tag(value) }),
+				(None, None) => unreachable!("index is below the length of one of the sequences"),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::{write_tag, Indentation};
+
+	/// Describes the changes on single lines, sorted, because key order is unknown without `preserve_order`.
+	fn changes(before: &str, after: &str, options: &DiffOptions) -> Vec<String> {
+		let compact = |tag: &Tag| {
+			let mut text = String::new();
+			write_tag(&mut text, tag, true, Indentation::tabs()).unwrap();
+			text
+		};
+		let before = Tag::parse(before).unwrap();
+		let after = Tag::parse(after).unwrap();
+		let mut lines: Vec<String> = diff(&before, &after, options).iter().map(|change| match change {
+			Change::Added { path, value } => format!("+{path} {}", compact(value)),
+			Change::Removed { path, value } => format!("-{path} {}", compact(value)),
+			Change::Changed { path, before, after } => format!("~{path} {} {}", compact(before), compact(after)),
+			Change::Reordered { path, before, after } => format!("^{path} {before:?} {after:?}"),
+		}).collect();
+		lines.sort();
+		lines
+	}
+
+	#[test]
+	fn diff_test() {
+		let before = r#"{
+			Name: "Steve", Health: 20f, Gone: 1b, Type: 1,
+			Pos: [1.0d, 2.0d], Inventory: [{Slot: 0b}, {Slot: 1b}],
+			Heights: [L; 1L, 2L, 3L], Tags: [], Mixed: [1, 2],
+		}"#;
+		let after = r#"{
+			Name: "Alex", Health: 20f, Type: 1s,
+			Pos: [1.0d, 2.0d, 3.0d], Inventory: [{Slot: 0b, Count: 2b}],
+			Heights: [L; 1L, 5L, 3L], Tags: ["a"], Mixed: ["1", "2"], New: {},
+		}"#;
+		assert_eq!(changes(before, after, &DiffOptions::default()), [
+			"+Inventory[0].Count 2B",
+			"+New {}",
+			"+Pos[2] 3.0D",
+			"+Tags[0] \"a\"",
+			"-Gone 1B",
+			"-Inventory[1] { Slot : 1B }",
+			"~Heights[1] 2L 5L",
+			"~Mixed [1, 2] [\"1\", \"2\"]",
+			"~Name \"Steve\" \"Alex\"",
+			"~Type 1 1S",
+		]);
+		assert!(changes(before, before, &DiffOptions::default()).is_empty());
+	}
+
+	#[test]
+	fn options_test() {
+		let before = "{Time: 100L, Pos: [0.5d, 1.0d], Motion: [0.1f], Items: [{Slot: 0b, Damage: 3}]}";
+		let after = "{Time: 200L, Pos: [0.5000001d, 1.0d], Motion: [0.1000001f], Items: [{Slot: 0b, Damage: 4}]}";
+		assert_eq!(changes(before, after, &DiffOptions::default()).len(), 4);
+		let options = DiffOptions {
+			ignore_paths: vec!["Time".parse().unwrap(), "Items[0].Damage".parse().unwrap()],
+			epsilon: 1e-6,
+			..Default::default()
+		};
+		assert!(changes(before, after, &options).is_empty());
+	}
+
+	#[cfg(feature = "preserve_order")]
+	#[test]
+	fn order_test() {
+		let (before, after) = ("{a: 1, b: 2, c: 3}", "{b: 2, a: 1, d: 4}");
+		assert_eq!(changes(before, after, &DiffOptions::default()), [
+			"+d 4",
+			"-c 3",
+			"^ [\"a\", \"b\"] [\"b\", \"a\"]",
+		]);
+		assert_eq!(changes(before, after, &DiffOptions { ignore_order: true, ..Default::default() }).len(), 2);
+	}
+}
@@ -0,0 +1,336 @@
+// Typed access to the common fields of entities and block entities.
+// https://minecraft.wiki/w/Entity_format
+//
+// Entity and BlockEntity wrap the raw compound and read and write the fields they know in place,
+// so that the many fields specific to each kind of entity are kept unchanged.
+
+use crate::{
+	Map,
+	codegen::{self, DecodeError},
+	tag::{DecodeNbt, EncodeNbt, ListTag, Tag},
+	uuid::Uuid,
+};
+
+/// An entity, such as a mob, item or minecart.
+/// ```
+/// # use rustnbt::{entity::Entity, tag::{DecodeNbt, Tag}};
+/// let tag = Tag::parse("{id: \"minecraft:pig\", Pos: [1.5d, 64d, -2.5d], Saddle: 1b}").unwrap();
+/// let mut pig = Entity::decode_nbt(tag).unwrap();
+/// pig.set_pos([0.5, 70.0, 0.5]).add_tag("spawned");
+/// assert_eq!(pig.pos(), Some([0.5, 70.0, 0.5]));
+/// assert!(pig.has_tag("spawned"));
+/// assert!(pig.map().contains_key("Saddle"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Entity {
+	map: Map,
+}
+
+fn list<'a>(map: &'a Map, key: &str) -> Option<&'a ListTag> {
+	match map.get(key) {
+		Some(Tag::List(list)) => Some(list),
+		_ => None,
+	}
+}
+
+impl Entity {
+	/// An entity with only an `id`, such as `minecraft:pig`.
+	pub fn new<S: Into<String>>(id: S) -> Self {
+		let mut map = Map::new();
+		map.insert("id".to_owned(), Tag::String(id.into()));
+		Self { map }
+	}
+
+	pub fn map(&self) -> &Map {
+		&self.map
+	}
+
+	/// The raw compound, for fields that Entity doesn't have methods for.
+	pub fn map_mut(&mut self) -> &mut Map {
+		&mut self.map
+	}
+
+	pub fn into_map(self) -> Map {
+		self.map
+	}
+
+	/// The entity's ID, such as `minecraft:pig`. Passengers and entities in spawners have one,
+	/// but players don't.
+	pub fn id(&self) -> Option<&str> {
+		match self.map.get("id") {
+			Some(Tag::String(id)) => Some(id),
+			_ => None,
+		}
+	}
+
+	pub fn set_id<S: Into<String>>(&mut self, id: S) -> &mut Self {
+		self.map.insert("id".to_owned(), Tag::String(id.into()));
+		self
+	}
+
+	/// The entity's UUID, from `UUID` or from `UUIDMost` and `UUIDLeast`.
+	pub fn uuid(&self) -> Option<Uuid> {
+		Uuid::read(&self.map, "UUID")
+	}
+
+	/// Sets the UUID, keeping the legacy form if the entity already uses it.
+	pub fn set_uuid(&mut self, uuid: Uuid) -> &mut Self {
+		if self.map.contains_key("UUIDMost") && !self.map.contains_key("UUID") {
+			uuid.write_legacy(&mut self.map, "UUID");
+		} else {
+			uuid.write(&mut self.map, "UUID");
+		}
+		self
+	}
+
+	fn doubles(&self, key: &str) -> Option<[f64; 3]> {
+		match list(&self.map, key)? {
+			ListTag::Double(values) => values.as_slice().try_into().ok(),
+			_ => None,
+		}
+	}
+
+	/// The position of the entity, as x, y and z.
+	pub fn pos(&self) -> Option<[f64; 3]> {
+		self.doubles("Pos")
+	}
+
+	pub fn set_pos(&mut self, pos: [f64; 3]) -> &mut Self {
+		self.map.insert("Pos".to_owned(), Tag::List(ListTag::Double(pos.to_vec())));
+		self
+	}
+
+	/// The velocity of the entity in blocks per tick, as x, y and z.
+	pub fn motion(&self) -> Option<[f64; 3]> {
+		self.doubles("Motion")
+	}
+
+	pub fn set_motion(&mut self, motion: [f64; 3]) -> &mut Self {
+		self.map.insert("Motion".to_owned(), Tag::List(ListTag::Double(motion.to_vec())));
+		self
+	}
+
+	/// The rotation of the entity in degrees, as yaw and pitch.
+	pub fn rotation(&self) -> Option<[f32; 2]> {
+		match list(&self.map, "Rotation")? {
+			ListTag::Float(values) => values.as_slice().try_into().ok(),
+			_ => None,
+		}
+	}
+
+	pub fn set_rotation(&mut self, rotation: [f32; 2]) -> &mut Self {
+		self.map.insert("Rotation".to_owned(), Tag::List(ListTag::Float(rotation.to_vec())));
+		self
+	}
+
+	/// The entities riding this one. Each can have passengers of its own.
+	pub fn passengers(&self) -> Vec<Entity> {
+		match list(&self.map, "Passengers") {
+			Some(ListTag::Compound(maps)) => maps.iter().cloned().map(Entity::from).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Sets the passengers, removing `Passengers` if there are none.
+	pub fn set_passengers(&mut self, passengers: Vec<Entity>) -> &mut Self {
+		if passengers.is_empty() {
+			self.map.remove("Passengers");
+		} else {
+			let maps: Vec<Map> = passengers.into_iter().map(Entity::into_map).collect();
+			self.map.insert("Passengers".to_owned(), Tag::List(ListTag::from(maps)));
+		}
+		self
+	}
+
+	pub fn add_passenger(&mut self, passenger: Entity) -> &mut Self {
+		let mut passengers = self.passengers();
+		passengers.push(passenger);
+		self.set_passengers(passengers)
+	}
+
+	/// The scoreboard tags of the entity, which are set with `/tag`.
+	pub fn tags(&self) -> Vec<&str> {
+		match list(&self.map, "Tags") {
+			Some(ListTag::String(tags)) => tags.iter().map(String::as_str).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	pub fn has_tag(&self, tag: &str) -> bool {
+		self.tags().contains(&tag)
+	}
+
+	/// Adds a scoreboard tag, unless the entity already has it.
+	pub fn add_tag<S: Into<String>>(&mut self, tag: S) -> &mut Self {
+		let tag = tag.into();
+		if !self.has_tag(&tag) {
+			let mut tags: Vec<String> = self.tags().into_iter().map(str::to_owned).collect();
+			tags.push(tag);
+			self.map.insert("Tags".to_owned(), Tag::List(ListTag::String(tags)));
+		}
+		self
+	}
+
+	/// Removes a scoreboard tag, returning whether the entity had it.
+	pub fn remove_tag(&mut self, tag: &str) -> bool {
+		let had = self.has_tag(tag);
+		if had {
+			let tags: Vec<String> = self.tags().into_iter().filter(|&other| other != tag).map(str::to_owned).collect();
+			if tags.is_empty() {
+				self.map.remove("Tags");
+			} else {
+				self.map.insert("Tags".to_owned(), Tag::List(ListTag::String(tags)));
+			}
+		}
+		had
+	}
+}
+
+impl From<Map> for Entity {
+	fn from(map: Map) -> Self {
+		Self { map }
+	}
+}
+
+impl From<Entity> for Map {
+	fn from(entity: Entity) -> Self {
+		entity.map
+	}
+}
+
+impl DecodeNbt for Entity {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		codegen::compound(nbt).map(Self::from)
+	}
+}
+
+impl EncodeNbt for Entity {
+	fn encode_nbt(self) -> Tag {
+		Tag::Compound(self.map)
+	}
+}
+
+/// A block entity, such as a chest or sign, as stored in chunks.
+#[derive(Clone, Debug, Default)]
+pub struct BlockEntity {
+	map: Map,
+}
+
+impl BlockEntity {
+	/// A block entity with an `id`, such as `minecraft:chest`, at `pos`.
+	pub fn new<S: Into<String>>(id: S, pos: [i32; 3]) -> Self {
+		let mut entity = Self::default();
+		entity.map.insert("id".to_owned(), Tag::String(id.into()));
+		entity.set_pos(pos);
+		entity
+	}
+
+	pub fn map(&self) -> &Map {
+		&self.map
+	}
+
+	/// The raw compound, for fields that BlockEntity doesn't have methods for.
+	pub fn map_mut(&mut self) -> &mut Map {
+		&mut self.map
+	}
+
+	pub fn into_map(self) -> Map {
+		self.map
+	}
+
+	pub fn id(&self) -> Option<&str> {
+		match self.map.get("id") {
+			Some(Tag::String(id)) => Some(id),
+			_ => None,
+		}
+	}
+
+	/// The position of the block, from `x`, `y` and `z`.
+	pub fn pos(&self) -> Option<[i32; 3]> {
+		let coordinate = |key| match self.map.get(key) {
+			Some(&Tag::Int(value)) => Some(value),
+			_ => None,
+		};
+		Some([coordinate("x")?, coordinate("y")?, coordinate("z")?])
+	}
+
+	pub fn set_pos(&mut self, pos: [i32; 3]) -> &mut Self {
+		for (key, value) in ["x", "y", "z"].into_iter().zip(pos) {
+			self.map.insert(key.to_owned(), Tag::Int(value));
+		}
+		self
+	}
+
+	/// The block entity's custom name, as a JSON text component before Minecraft 1.21.5 and as NBT since.
+	pub fn custom_name(&self) -> Option<&Tag> {
+		self.map.get("CustomName")
+	}
+}
+
+impl From<Map> for BlockEntity {
+	fn from(map: Map) -> Self {
+		Self { map }
+	}
+}
+
+impl From<BlockEntity> for Map {
+	fn from(entity: BlockEntity) -> Self {
+		entity.map
+	}
+}
+
+impl DecodeNbt for BlockEntity {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		codegen::compound(nbt).map(Self::from)
+	}
+}
+
+impl EncodeNbt for BlockEntity {
+	fn encode_nbt(self) -> Tag {
+		Tag::Compound(self.map)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn entity_test() {
+		let source = "{id: \"minecraft:zombie\", UUIDMost: 1L, UUIDLeast: 2L, Rotation: [90f, 0f], Motion: [0d, 0d],
+			Tags: [\"a\", \"b\"], Passengers: [{id: \"minecraft:chicken\", Tags: [\"jockey\"]}]}";
+		let mut zombie = Entity::decode_nbt(Tag::parse(source).unwrap()).unwrap();
+		assert_eq!(zombie.id(), Some("minecraft:zombie"));
+		assert_eq!(zombie.uuid(), Some(Uuid::from_longs(1, 2)));
+		assert_eq!(zombie.rotation(), Some([90.0, 0.0]));
+		// Motion needs three values.
+		assert_eq!(zombie.motion(), None);
+		assert_eq!(zombie.pos(), None);
+
+		zombie.set_uuid(Uuid(3));
+		assert!(matches!(zombie.map().get("UUIDLeast"), Some(Tag::Long(3))));
+		assert!(!zombie.map().contains_key("UUID"));
+
+		let passengers = zombie.passengers();
+		assert_eq!(passengers.len(), 1);
+		assert_eq!(passengers[0].tags(), ["jockey"]);
+		zombie.add_passenger(Entity::new("minecraft:parrot"));
+		assert_eq!(zombie.passengers()[1].id(), Some("minecraft:parrot"));
+
+		assert!(zombie.remove_tag("a"));
+		assert!(!zombie.remove_tag("a"));
+		zombie.add_tag("b").add_tag("c");
+		assert_eq!(zombie.tags(), ["b", "c"]);
+		assert!(zombie.remove_tag("b") && zombie.remove_tag("c"));
+		assert!(!zombie.map().contains_key("Tags"));
+
+		let mut chest = BlockEntity::new("minecraft:chest", [1, -2, 3]);
+		chest.set_pos([4, 5, 6]);
+		assert_eq!(chest.pos(), Some([4, 5, 6]));
+		assert_eq!(chest.id(), Some("minecraft:chest"));
+	}
+}
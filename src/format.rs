@@ -26,43 +26,50 @@ pub fn write_long<W: Write>(writer: &mut W, value: Long) -> std::fmt::Result {
 }
 
 pub fn write_float<W: Write>(writer: &mut W, value: Float) -> std::fmt::Result {
-	write!(writer, "{value}F")
+	write_decimal(writer, value, 'F')
 }
 
 pub fn write_double<W: Write>(writer: &mut W, value: Double) -> std::fmt::Result {
-	write!(writer, "{value}D")
+	write_decimal(writer, value, 'D')
+}
+
+/// Writes a floating point value in a form that [crate::snbt] can read back exactly.
+/// Finite values use the shortest representation that round-trips (which may be in
+/// scientific notation), and NaN/infinity are written as `NaN`, `Infinity` and `-Infinity`.
+fn write_decimal<W: Write, F: num_traits::Float + Debug>(writer: &mut W, value: F, suffix: char) -> std::fmt::Result {
+	if value.is_nan() {
+		write!(writer, "NaN{suffix}")
+	} else if value.is_infinite() {
+		let sign = if value.is_sign_negative() { "-" } else { "" };
+		write!(writer, "{sign}Infinity{suffix}")
+	} else {
+		write!(writer, "{value:?}{suffix}")
+	}
 }
 
 macro_rules! array_writer {
-	([$prefix:ident;]: $writer:ident, $array:ident, $sameline:ident, $indentation:ident) => {
+	([$prefix:ident;]: $writer:ident, $array:ident, $sameline:ident, $indentation:ident, $func:ident) => {
 		{
-			if $array.len() > 0 {
+			if !$array.is_empty() {
 				// If there is only one item, we will write it on the same line.
 				if $sameline || $array.len() == 1{
 					write!($writer, "[{}; ", stringify!($prefix))?;
-					write!($writer, "{}", $array[0])?;
-					// $(write!($writer, "{}", stringify!($suffix))?;)?
-					// write_byte(array[0], writer)?;
+					$func($writer, $array[0])?;
 					$array[1..].iter().try_for_each(|value| {
 						write!($writer, ", ")?;
-						write!($writer, "{}", *value)?;
-						// $(write!($writer, "{}", stringify!($suffix))?;)?
-						Ok(())
+						$func($writer, *value)
 					})?;
 					write!($writer, "]")?;
 				} else {
-					write!($writer, "[{};\n", stringify!($prefix))?;
+					writeln!($writer, "[{};", stringify!($prefix))?;
 					let indent = $indentation.indent();
 					{
 						write!($writer, "{indent}")?;
-						write!($writer, "{}", $array[0])?;
-						// $(write!($writer, "{}", stringify!($suffix))?;)?
+						$func($writer, $array[0])?;
 						$array[1..].iter().try_for_each(|value| {
-							write!($writer, ",\n")?;
+							writeln!($writer, ",")?;
 							write!($writer, "{indent}")?;
-							write!($writer, "{}", *value)?;
-							// $(write!($writer, "{}", stringify!($suffix))?;)?
-							Ok(())
+							$func($writer, *value)
 						})?;
 						write!($writer, "\n{}]", $indentation)?;
 					}
@@ -76,15 +83,15 @@ macro_rules! array_writer {
 }
 
 pub fn write_bytearray<W: Write>(writer: &mut W, array: &[Byte], sameline: bool, indentation: Indentation) -> std::fmt::Result {
-	array_writer!([B;]: writer, array, sameline, indentation)
+	array_writer!([B;]: writer, array, sameline, indentation, write_byte)
 }
 
 pub fn write_intarray<W: Write>(writer: &mut W, array: &[Int], sameline: bool, indentation: Indentation) -> std::fmt::Result {
-	array_writer!([I;]: writer, array, sameline, indentation)
+	array_writer!([I;]: writer, array, sameline, indentation, write_int)
 }
 
 pub fn write_longarray<W: Write>(writer: &mut W, array: &[Long], sameline: bool, indentation: Indentation) -> std::fmt::Result {
-	array_writer!([L;]: writer, array, sameline, indentation)
+	array_writer!([L;]: writer, array, sameline, indentation, write_long)
 }
 
 pub fn write_string<W: Write>(writer: &mut W, value: &str) -> std::fmt::Result {
@@ -97,29 +104,32 @@ pub fn write_list<W: Write>(writer: &mut W, value: &ListTag, sameline: bool, ind
 	macro_rules! write_func {
 		($writer:ident, $list:ident, $sameline:ident, $indentation: ident: $func:ident($($arg:expr),*)$([$ref:tt])?) => {
 			{
+				// A list with zero or one element is always written on the same line.
+				let multiline = !$sameline && $list.len() > 1;
 				write!($writer, "[")?;
-				if !$sameline && $list.len() > 1 {
-					write!($writer, "\n")?;
+				if multiline {
+					writeln!($writer)?;
 				}
-				let last_index = $list.len() - 1;
+				let last_index = $list.len().saturating_sub(1);
 				$list.iter().enumerate().try_for_each(|(index, value)| {
-					// write_byte($writer, *value)?;
-					if !$sameline && $list.len() >= 1 {
+					if multiline {
 						write!($writer, "{}", $indentation)?;
 					}
 					$func(writer, $($ref)?value, $($arg),*)?;
 					if index != last_index {
 						write!($writer, ",")?;
-						if $sameline {
+						if !multiline {
 							write!($writer, " ")?;
 						}
 					}
-					if !$sameline && $list.len() > 1{
-						write!($writer, "\n")?;
+					if multiline {
+						writeln!($writer)?;
 					}
 					Ok(())
 				})?;
-				write!($writer, "{}", $indentation.outdent())?;
+				if multiline {
+					write!($writer, "{}", $indentation.outdent())?;
+				}
 				write!($writer, "]")
 			}	
 		}
@@ -157,12 +167,14 @@ pub fn write_compound<W: Write>(writer: &mut W, value: &crate::Map, sameline: bo
 	} else {
 		write!(writer, "{{ ")?;
 		if !sameline {
-			write!(writer, "\n")?;
+			writeln!(writer)?;
 		}
 		let last_index = value.len() - 1;
 		let indent = indentation.indent();
 		value.iter().enumerate().try_for_each(|(index, (key, tag))| {
-			write!(writer, "{}", indent)?;
+			if !sameline {
+				write!(writer, "{}", indent)?;
+			}
 			write_identifier(writer, key)?;
 			write!(writer, " : ")?;
 			match tag {
@@ -184,7 +196,7 @@ pub fn write_compound<W: Write>(writer: &mut W, value: &crate::Map, sameline: bo
 				if sameline {
 					write!(writer, " ")?;
 				} else {
-					write!(writer, "\n")?;
+					writeln!(writer)?;
 				}
 			}
 			Ok(())
@@ -219,8 +231,6 @@ pub fn write_tag<W: Write>(writer: &mut W, tag: &crate::tag::Tag, sameline: bool
 
 #[test]
 fn format_test() {
-	let mut file = std::fs::File::create("./ignore/test_output.txt").expect("Failed to open file.");
-	use crate::snbt;
 	use crate::tag::*;
 
 	let snbt = r#"
@@ -259,12 +269,21 @@ fn format_test() {
 		}
 	}
 	"#;
-	if let Ok(Tag::Compound(compound)) = Tag::parse(snbt) {
+	let tag = Tag::parse(snbt).expect("Failed to parse.");
+	for sameline in [false, true] {
 		let mut text = String::new();
-		write_compound(&mut text, &compound, false, Indentation::tabs());
-		use std::io::Write as WriteIO;
-		write!(file, "{}", text);
+		write_tag(&mut text, &tag, sameline, Indentation::tabs()).expect("Failed to format.");
+		let reparsed = Tag::parse(&text).expect("Failed to parse formatted SNBT.");
+		assert!(tests::same_tag(&tag, &reparsed), "{text}");
+		assert!(text.contains("float2 : 3.14F") && text.contains("long : 69420L"), "{text}");
 	}
+	// Compounds with one key have a predictable layout.
+	let mut text = String::new();
+	write_tag(&mut text, &Tag::parse(r#"{leaf: "This is a secret.", list: ["Hello", 'world']}"#).unwrap(), true, Indentation::tabs()).unwrap();
+	assert!(text == r#"{ leaf : "This is a secret.", list : ["Hello", "world"] }"# || text == r#"{ list : ["Hello", "world"], leaf : "This is a secret." }"#, "{text}");
+	let mut text = String::new();
+	write_tag(&mut text, &Tag::parse(r#"{nested: {leaf: "x"}}"#).unwrap(), false, Indentation::tabs()).unwrap();
+	assert_eq!(text, "{ \n\tnested : { \n\t\tleaf : \"x\"\n\t}\n}");
 }
 
 #[test]
@@ -272,10 +291,12 @@ fn arrays_test() {
 	use super::*;
 	let array: Vec<Long> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
 	let mut writer = String::new();
-	write_longarray(&mut writer, &array, false, Indentation::tabs());
-	let mut file = std::fs::File::create("./ignore/test_output.txt").expect("Failed");
-	use std::io::Write;
-	write!(file, "{}", &writer);
+	write_longarray(&mut writer, &array, false, Indentation::tabs()).unwrap();
+	let expected = format!("[L;\n{}\n]", (0..10).map(|value| format!("\t{value}L")).collect::<Vec<_>>().join(",\n"));
+	assert_eq!(writer, expected);
+	let mut writer = String::new();
+	write_bytearray(&mut writer, &[1, -2], true, Indentation::tabs()).unwrap();
+	assert_eq!(writer, "[B; 1B, -2B]");
 }
 
 // use chumsky::chain::Chain;
@@ -307,41 +328,34 @@ fn write_indent<W: Write>(buffer: &mut W, indent: usize, indent_string: &str) {
 		});
 }
 
+/// Determines whether `value` can be written as a compound key without quotes.
+/// Keys that would be read back as something other than an identifier (such as
+/// numbers, booleans, or special floats) must be quoted.
 fn is_identifier(value: &str) -> bool {
-	value.chars().try_for_each(|c| {
-		if c.is_ascii_alphanumeric() || "+-_.".contains(c) {
-			Ok(())
-		} else {
-			Err(())
-		}
-	}).is_ok()
+	let Some(first) = value.chars().next() else {
+		return false;
+	};
+	(first.is_ascii_alphabetic() || first == '_')
+	&& value.chars().all(|c| c.is_ascii_alphanumeric() || "+-_.".contains(c))
+	&& !matches!(value, "true" | "false")
+	&& !value.starts_with("NaN")
+	&& !value.starts_with("Infinity")
 }
 
+/// Writes `unescaped` with every character that can't appear verbatim inside of a
+/// double-quoted SNBT string replaced with an escape sequence understood by [crate::snbt].
 fn write_escaped_string<S: AsRef<str>, W: Write>(writer: &mut W, unescaped: S) -> std::fmt::Result {
-	// Macros make the whole world better!
-	macro_rules! match_char {
-		($buffer:expr, $input:expr; $($tok:tt => $escaped:expr),+) => {
-			match $input {
-				$(
-					$tok => write!($buffer, "{}", $escaped),
-				)+
-			}
-		};
-	}
 	unescaped.as_ref().chars().try_for_each(|ch| {
-		match_char!{writer, ch;
-			// TODO: Find out what escape sequences are supported by Minecraft.
-			'\\' => "\\\\",
-			'/' => "\\/",
-			'"' => "\\\"",
-			'\'' => "\\'",
-			'\x08' => "\\b",
-			'\x0C' => "\\f",
-			'\n' => "\\n",
-			'\r' => "\\r",
-			'\t' => "\\t",
-			'\0' => "\\0",
-			other => other
+		match ch {
+			'\\' => write!(writer, "\\\\"),
+			'"' => write!(writer, "\\\""),
+			'\x08' => write!(writer, "\\b"),
+			'\x0C' => write!(writer, "\\f"),
+			'\n' => write!(writer, "\\n"),
+			'\r' => write!(writer, "\\r"),
+			'\t' => write!(writer, "\\t"),
+			other if other.is_control() => write!(writer, "\\u{:04x}", other as u32),
+			other => writer.write_char(other),
 		}
 	})
 }
@@ -374,6 +388,10 @@ impl SpaceCount {
 			SpaceCount::Exact(count) => *count,
 		}
 	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
 }
 
 impl Default for SpaceCount {
@@ -409,6 +427,11 @@ impl Indent {
 		}
 	}
 
+	/// Returns `true` if the indent string is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
 	pub fn indentation(self) -> Indentation {
 		Indentation::new(self)
 	}
@@ -444,6 +467,11 @@ impl Indentation {
 		self.indent.len() * self.level as usize
 	}
 
+	/// Returns `true` if the indentation string is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
 	pub const fn new(indent: Indent) -> Self {
 		Self {
 			indent,
@@ -541,8 +569,10 @@ impl Display for Indent {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 	use super::*;
+	use crate::tag::Tag;
+
 	#[test]
 	fn num_width_test() {
 		assert!(num_width(0) == 1);
@@ -558,4 +588,187 @@ mod tests {
 		assert!(num_width(-123456) == 7);
 	}
 
+	/// Compares two tags for equality, treating all NaNs as equal and comparing
+	/// other floating point values bitwise so that `-0.0` is distinguished from `0.0`.
+	pub(crate) fn same_tag(lhs: &Tag, rhs: &Tag) -> bool {
+		match (lhs, rhs) {
+			(Tag::Byte(a), Tag::Byte(b)) => a == b,
+			(Tag::Short(a), Tag::Short(b)) => a == b,
+			(Tag::Int(a), Tag::Int(b)) => a == b,
+			(Tag::Long(a), Tag::Long(b)) => a == b,
+			(Tag::Float(a), Tag::Float(b)) => same_float(*a, *b),
+			(Tag::Double(a), Tag::Double(b)) => same_float(*a, *b),
+			(Tag::ByteArray(a), Tag::ByteArray(b)) => a == b,
+			(Tag::String(a), Tag::String(b)) => a == b,
+			(Tag::List(a), Tag::List(b)) => same_list(a, b),
+			(Tag::Compound(a), Tag::Compound(b)) => same_compound(a, b),
+			(Tag::IntArray(a), Tag::IntArray(b)) => a == b,
+			(Tag::LongArray(a), Tag::LongArray(b)) => a == b,
+			_ => false,
+		}
+	}
+
+	fn same_float<F: num_traits::Float>(lhs: F, rhs: F) -> bool {
+		(lhs.is_nan() && rhs.is_nan())
+		|| (lhs == rhs && lhs.is_sign_negative() == rhs.is_sign_negative())
+	}
+
+	fn same_compound(lhs: &crate::Map, rhs: &crate::Map) -> bool {
+		lhs.len() == rhs.len()
+		&& lhs.iter().all(|(key, tag)| {
+			rhs.get(key).is_some_and(|other| same_tag(tag, other))
+		})
+	}
+
+	fn same_list(lhs: &ListTag, rhs: &ListTag) -> bool {
+		fn all<T>(lhs: &[T], rhs: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+			lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(a, b)| eq(a, b))
+		}
+		match (lhs, rhs) {
+			(ListTag::Empty, ListTag::Empty) => true,
+			(ListTag::Byte(a), ListTag::Byte(b)) => a == b,
+			(ListTag::Short(a), ListTag::Short(b)) => a == b,
+			(ListTag::Int(a), ListTag::Int(b)) => a == b,
+			(ListTag::Long(a), ListTag::Long(b)) => a == b,
+			(ListTag::Float(a), ListTag::Float(b)) => all(a, b, |a, b| same_float(*a, *b)),
+			(ListTag::Double(a), ListTag::Double(b)) => all(a, b, |a, b| same_float(*a, *b)),
+			(ListTag::ByteArray(a), ListTag::ByteArray(b)) => a == b,
+			(ListTag::String(a), ListTag::String(b)) => a == b,
+			(ListTag::List(a), ListTag::List(b)) => all(a, b, same_list),
+			(ListTag::Compound(a), ListTag::Compound(b)) => all(a, b, same_compound),
+			(ListTag::IntArray(a), ListTag::IntArray(b)) => a == b,
+			(ListTag::LongArray(a), ListTag::LongArray(b)) => a == b,
+			_ => false,
+		}
+	}
+
+	fn roundtrip(tag: &Tag, sameline: bool) -> Tag {
+		let mut text = String::new();
+		write_tag(&mut text, tag, sameline, Indentation::tabs()).expect("Failed to format.");
+		match Tag::parse(&text) {
+			Ok(result) => result,
+			Err(err) => panic!("Failed to parse {text:?}: {err:?}"),
+		}
+	}
+
+	#[test]
+	fn special_floats_test() {
+		for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -0.0, 1e30, 1e-30, f32::MAX, f32::MIN_POSITIVE, 1e-45] {
+			let tag = Tag::Float(value);
+			assert!(same_tag(&tag, &roundtrip(&tag, true)), "{value:?}");
+		}
+		for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0, 1e300, 1e-300, f64::MAX, 5e-324] {
+			let tag = Tag::Double(value);
+			assert!(same_tag(&tag, &roundtrip(&tag, true)), "{value:?}");
+		}
+		let mut text = String::new();
+		write_float(&mut text, f32::NEG_INFINITY).unwrap();
+		assert_eq!(text, "-InfinityF");
+	}
+
+	#[test]
+	fn escapes_test() {
+		let value = "quote\" backslash\\ slash/ apostrophe' \x08\x0C\n\r\t nul\0 bell\x07 del\x7F 🎈";
+		let tag = Tag::String(value.to_owned());
+		assert!(same_tag(&tag, &roundtrip(&tag, true)));
+		let mut text = String::new();
+		write_string(&mut text, "\0/").unwrap();
+		assert_eq!(text, "\"\\u0000/\"");
+	}
+
+	#[test]
+	fn keys_test() {
+		let keys = ["", "true", "false", "123", "1b", "-5", "1.5", "1e5", "NaNF", "InfinityD", "has space", "a:b", "ok_key", "é"];
+		let tag = Tag::Compound(keys.iter().map(|key| (key.to_string(), Tag::Int(1))).collect());
+		assert!(same_tag(&tag, &roundtrip(&tag, true)));
+		assert!(same_tag(&tag, &roundtrip(&tag, false)));
+		assert!(is_identifier("ok_key"));
+		assert!(!is_identifier("1b"));
+	}
+
+	#[test]
+	fn empty_collections_test() {
+		let tag = crate::compound!{
+			("list", ListTag::Empty),
+			("nested", ListTag::List(vec![ListTag::Empty, ListTag::from(vec![1i32])])),
+			("bytes", Vec::<i8>::new()),
+			("ints", Vec::<i32>::new()),
+			("longs", Vec::<i64>::new()),
+			("compound", crate::Map::new())
+		};
+		assert!(same_tag(&tag, &roundtrip(&tag, true)));
+		assert!(same_tag(&tag, &roundtrip(&tag, false)));
+		// Typed empty lists have no SNBT representation other than `[]`.
+		let typed = Tag::List(ListTag::Int(vec![]));
+		assert!(same_tag(&Tag::List(ListTag::Empty), &roundtrip(&typed, true)));
+	}
+
+	mod proptests {
+		use super::*;
+		use proptest::prelude::*;
+		use proptest::collection::vec;
+
+		fn compound(element: impl Strategy<Value = Tag>) -> impl Strategy<Value = crate::Map> {
+			proptest::collection::hash_map(any::<String>(), element, 0..6)
+				.prop_map(|map| map.into_iter().collect())
+		}
+
+		fn list(element: impl Strategy<Value = Tag> + Clone + 'static) -> impl Strategy<Value = ListTag> {
+			// Empty lists are always read back as ListTag::Empty, so typed lists are never empty.
+			let leaf = prop_oneof![
+				Just(ListTag::Empty),
+				vec(any::<i8>(), 1..6).prop_map(ListTag::Byte),
+				vec(any::<i16>(), 1..6).prop_map(ListTag::Short),
+				vec(any::<i32>(), 1..6).prop_map(ListTag::Int),
+				vec(any::<i64>(), 1..6).prop_map(ListTag::Long),
+				vec(any::<f32>(), 1..6).prop_map(ListTag::Float),
+				vec(any::<f64>(), 1..6).prop_map(ListTag::Double),
+				vec(vec(any::<i8>(), 0..4), 1..4).prop_map(ListTag::ByteArray),
+				vec(any::<String>(), 1..4).prop_map(ListTag::String),
+				vec(vec(any::<i32>(), 0..4), 1..4).prop_map(ListTag::IntArray),
+				vec(vec(any::<i64>(), 0..4), 1..4).prop_map(ListTag::LongArray),
+			];
+			leaf.prop_recursive(2, 16, 4, move |inner| prop_oneof![
+				vec(inner, 1..4).prop_map(ListTag::List),
+				vec(compound(element.clone()), 1..4).prop_map(ListTag::Compound),
+			])
+		}
+
+		fn tag() -> impl Strategy<Value = Tag> {
+			let leaf = prop_oneof![
+				any::<i8>().prop_map(Tag::Byte),
+				any::<i16>().prop_map(Tag::Short),
+				any::<i32>().prop_map(Tag::Int),
+				any::<i64>().prop_map(Tag::Long),
+				any::<f32>().prop_map(Tag::Float),
+				any::<f64>().prop_map(Tag::Double),
+				vec(any::<i8>(), 0..8).prop_map(Tag::ByteArray),
+				any::<String>().prop_map(Tag::String),
+				vec(any::<i32>(), 0..8).prop_map(Tag::IntArray),
+				vec(any::<i64>(), 0..8).prop_map(Tag::LongArray),
+			];
+			leaf.prop_recursive(3, 32, 6, |inner| prop_oneof![
+				list(inner.clone()).prop_map(Tag::List),
+				compound(inner).prop_map(Tag::Compound),
+			])
+		}
+
+		proptest! {
+			#[test]
+			fn roundtrip_sameline(tag in tag()) {
+				prop_assert!(same_tag(&tag, &roundtrip(&tag, true)));
+			}
+
+			#[test]
+			fn roundtrip_pretty(tag in tag()) {
+				prop_assert!(same_tag(&tag, &roundtrip(&tag, false)));
+			}
+
+			#[test]
+			fn roundtrip_key(key in any::<String>()) {
+				let tag = Tag::Compound(crate::Map::from_iter([(key, Tag::Byte(0))]));
+				prop_assert!(same_tag(&tag, &roundtrip(&tag, true)));
+			}
+		}
+	}
 }
\ No newline at end of file
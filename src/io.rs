@@ -507,6 +507,7 @@ mod tests {
 	use crate::io::*;
 	use crate::tag::*;
 
+	#[allow(clippy::approx_constant)]
 	fn test_tag() -> Tag {
 		let byte = Tag::Byte(i8::MAX);
 		let short = Tag::Short(i16::MAX);
@@ -0,0 +1,531 @@
+// Item stacks, as stored in inventories, containers and item entities.
+// https://minecraft.wiki/w/Item_format
+//
+// Before Minecraft 1.20.5, an item is `{id, Count: byte, tag: {...}}`, where `tag` is free-form NBT.
+// Since then, it is `{id, count: int, components: {...}}`, where every component has a namespaced name and a fixed shape,
+// and the free-form NBT is kept in `minecraft:custom_data`.
+
+use crate::{
+	Map,
+	codegen::{self, DecodeError},
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+	},
+	text::TextComponent,
+};
+
+/// How an item's data is stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ItemLayout {
+	/// `Count` and `tag`, used before Minecraft 1.20.5.
+	Tag,
+	/// `count` and `components`, used since Minecraft 1.20.5.
+	/// Text is NBT, unless [ItemStack::data_version] is from before Minecraft 1.21.5, when it was JSON strings.
+	#[default]
+	Components,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Enchantment {
+	/// The enchantment's ID, such as `minecraft:sharpness`.
+	pub id: String,
+	pub level: i32,
+}
+
+/// The data version of Minecraft 1.21.5, since which enchantment components are `{id: level}` without `levels`.
+pub const FLAT_ENCHANTMENTS_VERSION: i32 = 4325;
+/// The data version of Minecraft 1.21.5, since which text components are NBT rather than JSON strings.
+pub const NBT_TEXT_VERSION: i32 = 4325;
+
+/// Tag keys and the components that replaced them, which have the same value.
+const RENAMED: [(&str, &str); 3] = [
+	("Damage", "minecraft:damage"),
+	("RepairCost", "minecraft:repair_cost"),
+	("CustomModelData", "minecraft:custom_model_data"),
+];
+
+/// An item stack in either [ItemLayout].
+/// ```
+/// # use rustnbt::{item::{ItemLayout, ItemStack}, text::TextComponent};
+/// let mut sword = ItemStack::new("minecraft:diamond_sword", 1, ItemLayout::Tag);
+/// sword.set_custom_name(Some(TextComponent::text("Excalibur"))).add_enchantment("minecraft:sharpness", 5);
+/// let (sword, _) = sword.convert(ItemLayout::Components);
+/// assert!(sword.data.contains_key("minecraft:custom_name"));
+/// assert_eq!(sword.custom_name().unwrap().plain_text(), "Excalibur");
+/// assert_eq!(sword.enchantments()[0].level, 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ItemStack {
+	pub id: String,
+	pub count: i32,
+	pub layout: ItemLayout,
+	/// The `tag` or `components` compound, depending on [ItemStack::layout].
+	pub data: Map,
+	/// Keys that aren't part of the item itself, such as `Slot`.
+	pub other: Map,
+	/// The data version of the file the item is in, which items don't store themselves.
+	/// It decides the shape of components that are created rather than edited.
+	pub data_version: Option<i32>,
+}
+
+/// Whether text is stored as JSON strings, which it is in the tag layout, and in components before Minecraft 1.21.5.
+fn json_text(layout: ItemLayout, data_version: Option<i32>) -> bool {
+	match layout {
+		ItemLayout::Tag => true,
+		ItemLayout::Components => data_version.is_some_and(|version| version < NBT_TEXT_VERSION),
+	}
+}
+
+/// Decodes a text component from a JSON string if `json` is true, and from NBT otherwise.
+/// Strings that aren't JSON are plain text.
+fn text(tag: &Tag, json: bool) -> Option<TextComponent> {
+	match tag {
+		Tag::String(source) if json => Some(TextComponent::from_json(source).unwrap_or_else(|_| TextComponent::text(source.clone()))),
+		other => TextComponent::decode_nbt(other.clone()).ok(),
+	}
+}
+
+fn encode_text(component: TextComponent, json: bool) -> Tag {
+	match json {
+		true => Tag::String(component.to_json()),
+		false => component.encode_nbt(),
+	}
+}
+
+/// Rewrites the text of `display.Name` or `display.Lore`, or of the components that replaced them,
+/// from JSON strings to NBT or back. Anything that isn't text is kept as it is.
+fn convert_text(key: &str, tag: Tag, from_json: bool, to_json: bool) -> Tag {
+	let convert = |tag: Tag| match text(&tag, from_json) {
+		Some(component) if from_json != to_json => encode_text(component, to_json),
+		_ => tag,
+	};
+	match (key, tag) {
+		("Lore", Tag::List(lines)) => Tag::List(ListTag::from_mixed(lines.into_mixed().into_iter().map(convert).collect())),
+		(_, tag) => convert(tag),
+	}
+}
+
+fn integer(tag: &Tag) -> Option<i32> {
+	match *tag {
+		Tag::Byte(value) => Some(value as i32),
+		Tag::Short(value) => Some(value as i32),
+		Tag::Int(value) => Some(value),
+		_ => None,
+	}
+}
+
+fn compound<'a>(map: &'a Map, key: &str) -> Option<&'a Map> {
+	match map.get(key) {
+		Some(Tag::Compound(map)) => Some(map),
+		_ => None,
+	}
+}
+
+/// Decodes a list of `{id, lvl}` compounds, skipping any that are malformed.
+fn legacy_enchantments(tag: &Tag) -> Vec<Enchantment> {
+	let Tag::List(ListTag::Compound(list)) = tag else {
+		return Vec::new();
+	};
+	list.iter().filter_map(|map| match (map.get("id"), map.get("lvl").and_then(integer)) {
+		(Some(Tag::String(id)), Some(level)) => Some(Enchantment { id: id.clone(), level }),
+		_ => None,
+	}).collect()
+}
+
+fn encode_legacy_enchantments(enchantments: Vec<Enchantment>) -> Tag {
+	let list = enchantments.into_iter().map(|enchantment| {
+		let mut map = Map::new();
+		map.insert("id".to_owned(), Tag::String(enchantment.id));
+		map.insert("lvl".to_owned(), Tag::Short(enchantment.level as i16));
+		map
+	}).collect::<Vec<_>>();
+	Tag::List(ListTag::from(list))
+}
+
+/// Decodes an enchantments component, which is `{levels: {id: level}}`, or just `{id: level}` since Minecraft 1.21.5.
+fn component_enchantments(tag: &Tag) -> Vec<Enchantment> {
+	let Tag::Compound(map) = tag else {
+		return Vec::new();
+	};
+	let levels = compound(map, "levels").unwrap_or(map);
+	levels.iter()
+		.filter_map(|(id, level)| Some(Enchantment { id: id.clone(), level: integer(level)? }))
+		.collect()
+}
+
+/// Sets the levels of an enchantments component in the shape it already has,
+/// keeping its other keys, such as `show_in_tooltip`.
+/// A new component is flat if `data_version` is at least [FLAT_ENCHANTMENTS_VERSION].
+fn encode_component_enchantments(component: Option<Tag>, enchantments: Vec<Enchantment>, data_version: Option<i32>) -> Tag {
+	let levels: Map = enchantments.into_iter().map(|enchantment| (enchantment.id, Tag::Int(enchantment.level))).collect();
+	let flat = match &component {
+		Some(Tag::Compound(map)) if !map.is_empty() => !map.contains_key("levels"),
+		_ => data_version.is_some_and(|version| version >= FLAT_ENCHANTMENTS_VERSION),
+	};
+	if flat {
+		return Tag::Compound(levels);
+	}
+	let mut map = match component {
+		Some(Tag::Compound(map)) => map,
+		_ => Map::new(),
+	};
+	map.insert("levels".to_owned(), Tag::Compound(levels));
+	Tag::Compound(map)
+}
+
+impl ItemStack {
+	pub fn new<S: Into<String>>(id: S, count: i32, layout: ItemLayout) -> Self {
+		Self { id: id.into(), count, layout, data: Map::new(), other: Map::new(), data_version: None }
+	}
+
+	fn display(&self) -> Option<&Map> {
+		compound(&self.data, "display")
+	}
+
+	/// Inserts or removes a key of `display`, removing `display` if it becomes empty.
+	fn set_display(&mut self, key: &str, value: Option<Tag>) {
+		let mut display = match self.data.remove("display") {
+			Some(Tag::Compound(display)) => display,
+			_ => Map::new(),
+		};
+		match value {
+			Some(value) => display.insert(key.to_owned(), value),
+			None => display.remove(key),
+		};
+		if !display.is_empty() {
+			self.data.insert("display".to_owned(), Tag::Compound(display));
+		}
+	}
+
+	/// Inserts or removes a key of [ItemStack::data].
+	fn set_data(&mut self, key: &str, value: Option<Tag>) {
+		match value {
+			Some(value) => self.data.insert(key.to_owned(), value),
+			None => self.data.remove(key),
+		};
+	}
+
+	/// The custom name, from `display.Name` or `minecraft:custom_name`.
+	pub fn custom_name(&self) -> Option<TextComponent> {
+		let json = json_text(self.layout, self.data_version);
+		match self.layout {
+			ItemLayout::Tag => text(self.display()?.get("Name")?, json),
+			ItemLayout::Components => text(self.data.get("minecraft:custom_name")?, json),
+		}
+	}
+
+	pub fn set_custom_name(&mut self, name: Option<TextComponent>) -> &mut Self {
+		let json = json_text(self.layout, self.data_version);
+		let name = name.map(|name| encode_text(name, json));
+		match self.layout {
+			ItemLayout::Tag => self.set_display("Name", name),
+			ItemLayout::Components => self.set_data("minecraft:custom_name", name),
+		}
+		self
+	}
+
+	/// The lines of lore, from `display.Lore` or `minecraft:lore`.
+	pub fn lore(&self) -> Vec<TextComponent> {
+		let lore = match self.layout {
+			ItemLayout::Tag => self.display().and_then(|display| display.get("Lore")),
+			ItemLayout::Components => self.data.get("minecraft:lore"),
+		};
+		match lore {
+			Some(Tag::List(list)) => list.clone().into_mixed().iter().filter_map(|tag| text(tag, json_text(self.layout, self.data_version))).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Replaces the lore, or removes it if `lore` is empty.
+	pub fn set_lore(&mut self, lore: Vec<TextComponent>) -> &mut Self {
+		let json = json_text(self.layout, self.data_version);
+		let lore = (!lore.is_empty()).then(|| codegen::encode_list(lore.into_iter().map(|line| encode_text(line, json))));
+		match self.layout {
+			ItemLayout::Tag => self.set_display("Lore", lore),
+			ItemLayout::Components => self.set_data("minecraft:lore", lore),
+		}
+		self
+	}
+
+	/// The enchantments, from `Enchantments` or `minecraft:enchantments`. Malformed entries are skipped.
+	pub fn enchantments(&self) -> Vec<Enchantment> {
+		match self.layout {
+			ItemLayout::Tag => self.data.get("Enchantments").map(legacy_enchantments),
+			ItemLayout::Components => self.data.get("minecraft:enchantments").map(component_enchantments),
+		}.unwrap_or_default()
+	}
+
+	/// Replaces the enchantments, or removes them if `enchantments` is empty.
+	pub fn set_enchantments(&mut self, enchantments: Vec<Enchantment>) -> &mut Self {
+		let (key, tag) = match self.layout {
+			ItemLayout::Tag => ("Enchantments", encode_legacy_enchantments(enchantments.clone())),
+			ItemLayout::Components => {
+				let component = self.data.remove("minecraft:enchantments");
+				("minecraft:enchantments", encode_component_enchantments(component, enchantments.clone(), self.data_version))
+			}
+		};
+		self.set_data(key, (!enchantments.is_empty()).then_some(tag));
+		self
+	}
+
+	/// Adds an enchantment, or changes its level if the item already has it.
+	pub fn add_enchantment<S: Into<String>>(&mut self, id: S, level: i32) -> &mut Self {
+		let id = id.into();
+		let mut enchantments = self.enchantments();
+		match enchantments.iter_mut().find(|enchantment| enchantment.id == id) {
+			Some(enchantment) => enchantment.level = level,
+			None => enchantments.push(Enchantment { id, level }),
+		}
+		self.set_enchantments(enchantments)
+	}
+
+	/// Converts the item to `layout`, returning it with the data that can't be converted.
+	/// Everything in `tag` can be converted, because keys without a component are moved to `minecraft:custom_data`.
+	/// Components without a tag key, other than `minecraft:custom_data`, are returned instead.
+	pub fn convert(self, layout: ItemLayout) -> (ItemStack, Map) {
+		if layout == self.layout {
+			return (self, Map::new());
+		}
+		let (from_json, to_json) = (json_text(self.layout, self.data_version), json_text(layout, self.data_version));
+		let mut data = self.data;
+		let mut converted = Map::new();
+		let mut unconverted = Map::new();
+		match layout {
+			ItemLayout::Components => {
+				if let Some(Tag::Compound(mut display)) = data.remove("display") {
+					for (key, component) in [("Name", "minecraft:custom_name"), ("Lore", "minecraft:lore")] {
+						if let Some(value) = display.remove(key) {
+							converted.insert(component.to_owned(), convert_text(key, value, from_json, to_json));
+						}
+					}
+					if let Some(rgb) = display.remove("color") {
+						converted.insert("minecraft:dyed_color".to_owned(), Tag::Compound(Map::from_iter([("rgb".to_owned(), rgb)])));
+					}
+					if !display.is_empty() {
+						data.insert("display".to_owned(), Tag::Compound(display));
+					}
+				}
+				for (key, component) in [("Enchantments", "minecraft:enchantments"), ("StoredEnchantments", "minecraft:stored_enchantments")] {
+					if let Some(enchantments) = data.remove(key) {
+						converted.insert(component.to_owned(), encode_component_enchantments(None, legacy_enchantments(&enchantments), self.data_version));
+					}
+				}
+				if matches!(data.remove("Unbreakable"), Some(Tag::Byte(1))) {
+					converted.insert("minecraft:unbreakable".to_owned(), Tag::Compound(Map::new()));
+				}
+				for (key, component) in RENAMED {
+					if let Some(value) = data.remove(key) {
+						converted.insert(component.to_owned(), value);
+					}
+				}
+				if !data.is_empty() {
+					converted.insert("minecraft:custom_data".to_owned(), Tag::Compound(data));
+				}
+			}
+			ItemLayout::Tag => {
+				if let Some(Tag::Compound(custom)) = data.remove("minecraft:custom_data") {
+					converted = custom;
+				}
+				let mut display = match converted.remove("display") {
+					Some(Tag::Compound(display)) => display,
+					_ => Map::new(),
+				};
+				for (key, component) in [("Name", "minecraft:custom_name"), ("Lore", "minecraft:lore")] {
+					if let Some(value) = data.remove(component) {
+						display.insert(key.to_owned(), convert_text(key, value, from_json, to_json));
+					}
+				}
+				if let Some(Tag::Compound(mut dyed)) = data.remove("minecraft:dyed_color") {
+					if let Some(rgb) = dyed.remove("rgb") {
+						display.insert("color".to_owned(), rgb);
+					}
+				}
+				if !display.is_empty() {
+					converted.insert("display".to_owned(), Tag::Compound(display));
+				}
+				for (key, component) in [("Enchantments", "minecraft:enchantments"), ("StoredEnchantments", "minecraft:stored_enchantments")] {
+					if let Some(enchantments) = data.remove(component) {
+						converted.insert(key.to_owned(), encode_legacy_enchantments(component_enchantments(&enchantments)));
+					}
+				}
+				if data.remove("minecraft:unbreakable").is_some() {
+					converted.insert("Unbreakable".to_owned(), Tag::Byte(1));
+				}
+				for (key, component) in RENAMED {
+					if let Some(value) = data.remove(component) {
+						converted.insert(key.to_owned(), value);
+					}
+				}
+				unconverted = data;
+			}
+		}
+		let item = ItemStack { id: self.id, count: self.count, layout, data: converted, other: self.other, data_version: self.data_version };
+		(item, unconverted)
+	}
+}
+
+impl DecodeNbt for ItemStack {
+	type Error = DecodeError;
+
+	/// Decodes an item in either layout, which is decided by whether it has `Count` or `tag`.
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let id = codegen::required(&mut map, "id", codegen::value)?;
+		let layout = match map.contains_key("Count") || map.contains_key("tag") {
+			true => ItemLayout::Tag,
+			false => ItemLayout::Components,
+		};
+		let (count, data) = match layout {
+			ItemLayout::Tag => (
+				codegen::optional(&mut map, "Count", codegen::value::<i8>)?.map(i32::from),
+				codegen::optional(&mut map, "tag", codegen::compound)?,
+			),
+			ItemLayout::Components => (
+				codegen::optional(&mut map, "count", codegen::value::<i32>)?,
+				codegen::optional(&mut map, "components", codegen::compound)?,
+			),
+		};
+		Ok(Self { id, count: count.unwrap_or(1), layout, data: data.unwrap_or_default(), other: map, data_version: None })
+	}
+}
+
+impl EncodeNbt for ItemStack {
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		map.insert("id".to_owned(), Tag::String(self.id));
+		let (count, data) = match self.layout {
+			ItemLayout::Tag => (("Count", Tag::Byte(self.count as i8)), "tag"),
+			ItemLayout::Components => (("count", Tag::Int(self.count)), "components"),
+		};
+		map.insert(count.0.to_owned(), count.1);
+		if !self.data.is_empty() {
+			map.insert(data.to_owned(), Tag::Compound(self.data));
+		}
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::diff::{diff, DiffOptions};
+
+	const LEGACY: &str = r#"{
+		id: "minecraft:diamond_sword", Count: 1b, Slot: 3b,
+		tag: {
+			Damage: 12, Unbreakable: 1b, HideFlags: 2,
+			display: {Name: '{"text":"Excalibur","italic":false}', Lore: ['"Pulled from a stone"'], color: 16711680},
+			Enchantments: [{id: "minecraft:sharpness", lvl: 5s}, {id: "minecraft:unbreaking", lvl: 3s}],
+		},
+	}"#;
+
+	#[test]
+	fn tag_layout_test() {
+		let mut item = ItemStack::decode_nbt(Tag::parse(LEGACY).unwrap()).unwrap();
+		assert_eq!((item.layout, item.count), (ItemLayout::Tag, 1));
+		assert_eq!(item.custom_name().unwrap().style.italic, Some(false));
+		assert_eq!(item.lore()[0].plain_text(), "Pulled from a stone");
+		assert_eq!(item.enchantments()[1], Enchantment { id: "minecraft:unbreaking".to_owned(), level: 3 });
+
+		item.set_custom_name(None).set_lore(Vec::new()).add_enchantment("minecraft:sharpness", 4);
+		// `display` is kept for its color.
+		assert!(item.display().unwrap().contains_key("color") && item.custom_name().is_none());
+		assert_eq!(item.enchantments()[0].level, 4);
+		let tag = item.encode_nbt();
+		assert!(matches!(crate::path::TagPath::root().key("tag").key("Enchantments").index(0).key("lvl").get(&tag), Ok(Tag::Short(4))));
+	}
+
+	#[test]
+	fn convert_test() {
+		let legacy = Tag::parse(LEGACY).unwrap();
+		let mut item = ItemStack::decode_nbt(legacy.clone()).unwrap();
+		// Until 1.21.5, components hold the same JSON strings as the tag.
+		item.data_version = Some(NBT_TEXT_VERSION - 1);
+		let (modern, unconverted) = item.convert(ItemLayout::Components);
+		assert!(matches!(modern.data.get("minecraft:custom_name"), Some(Tag::String(_))));
+		assert!(unconverted.is_empty());
+		assert_eq!(modern.custom_name().unwrap().plain_text(), "Excalibur");
+		assert_eq!(modern.enchantments().len(), 2);
+		assert!(matches!(modern.data.get("minecraft:damage"), Some(Tag::Int(12))));
+		assert!(compound(&modern.data, "minecraft:custom_data").unwrap().contains_key("HideFlags"));
+		let tag = modern.clone().encode_nbt();
+		assert!(matches!(crate::path::TagPath::root().key("count").get(&tag), Ok(Tag::Int(1))));
+		assert_eq!(ItemStack::decode_nbt(tag).unwrap().layout, ItemLayout::Components);
+
+		// Converting back gives the same item, except for components without a tag key,
+		// and for the order of enchantments, which are a compound in the component.
+		let mut modern = modern;
+		modern.data.insert("minecraft:rarity".to_owned(), Tag::string("epic"));
+		let (back, unconverted) = modern.convert(ItemLayout::Tag);
+		assert!(unconverted.contains_key("minecraft:rarity"));
+		let mut enchantments = back.enchantments();
+		enchantments.sort_by(|a, b| a.id.cmp(&b.id));
+		assert_eq!(enchantments, ItemStack::decode_nbt(legacy.clone()).unwrap().enchantments());
+		let options = DiffOptions { ignore_order: true, ignore_paths: vec!["tag.Enchantments".parse().unwrap()], ..Default::default() };
+		assert!(diff(&legacy, &back.encode_nbt(), &options).is_empty());
+	}
+
+	#[test]
+	fn components_test() {
+		// Since 1.21.5, text is NBT and enchantments have no `levels`.
+		let source = r#"{id: "minecraft:book", count: 2, components: {
+			"minecraft:custom_name": {text: "Notes", color: "red"},
+			"minecraft:lore": ["plain text"],
+			"minecraft:stored_enchantments": {"minecraft:mending": 1},
+			"minecraft:enchantments": {"minecraft:unbreaking": 2},
+		}}"#;
+		let mut item = ItemStack::decode_nbt(Tag::parse(source).unwrap()).unwrap();
+		assert_eq!(item.custom_name().unwrap().style.color.as_deref(), Some("red"));
+		assert_eq!(item.lore()[0].plain_text(), "plain text");
+		assert_eq!(item.enchantments(), [Enchantment { id: "minecraft:unbreaking".to_owned(), level: 2 }]);
+		// Edits keep the flat shape.
+		item.add_enchantment("minecraft:sharpness", 5);
+		let enchantments = compound(&item.data, "minecraft:enchantments").unwrap();
+		assert!(matches!(enchantments.get("minecraft:sharpness"), Some(Tag::Int(5))) && !enchantments.contains_key("levels"));
+		item.set_enchantments(Vec::new());
+		assert!(!item.data.contains_key("minecraft:enchantments"));
+		// New components follow the data version.
+		item.data_version = Some(FLAT_ENCHANTMENTS_VERSION);
+		item.add_enchantment("minecraft:mending", 1);
+		assert!(matches!(compound(&item.data, "minecraft:enchantments").unwrap().get("minecraft:mending"), Some(Tag::Int(1))));
+		item.data.remove("minecraft:enchantments");
+		item.data_version = Some(FLAT_ENCHANTMENTS_VERSION - 1);
+		item.add_enchantment("minecraft:mending", 1);
+		assert!(compound(&item.data, "minecraft:enchantments").unwrap().contains_key("levels"));
+		item.data.remove("minecraft:enchantments");
+		item.data_version = Some(NBT_TEXT_VERSION);
+		let (legacy, _) = item.convert(ItemLayout::Tag);
+		assert_eq!(legacy.count, 2);
+		assert!(matches!(legacy.data.get("StoredEnchantments"), Some(Tag::List(ListTag::Compound(list))) if list.len() == 1));
+		// Converting turns NBT text into JSON strings, and back.
+		assert_eq!(legacy.custom_name().unwrap().style.color.as_deref(), Some("red"));
+		assert!(matches!(legacy.display().unwrap().get("Lore"), Some(Tag::List(ListTag::String(lines))) if lines[0] == r#""plain text""#));
+		let (modern, _) = legacy.convert(ItemLayout::Components);
+		assert!(matches!(modern.data.get("minecraft:custom_name"), Some(Tag::Compound(_))));
+		assert_eq!(modern.lore()[0].plain_text(), "plain text");
+	}
+
+	#[test]
+	fn text_test() {
+		// Since 1.21.5, strings are literal text, even when they look like JSON.
+		let lines = ["1b", "007", "1.0", "true", r#"{"text":"x"}"#];
+		let source = format!(r#"{{id: "minecraft:paper", count: 1, components: {{"minecraft:lore": ['{}']}}}}"#, lines.join("', '"));
+		let mut item = ItemStack::decode_nbt(Tag::parse(source).unwrap()).unwrap();
+		for data_version in [None, Some(NBT_TEXT_VERSION)] {
+			item.data_version = data_version;
+			assert_eq!(item.lore().iter().map(TextComponent::plain_text).collect::<Vec<_>>(), lines);
+		}
+		// Before, they are JSON, and only strings that aren't JSON are literal.
+		item.data_version = Some(NBT_TEXT_VERSION - 1);
+		assert_eq!(item.lore().iter().map(TextComponent::plain_text).collect::<Vec<_>>(), ["1b", "007", "1.0", "true", "x"]);
+		item.set_custom_name(Some(TextComponent::text("1.0")));
+		assert!(matches!(item.data.get("minecraft:custom_name"), Some(Tag::String(json)) if json == r#""1.0""#));
+		item.data_version = None;
+		item.set_custom_name(Some(TextComponent::text("1.0")));
+		assert!(matches!(item.data.get("minecraft:custom_name"), Some(Tag::String(text)) if text == "1.0"));
+	}
+}
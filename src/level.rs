@@ -0,0 +1,347 @@
+// The level.dat file of a world, which is a gzipped compound with everything under `Data`.
+// https://minecraft.wiki/w/Java_Edition_level_format#level.dat_format
+//
+// LevelDat keeps the whole file, and reads and writes the keys it knows in place,
+// so that keys added by other versions or mods are written back unchanged.
+
+use crate::{
+	Map,
+	NbtError,
+	codegen::{DecodeError, DecodeErrorKind},
+	compression::{self, Compression},
+	io::{read_named_tag, write_named_tag},
+	path::PathSegment,
+	tag::{NamedTag, Tag, TagID},
+};
+use std::fmt::Display;
+use std::io::{Read, Write};
+
+#[derive(thiserror::Error, Debug)]
+pub enum LevelDatError {
+	#[error("{0}")]
+	Io(#[from] std::io::Error),
+	#[error("{0}")]
+	Nbt(#[from] NbtError),
+	#[error("{0}")]
+	Decode(#[from] DecodeError),
+}
+
+/// The version of Minecraft that last saved a world.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameVersion {
+	/// The data version.
+	pub id: i32,
+	/// The version's name, such as `1.20.1` or `23w31a`.
+	pub name: String,
+	/// The series of the version, which is `main` except for experimental versions.
+	pub series: String,
+	pub snapshot: bool,
+}
+
+/// A level.dat file.
+/// ```
+/// # use rustnbt::{level::LevelDat, tag::{NamedTag, Tag}};
+/// let tag = Tag::parse("{Data: {LevelName: \"world\", GameRules: {keepInventory: \"false\"}}}").unwrap();
+/// let mut level = LevelDat::try_from(NamedTag::new(tag)).unwrap();
+/// level.set_level_name("survival").set_game_rule("keepInventory", true);
+/// assert_eq!(level.level_name(), Some("survival"));
+/// assert_eq!(level.game_rule("keepInventory"), Some("true"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LevelDat {
+	/// A compound with a `Data` compound in it.
+	root: NamedTag,
+}
+
+fn string(map: &Map, key: &str) -> Option<String> {
+	match map.get(key) {
+		Some(Tag::String(value)) => Some(value.clone()),
+		_ => None,
+	}
+}
+
+fn int(map: &Map, key: &str) -> Option<i32> {
+	match map.get(key) {
+		Some(Tag::Int(value)) => Some(*value),
+		_ => None,
+	}
+}
+
+/// Returns the compound under `key`, replacing anything else there with an empty compound.
+fn compound_mut<'a>(map: &'a mut Map, key: &str) -> &'a mut Map {
+	if !matches!(map.get(key), Some(Tag::Compound(_))) {
+		map.insert(key.to_owned(), Tag::Compound(Map::new()));
+	}
+	match map.get_mut(key) {
+		Some(Tag::Compound(map)) => map,
+		_ => unreachable!("a compound was just inserted"),
+	}
+}
+
+impl LevelDat {
+	/// Reads a level.dat file, which is compressed with gzip.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, LevelDatError> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let (_, data) = compression::decompress(&data)?;
+		let root = NamedTag::from(read_named_tag(&mut data.as_slice())?);
+		Ok(Self::try_from(root)?)
+	}
+
+	/// Writes a level.dat file compressed with gzip.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), LevelDatError> {
+		let mut data = Vec::new();
+		write_named_tag(&mut data, self.root.tag(), self.root.name())?;
+		writer.write_all(&Compression::Gzip.compress(&data)?)?;
+		Ok(())
+	}
+
+	pub fn named_tag(&self) -> &NamedTag {
+		&self.root
+	}
+
+	/// The `Data` compound, which holds everything in the file.
+	pub fn data(&self) -> &Map {
+		match self.root.tag() {
+			Tag::Compound(root) => match root.get("Data") {
+				Some(Tag::Compound(data)) => data,
+				_ => unreachable!("the root has a Data compound"),
+			},
+			_ => unreachable!("the root is a compound"),
+		}
+	}
+
+	/// The `Data` compound, for keys that LevelDat doesn't have methods for.
+	pub fn data_mut(&mut self) -> &mut Map {
+		match self.root.tag_mut() {
+			Tag::Compound(root) => compound_mut(root, "Data"),
+			_ => unreachable!("the root is a compound"),
+		}
+	}
+
+	pub fn level_name(&self) -> Option<&str> {
+		match self.data().get("LevelName") {
+			Some(Tag::String(name)) => Some(name),
+			_ => None,
+		}
+	}
+
+	pub fn set_level_name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+		self.data_mut().insert("LevelName".to_owned(), Tag::String(name.into()));
+		self
+	}
+
+	pub fn data_version(&self) -> Option<i32> {
+		int(self.data(), "DataVersion")
+	}
+
+	pub fn set_data_version(&mut self, data_version: i32) -> &mut Self {
+		self.data_mut().insert("DataVersion".to_owned(), Tag::Int(data_version));
+		self
+	}
+
+	/// The version that last saved the world, which is missing before Minecraft 1.9.
+	pub fn version(&self) -> Option<GameVersion> {
+		let Some(Tag::Compound(version)) = self.data().get("Version") else {
+			return None;
+		};
+		Some(GameVersion {
+			id: int(version, "Id")?,
+			name: string(version, "Name")?,
+			series: string(version, "Series").unwrap_or_else(|| "main".to_owned()),
+			snapshot: matches!(version.get("Snapshot"), Some(Tag::Byte(1))),
+		})
+	}
+
+	/// Sets `Version`, and `DataVersion` to match.
+	pub fn set_version(&mut self, version: GameVersion) -> &mut Self {
+		self.set_data_version(version.id);
+		let map = compound_mut(self.data_mut(), "Version");
+		map.insert("Id".to_owned(), Tag::Int(version.id));
+		map.insert("Name".to_owned(), Tag::String(version.name));
+		map.insert("Series".to_owned(), Tag::String(version.series));
+		map.insert("Snapshot".to_owned(), Tag::Byte(version.snapshot as i8));
+		self
+	}
+
+	/// The world spawn, from `SpawnX`, `SpawnY` and `SpawnZ`.
+	pub fn spawn(&self) -> Option<[i32; 3]> {
+		let data = self.data();
+		Some([int(data, "SpawnX")?, int(data, "SpawnY")?, int(data, "SpawnZ")?])
+	}
+
+	pub fn set_spawn(&mut self, [x, y, z]: [i32; 3]) -> &mut Self {
+		let data = self.data_mut();
+		data.insert("SpawnX".to_owned(), Tag::Int(x));
+		data.insert("SpawnY".to_owned(), Tag::Int(y));
+		data.insert("SpawnZ".to_owned(), Tag::Int(z));
+		self
+	}
+
+	/// Every game rule and its value. Game rules are stored as strings, even when they are numbers or booleans.
+	pub fn game_rules(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+		let rules = match self.data().get("GameRules") {
+			Some(Tag::Compound(rules)) => Some(rules),
+			_ => None,
+		};
+		rules.into_iter().flatten().filter_map(|(name, value)| match value {
+			Tag::String(value) => Some((name.as_str(), value.as_str())),
+			_ => None,
+		})
+	}
+
+	pub fn game_rule(&self, name: &str) -> Option<&str> {
+		self.game_rules().find(|(rule, _)| *rule == name).map(|(_, value)| value)
+	}
+
+	/// Sets a game rule to the string form of `value`, such as `true` or `3`.
+	pub fn set_game_rule<V: Display>(&mut self, name: &str, value: V) -> &mut Self {
+		compound_mut(self.data_mut(), "GameRules").insert(name.to_owned(), Tag::String(value.to_string()));
+		self
+	}
+
+	/// The seed and dimension generators, which are only in `WorldGenSettings` since Minecraft 1.16.
+	pub fn world_gen_settings(&self) -> Option<&Map> {
+		match self.data().get("WorldGenSettings") {
+			Some(Tag::Compound(settings)) => Some(settings),
+			_ => None,
+		}
+	}
+
+	/// The `WorldGenSettings` compound, which is created if it is missing.
+	pub fn world_gen_settings_mut(&mut self) -> &mut Map {
+		compound_mut(self.data_mut(), "WorldGenSettings")
+	}
+
+	/// The world seed, from `WorldGenSettings`, or from `RandomSeed` before Minecraft 1.16.
+	pub fn seed(&self) -> Option<i64> {
+		let seed = match self.world_gen_settings() {
+			Some(settings) => settings.get("seed"),
+			None => self.data().get("RandomSeed"),
+		};
+		match seed {
+			Some(Tag::Long(seed)) => Some(*seed),
+			_ => None,
+		}
+	}
+
+	/// Sets the world seed wherever [LevelDat::seed] reads it from.
+	pub fn set_seed(&mut self, seed: i64) -> &mut Self {
+		match self.world_gen_settings().is_some() {
+			true => self.world_gen_settings_mut().insert("seed".to_owned(), Tag::Long(seed)),
+			false => self.data_mut().insert("RandomSeed".to_owned(), Tag::Long(seed)),
+		};
+		self
+	}
+
+	/// The singleplayer player, who is saved here rather than in `playerdata`.
+	pub fn player(&self) -> Option<&Map> {
+		match self.data().get("Player") {
+			Some(Tag::Compound(player)) => Some(player),
+			_ => None,
+		}
+	}
+
+	pub fn player_mut(&mut self) -> Option<&mut Map> {
+		match self.data_mut().get_mut("Player") {
+			Some(Tag::Compound(player)) => Some(player),
+			_ => None,
+		}
+	}
+
+	/// Replaces the singleplayer player, or removes it if `player` is `None`.
+	pub fn set_player(&mut self, player: Option<Map>) -> &mut Self {
+		match player {
+			Some(player) => self.data_mut().insert("Player".to_owned(), Tag::Compound(player)),
+			None => self.data_mut().remove("Player"),
+		};
+		self
+	}
+}
+
+impl TryFrom<NamedTag> for LevelDat {
+	type Error = DecodeError;
+
+	/// Wraps the root of a level.dat file, which must have a `Data` compound.
+	fn try_from(value: NamedTag) -> Result<Self, Self::Error> {
+		let wrong_type = |found: &Tag| DecodeError::new(DecodeErrorKind::WrongType { expected: TagID::Compound, found: found.id() });
+		let Tag::Compound(root) = value.tag() else {
+			return Err(wrong_type(value.tag()));
+		};
+		let error = match root.get("Data") {
+			Some(Tag::Compound(_)) => return Ok(Self { root: value }),
+			Some(other) => wrong_type(other),
+			None => DecodeError::new(DecodeErrorKind::MissingKey),
+		};
+		Err(error.within(PathSegment::Key("Data".to_owned())))
+	}
+}
+
+impl From<LevelDat> for NamedTag {
+	fn from(value: LevelDat) -> Self {
+		value.root
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample() -> LevelDat {
+		let source = r#"{Data: {
+			LevelName: "world", DataVersion: 3465, SpawnX: 16, SpawnY: 64, SpawnZ: -8, SpawnAngle: 0.0f,
+			Version: {Id: 3465, Name: "1.20.1", Series: "main", Snapshot: 0b},
+			GameRules: {doDaylightCycle: "true", randomTickSpeed: "3"},
+			WorldGenSettings: {seed: 42L, generate_features: 1b, dimensions: {}},
+			Player: {Health: 20.0f},
+			ModData: {enabled: 1b},
+		}}"#;
+		LevelDat::try_from(NamedTag::new(Tag::parse(source).unwrap())).unwrap()
+	}
+
+	#[test]
+	fn read_test() {
+		let level = sample();
+		assert_eq!(level.level_name(), Some("world"));
+		assert_eq!(level.data_version(), Some(3465));
+		assert_eq!(level.version().unwrap(), GameVersion { id: 3465, name: "1.20.1".to_owned(), series: "main".to_owned(), snapshot: false });
+		assert_eq!(level.spawn(), Some([16, 64, -8]));
+		assert_eq!(level.game_rule("randomTickSpeed"), Some("3"));
+		assert_eq!(level.game_rules().count(), 2);
+		assert_eq!(level.seed(), Some(42));
+		assert!(level.player().unwrap().contains_key("Health"));
+
+		let old = Tag::parse("{Data: {RandomSeed: 7L}}").unwrap();
+		assert_eq!(LevelDat::try_from(NamedTag::new(old)).unwrap().seed(), Some(7));
+		let error = |source: &str| LevelDat::try_from(NamedTag::new(Tag::parse(source).unwrap())).unwrap_err().to_string();
+		assert_eq!(error("{}"), "Data: Required key is missing.");
+		assert_eq!(error("{Data: 1}"), "Data: Expected Compound, found Int.");
+	}
+
+	#[test]
+	fn write_test() {
+		let mut level = sample();
+		level
+			.set_level_name("renamed")
+			.set_spawn([0, 70, 0])
+			.set_game_rule("randomTickSpeed", 10)
+			.set_game_rule("keepInventory", true)
+			.set_seed(-1)
+			.set_version(GameVersion { id: 3700, name: "24w03a".to_owned(), series: "main".to_owned(), snapshot: true })
+			.set_player(None);
+
+		let mut file = Vec::new();
+		level.write(&mut file).unwrap();
+		let read = LevelDat::read(&mut file.as_slice()).unwrap();
+		assert_eq!(read.level_name(), Some("renamed"));
+		assert_eq!(read.spawn(), Some([0, 70, 0]));
+		assert_eq!(read.game_rule("randomTickSpeed"), Some("10"));
+		assert_eq!(read.game_rule("keepInventory"), Some("true"));
+		assert_eq!(read.seed(), Some(-1));
+		assert_eq!((read.data_version(), read.version().unwrap().snapshot), (Some(3700), true));
+		assert!(read.player().is_none());
+		// Keys without methods are kept.
+		assert!(read.data().contains_key("ModData") && read.data().contains_key("SpawnAngle"));
+		assert!(read.world_gen_settings().unwrap().contains_key("generate_features"));
+	}
+}
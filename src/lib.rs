@@ -5,6 +5,30 @@ pub(crate) mod table;
 pub mod tag;
 pub mod macros;
 pub mod snbt;
+pub mod path;
+pub mod compression;
+pub mod diff;
+pub mod schema;
+pub mod codegen;
+pub mod packed;
+pub mod chunk;
+pub mod schematic;
+pub mod structure;
+pub mod litematic;
+pub mod mcedit;
+pub mod level;
+pub mod text;
+pub mod item;
+pub mod datafix;
+pub mod uuid;
+pub mod entity;
+pub mod player;
+pub mod region;
+pub mod world;
+pub mod maintenance;
+mod snbtreader;
+#[cfg(test)]
+mod testutil;
 pub mod tagtype;
 // format is incomplete, and I have no need to finish it, so it will remain incomplete until it is needed.
 pub mod format;
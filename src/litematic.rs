@@ -0,0 +1,486 @@
+// Litematica schematics (.litematic).
+// https://github.com/maruohon/litematica
+//
+// A schematic holds any number of named regions, each a box of blocks placed relative to the schematic's origin.
+// A region's size can be negative on any axis, in which case the box extends from `Position` in the negative direction.
+// Blocks are indices into the region's palette, packed into a LongArray with entries spanning longs,
+// and ordered by y, then z, then x, starting from the region's lowest corner.
+
+use crate::{
+	Map,
+	MapType,
+	chunk::{self, BlockState, Paletted},
+	codegen::{self, DecodeError},
+	compression::{self, Compression},
+	io::{read_named_tag, write_named_tag},
+	packed::Layout,
+	path::PathSegment,
+	schematic::SchematicError,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+	},
+};
+use std::io::{Read, Write};
+
+/// The format version that new schematics are written in, used by Litematica for Minecraft 1.17 to 1.20.4.
+pub const VERSION: i32 = 6;
+/// Indices are always at least 2 bits wide.
+const MIN_BITS: u8 = 2;
+
+/// Information about a schematic. The region count, volume, block count and enclosing size
+/// are worked out from the regions when the schematic is encoded.
+#[derive(Clone, Debug, Default)]
+pub struct Metadata {
+	pub name: String,
+	pub author: String,
+	pub description: String,
+	/// Milliseconds since the Unix epoch.
+	pub time_created: i64,
+	/// Milliseconds since the Unix epoch.
+	pub time_modified: i64,
+	/// Other keys, such as `PreviewImageData`.
+	pub other: Map,
+}
+
+/// A block entity, such as the contents of a chest.
+#[derive(Clone, Debug)]
+pub struct TileEntity {
+	/// The position of the block, relative to the region's lowest corner.
+	pub pos: [i32; 3],
+	/// The rest of the block entity's NBT.
+	pub data: Map,
+}
+
+/// A block or fluid update that was scheduled when the region was saved.
+#[derive(Clone, Debug)]
+pub struct PendingTick {
+	/// The position of the block, relative to the region's lowest corner.
+	pub pos: [i32; 3],
+	/// The block or fluid that scheduled the tick.
+	pub target: String,
+	pub priority: i32,
+	/// The order of ticks scheduled for the same game tick.
+	pub sub_tick: i64,
+	/// The number of game ticks until the tick runs.
+	pub time: i32,
+	pub other: Map,
+}
+
+/// A box of blocks in a [Litematic].
+/// ```
+/// # use rustnbt::{chunk::BlockState, litematic::Region};
+/// let mut region = Region::new([10, 0, 0], [-3, 2, 1]);
+/// region.set_block(0, 1, 0, BlockState::new("minecraft:stone"));
+/// assert_eq!(region.corners(), ([8, 0, 0], [10, 1, 0]));
+/// assert_eq!(region.block_at(0, 1, 0).unwrap().name, "minecraft:stone");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Region {
+	/// The corner that the region was selected from, relative to the schematic's origin.
+	pub position: [i32; 3],
+	size: [i32; 3],
+	blocks: Paletted<BlockState>,
+	pub tile_entities: Vec<TileEntity>,
+	/// Entities, whose positions are relative to the region's lowest corner.
+	pub entities: Vec<Map>,
+	pub block_ticks: Vec<PendingTick>,
+	pub fluid_ticks: Vec<PendingTick>,
+	/// Keys that aren't modeled.
+	pub other: Map,
+}
+
+impl Region {
+	/// Creates a region filled with air.
+	pub fn new(position: [i32; 3], size: [i32; 3]) -> Self {
+		let len = size.iter().map(|size| size.unsigned_abs() as usize).product();
+		Self {
+			position,
+			size,
+			blocks: Paletted::filled(len, BlockState::air(), MIN_BITS, Layout::Spanning),
+			tile_entities: Vec::new(),
+			entities: Vec::new(),
+			block_ticks: Vec::new(),
+			fluid_ticks: Vec::new(),
+			other: Map::new(),
+		}
+	}
+
+	/// The size of the region, which is negative on axes where it extends from [Region::position] in the negative direction.
+	pub fn size(&self) -> [i32; 3] {
+		self.size
+	}
+
+	/// The number of blocks along each axis.
+	pub fn dimensions(&self) -> [usize; 3] {
+		self.size.map(|size| size.unsigned_abs() as usize)
+	}
+
+	/// The lowest and highest corners of the region, relative to the schematic's origin.
+	pub fn corners(&self) -> ([i32; 3], [i32; 3]) {
+		let mut low = [0; 3];
+		let mut high = [0; 3];
+		for axis in 0..3 {
+			let end = self.position[axis] + self.size[axis] - self.size[axis].signum();
+			low[axis] = self.position[axis].min(end);
+			high[axis] = self.position[axis].max(end);
+		}
+		(low, high)
+	}
+
+	/// The block states that blocks refer to. Some of them may be unused.
+	pub fn palette(&self) -> &[BlockState] {
+		self.blocks.palette()
+	}
+
+	fn index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+		let [width, height, length] = self.dimensions();
+		(x < width && y < height && z < length).then(|| (y * length + z) * width + x)
+	}
+
+	/// Returns the block at a position relative to the region's lowest corner, or `None` if it is outside.
+	pub fn block_at(&self, x: usize, y: usize, z: usize) -> Option<&BlockState> {
+		self.blocks.get(self.index(x, y, z)?)
+	}
+
+	/// Replaces the block at a position relative to the region's lowest corner, returning the old one.
+	/// # Panics
+	/// Panics if the position is outside the region.
+	pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: BlockState) -> BlockState {
+		let index = self.index(x, y, z)
+			.unwrap_or_else(|| panic!("({x}, {y}, {z}) is outside a region of size {:?}", self.dimensions()));
+		self.blocks.set(index, state)
+	}
+
+	/// Every position in the region with its block, ordered by y, then z, then x.
+	pub fn blocks(&self) -> impl Iterator<Item = ([usize; 3], &BlockState)> + '_ {
+		let [width, _, length] = self.dimensions();
+		self.blocks.iter().enumerate().map(move |(index, state)| {
+			([index % width, index / (width * length), index / width % length], state)
+		})
+	}
+
+	/// The number of blocks that aren't air.
+	pub fn block_count(&self) -> usize {
+		self.blocks.iter().filter(|state| state.name != "minecraft:air").count()
+	}
+
+	/// Removes palette entries that no block uses.
+	pub fn compact(&mut self) {
+		self.blocks.compact();
+	}
+}
+
+/// A Litematica schematic.
+/// ```
+/// # use rustnbt::{chunk::BlockState, litematic::{Litematic, Region}};
+/// let mut litematic = Litematic::new(3465);
+/// litematic.metadata.name = "hut".to_owned();
+/// let mut region = Region::new([0, 0, 0], [2, 2, 2]);
+/// region.set_block(1, 0, 1, BlockState::new("minecraft:oak_planks"));
+/// litematic.regions.insert("hut".to_owned(), region);
+/// let mut file = Vec::new();
+/// litematic.write(&mut file).unwrap();
+/// let read = Litematic::read(&mut file.as_slice()).unwrap();
+/// assert_eq!(read.regions["hut"].block_count(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Litematic {
+	/// The format version, from 4 to 7.
+	pub version: i32,
+	/// The minor format version, written by Litematica since version 7.
+	pub sub_version: Option<i32>,
+	pub data_version: i32,
+	pub metadata: Metadata,
+	pub regions: MapType<String, Region>,
+	/// Keys that aren't modeled.
+	pub other: Map,
+}
+
+impl Litematic {
+	/// Creates an empty schematic in [VERSION].
+	pub fn new(data_version: i32) -> Self {
+		Self {
+			version: VERSION,
+			sub_version: None,
+			data_version,
+			metadata: Metadata::default(),
+			regions: MapType::new(),
+			other: Map::new(),
+		}
+	}
+
+	/// Reads a schematic file, which is compressed with gzip.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, SchematicError> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let (_, data) = compression::decompress(&data)?;
+		let (_, tag) = read_named_tag(&mut data.as_slice())?;
+		Ok(Self::decode_nbt(tag)?)
+	}
+
+	/// Writes a schematic file compressed with gzip.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), SchematicError> {
+		let mut data = Vec::new();
+		write_named_tag(&mut data, &self.clone().encode_nbt(), "")?;
+		writer.write_all(&Compression::Gzip.compress(&data)?)?;
+		Ok(())
+	}
+
+	/// The size of the smallest box that holds every region.
+	pub fn enclosing_size(&self) -> [i32; 3] {
+		let mut corners = self.regions.values().map(Region::corners);
+		let Some((mut low, mut high)) = corners.next() else {
+			return [0; 3];
+		};
+		for (region_low, region_high) in corners {
+			for axis in 0..3 {
+				low[axis] = low[axis].min(region_low[axis]);
+				high[axis] = high[axis].max(region_high[axis]);
+			}
+		}
+		[0, 1, 2].map(|axis| high[axis] - low[axis] + 1)
+	}
+}
+
+fn decode_xyz(tag: Tag) -> Result<[i32; 3], DecodeError> {
+	take_xyz(&mut codegen::compound(tag)?)
+}
+
+fn encode_xyz(map: &mut Map, [x, y, z]: [i32; 3]) {
+	map.insert("x".to_owned(), Tag::Int(x));
+	map.insert("y".to_owned(), Tag::Int(y));
+	map.insert("z".to_owned(), Tag::Int(z));
+}
+
+fn xyz(pos: [i32; 3]) -> Tag {
+	let mut map = Map::new();
+	encode_xyz(&mut map, pos);
+	Tag::Compound(map)
+}
+
+/// Decodes pending ticks, whose target is under `key`.
+fn decode_ticks(tag: Tag, key: &str) -> Result<Vec<PendingTick>, DecodeError> {
+	codegen::list(tag, |tag| {
+		let mut map = codegen::compound(tag)?;
+		let target = codegen::required(&mut map, key, codegen::value)?;
+		let priority = codegen::optional(&mut map, "Priority", codegen::value)?.unwrap_or_default();
+		let sub_tick = codegen::optional(&mut map, "SubTick", codegen::value)?.unwrap_or_default();
+		let time = codegen::required(&mut map, "Time", codegen::value)?;
+		let pos = take_xyz(&mut map)?;
+		Ok(PendingTick { pos, target, priority, sub_tick, time, other: map })
+	})
+}
+
+fn encode_ticks(ticks: Vec<PendingTick>, key: &str) -> Tag {
+	codegen::encode_list(ticks.into_iter().map(|tick| {
+		let mut map = tick.other;
+		encode_xyz(&mut map, tick.pos);
+		map.insert(key.to_owned(), Tag::String(tick.target));
+		map.insert("Priority".to_owned(), Tag::Int(tick.priority));
+		map.insert("SubTick".to_owned(), Tag::Long(tick.sub_tick));
+		map.insert("Time".to_owned(), Tag::Int(tick.time));
+		Tag::Compound(map)
+	}))
+}
+
+/// Removes `x`, `y` and `z` from a compound, leaving the rest of it.
+fn take_xyz(map: &mut Map) -> Result<[i32; 3], DecodeError> {
+	let x = codegen::required(map, "x", codegen::value)?;
+	let y = codegen::required(map, "y", codegen::value)?;
+	let z = codegen::required(map, "z", codegen::value)?;
+	Ok([x, y, z])
+}
+
+impl DecodeNbt for Region {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let position = codegen::required(&mut map, "Position", decode_xyz)?;
+		let size = codegen::required(&mut map, "Size", decode_xyz)?;
+		let len = size.iter().map(|size| size.unsigned_abs() as usize).product();
+		let palette = codegen::required(&mut map, "BlockStatePalette", |tag| codegen::list(tag, BlockState::decode_nbt))?;
+		let data = codegen::required(&mut map, "BlockStates", codegen::any)?;
+		let blocks = chunk::decode_paletted(palette, Some(data), len, MIN_BITS, Layout::Spanning, "BlockStates")?;
+		let tile_entities = codegen::optional(&mut map, "TileEntities", |tag| codegen::list(tag, |tag| {
+			let mut data = codegen::compound(tag)?;
+			let pos = take_xyz(&mut data)?;
+			Ok(TileEntity { pos, data })
+		}))?.unwrap_or_default();
+		let entities = codegen::optional(&mut map, "Entities", |tag| codegen::list(tag, codegen::compound))?.unwrap_or_default();
+		let block_ticks = codegen::optional(&mut map, "PendingBlockTicks", |tag| decode_ticks(tag, "Block"))?.unwrap_or_default();
+		let fluid_ticks = codegen::optional(&mut map, "PendingFluidTicks", |tag| decode_ticks(tag, "Fluid"))?.unwrap_or_default();
+		Ok(Self { position, size, blocks, tile_entities, entities, block_ticks, fluid_ticks, other: map })
+	}
+}
+
+impl EncodeNbt for Region {
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		map.insert("Position".to_owned(), xyz(self.position));
+		map.insert("Size".to_owned(), xyz(self.size));
+		let states = self.blocks.packed();
+		let palette = self.blocks.palette().iter().cloned().map(BlockState::encode_nbt);
+		map.insert("BlockStatePalette".to_owned(), codegen::encode_list(palette));
+		map.insert("BlockStates".to_owned(), states.into());
+		let tile_entities = self.tile_entities.into_iter().map(|entity| {
+			let mut data = entity.data;
+			encode_xyz(&mut data, entity.pos);
+			data
+		}).collect::<Vec<_>>();
+		map.insert("TileEntities".to_owned(), Tag::List(ListTag::from(tile_entities)));
+		map.insert("Entities".to_owned(), Tag::List(ListTag::from(self.entities)));
+		map.insert("PendingBlockTicks".to_owned(), encode_ticks(self.block_ticks, "Block"));
+		map.insert("PendingFluidTicks".to_owned(), encode_ticks(self.fluid_ticks, "Fluid"));
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+impl DecodeNbt for Litematic {
+	type Error = DecodeError;
+
+	/// Decodes the root compound of a schematic file.
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let version = codegen::required(&mut map, "Version", |tag| match codegen::value::<i32>(tag)? {
+			version @ 4..=7 => Ok(version),
+			version => Err(DecodeError::invalid(format!("Version {version} isn't supported. Only versions 4 to 7 are."))),
+		})?;
+		let sub_version = codegen::optional(&mut map, "SubVersion", codegen::value)?;
+		let data_version = codegen::required(&mut map, "MinecraftDataVersion", codegen::value)?;
+		let metadata = codegen::required(&mut map, "Metadata", |tag| {
+			let mut map = codegen::compound(tag)?;
+			let text = |map: &mut Map, key| codegen::optional(map, key, codegen::value::<String>).map(Option::unwrap_or_default);
+			let time = |map: &mut Map, key| codegen::optional(map, key, codegen::value::<i64>).map(Option::unwrap_or_default);
+			let (name, author, description) = (text(&mut map, "Name")?, text(&mut map, "Author")?, text(&mut map, "Description")?);
+			let (time_created, time_modified) = (time(&mut map, "TimeCreated")?, time(&mut map, "TimeModified")?);
+			// These are written again from the regions.
+			for key in ["RegionCount", "TotalVolume", "TotalBlocks", "EnclosingSize"] {
+				codegen::optional(&mut map, key, codegen::any)?;
+			}
+			Ok(Metadata { name, author, description, time_created, time_modified, other: map })
+		})?;
+		let regions = codegen::required(&mut map, "Regions", |tag| {
+			codegen::compound(tag)?.into_iter()
+				.map(|(name, region)| {
+					let region = Region::decode_nbt(region).map_err(|error| error.within(PathSegment::Key(name.clone())))?;
+					Ok((name, region))
+				})
+				.collect()
+		})?;
+		Ok(Self { version, sub_version, data_version, metadata, regions, other: map })
+	}
+}
+
+impl EncodeNbt for Litematic {
+	/// Encodes the root compound of a schematic file.
+	fn encode_nbt(self) -> Tag {
+		let enclosing_size = self.enclosing_size();
+		let mut metadata = Map::new();
+		metadata.insert("Name".to_owned(), Tag::String(self.metadata.name));
+		metadata.insert("Author".to_owned(), Tag::String(self.metadata.author));
+		metadata.insert("Description".to_owned(), Tag::String(self.metadata.description));
+		metadata.insert("TimeCreated".to_owned(), Tag::Long(self.metadata.time_created));
+		metadata.insert("TimeModified".to_owned(), Tag::Long(self.metadata.time_modified));
+		metadata.insert("RegionCount".to_owned(), Tag::Int(self.regions.len() as i32));
+		let volume: usize = self.regions.values().map(|region| region.blocks.len()).sum();
+		metadata.insert("TotalVolume".to_owned(), Tag::Int(volume as i32));
+		let blocks: usize = self.regions.values().map(Region::block_count).sum();
+		metadata.insert("TotalBlocks".to_owned(), Tag::Int(blocks as i32));
+		metadata.insert("EnclosingSize".to_owned(), xyz(enclosing_size));
+		metadata.extend(self.metadata.other);
+
+		let mut map = Map::new();
+		map.insert("Version".to_owned(), Tag::Int(self.version));
+		if let Some(sub_version) = self.sub_version {
+			map.insert("SubVersion".to_owned(), Tag::Int(sub_version));
+		}
+		map.insert("MinecraftDataVersion".to_owned(), Tag::Int(self.data_version));
+		map.insert("Metadata".to_owned(), Tag::Compound(metadata));
+		let regions = self.regions.into_iter().map(|(name, region)| (name, region.encode_nbt())).collect();
+		map.insert("Regions".to_owned(), Tag::Compound(regions));
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::path::TagPath;
+
+	#[test]
+	fn round_trip_test() {
+		let mut litematic = Litematic::new(3465);
+		litematic.metadata.name = "farm".to_owned();
+		litematic.metadata.other.insert("PreviewImageData".to_owned(), Tag::IntArray(vec![0; 4]));
+		let mut base = Region::new([0, 0, 0], [3, 1, 3]);
+		for index in 0..9 {
+			let state = ["minecraft:stone", "minecraft:dirt", "minecraft:farmland", "minecraft:water"][index % 4];
+			base.set_block(index % 3, 0, index / 3, BlockState::new(state));
+		}
+		base.tile_entities.push(TileEntity { pos: [1, 0, 1], data: Map::new() });
+		base.block_ticks.push(PendingTick {
+			pos: [0, 0, 0],
+			target: "minecraft:stone".to_owned(),
+			priority: -1,
+			sub_tick: 7,
+			time: 2,
+			other: Map::new(),
+		});
+		let mut roof = Region::new([2, 3, 2], [-3, -1, -3]);
+		roof.set_block(0, 0, 0, BlockState::new("minecraft:glass"));
+		litematic.regions.insert("base".to_owned(), base);
+		litematic.regions.insert("roof".to_owned(), roof);
+		assert_eq!(litematic.enclosing_size(), [3, 4, 3]);
+
+		let tag = litematic.clone().encode_nbt();
+		let states = TagPath::root().key("Regions").key("base").key("BlockStates").get(&tag).unwrap();
+		// Five palette entries need 3 bits, so 9 entries fit in one long.
+		assert!(matches!(states, Tag::LongArray(longs) if longs.len() == 1));
+		let metadata = TagPath::root().key("Metadata");
+		assert!(matches!(metadata.clone().key("TotalBlocks").get(&tag), Ok(Tag::Int(10))));
+		assert!(matches!(metadata.clone().key("TotalVolume").get(&tag), Ok(Tag::Int(18))));
+		assert!(matches!(metadata.key("EnclosingSize").key("y").get(&tag), Ok(Tag::Int(4))));
+
+		let mut file = Vec::new();
+		litematic.write(&mut file).unwrap();
+		let read = Litematic::read(&mut file.as_slice()).unwrap();
+		assert_eq!(read.metadata.name, "farm");
+		assert!(read.metadata.other.contains_key("PreviewImageData"));
+		let base = &read.regions["base"];
+		assert_eq!(base.block_at(2, 0, 2).unwrap().name, "minecraft:stone");
+		assert_eq!(base.block_at(1, 0, 2).unwrap().name, "minecraft:water");
+		assert_eq!(base.tile_entities[0].pos, [1, 0, 1]);
+		assert_eq!((base.block_ticks[0].priority, base.block_ticks[0].sub_tick), (-1, 7));
+		let roof = &read.regions["roof"];
+		assert_eq!((roof.size(), roof.corners()), ([-3, -1, -3], ([0, 3, 0], [2, 3, 2])));
+		assert_eq!(roof.blocks().find(|(_, state)| state.name == "minecraft:glass").unwrap().0, [0, 0, 0]);
+	}
+
+	#[test]
+	fn decode_test() {
+		let source = r#"{
+			Version: 6, MinecraftDataVersion: 3465, Metadata: {Name: "wall", RegionCount: 1},
+			Regions: {wall: {
+				Position: {x: 0, y: 0, z: 0}, Size: {x: 2, y: 1, z: 1},
+				BlockStatePalette: [{Name: "minecraft:air"}, {Name: "minecraft:stone"}], BlockStates: [L; 4L],
+				TileEntities: [{x: 1, y: 0, z: 0, id: "minecraft:sign"}], Entities: [], PendingBlockTicks: [],
+			}},
+		}"#;
+		let litematic = Litematic::decode_nbt(Tag::parse(source).unwrap()).unwrap();
+		let wall = &litematic.regions["wall"];
+		assert_eq!(wall.block_at(0, 0, 0), Some(&BlockState::air()));
+		assert_eq!(wall.block_at(1, 0, 0).unwrap().name, "minecraft:stone");
+		assert!(wall.tile_entities[0].data.contains_key("id"));
+		assert!(litematic.metadata.other.is_empty());
+
+		let error = |source: &str| Litematic::decode_nbt(Tag::parse(source).unwrap()).unwrap_err().to_string();
+		assert_eq!(error(&source.replace("4L]", "12L]")), "Regions.wall.BlockStates: Entry 1 is 3, but the palette only has 2 entries.");
+		assert_eq!(error(&source.replace("Version: 6", "Version: 3")), "Version: Version 3 isn't supported. Only versions 4 to 7 are.");
+	}
+}
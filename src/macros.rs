@@ -1,11 +1,12 @@
 /// Shorthand way to create a Tag::Compound.
 /// Example:
 /// ```no_run
-/// compound!{
+/// # use rustnbt::{compound, tag::Tag};
+/// let tag = compound!{
 ///     ("Item One", 0i8),
 ///     (String::from("Item Two"), 2i32),
 ///     ("Item Three", Tag::Byte(1))
-/// }
+/// };
 /// ```
 #[macro_export]
 macro_rules! compound {
@@ -21,6 +22,7 @@ macro_rules! compound {
 /// Shorthand way to create a Tag::List.
 /// Example:
 /// ```no_run
+/// # use rustnbt::list;
 /// list!{ 1, 2, 3, 4, 5, 6, 7, 8, 9, 10 };
 /// list![
 ///     "One",
@@ -0,0 +1,500 @@
+// Maintenance of region files, which Minecraft never compacts and which crashes and other tools can leave broken.
+// https://minecraft.wiki/w/Region_file_format
+//
+// check reads the header itself rather than through Region, so that it can report every problem in a file
+// instead of stopping at the first. repair keeps the chunks that are still usable and drops the rest.
+
+use crate::{
+	region::{
+		external_path,
+		ChunkCompression,
+		CompressionRegistry,
+		Region,
+		RegionChunk,
+		RegionError,
+		CHUNK_COUNT,
+		EXTERNAL,
+		REGION_WIDTH,
+		SECTOR_SIZE,
+	},
+	tag::Tag,
+};
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How far in the future a timestamp can be before it's reported, to allow for clocks that are slightly off.
+const FUTURE_LEEWAY: u32 = 24 * 60 * 60;
+
+/// A problem found by [check].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+	/// The file isn't a whole number of sectors.
+	UnalignedLength(usize),
+	/// The chunk's sectors overlap the header.
+	InHeader,
+	/// The chunk's sectors, or its data, go past the end of the file.
+	OutOfBounds,
+	/// The chunk shares sectors with another chunk, given by its position within the region.
+	/// Both chunks report it, since either of them may have overwritten the other.
+	Overlap { other: (usize, usize) },
+	/// The chunk's length is 0, or longer than its sectors.
+	BadLength { len: usize, sectors: usize },
+	UnknownCompression(u8),
+	/// The chunk is stored externally, but its `.mcc` file can't be read.
+	MissingExternal(String),
+	/// The chunk can't be decompressed, or its NBT can't be read.
+	Unreadable(String),
+	/// The chunk's `xPos` and `zPos` don't match where it is in the region.
+	Misplaced { found: (i32, i32) },
+	/// The chunk has no timestamp.
+	ZeroTimestamp,
+	/// The chunk was saved in the future.
+	FutureTimestamp(u32),
+}
+
+impl IssueKind {
+	/// Whether the chunk can't be used as it is. Timestamp anomalies and unaligned files are harmless.
+	pub fn is_broken(&self) -> bool {
+		!matches!(self, IssueKind::UnalignedLength(_) | IssueKind::ZeroTimestamp | IssueKind::FutureTimestamp(_))
+	}
+}
+
+impl Display for IssueKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			IssueKind::UnalignedLength(len) => write!(f, "The file is {len} bytes, which isn't a whole number of sectors."),
+			IssueKind::InHeader => write!(f, "The chunk's sectors overlap the header."),
+			IssueKind::OutOfBounds => write!(f, "The chunk goes past the end of the file."),
+			IssueKind::Overlap { other: (x, z) } => write!(f, "The chunk shares sectors with chunk ({x}, {z})."),
+			IssueKind::BadLength { len, sectors } => write!(f, "The chunk's length of {len} bytes doesn't fit in its {sectors} sectors."),
+			IssueKind::UnknownCompression(id) => write!(f, "Unknown compression type {id}."),
+			IssueKind::MissingExternal(error) => write!(f, "The chunk's .mcc file can't be read: {error}"),
+			IssueKind::Unreadable(error) => write!(f, "The chunk can't be read: {error}"),
+			IssueKind::Misplaced { found: (x, z) } => write!(f, "The chunk belongs at ({x}, {z})."),
+			IssueKind::ZeroTimestamp => write!(f, "The chunk has no timestamp."),
+			IssueKind::FutureTimestamp(timestamp) => write!(f, "The chunk's timestamp {timestamp} is in the future."),
+		}
+	}
+}
+
+/// A problem with a region file, or with one of its chunks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+	/// The position of the chunk within the region, or `None` for problems with the whole file.
+	pub chunk: Option<(usize, usize)>,
+	pub kind: IssueKind,
+}
+
+impl Display for Issue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.chunk {
+			Some((x, z)) => write!(f, "Chunk ({x}, {z}): {}", self.kind),
+			None => self.kind.fmt(f),
+		}
+	}
+}
+
+/// The result of [check].
+#[derive(Clone, Debug, Default)]
+pub struct CheckReport {
+	/// The number of chunks in the header.
+	pub chunks: usize,
+	/// Sectors after the header that no chunk uses.
+	pub wasted_sectors: usize,
+	pub issues: Vec<Issue>,
+}
+
+impl CheckReport {
+	/// Whether no chunk is broken. Harmless issues are still reported in [CheckReport::issues].
+	pub fn is_ok(&self) -> bool {
+		!self.issues.iter().any(|issue| issue.kind.is_broken())
+	}
+}
+
+/// What [repair] does with broken chunks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RepairMode {
+	/// Drops every broken chunk.
+	#[default]
+	Drop,
+	/// Keeps broken chunks whose data can still be read from where their header points,
+	/// such as chunks with a bad length or sectors shared with another chunk, and drops the rest.
+	/// Kept chunks are compressed again, so that they don't carry whatever followed their data.
+	Salvage,
+}
+
+/// The result of [repair].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RepairReport {
+	/// The positions of broken chunks that were kept.
+	pub salvaged: Vec<(usize, usize)>,
+	/// The positions of broken chunks that were dropped.
+	pub dropped: Vec<(usize, usize)>,
+}
+
+/// What [scan] found for a chunk.
+struct Scanned {
+	chunk: Option<RegionChunk>,
+	/// Whether the chunk's data was read and decoded, and is where it belongs.
+	readable: bool,
+	broken: bool,
+}
+
+/// Whether a chunk's position, from `xPos` and `zPos` (or `Level.xPos` and `Level.zPos` before Minecraft 1.18),
+/// or `Position` for entity chunks, doesn't match where it is in the region.
+fn misplaced(tag: &Tag, x: usize, z: usize) -> Option<(i32, i32)> {
+	let Tag::Compound(map) = tag else {
+		return None;
+	};
+	let map = match map.get("Level") {
+		Some(Tag::Compound(level)) => level,
+		_ => map,
+	};
+	let found = match (map.get("xPos"), map.get("zPos"), map.get("Position")) {
+		(Some(&Tag::Int(x)), Some(&Tag::Int(z)), _) => (x, z),
+		(_, _, Some(Tag::IntArray(position))) if position.len() == 2 => (position[0], position[1]),
+		_ => return None,
+	};
+	let width = REGION_WIDTH as i32;
+	let matches = found.0.rem_euclid(width) as usize == x && found.1.rem_euclid(width) as usize == z;
+	(!matches).then_some(found)
+}
+
+fn scan<F>(file: &[u8], registry: &CompressionRegistry, mut load_external: F) -> Result<(CheckReport, Vec<Option<Scanned>>), RegionError>
+where
+	F: FnMut(usize, usize) -> Result<Vec<u8>, RegionError>,
+{
+	let mut report = CheckReport::default();
+	let mut scanned: Vec<Option<Scanned>> = (0..CHUNK_COUNT).map(|_| None).collect();
+	if file.is_empty() {
+		return Ok((report, scanned));
+	}
+	if file.len() < SECTOR_SIZE * 2 {
+		return Err(RegionError::Truncated(file.len()));
+	}
+	if file.len() % SECTOR_SIZE != 0 {
+		report.issues.push(Issue { chunk: None, kind: IssueKind::UnalignedLength(file.len()) });
+	}
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs() as u32).unwrap_or_default();
+	let file_sectors = file.len().div_ceil(SECTOR_SIZE);
+	let mut owners: Vec<Option<usize>> = vec![None; file_sectors];
+	// The issues of every chunk, which overlaps add to after the chunk has been scanned.
+	let mut chunk_issues: Vec<Vec<IssueKind>> = vec![Vec::new(); CHUNK_COUNT];
+	for (index, slot) in scanned.iter_mut().enumerate() {
+		let (x, z) = (index % REGION_WIDTH, index / REGION_WIDTH);
+		let location = u32::from_be_bytes(file[index * 4..index * 4 + 4].try_into().unwrap());
+		if location == 0 {
+			continue;
+		}
+		report.chunks += 1;
+		let mut issues = std::mem::take(&mut chunk_issues[index]);
+		let timestamp = u32::from_be_bytes(file[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4].try_into().unwrap());
+		match timestamp {
+			0 => issues.push(IssueKind::ZeroTimestamp),
+			timestamp if timestamp > now.saturating_add(FUTURE_LEEWAY) => issues.push(IssueKind::FutureTimestamp(timestamp)),
+			_ => {}
+		}
+		let (start, sectors) = ((location >> 8) as usize, (location & 0xff) as usize);
+		if start < 2 {
+			issues.push(IssueKind::InHeader);
+		} else if start + sectors > file_sectors {
+			issues.push(IssueKind::OutOfBounds);
+		}
+		let used = start.max(2)..(start + sectors).min(file_sectors);
+		for owner in owners.get_mut(used).unwrap_or_default() {
+			match *owner {
+				Some(other) => {
+					let overlap = IssueKind::Overlap { other: (x, z) };
+					if !chunk_issues[other].contains(&overlap) {
+						chunk_issues[other].push(overlap);
+					}
+					let overlap = IssueKind::Overlap { other: (other % REGION_WIDTH, other / REGION_WIDTH) };
+					if !issues.contains(&overlap) {
+						issues.push(overlap);
+					}
+				}
+				None => *owner = Some(index),
+			}
+		}
+
+		// Reads whatever data the header points to, even if it is past the chunk's sectors.
+		let offset = start * SECTOR_SIZE;
+		let mut chunk = None;
+		let mut readable = false;
+		match file.get(offset..offset + 5).filter(|_| start >= 2) {
+			None => {
+				if !issues.contains(&IssueKind::InHeader) && !issues.contains(&IssueKind::OutOfBounds) {
+					issues.push(IssueKind::OutOfBounds);
+				}
+			}
+			Some(header) => {
+				let len = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+				if len == 0 || len + 4 > sectors * SECTOR_SIZE {
+					issues.push(IssueKind::BadLength { len, sectors });
+				}
+				let end = (offset + 4 + len).min(file.len());
+				if offset + 4 + len > file.len() && !issues.contains(&IssueKind::OutOfBounds) {
+					issues.push(IssueKind::OutOfBounds);
+				}
+				let payload = match header[4] & EXTERNAL {
+					0 => Some(file[(offset + 5).min(end)..end].to_vec()),
+					_ => load_external(x, z).map_err(|error| issues.push(IssueKind::MissingExternal(error.to_string()))).ok(),
+				};
+				let compression = header[4] & !EXTERNAL;
+				if let Some(payload) = payload {
+					let mut data = payload.as_slice();
+					match ChunkCompression::read(compression, &mut data) {
+						Ok(compression) => {
+							let candidate = RegionChunk { timestamp, compression, data: data.to_vec() };
+							match candidate.decode_with(registry) {
+								Ok(tag) => match misplaced(&tag, x, z) {
+									Some(found) => issues.push(IssueKind::Misplaced { found }),
+									None => readable = true,
+								},
+								Err(error) => issues.push(IssueKind::Unreadable(error.to_string())),
+							}
+							chunk = Some(candidate);
+						}
+						Err(RegionError::UnknownCompression(id)) => issues.push(IssueKind::UnknownCompression(id)),
+						Err(error) => issues.push(IssueKind::Unreadable(error.to_string())),
+					}
+				}
+			}
+		}
+		chunk_issues[index] = issues;
+		*slot = Some(Scanned { chunk, readable, broken: false });
+	}
+	for (index, (slot, issues)) in scanned.iter_mut().zip(chunk_issues).enumerate() {
+		if let Some(scanned) = slot {
+			scanned.broken = issues.iter().any(IssueKind::is_broken);
+			let chunk = Some((index % REGION_WIDTH, index / REGION_WIDTH));
+			report.issues.extend(issues.into_iter().map(|kind| Issue { chunk, kind }));
+		}
+	}
+	report.wasted_sectors = owners.iter().skip(2).filter(|owner| owner.is_none()).count();
+	Ok((report, scanned))
+}
+
+/// Checks a region file for problems, decoding every chunk with the algorithms in `registry`.
+/// `load_external` reads the data of chunks stored in `.mcc` files, given their position within the region.
+/// Only files too short to have a header fail; every other problem is reported.
+pub fn check<F>(file: &[u8], registry: &CompressionRegistry, load_external: F) -> Result<CheckReport, RegionError>
+where
+	F: FnMut(usize, usize) -> Result<Vec<u8>, RegionError>,
+{
+	Ok(scan(file, registry, load_external)?.0)
+}
+
+/// Checks a region file on disk, along with its `.mcc` files.
+pub fn check_file<P: AsRef<Path>>(path: P, registry: &CompressionRegistry) -> Result<CheckReport, RegionError> {
+	let path = path.as_ref();
+	check(&std::fs::read(path)?, registry, external_loader(path))
+}
+
+fn external_loader(path: &Path) -> impl FnMut(usize, usize) -> Result<Vec<u8>, RegionError> + '_ {
+	|x, z| match external_path(path, x, z) {
+		Some(external) => Ok(std::fs::read(external)?),
+		None => Err(RegionError::External { x, z }),
+	}
+}
+
+/// Reads a region file on disk, keeping every chunk whose data can be found, even if it is broken,
+/// so that the region can be written back without losing them. Returns the region and the issues of broken chunks.
+pub(crate) fn read_lenient(path: &Path, registry: &CompressionRegistry) -> Result<(Region, Vec<Issue>), RegionError> {
+	let (report, scanned) = scan(&std::fs::read(path)?, registry, external_loader(path))?;
+	let mut region = Region::new();
+	for (index, scanned) in scanned.into_iter().enumerate() {
+		if let Some(Scanned { chunk: Some(chunk), .. }) = scanned {
+			region.set_chunk(index % REGION_WIDTH, index / REGION_WIDTH, Some(chunk));
+		}
+	}
+	Ok((region, report.issues.into_iter().filter(|issue| issue.kind.is_broken()).collect()))
+}
+
+/// Reads the usable chunks of a region file into a [Region], which can then be written without the broken ones.
+/// ```
+/// # use rustnbt::{maintenance::{check, repair, RepairMode}, region::{CompressionRegistry, Region, RegionError}, tag::Tag};
+/// let mut region = Region::new();
+/// region.write_chunk(0, 0, &Tag::parse("{xPos: 0, zPos: 0}").unwrap()).unwrap();
+/// region.write_chunk(1, 0, &Tag::parse("{xPos: 1, zPos: 0}").unwrap()).unwrap();
+/// let mut file = Vec::new();
+/// region.write(&mut file).unwrap();
+/// // Corrupts the compressed data of the second chunk.
+/// file[4096 * 3 + 10] ^= 0xff;
+///
+/// let registry = CompressionRegistry::new();
+/// let no_external = |x, z| Err(RegionError::External { x, z });
+/// assert!(!check(&file, &registry, no_external).unwrap().is_ok());
+/// let (region, report) = repair(&file, RepairMode::Salvage, &registry, no_external).unwrap();
+/// assert_eq!(report.dropped, [(1, 0)]);
+/// assert_eq!(region.len(), 1);
+/// ```
+pub fn repair<F>(file: &[u8], mode: RepairMode, registry: &CompressionRegistry, load_external: F) -> Result<(Region, RepairReport), RegionError>
+where
+	F: FnMut(usize, usize) -> Result<Vec<u8>, RegionError>,
+{
+	let (_, scanned) = scan(file, registry, load_external)?;
+	let mut region = Region::new();
+	let mut report = RepairReport::default();
+	for (index, scanned) in scanned.into_iter().enumerate() {
+		let Some(Scanned { chunk, readable, broken }) = scanned else {
+			continue;
+		};
+		let position = (index % REGION_WIDTH, index / REGION_WIDTH);
+		let keep = !broken || (mode == RepairMode::Salvage && readable);
+		match (keep, broken) {
+			(true, true) => report.salvaged.push(position),
+			(false, _) => report.dropped.push(position),
+			(true, false) => {}
+		}
+		if !keep {
+			continue;
+		}
+		let chunk = match (broken, chunk) {
+			// The data of a salvaged chunk may be followed by anything that was in its sectors.
+			(true, Some(chunk)) => {
+				let tag = chunk.decode_with(registry)?;
+				let encoded = RegionChunk::encode_with(&tag, chunk.compression, registry)?;
+				Some(RegionChunk { timestamp: chunk.timestamp, ..encoded })
+			}
+			(_, chunk) => chunk,
+		};
+		region.set_chunk(position.0, position.1, chunk);
+	}
+	Ok((region, report))
+}
+
+/// Repairs a region file on disk, replacing it with the chunks that [repair] keeps, written contiguously.
+pub fn repair_file<P: AsRef<Path>>(path: P, mode: RepairMode, registry: &CompressionRegistry) -> Result<RepairReport, RegionError> {
+	let path = path.as_ref();
+	let (region, report) = repair(&std::fs::read(path)?, mode, registry, external_loader(path))?;
+	region.save(path)?;
+	Ok(report)
+}
+
+/// The size of a region file before and after [compact_file].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactReport {
+	pub before: u64,
+	pub after: u64,
+}
+
+/// Rewrites a region file with its chunks in order and without unused sectors.
+/// Chunks aren't decoded or compressed again. Files with broken chunks fail, and need [repair_file] instead.
+pub fn compact_file<P: AsRef<Path>>(path: P) -> Result<CompactReport, RegionError> {
+	let path = path.as_ref();
+	let before = std::fs::metadata(path)?.len();
+	Region::open(path)?.save(path)?;
+	Ok(CompactReport { before, after: std::fs::metadata(path)?.len() })
+}
+
+/// Rewrites a region file in memory with its chunks in order and without unused sectors.
+pub fn compact(file: &[u8]) -> Result<Vec<u8>, RegionError> {
+	let mut compacted = Vec::new();
+	Region::read(&mut &file[..])?.write(&mut compacted)?;
+	Ok(compacted)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::testutil::{chunk, TempDir};
+
+	/// Sets the location of the chunk at `index`.
+	fn locate(file: &mut [u8], index: usize, start: u32, sectors: u32) {
+		file[index * 4..index * 4 + 4].copy_from_slice(&(start << 8 | sectors).to_be_bytes());
+	}
+
+	#[test]
+	fn check_test() {
+		let mut region = Region::new();
+		for x in 0..6 {
+			region.write_chunk(x, 0, &chunk(x as i32, 0)).unwrap();
+		}
+		let mut file = Vec::new();
+		region.write(&mut file).unwrap();
+		let registry = CompressionRegistry::new();
+		let no_external = |x, z| Err(RegionError::External { x, z });
+		let report = check(&file, &registry, no_external).unwrap();
+		assert!(report.is_ok() && report.issues.is_empty());
+		assert_eq!((report.chunks, report.wasted_sectors), (6, 0));
+
+		// Chunk 1 points at chunk 0's sector, leaving its own unused.
+		locate(&mut file, 1, 2, 1);
+		// Chunk 2 claims more sectors than it has.
+		file[SECTOR_SIZE * 4..SECTOR_SIZE * 4 + 4].copy_from_slice(&5000u32.to_be_bytes());
+		// Chunk 3 is past the end of the file, and chunk 4 is in the header.
+		locate(&mut file, 3, 100, 1);
+		locate(&mut file, 4, 1, 1);
+		// Chunk 5 was saved in the future, and a chunk at (7, 0) is a copy of chunk 5.
+		file[SECTOR_SIZE + 5 * 4..SECTOR_SIZE + 6 * 4].copy_from_slice(&u32::MAX.to_be_bytes());
+		locate(&mut file, 7, 7, 1);
+
+		let report = check(&file, &registry, no_external).unwrap();
+		let issues: Vec<_> = report.issues.iter().map(|issue| (issue.chunk.unwrap(), issue.kind.clone())).collect();
+		assert_eq!(issues, [
+			((0, 0), IssueKind::Overlap { other: (1, 0) }),
+			((1, 0), IssueKind::Overlap { other: (0, 0) }),
+			((1, 0), IssueKind::Misplaced { found: (0, 0) }),
+			((2, 0), IssueKind::BadLength { len: 5000, sectors: 1 }),
+			((3, 0), IssueKind::OutOfBounds),
+			((4, 0), IssueKind::InHeader),
+			((5, 0), IssueKind::FutureTimestamp(u32::MAX)),
+			((5, 0), IssueKind::Overlap { other: (7, 0) }),
+			((7, 0), IssueKind::ZeroTimestamp),
+			((7, 0), IssueKind::Overlap { other: (5, 0) }),
+			((7, 0), IssueKind::Misplaced { found: (5, 0) }),
+		]);
+		assert_eq!((report.chunks, report.wasted_sectors), (7, 3));
+		assert!(!report.is_ok());
+
+		let (_, dropped) = repair(&file, RepairMode::Drop, &registry, no_external).unwrap();
+		assert_eq!(dropped.dropped, [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (7, 0)]);
+		// Chunk 2's data is still in its sector, and zlib doesn't need to know where it ends.
+		let (repaired, salvaged) = repair(&file, RepairMode::Salvage, &registry, no_external).unwrap();
+		assert_eq!((salvaged.salvaged, salvaged.dropped), (vec![(0, 0), (2, 0), (5, 0)], vec![(1, 0), (3, 0), (4, 0), (7, 0)]));
+		assert_eq!(repaired.positions().collect::<Vec<_>>(), [(0, 0), (2, 0), (5, 0)]);
+		// Salvaged chunks are compressed again without what followed their data, keeping their timestamps.
+		let salvaged_chunk = repaired.chunk(2, 0).unwrap();
+		assert!(salvaged_chunk.data.len() < SECTOR_SIZE);
+		assert!(crate::diff::diff(&salvaged_chunk.decode().unwrap(), &chunk(2, 0), &Default::default()).is_empty());
+		assert_eq!(repaired.chunk(5, 0).unwrap().timestamp, u32::MAX);
+		let mut written = Vec::new();
+		repaired.write(&mut written).unwrap();
+		assert!(check(&written, &registry, no_external).unwrap().is_ok());
+	}
+
+	#[test]
+	fn compact_test() {
+		let directory = TempDir::new("maintenance");
+		let path = directory.join("r.0.0.mca");
+		let mut region = Region::new();
+		region.write_chunk(0, 0, &chunk(0, 0)).unwrap();
+		region.write_chunk(1, 0, &chunk(1, 0)).unwrap();
+		let mut file = Vec::new();
+		region.write(&mut file).unwrap();
+		// Moves chunk 1 further into the file, as if it had grown and been moved, leaving a gap.
+		let moved = file[SECTOR_SIZE * 3..].to_vec();
+		file.resize(SECTOR_SIZE * 6, 0);
+		file.extend_from_slice(&moved);
+		locate(&mut file, 1, 6, 1);
+		std::fs::write(&path, &file).unwrap();
+		assert_eq!(check_file(&path, &CompressionRegistry::new()).unwrap().wasted_sectors, 3);
+
+		assert_eq!(compact(&file).unwrap().len(), SECTOR_SIZE * 4);
+		let report = compact_file(&path).unwrap();
+		assert_eq!(report, CompactReport { before: SECTOR_SIZE as u64 * 7, after: SECTOR_SIZE as u64 * 4 });
+		assert_eq!(Region::open(&path).unwrap().chunk(1, 0), region.chunk(1, 0));
+
+		// Repairing a file on disk drops the chunk with a missing .mcc file.
+		let mut file = std::fs::read(&path).unwrap();
+		file[SECTOR_SIZE * 3 + 4] |= EXTERNAL;
+		std::fs::write(&path, &file).unwrap();
+		let report = check_file(&path, &CompressionRegistry::new()).unwrap();
+		assert!(matches!(report.issues[0].kind, IssueKind::MissingExternal(_)));
+		let report = repair_file(&path, RepairMode::Salvage, &CompressionRegistry::new()).unwrap();
+		assert_eq!(report.dropped, [(1, 0)]);
+		assert!(check_file(&path, &CompressionRegistry::new()).unwrap().issues.is_empty());
+	}
+}
@@ -0,0 +1,462 @@
+// MCEdit schematics (.schematic), from before Minecraft 1.13.
+// https://minecraft.wiki/w/Schematic_file_format
+//
+// Blocks are numeric IDs with 4 bits of data each, ordered by y, then z, then x.
+// The low 8 bits of every ID are in `Blocks`, and IDs above 255 keep their high 4 bits in `AddBlocks`,
+// two to a byte, with even indices in the low nibble.
+// Numeric IDs were removed in 1.13, so converting a schematic needs a table from IDs to block states.
+
+use crate::{
+	Map,
+	chunk::{BlockState, InvalidBlockState},
+	codegen::{self, DecodeError},
+	compression::{self, Compression},
+	io::{read_named_tag, write_named_tag},
+	schematic::{self, Schematic, SchematicError},
+	structure::StructureTemplate,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+	},
+};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+/// Maps numeric block IDs and data values to block states.
+/// Closures taking `(id, data)` are mappings too.
+pub trait LegacyMapping {
+	fn block_state(&self, id: u16, data: u8) -> Option<BlockState>;
+}
+
+impl<F: Fn(u16, u8) -> Option<BlockState>> LegacyMapping for F {
+	fn block_state(&self, id: u16, data: u8) -> Option<BlockState> {
+		self(id, data)
+	}
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Line {line}: {message}")]
+pub struct InvalidTable {
+	pub line: usize,
+	pub message: String,
+}
+
+/// A [LegacyMapping] read from text, with one entry per line:
+/// ```text
+/// # Comments start with #.
+/// 1 minecraft:stone
+/// 1:1 minecraft:granite
+/// 17:4 minecraft:oak_log[axis=x]
+/// ```
+/// An entry without a data value is used for every data value that doesn't have its own entry.
+/// ```
+/// # use rustnbt::mcedit::{LegacyMapping, LegacyTable};
+/// let table: LegacyTable = "1 minecraft:stone\n1:1 minecraft:granite".parse().unwrap();
+/// assert_eq!(table.block_state(1, 1).unwrap().name, "minecraft:granite");
+/// assert_eq!(table.block_state(1, 5).unwrap().name, "minecraft:stone");
+/// assert_eq!(table.block_state(2, 0), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LegacyTable {
+	exact: HashMap<(u16, u8), BlockState>,
+	any_data: HashMap<u16, BlockState>,
+}
+
+impl LegacyTable {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Maps `id` with `data` to `state`.
+	pub fn insert(&mut self, id: u16, data: u8, state: BlockState) -> &mut Self {
+		self.exact.insert((id, data), state);
+		self
+	}
+
+	/// Maps `id` with any data value that isn't mapped by itself to `state`.
+	pub fn insert_any(&mut self, id: u16, state: BlockState) -> &mut Self {
+		self.any_data.insert(id, state);
+		self
+	}
+}
+
+impl LegacyMapping for LegacyTable {
+	fn block_state(&self, id: u16, data: u8) -> Option<BlockState> {
+		self.exact.get(&(id, data)).or_else(|| self.any_data.get(&id)).cloned()
+	}
+}
+
+impl FromStr for LegacyTable {
+	type Err = InvalidTable;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut table = Self::new();
+		for (index, line) in s.lines().enumerate() {
+			let error = |message: String| InvalidTable { line: index + 1, message };
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let (key, state) = line.split_once(char::is_whitespace)
+				.ok_or_else(|| error(format!("Expected an ID and a block state, found `{line}`.")))?;
+			let state: BlockState = state.trim().parse().map_err(|invalid: InvalidBlockState| error(invalid.to_string()))?;
+			let number = |text: &str, max: u16| match text.parse() {
+				Ok(number) if number <= max => Ok(number),
+				_ => Err(error(format!("`{text}` isn't a valid ID or data value."))),
+			};
+			match key.split_once(':') {
+				Some((id, data)) => table.insert(number(id, 4095)?, number(data, 15)? as u8, state),
+				None => table.insert_any(number(key, 4095)?, state),
+			};
+		}
+		Ok(table)
+	}
+}
+
+/// A block that the mapping has no block state for.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Block {id}:{data} at {pos:?} has no block state.")]
+pub struct UnmappedBlock {
+	pub id: u16,
+	pub data: u8,
+	pub pos: [usize; 3],
+}
+
+/// An MCEdit schematic.
+/// ```
+/// # use rustnbt::mcedit::LegacySchematic;
+/// let mut schematic = LegacySchematic::new(2, 1, 1);
+/// schematic.set_block(1, 0, 0, 35, 14);
+/// assert_eq!(schematic.block_at(1, 0, 0), Some((35, 14)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LegacySchematic {
+	width: u16,
+	height: u16,
+	length: u16,
+	/// The block ID namespace, which is `Alpha` for Java Edition.
+	pub materials: String,
+	/// 12-bit block IDs.
+	blocks: Vec<u16>,
+	/// 4-bit data values.
+	data: Vec<u8>,
+	/// Block entities, with their positions in `x`, `y` and `z`.
+	pub tile_entities: Vec<Map>,
+	pub entities: Vec<Map>,
+	/// Keys that aren't modeled, such as WorldEdit's `WEOriginX`.
+	pub other: Map,
+}
+
+impl LegacySchematic {
+	/// Creates a schematic filled with air.
+	pub fn new(width: u16, height: u16, length: u16) -> Self {
+		let volume = width as usize * height as usize * length as usize;
+		Self {
+			width,
+			height,
+			length,
+			materials: "Alpha".to_owned(),
+			blocks: vec![0; volume],
+			data: vec![0; volume],
+			tile_entities: Vec::new(),
+			entities: Vec::new(),
+			other: Map::new(),
+		}
+	}
+
+	/// Reads a schematic file, which is usually compressed with gzip.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, SchematicError> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let (_, data) = compression::decompress(&data)?;
+		let (_, tag) = read_named_tag(&mut data.as_slice())?;
+		Ok(Self::decode_nbt(tag)?)
+	}
+
+	/// Writes a schematic file compressed with gzip.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), SchematicError> {
+		let mut data = Vec::new();
+		write_named_tag(&mut data, &self.clone().encode_nbt(), "Schematic")?;
+		writer.write_all(&Compression::Gzip.compress(&data)?)?;
+		Ok(())
+	}
+
+	/// The size along the x axis.
+	pub fn width(&self) -> u16 {
+		self.width
+	}
+
+	/// The size along the y axis.
+	pub fn height(&self) -> u16 {
+		self.height
+	}
+
+	/// The size along the z axis.
+	pub fn length(&self) -> u16 {
+		self.length
+	}
+
+	fn index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+		let (width, length) = (self.width as usize, self.length as usize);
+		(x < width && y < self.height as usize && z < length).then(|| (y * length + z) * width + x)
+	}
+
+	/// Returns the ID and data value of the block at a position, or `None` if it is outside.
+	pub fn block_at(&self, x: usize, y: usize, z: usize) -> Option<(u16, u8)> {
+		let index = self.index(x, y, z)?;
+		Some((self.blocks[index], self.data[index]))
+	}
+
+	/// Replaces the block at a position.
+	/// # Panics
+	/// Panics if the position is outside the schematic, the ID doesn't fit in 12 bits, or the data value doesn't fit in 4.
+	pub fn set_block(&mut self, x: usize, y: usize, z: usize, id: u16, data: u8) {
+		let index = self.index(x, y, z)
+			.unwrap_or_else(|| panic!("({x}, {y}, {z}) is outside a schematic of size {}×{}×{}", self.width, self.height, self.length));
+		assert!(id < 4096 && data < 16, "block {id}:{data} doesn't fit in 12 + 4 bits");
+		self.blocks[index] = id;
+		self.data[index] = data;
+	}
+
+	/// Every position in the schematic with its ID and data value, ordered by y, then z, then x.
+	pub fn blocks(&self) -> impl Iterator<Item = ([usize; 3], u16, u8)> + '_ {
+		let (width, length) = (self.width as usize, self.length as usize);
+		self.blocks.iter().zip(&self.data).enumerate().map(move |(index, (&id, &data))| {
+			([index % width, index / (width * length), index / width % length], id, data)
+		})
+	}
+
+	/// Looks up the block state of every block.
+	fn block_states<M: LegacyMapping + ?Sized>(&self, mapping: &M) -> Result<Vec<([usize; 3], BlockState)>, UnmappedBlock> {
+		let mut cache: HashMap<(u16, u8), BlockState> = HashMap::new();
+		self.blocks().map(|(pos, id, data)| {
+			let state = match cache.get(&(id, data)) {
+				Some(state) => state.clone(),
+				None => {
+					let state = mapping.block_state(id, data).ok_or(UnmappedBlock { id, data, pos })?;
+					cache.insert((id, data), state.clone());
+					state
+				}
+			};
+			Ok((pos, state))
+		}).collect()
+	}
+
+	/// Splits the position out of a tile entity.
+	fn tile_entity_pos(entity: &Map) -> Option<[i32; 3]> {
+		let coordinate = |key| match entity.get(key) {
+			Some(Tag::Int(value)) => Some(*value),
+			_ => None,
+		};
+		Some([coordinate("x")?, coordinate("y")?, coordinate("z")?])
+	}
+
+	/// Converts the schematic to a version 3 Sponge schematic.
+	/// Block entities and entities are copied as they are. Their NBT is still in the format of their own version,
+	/// and their `id`s are legacy names such as `Chest`. Tile entities without a position are dropped.
+	pub fn to_sponge<M: LegacyMapping + ?Sized>(&self, mapping: &M, data_version: i32) -> Result<Schematic, UnmappedBlock> {
+		let mut sponge = Schematic::new(self.width, self.height, self.length, data_version);
+		for ([x, y, z], state) in self.block_states(mapping)? {
+			sponge.set_block(x, y, z, state);
+		}
+		for entity in &self.tile_entities {
+			let Some(pos) = Self::tile_entity_pos(entity) else { continue };
+			let mut data = entity.clone();
+			for key in ["x", "y", "z"] {
+				data.remove(key);
+			}
+			let id = match data.remove("id") {
+				Some(Tag::String(id)) => id,
+				_ => String::new(),
+			};
+			sponge.block_entities.push(schematic::BlockEntity { pos, id, data, other: Map::new() });
+		}
+		for entity in &self.entities {
+			let Some(pos) = entity_pos(entity) else { continue };
+			let mut data = entity.clone();
+			let id = match data.remove("id") {
+				Some(Tag::String(id)) => id,
+				_ => String::new(),
+			};
+			sponge.entities.push(schematic::Entity { pos, id, data, other: Map::new() });
+		}
+		Ok(sponge)
+	}
+
+	/// Converts the schematic to a structure template, including air so that placing it clears the space.
+	/// Like [LegacySchematic::to_sponge], block entities and entities are copied as they are.
+	pub fn to_structure<M: LegacyMapping + ?Sized>(&self, mapping: &M, data_version: i32) -> Result<StructureTemplate, UnmappedBlock> {
+		let mut structure = StructureTemplate::new(data_version);
+		let mut tile_entities: HashMap<[i32; 3], Map> = self.tile_entities.iter()
+			.filter_map(|entity| Some((Self::tile_entity_pos(entity)?, entity.clone())))
+			.collect();
+		for ([x, y, z], state) in self.block_states(mapping)? {
+			let pos = [x as i32, y as i32, z as i32];
+			match tile_entities.remove(&pos) {
+				Some(mut nbt) => {
+					for key in ["x", "y", "z"] {
+						nbt.remove(key);
+					}
+					structure.place_block_entity(pos, state, nbt)
+				}
+				None => structure.place_block(pos, state),
+			};
+		}
+		for entity in &self.entities {
+			if let Some(pos) = entity_pos(entity) {
+				structure.place_entity(pos, entity.clone());
+			}
+		}
+		structure.size = [self.width as i32, self.height as i32, self.length as i32];
+		Ok(structure)
+	}
+}
+
+/// The `Pos` of an entity.
+fn entity_pos(entity: &Map) -> Option<[f64; 3]> {
+	let Some(Tag::List(ListTag::Double(pos))) = entity.get("Pos") else {
+		return None;
+	};
+	pos.as_slice().try_into().ok()
+}
+
+impl DecodeNbt for LegacySchematic {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		// Sizes are unsigned shorts.
+		let size = |map: &mut Map, key| codegen::required(map, key, codegen::value::<i16>).map(|size| size as u16);
+		let (width, height, length) = (size(&mut map, "Width")?, size(&mut map, "Height")?, size(&mut map, "Length")?);
+		let volume = width as usize * height as usize * length as usize;
+		let materials = codegen::optional(&mut map, "Materials", codegen::value)?.unwrap_or_else(|| "Alpha".to_owned());
+		let bytes = |tag| {
+			let bytes: Vec<i8> = codegen::value(tag)?;
+			match bytes.len() == volume {
+				true => Ok(bytes.into_iter().map(|byte| byte as u8).collect::<Vec<_>>()),
+				false => Err(DecodeError::invalid(format!("Expected {volume} entries, found {}.", bytes.len()))),
+			}
+		};
+		let low = codegen::required(&mut map, "Blocks", bytes)?;
+		let data = codegen::required(&mut map, "Data", |tag| {
+			let data = bytes(tag)?;
+			match data.iter().position(|&value| value > 15) {
+				Some(index) => Err(DecodeError::invalid(format!("Entry {index} is {}, which doesn't fit in 4 bits.", data[index]))),
+				None => Ok(data),
+			}
+		})?;
+		// Like WorldEdit, missing nibbles at the end are zero.
+		let add = codegen::optional(&mut map, "AddBlocks", codegen::value::<Vec<i8>>)?.unwrap_or_default();
+		let blocks = low.into_iter().enumerate().map(|(index, low)| {
+			let nibble = add.get(index >> 1).map_or(0, |&byte| match index & 1 {
+				0 => byte as u8 & 0x0f,
+				_ => byte as u8 >> 4,
+			});
+			(nibble as u16) << 8 | low as u16
+		}).collect();
+		let tile_entities = codegen::optional(&mut map, "TileEntities", |tag| codegen::list(tag, codegen::compound))?.unwrap_or_default();
+		let entities = codegen::optional(&mut map, "Entities", |tag| codegen::list(tag, codegen::compound))?.unwrap_or_default();
+		Ok(Self { width, height, length, materials, blocks, data, tile_entities, entities, other: map })
+	}
+}
+
+impl EncodeNbt for LegacySchematic {
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		map.insert("Width".to_owned(), Tag::Short(self.width as i16));
+		map.insert("Height".to_owned(), Tag::Short(self.height as i16));
+		map.insert("Length".to_owned(), Tag::Short(self.length as i16));
+		map.insert("Materials".to_owned(), Tag::String(self.materials));
+		map.insert("Blocks".to_owned(), Tag::ByteArray(self.blocks.iter().map(|&id| id as u8 as i8).collect()));
+		map.insert("Data".to_owned(), Tag::ByteArray(self.data.iter().map(|&data| data as i8).collect()));
+		if self.blocks.iter().any(|&id| id > 0xff) {
+			let add = self.blocks.chunks(2)
+				.map(|pair| ((pair[0] >> 8) as u8 | (pair.get(1).map_or(0, |&id| id >> 8) as u8) << 4) as i8)
+				.collect();
+			map.insert("AddBlocks".to_owned(), Tag::ByteArray(add));
+		}
+		map.insert("TileEntities".to_owned(), Tag::List(ListTag::from(self.tile_entities)));
+		map.insert("Entities".to_owned(), Tag::List(ListTag::from(self.entities)));
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample() -> LegacySchematic {
+		let source = r#"{
+			Width: 3s, Height: 1s, Length: 1s, Materials: "Alpha", WEOriginX: 4,
+			Blocks: [B; 1b, 54b, 2b], Data: [B; 1b, 3b, 0b], AddBlocks: [B; 16b],
+			TileEntities: [{id: "Chest", x: 1, y: 0, z: 0, Items: []}],
+			Entities: [{id: "Pig", Pos: [0.5d, 1.0d, 0.5d]}],
+		}"#;
+		LegacySchematic::decode_nbt(Tag::parse(source).unwrap()).unwrap()
+	}
+
+	fn table() -> LegacyTable {
+		"# Stone and its variants.\n1 minecraft:stone\n1:1 minecraft:granite\n54:3 minecraft:chest[facing=south]\n0 minecraft:air\n"
+			.parse()
+			.unwrap()
+	}
+
+	#[test]
+	fn decode_test() {
+		let schematic = sample();
+		assert_eq!(schematic.block_at(0, 0, 0), Some((1, 1)));
+		// The high nibble of the first AddBlocks byte belongs to the second block.
+		assert_eq!(schematic.block_at(1, 0, 0), Some((0x136, 3)));
+		assert_eq!(schematic.block_at(2, 0, 0), Some((2, 0)));
+		assert!(schematic.other.contains_key("WEOriginX"));
+
+		let mut file = Vec::new();
+		schematic.write(&mut file).unwrap();
+		let read = LegacySchematic::read(&mut file.as_slice()).unwrap();
+		assert_eq!(read.blocks().collect::<Vec<_>>(), schematic.blocks().collect::<Vec<_>>());
+		assert_eq!(read.tile_entities.len(), 1);
+
+		let error = |source: &str| LegacySchematic::decode_nbt(Tag::parse(source).unwrap()).unwrap_err().to_string();
+		assert_eq!(error("{Width: 2s, Height: 1s, Length: 1s, Blocks: [B; 1b], Data: [B; 0b, 0b]}"), "Blocks: Expected 2 entries, found 1.");
+		assert_eq!(error("{Width: 1s, Height: 1s, Length: 1s, Blocks: [B; 1b], Data: [B; 16b]}"), "Data: Entry 0 is 16, which doesn't fit in 4 bits.");
+	}
+
+	#[test]
+	fn convert_test() {
+		let mut schematic = sample();
+		schematic.set_block(1, 0, 0, 54, 3);
+		let mut table = table();
+		assert_eq!(schematic.to_sponge(&table, 1343).unwrap_err(), UnmappedBlock { id: 2, data: 0, pos: [2, 0, 0] });
+		table.insert_any(2, BlockState::new("minecraft:grass_block").with("snowy", "false"));
+
+		let sponge = schematic.to_sponge(&table, 3465).unwrap();
+		assert_eq!(sponge.block_at(0, 0, 0).unwrap().name, "minecraft:granite");
+		assert_eq!(sponge.block_at(1, 0, 0).unwrap().to_string(), "minecraft:chest[facing=south]");
+		assert_eq!((sponge.block_entities[0].pos, sponge.block_entities[0].id.as_str()), ([1, 0, 0], "Chest"));
+		assert!(sponge.block_entities[0].data.contains_key("Items"));
+		assert_eq!((sponge.entities[0].pos, sponge.entities[0].id.as_str()), ([0.5, 1.0, 0.5], "Pig"));
+
+		let structure = schematic.to_structure(&table, 3465).unwrap();
+		assert_eq!(structure.size, [3, 1, 1]);
+		let (state, nbt) = structure.block_at([1, 0, 0], 0).unwrap();
+		assert_eq!(state.name, "minecraft:chest");
+		assert!(nbt.unwrap().contains_key("Items") && !nbt.unwrap().contains_key("x"));
+		assert_eq!(structure.entities[0].block_pos, [0, 1, 0]);
+
+		// Closures are mappings too.
+		let everything_stone = |_, _| Some(BlockState::new("minecraft:stone"));
+		assert_eq!(schematic.to_structure(&everything_stone, 3465).unwrap().palette(0).len(), 1);
+	}
+
+	#[test]
+	fn table_test() {
+		let error = |source: &str| source.parse::<LegacyTable>().unwrap_err().to_string();
+		assert_eq!(error("1 minecraft:stone\nstone minecraft:stone"), "Line 2: `stone` isn't a valid ID or data value.");
+		assert_eq!(error("\n1"), "Line 2: Expected an ID and a block state, found `1`.");
+		assert_eq!(error("1:16 minecraft:stone"), "Line 1: `16` isn't a valid ID or data value.");
+	}
+}
@@ -0,0 +1,289 @@
+// Unsigned integers packed into the longs of a LongArray, as used by chunk block states,
+// biomes and heightmaps.
+
+use crate::tag::{Tag, TagID};
+
+/// How the entries of a [PackedArray] are laid out in its longs.<br>
+/// In both layouts the first entry is in the lowest bits of the first long.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Layout {
+	/// Entries never span two longs, so the highest bits of each long may be unused.
+	/// Used since Minecraft 1.16.
+	#[default]
+	Aligned,
+	/// Entries are packed end to end, so an entry may start in one long and end in the next.
+	/// Used before Minecraft 1.16.
+	Spanning,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PackedError {
+	#[error("Entries must have 1 to 64 bits, not {0}.")]
+	InvalidBits(u8),
+	#[error("{len} entries of {bits} bits need {expected} longs, found {found}.")]
+	WrongLength { len: usize, bits: u8, expected: usize, found: usize },
+	#[error("Expected LongArray, found {}.", .0.title())]
+	NotALongArray(TagID),
+	#[error("Entry {index} is {value}, which doesn't fit in {bits} bits.")]
+	TooLarge { index: usize, value: u64, bits: u8 },
+}
+
+/// A fixed number of unsigned entries, each `bits` wide, packed into a `Vec<i64>`.
+/// ```
+/// # use rustnbt::packed::{Layout, PackedArray};
+/// let mut heights = PackedArray::new(256, 9, Layout::Aligned).unwrap();
+/// heights.set(0, 320);
+/// assert_eq!(heights.get(0), Some(320));
+/// assert_eq!(heights.longs().len(), 37);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PackedArray {
+	data: Vec<i64>,
+	len: usize,
+	bits: u8,
+	layout: Layout,
+}
+
+impl PackedArray {
+	/// Creates an array of `len` zeros.
+	pub fn new(len: usize, bits: u8, layout: Layout) -> Result<Self, PackedError> {
+		let longs = Self::longs_needed(len, bits, layout)?;
+		Ok(Self { data: vec![0; longs], len, bits, layout })
+	}
+
+	/// Wraps packed longs, such as the value of a `block_states.data` tag.
+	/// `data` must have exactly as many longs as `len` entries need.
+	pub fn from_longs(data: Vec<i64>, len: usize, bits: u8, layout: Layout) -> Result<Self, PackedError> {
+		let expected = Self::longs_needed(len, bits, layout)?;
+		if data.len() != expected {
+			return Err(PackedError::WrongLength { len, bits, expected, found: data.len() });
+		}
+		Ok(Self { data, len, bits, layout })
+	}
+
+	/// Wraps the longs of a LongArray. See [PackedArray::from_longs].
+	pub fn from_tag(tag: Tag, len: usize, bits: u8, layout: Layout) -> Result<Self, PackedError> {
+		match tag {
+			Tag::LongArray(data) => Self::from_longs(data, len, bits, layout),
+			other => Err(PackedError::NotALongArray(other.id())),
+		}
+	}
+
+	/// Packs `values`, which must all fit in `bits`.
+	pub fn from_values<I: IntoIterator<Item = u64>>(values: I, bits: u8, layout: Layout) -> Result<Self, PackedError> {
+		let values: Vec<u64> = values.into_iter().collect();
+		let mut array = Self::new(values.len(), bits, layout)?;
+		for (index, value) in values.into_iter().enumerate() {
+			Self::check(index, value, bits)?;
+			array.write(index, value);
+		}
+		Ok(array)
+	}
+
+	/// The number of longs needed to store `len` entries of `bits` bits.
+	pub fn longs_needed(len: usize, bits: u8, layout: Layout) -> Result<usize, PackedError> {
+		if !(1..=64).contains(&bits) {
+			return Err(PackedError::InvalidBits(bits));
+		}
+		Ok(match layout {
+			Layout::Aligned => len.div_ceil(64 / bits as usize),
+			Layout::Spanning => (len * bits as usize).div_ceil(64),
+		})
+	}
+
+	/// The fewest bits per entry for which `len` entries fill exactly `longs` longs,
+	/// for data that doesn't record its width, such as heightmaps.
+	pub fn bits_for_longs(len: usize, longs: usize, layout: Layout) -> Option<u8> {
+		(1..=64).find(|&bits| Self::longs_needed(len, bits, layout) == Ok(longs))
+	}
+
+	/// The fewest bits that can hold every value up to `max`, such as a palette index.
+	pub fn bits_needed(max: u64) -> u8 {
+		(64 - max.leading_zeros() as u8).max(1)
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The width of every entry.
+	pub fn bits(&self) -> u8 {
+		self.bits
+	}
+
+	pub fn layout(&self) -> Layout {
+		self.layout
+	}
+
+	/// The largest value that an entry can hold.
+	pub fn max_value(&self) -> u64 {
+		u64::MAX >> (64 - self.bits)
+	}
+
+	pub fn longs(&self) -> &[i64] {
+		&self.data
+	}
+
+	pub fn into_longs(self) -> Vec<i64> {
+		self.data
+	}
+
+	/// Returns the entry at `index`, or `None` if it is out of bounds.
+	pub fn get(&self, index: usize) -> Option<u64> {
+		if index >= self.len {
+			return None;
+		}
+		let mask = self.max_value();
+		let (long, offset) = self.position(index);
+		let mut value = self.data[long] as u64 >> offset;
+		if offset + self.bits as usize > 64 {
+			value |= (self.data[long + 1] as u64) << (64 - offset);
+		}
+		Some(value & mask)
+	}
+
+	/// Replaces the entry at `index`, returning the old value.
+	/// # Panics
+	/// Panics if `index` is out of bounds, or `value` doesn't fit in [PackedArray::bits].
+	pub fn set(&mut self, index: usize, value: u64) -> u64 {
+		let old = self.get(index).unwrap_or_else(|| panic!("index {index} is out of bounds for a PackedArray of length {}", self.len));
+		if let Err(error) = Self::check(index, value, self.bits) {
+			panic!("{error}");
+		}
+		self.write(index, value);
+		old
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+		(0..self.len).map(|index| self.get(index).expect("index is below the length"))
+	}
+
+	/// Repacks the entries with a different width, such as after a palette grows or shrinks.
+	/// Fails without changing anything if an entry doesn't fit in `bits`.
+	pub fn resize_bits(&mut self, bits: u8) -> Result<(), PackedError> {
+		self.repack(bits, self.layout)
+	}
+
+	/// Repacks the entries with a different width and layout, such as when converting chunks between versions.
+	/// Fails without changing anything if an entry doesn't fit in `bits`.
+	pub fn repack(&mut self, bits: u8, layout: Layout) -> Result<(), PackedError> {
+		if bits == self.bits && layout == self.layout {
+			return Ok(());
+		}
+		let mut repacked = Self::new(self.len, bits, layout)?;
+		for (index, value) in self.iter().enumerate() {
+			Self::check(index, value, bits)?;
+			repacked.write(index, value);
+		}
+		*self = repacked;
+		Ok(())
+	}
+
+	/// The long that holds the lowest bit of entry `index`, and the offset of that bit.
+	fn position(&self, index: usize) -> (usize, usize) {
+		let bits = self.bits as usize;
+		match self.layout {
+			Layout::Aligned => {
+				let per_long = 64 / bits;
+				(index / per_long, index % per_long * bits)
+			}
+			Layout::Spanning => {
+				let bit = index * bits;
+				(bit / 64, bit % 64)
+			}
+		}
+	}
+
+	fn check(index: usize, value: u64, bits: u8) -> Result<(), PackedError> {
+		if value > u64::MAX >> (64 - bits) {
+			return Err(PackedError::TooLarge { index, value, bits });
+		}
+		Ok(())
+	}
+
+	/// Writes a value that is known to fit.
+	fn write(&mut self, index: usize, value: u64) {
+		let mask = self.max_value();
+		let (long, offset) = self.position(index);
+		let low = &mut self.data[long];
+		*low = ((*low as u64 & !(mask << offset)) | (value << offset)) as i64;
+		let end = offset + self.bits as usize;
+		if end > 64 {
+			let high_mask = u64::MAX >> (128 - end);
+			let high = &mut self.data[long + 1];
+			*high = ((*high as u64 & !high_mask) | (value >> (64 - offset))) as i64;
+		}
+	}
+}
+
+impl From<PackedArray> for Tag {
+	fn from(value: PackedArray) -> Self {
+		Tag::LongArray(value.data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn layout_test() {
+		// Block states and heightmaps, before and after 1.16.
+		assert_eq!(PackedArray::longs_needed(4096, 4, Layout::Aligned), Ok(256));
+		assert_eq!(PackedArray::longs_needed(4096, 5, Layout::Aligned), Ok(342));
+		assert_eq!(PackedArray::longs_needed(4096, 5, Layout::Spanning), Ok(320));
+		assert_eq!(PackedArray::longs_needed(256, 9, Layout::Aligned), Ok(37));
+		assert_eq!(PackedArray::longs_needed(256, 9, Layout::Spanning), Ok(36));
+		assert_eq!(PackedArray::longs_needed(1, 0, Layout::Aligned), Err(PackedError::InvalidBits(0)));
+		assert_eq!(PackedArray::bits_for_longs(256, 37, Layout::Aligned), Some(9));
+		assert_eq!(PackedArray::bits_for_longs(256, 36, Layout::Spanning), Some(9));
+		assert_eq!(PackedArray::bits_for_longs(256, 38, Layout::Aligned), None);
+
+		let mut aligned = PackedArray::new(13, 5, Layout::Aligned).unwrap();
+		aligned.set(12, 0b11111);
+		assert_eq!(aligned.longs(), [0, 0b11111]);
+		let mut spanning = PackedArray::new(13, 5, Layout::Spanning).unwrap();
+		spanning.set(12, 0b11111);
+		assert_eq!(spanning.longs(), [0xf << 60, 0b1]);
+		assert_eq!(spanning.get(12), Some(0b11111));
+		assert_eq!(spanning.set(12, 0b10001), 0b11111);
+		assert_eq!(spanning.longs(), [1 << 60, 0b1]);
+		assert_eq!(spanning.get(13), None);
+
+		let full = PackedArray::from_values([u64::MAX, 1], 64, Layout::Spanning).unwrap();
+		assert_eq!(full.longs(), [-1, 1]);
+		assert_eq!(full.iter().collect::<Vec<_>>(), [u64::MAX, 1]);
+	}
+
+	#[test]
+	fn repack_test() {
+		let values: Vec<u64> = (0..100).map(|value| value * 7 % 31).collect();
+		let mut array = PackedArray::from_values(values.clone(), 5, Layout::Aligned).unwrap();
+		for (bits, layout) in [(7, Layout::Aligned), (5, Layout::Spanning), (13, Layout::Spanning), (5, Layout::Aligned)] {
+			array.repack(bits, layout).unwrap();
+			assert_eq!((array.bits(), array.layout()), (bits, layout));
+			assert_eq!(array.iter().collect::<Vec<_>>(), values);
+		}
+		let before = array.clone();
+		assert_eq!(array.resize_bits(4), Err(PackedError::TooLarge { index: 3, value: 21, bits: 4 }));
+		assert_eq!(array, before);
+		assert_eq!(PackedArray::bits_needed(0), 1);
+		assert_eq!(PackedArray::bits_needed(15), 4);
+		assert_eq!(PackedArray::bits_needed(16), 5);
+	}
+
+	#[test]
+	fn tag_test() {
+		let array = PackedArray::from_values([1, 2, 3], 4, Layout::Aligned).unwrap();
+		let tag = Tag::from(array.clone());
+		assert!(matches!(&tag, Tag::LongArray(data) if data == &[0x321]));
+		assert_eq!(PackedArray::from_tag(tag, 3, 4, Layout::Aligned), Ok(array));
+		assert_eq!(PackedArray::from_tag(Tag::LongArray(vec![0; 2]), 3, 4, Layout::Aligned),
+			Err(PackedError::WrongLength { len: 3, bits: 4, expected: 1, found: 2 }));
+		assert_eq!(PackedArray::from_tag(Tag::Int(0), 3, 4, Layout::Aligned), Err(PackedError::NotALongArray(TagID::Int)));
+	}
+}
@@ -0,0 +1,499 @@
+// Paths that address a single tag inside of an NBT tree.
+// The syntax is a subset of the NBT paths used by Minecraft's `/data` command:
+//     Level.Sections[0].BlockStates
+//     "key with spaces".Items[-1].id
+// Keys are separated by `.`, and `[index]` selects an element of a list or array.
+// Negative indices count from the end.
+
+use crate::{
+	Map,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A single step in a [TagPath].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+	/// Selects the tag with this name in a compound.
+	Key(String),
+	/// Selects an element of a list or array. Negative values count from the end.
+	Index(isize),
+}
+
+/// A path to a tag inside of an NBT tree, such as `Data.Player.Inventory[0].id`.<br>
+/// The empty path refers to the root tag.
+/// ```
+/// # use rustnbt::{compound, path::TagPath, tag::Tag};
+/// let mut tag = compound!{ ("Inventory", Tag::list([1i32, 2, 3])) };
+/// let path: TagPath = "Inventory[-1]".parse().unwrap();
+/// assert!(matches!(path.get(&tag), Ok(Tag::Int(3))));
+/// path.set(&mut tag, Tag::Int(4)).unwrap();
+/// assert!(matches!(path.get(&tag), Ok(Tag::Int(4))));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TagPath {
+	segments: Vec<PathSegment>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+	/// The path string could not be parsed. `position` is a char offset.
+	#[error("Invalid path at position {position}: {message}")]
+	Syntax { position: usize, message: &'static str },
+	/// A compound does not contain the requested key.
+	#[error("`{path}` does not exist.")]
+	NotFound { path: String },
+	/// An index was outside of the bounds of a list or array.
+	#[error("Index {index} is out of bounds for `{path}`, which has {len} element(s).")]
+	OutOfBounds { path: String, index: isize, len: usize },
+	/// The path tried to select a child of a tag that doesn't have children of that kind.
+	#[error("`{path}` is a {found}, so it can't be {action}.")]
+	NotAContainer { path: String, found: TagID, action: &'static str },
+	/// The value given to [TagPath::set] doesn't match the element type of a list or array.
+	#[error("`{path}` holds {expected} elements, but the new value is {found}.")]
+	TypeMismatch { path: String, expected: TagID, found: TagID },
+}
+
+impl TagPath {
+	/// The path to the root tag.
+	pub fn root() -> Self {
+		Self::default()
+	}
+
+	pub fn segments(&self) -> &[PathSegment] {
+		&self.segments
+	}
+
+	/// Returns `true` if this is the path to the root tag.
+	pub fn is_root(&self) -> bool {
+		self.segments.is_empty()
+	}
+
+	/// Returns a new path that selects `key` below this path.
+	pub fn key<S: Into<String>>(&self, key: S) -> Self {
+		let mut path = self.clone();
+		path.segments.push(PathSegment::Key(key.into()));
+		path
+	}
+
+	/// Returns a new path that selects `index` below this path.
+	pub fn index(&self, index: isize) -> Self {
+		let mut path = self.clone();
+		path.segments.push(PathSegment::Index(index));
+		path
+	}
+
+	/// Returns this path with `segment` in front of it, for errors that are built from the inside out.
+	pub(crate) fn prefixed(mut self, segment: PathSegment) -> Self {
+		self.segments.insert(0, segment);
+		self
+	}
+
+	/// Returns a copy of the tag at this path.
+	pub fn get(&self, root: &Tag) -> Result<Tag, PathError> {
+		let mut node = Node::of(root);
+		for depth in 0..self.segments.len() {
+			node = node.child(self, depth)?;
+		}
+		Ok(node.to_tag())
+	}
+
+	/// Replaces the tag at this path with `value`, returning the previous tag if there was one.<br>
+	/// A missing key in the last compound is inserted, but every other part of the path must exist.
+	/// Elements of lists and arrays must keep the element type of their container.
+	pub fn set(&self, root: &mut Tag, value: Tag) -> Result<Option<Tag>, PathError> {
+		let Some(last) = self.segments.last() else {
+			return Ok(Some(std::mem::replace(root, value)));
+		};
+		let depth = self.segments.len() - 1;
+		let mut node = NodeMut::of(root);
+		for depth in 0..depth {
+			node = node.child(self, depth)?;
+		}
+		let mismatch = |expected: TagID, value: &Tag| PathError::TypeMismatch {
+			path: self.prefix(depth),
+			expected,
+			found: value.id(),
+		};
+		match (node, last) {
+			(NodeMut::Compound(map), PathSegment::Key(key)) => Ok(map.insert(key.clone(), value)),
+			(NodeMut::List(list), &PathSegment::Index(index)) => {
+				let position = self.resolve(depth, index, list.len())?;
+				let expected = list.id();
+				list.replace(position, value)
+					.map(Some)
+					.map_err(|value| mismatch(expected, &value))
+			}
+			(NodeMut::ByteArray(array), &PathSegment::Index(index)) => match value {
+				Tag::Byte(value) => Ok(Some(Tag::Byte(self.replace_element(depth, array, index, value)?))),
+				value => Err(mismatch(TagID::Byte, &value)),
+			},
+			(NodeMut::IntArray(array), &PathSegment::Index(index)) => match value {
+				Tag::Int(value) => Ok(Some(Tag::Int(self.replace_element(depth, array, index, value)?))),
+				value => Err(mismatch(TagID::Int, &value)),
+			},
+			(NodeMut::LongArray(array), &PathSegment::Index(index)) => match value {
+				Tag::Long(value) => Ok(Some(Tag::Long(self.replace_element(depth, array, index, value)?))),
+				value => Err(mismatch(TagID::Long, &value)),
+			},
+			(node, _) => Err(self.not_a_container(depth, node.id())),
+		}
+	}
+
+	/// The display form of the first `len` segments of this path.
+	fn prefix(&self, len: usize) -> String {
+		TagPath { segments: self.segments[..len].to_vec() }.to_string()
+	}
+
+	/// Turns the (possibly negative) index at `depth` into a position within `len` elements.
+	fn resolve(&self, depth: usize, index: isize, len: usize) -> Result<usize, PathError> {
+		let position = if index < 0 {
+			len.checked_sub(index.unsigned_abs())
+		} else {
+			Some(index as usize)
+		};
+		match position {
+			Some(position) if position < len => Ok(position),
+			_ => Err(PathError::OutOfBounds { path: self.prefix(depth), index, len }),
+		}
+	}
+
+	fn replace_element<T>(&self, depth: usize, array: &mut [T], index: isize, value: T) -> Result<T, PathError> {
+		let position = self.resolve(depth, index, array.len())?;
+		Ok(std::mem::replace(&mut array[position], value))
+	}
+
+	fn not_found(&self, depth: usize) -> PathError {
+		PathError::NotFound { path: self.prefix(depth + 1) }
+	}
+
+	fn not_a_container(&self, depth: usize, found: TagID) -> PathError {
+		let action = match self.segments[depth] {
+			PathSegment::Key(_) => "accessed by key",
+			PathSegment::Index(_) => "indexed",
+		};
+		PathError::NotAContainer { path: self.prefix(depth), found, action }
+	}
+}
+
+/// A position in an NBT tree while walking a [TagPath].
+enum Node<'a> {
+	Compound(&'a Map),
+	List(&'a ListTag),
+	ByteArray(&'a [i8]),
+	IntArray(&'a [i32]),
+	LongArray(&'a [i64]),
+	/// A tag without children.
+	Value(Tag),
+}
+
+impl<'a> Node<'a> {
+	fn of(tag: &'a Tag) -> Self {
+		match tag {
+			Tag::Compound(map) => Node::Compound(map),
+			Tag::List(list) => Node::List(list),
+			Tag::ByteArray(array) => Node::ByteArray(array),
+			Tag::IntArray(array) => Node::IntArray(array),
+			Tag::LongArray(array) => Node::LongArray(array),
+			other => Node::Value(other.clone()),
+		}
+	}
+
+	fn id(&self) -> TagID {
+		match self {
+			Node::Compound(_) => TagID::Compound,
+			Node::List(_) => TagID::List,
+			Node::ByteArray(_) => TagID::ByteArray,
+			Node::IntArray(_) => TagID::IntArray,
+			Node::LongArray(_) => TagID::LongArray,
+			Node::Value(tag) => tag.id(),
+		}
+	}
+
+	/// Selects the child named by the segment of `path` at `depth`.
+	fn child(self, path: &TagPath, depth: usize) -> Result<Node<'a>, PathError> {
+		match (self, &path.segments[depth]) {
+			(Node::Compound(map), PathSegment::Key(key)) => map.get(key)
+				.map(Node::of)
+				.ok_or_else(|| path.not_found(depth)),
+			(Node::List(list), &PathSegment::Index(index)) => {
+				let position = path.resolve(depth, index, list.len())?;
+				Ok(match list {
+					ListTag::ByteArray(arrays) => Node::ByteArray(&arrays[position]),
+					ListTag::List(lists) => Node::List(&lists[position]),
+					ListTag::Compound(maps) => Node::Compound(&maps[position]),
+					ListTag::IntArray(arrays) => Node::IntArray(&arrays[position]),
+					ListTag::LongArray(arrays) => Node::LongArray(&arrays[position]),
+					// The position is in bounds, so the list can't be Empty.
+					list => Node::Value(list.get(position).expect("position is in bounds")),
+				})
+			}
+			(Node::ByteArray(array), &PathSegment::Index(index)) => Ok(Node::Value(Tag::Byte(array[path.resolve(depth, index, array.len())?]))),
+			(Node::IntArray(array), &PathSegment::Index(index)) => Ok(Node::Value(Tag::Int(array[path.resolve(depth, index, array.len())?]))),
+			(Node::LongArray(array), &PathSegment::Index(index)) => Ok(Node::Value(Tag::Long(array[path.resolve(depth, index, array.len())?]))),
+			(node, _) => Err(path.not_a_container(depth, node.id())),
+		}
+	}
+
+	fn to_tag(&self) -> Tag {
+		match self {
+			Node::Compound(map) => Tag::Compound((*map).clone()),
+			Node::List(list) => Tag::List((*list).clone()),
+			Node::ByteArray(array) => Tag::ByteArray(array.to_vec()),
+			Node::IntArray(array) => Tag::IntArray(array.to_vec()),
+			Node::LongArray(array) => Tag::LongArray(array.to_vec()),
+			Node::Value(tag) => tag.clone(),
+		}
+	}
+}
+
+/// A mutable position in an NBT tree while walking a [TagPath].
+enum NodeMut<'a> {
+	Compound(&'a mut Map),
+	List(&'a mut ListTag),
+	ByteArray(&'a mut [i8]),
+	IntArray(&'a mut [i32]),
+	LongArray(&'a mut [i64]),
+	/// A tag without children.
+	Value(TagID),
+}
+
+impl<'a> NodeMut<'a> {
+	fn of(tag: &'a mut Tag) -> Self {
+		match tag {
+			Tag::Compound(map) => NodeMut::Compound(map),
+			Tag::List(list) => NodeMut::List(list),
+			Tag::ByteArray(array) => NodeMut::ByteArray(array),
+			Tag::IntArray(array) => NodeMut::IntArray(array),
+			Tag::LongArray(array) => NodeMut::LongArray(array),
+			other => NodeMut::Value(other.id()),
+		}
+	}
+
+	fn id(&self) -> TagID {
+		match self {
+			NodeMut::Compound(_) => TagID::Compound,
+			NodeMut::List(_) => TagID::List,
+			NodeMut::ByteArray(_) => TagID::ByteArray,
+			NodeMut::IntArray(_) => TagID::IntArray,
+			NodeMut::LongArray(_) => TagID::LongArray,
+			NodeMut::Value(id) => *id,
+		}
+	}
+
+	/// Selects the child named by the segment of `path` at `depth`.
+	/// Elements of primitive lists and arrays are never parents, so they are reported as [NodeMut::Value].
+	fn child(self, path: &TagPath, depth: usize) -> Result<NodeMut<'a>, PathError> {
+		match (self, &path.segments[depth]) {
+			(NodeMut::Compound(map), PathSegment::Key(key)) => map.get_mut(key)
+				.map(NodeMut::of)
+				.ok_or_else(|| path.not_found(depth)),
+			(NodeMut::List(list), &PathSegment::Index(index)) => {
+				let position = path.resolve(depth, index, list.len())?;
+				Ok(match list {
+					ListTag::ByteArray(arrays) => NodeMut::ByteArray(&mut arrays[position]),
+					ListTag::List(lists) => NodeMut::List(&mut lists[position]),
+					ListTag::Compound(maps) => NodeMut::Compound(&mut maps[position]),
+					ListTag::IntArray(arrays) => NodeMut::IntArray(&mut arrays[position]),
+					ListTag::LongArray(arrays) => NodeMut::LongArray(&mut arrays[position]),
+					list => NodeMut::Value(list.id()),
+				})
+			}
+			(NodeMut::ByteArray(array), &PathSegment::Index(index)) => path.resolve(depth, index, array.len()).map(|_| NodeMut::Value(TagID::Byte)),
+			(NodeMut::IntArray(array), &PathSegment::Index(index)) => path.resolve(depth, index, array.len()).map(|_| NodeMut::Value(TagID::Int)),
+			(NodeMut::LongArray(array), &PathSegment::Index(index)) => path.resolve(depth, index, array.len()).map(|_| NodeMut::Value(TagID::Long)),
+			(node, _) => Err(path.not_a_container(depth, node.id())),
+		}
+	}
+}
+
+/// Returns `true` if `key` can be written in a path without quotes.
+fn is_bare_key(key: &str) -> bool {
+	!key.is_empty() && key.chars().all(|c| !is_reserved(c))
+}
+
+fn is_reserved(c: char) -> bool {
+	matches!(c, '.' | '[' | ']' | '"' | '\'' | '\\') || c.is_whitespace()
+}
+
+impl Display for TagPath {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (depth, segment) in self.segments.iter().enumerate() {
+			match segment {
+				PathSegment::Key(key) => {
+					if depth != 0 {
+						write!(f, ".")?;
+					}
+					if is_bare_key(key) {
+						write!(f, "{key}")?;
+					} else {
+						crate::format::write_string(f, key)?;
+					}
+				}
+				PathSegment::Index(index) => write!(f, "[{index}]")?,
+			}
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for TagPath {
+	type Err = PathError;
+
+	fn from_str(source: &str) -> Result<Self, Self::Err> {
+		let mut chars = source.chars().enumerate().peekable();
+		let mut segments = Vec::new();
+		let syntax = |position: usize, message: &'static str| PathError::Syntax { position, message };
+		while let Some(&(position, c)) = chars.peek() {
+			match c {
+				'[' => {
+					chars.next();
+					let mut digits = String::new();
+					while let Some((_, c)) = chars.next_if(|&(_, c)| c == '-' || c.is_ascii_digit()) {
+						digits.push(c);
+					}
+					let index = digits.parse().map_err(|_| syntax(position + 1, "Expected an integer index."))?;
+					chars.next_if(|&(_, c)| c == ']').ok_or_else(|| syntax(position + 1 + digits.len(), "Expected `]`."))?;
+					segments.push(PathSegment::Index(index));
+				}
+				'.' if segments.is_empty() => return Err(syntax(position, "A path can't start with `.`.")),
+				'.' => {
+					chars.next();
+					match chars.peek() {
+						Some(&(_, c)) if c != '.' && c != '[' => segments.push(PathSegment::Key(parse_key(&mut chars)?)),
+						Some(&(position, _)) => return Err(syntax(position, "Expected a key after `.`.")),
+						None => return Err(syntax(source.chars().count(), "Expected a key after `.`.")),
+					}
+				}
+				_ if segments.is_empty() => segments.push(PathSegment::Key(parse_key(&mut chars)?)),
+				_ => return Err(syntax(position, "Expected `.` or `[` between path segments.")),
+			}
+		}
+		Ok(TagPath { segments })
+	}
+}
+
+/// Parses a bare or quoted key.
+fn parse_key(chars: &mut std::iter::Peekable<impl Iterator<Item = (usize, char)>>) -> Result<String, PathError> {
+	let syntax = |position: usize, message: &'static str| PathError::Syntax { position, message };
+	let mut key = String::new();
+	let &(start, first) = chars.peek().expect("parse_key is only called before a char");
+	if first == '"' || first == '\'' {
+		chars.next();
+		let mut end = start + 1;
+		loop {
+			match chars.next() {
+				Some((_, c)) if c == first => return Ok(key),
+				Some((position, '\\')) => match chars.next() {
+					Some((_, c)) if c == first || c == '\\' => key.push(c),
+					_ => return Err(syntax(position, "Only quotes and `\\` can be escaped in a path key.")),
+				},
+				Some((position, c)) => {
+					key.push(c);
+					end = position + 1;
+				}
+				None => return Err(syntax(end, "Unterminated quoted key.")),
+			}
+		}
+	}
+	while let Some((_, c)) = chars.next_if(|&(_, c)| !is_reserved(c)) {
+		key.push(c);
+	}
+	if key.is_empty() {
+		return Err(syntax(start, "Expected a key."));
+	}
+	Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::tests::same_tag;
+
+	fn sample() -> Tag {
+		Tag::parse(r#"{
+			Data: {
+				Player: {
+					Inventory: [{Slot: 0b, id: "minecraft:stone"}, {Slot: 1b, id: "minecraft:dirt"}],
+					Pos: [1.0d, 64.0d, -3.5d],
+				},
+				"odd key.name": [B; 1b, 2b, 3b],
+				Nested: [[I; 1, 2], [I; 3]],
+			}
+		}"#).unwrap()
+	}
+
+	#[test]
+	fn parse_test() {
+		let path: TagPath = r#"Data."odd key.name"[-1]"#.parse().unwrap();
+		assert_eq!(path.segments(), [
+			PathSegment::Key("Data".to_owned()),
+			PathSegment::Key("odd key.name".to_owned()),
+			PathSegment::Index(-1),
+		]);
+		assert_eq!(path.to_string(), r#"Data."odd key.name"[-1]"#);
+		assert_eq!("[0][1].a".parse::<TagPath>().unwrap(), TagPath::root().index(0).index(1).key("a"));
+		assert_eq!("'it\\'s'".parse::<TagPath>().unwrap(), TagPath::root().key("it's"));
+		assert!("".parse::<TagPath>().unwrap().is_root());
+		for invalid in [".a", "a..b", "a.", "a[", "a[x]", "a[1", "a\"b\"", "\"a", "a b"] {
+			assert!(matches!(invalid.parse::<TagPath>(), Err(PathError::Syntax { .. })), "{invalid}");
+		}
+	}
+
+	#[test]
+	fn get_test() {
+		let tag = sample();
+		let get = |path: &str| path.parse::<TagPath>().unwrap().get(&tag);
+		assert!(same_tag(&get("Data.Player.Inventory[1].id").unwrap(), &Tag::string("minecraft:dirt")));
+		assert!(same_tag(&get("Data.Player.Pos[-1]").unwrap(), &Tag::Double(-3.5)));
+		assert!(same_tag(&get("Data.\"odd key.name\"[0]").unwrap(), &Tag::Byte(1)));
+		assert!(same_tag(&get("Data.Nested[0][1]").unwrap(), &Tag::Int(2)));
+		assert!(same_tag(&get("").unwrap(), &tag));
+		assert_eq!(get("Data.Player.Missing").unwrap_err(), PathError::NotFound { path: "Data.Player.Missing".to_owned() });
+		assert_eq!(get("Data.Player.Pos[3]").unwrap_err(), PathError::OutOfBounds { path: "Data.Player.Pos".to_owned(), index: 3, len: 3 });
+		assert_eq!(get("Data.Player.Pos.x").unwrap_err(), PathError::NotAContainer {
+			path: "Data.Player.Pos".to_owned(),
+			found: TagID::List,
+			action: "accessed by key",
+		});
+	}
+
+	#[test]
+	fn set_test() {
+		let mut tag = sample();
+		let mut set = |path: &str, value: Tag| path.parse::<TagPath>().unwrap().set(&mut tag, value);
+		assert!(matches!(set("Data.Player.Inventory[0].Slot", Tag::Byte(5)), Ok(Some(Tag::Byte(0)))));
+		assert!(matches!(set("Data.Player.Health", Tag::Float(20.0)), Ok(None)));
+		assert!(matches!(set("Data.Nested[-1][0]", Tag::Int(7)), Ok(Some(Tag::Int(3)))));
+		assert!(matches!(set("Data.Player.Pos[0]", Tag::Double(2.0)), Ok(Some(Tag::Double(_)))));
+		assert_eq!(set("Data.Player.Pos[0]", Tag::Int(2)).unwrap_err(), PathError::TypeMismatch {
+			path: "Data.Player.Pos".to_owned(),
+			expected: TagID::Double,
+			found: TagID::Int,
+		});
+		assert_eq!(set("Data.Player.Pos[0].x", Tag::Int(2)).unwrap_err(), PathError::NotAContainer {
+			path: "Data.Player.Pos[0]".to_owned(),
+			found: TagID::Double,
+			action: "accessed by key",
+		});
+		assert_eq!(set("Data.Missing.x", Tag::Int(2)).unwrap_err(), PathError::NotFound { path: "Data.Missing".to_owned() });
+		let expected = Tag::parse(r#"{
+			Data: {
+				Player: {
+					Inventory: [{Slot: 5b, id: "minecraft:stone"}, {Slot: 1b, id: "minecraft:dirt"}],
+					Pos: [2.0d, 64.0d, -3.5d],
+					Health: 20f,
+				},
+				"odd key.name": [B; 1b, 2b, 3b],
+				Nested: [[I; 1, 2], [I; 7]],
+			}
+		}"#).unwrap();
+		assert!(same_tag(&tag, &expected));
+		assert!(matches!(TagPath::root().set(&mut tag, Tag::Int(1)), Ok(Some(Tag::Compound(_)))));
+	}
+}
@@ -0,0 +1,531 @@
+// Player data, which is kept in `playerdata/<uuid>.dat` as a gzipped compound, and statistics,
+// which are kept in `stats/<uuid>.json`.
+// https://minecraft.wiki/w/Player.dat_format
+// https://minecraft.wiki/w/Statistics#Statistics_file
+//
+// In singleplayer, the player is stored in level.dat instead (see [crate::level::LevelDat::player]),
+// and can be decoded from there with PlayerData::decode_nbt.
+
+use crate::{
+	Map,
+	MapType,
+	NbtError,
+	codegen::{self, DecodeError},
+	compression::{self, Compression},
+	io::{read_named_tag, write_named_tag},
+	item::ItemStack,
+	path::PathSegment,
+	tag::{DecodeNbt, EncodeNbt, ListTag, NbtType, Tag},
+	text::{json_tag, write_json},
+	uuid::Uuid,
+};
+use std::io::{Read, Write};
+use std::ops::Range;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PlayerDataError {
+	#[error("{0}")]
+	Io(#[from] std::io::Error),
+	#[error("{0}")]
+	Nbt(#[from] NbtError),
+	#[error("{0}")]
+	Json(#[from] serde_json::Error),
+	#[error("{0}")]
+	Decode(#[from] DecodeError),
+}
+
+/// The slots of the hotbar in the player's inventory.
+pub const HOTBAR: Range<i8> = 0..9;
+/// The slots of the player's inventory above the hotbar.
+pub const MAIN: Range<i8> = 9..36;
+/// The slots of the ender chest.
+pub const ENDER_CHEST: Range<i8> = 0..27;
+/// The armor slots of the player's inventory, from feet to head.
+/// Since Minecraft 1.21.5, armor is stored in the `equipment` compound instead.
+pub const ARMOR: Range<i8> = 100..104;
+/// The offhand slot of the player's inventory, which is also in `equipment` since Minecraft 1.21.5.
+pub const OFFHAND: i8 = -106;
+
+/// Items in slots, where every item has a `Slot` byte.
+/// ```
+/// # use rustnbt::{item::{ItemStack, ItemLayout}, player::{Inventory, HOTBAR}};
+/// let mut inventory = Inventory::default();
+/// inventory.set(0, ItemStack::new("minecraft:torch", 64, ItemLayout::Components));
+/// inventory.set(4, ItemStack::new("minecraft:torch", 12, ItemLayout::Components));
+/// assert_eq!(inventory.count("minecraft:torch"), 76);
+/// assert_eq!(inventory.first_empty(HOTBAR), Some(1));
+/// assert_eq!(inventory.remove(4).unwrap().count, 12);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Inventory {
+	/// The items, in the order that they were stored in.
+	pub items: Vec<ItemStack>,
+}
+
+impl Inventory {
+	/// The `Slot` of an item, if it has one.
+	pub fn slot_of(item: &ItemStack) -> Option<i8> {
+		match item.other.get("Slot") {
+			Some(&Tag::Byte(slot)) => Some(slot),
+			_ => None,
+		}
+	}
+
+	fn position(&self, slot: i8) -> Option<usize> {
+		self.items.iter().position(|item| Self::slot_of(item) == Some(slot))
+	}
+
+	pub fn get(&self, slot: i8) -> Option<&ItemStack> {
+		self.position(slot).map(|index| &self.items[index])
+	}
+
+	pub fn get_mut(&mut self, slot: i8) -> Option<&mut ItemStack> {
+		self.position(slot).map(|index| &mut self.items[index])
+	}
+
+	/// Puts an item in a slot, returning the item that was there.
+	pub fn set(&mut self, slot: i8, mut item: ItemStack) -> Option<ItemStack> {
+		item.other.insert("Slot".to_owned(), Tag::Byte(slot));
+		match self.position(slot) {
+			Some(index) => Some(std::mem::replace(&mut self.items[index], item)),
+			None => {
+				self.items.push(item);
+				None
+			}
+		}
+	}
+
+	/// Empties a slot, returning the item that was there.
+	pub fn remove(&mut self, slot: i8) -> Option<ItemStack> {
+		self.position(slot).map(|index| self.items.remove(index))
+	}
+
+	/// The items that have a slot, with their slots.
+	pub fn slots(&self) -> impl Iterator<Item = (i8, &ItemStack)> {
+		self.items.iter().filter_map(|item| Some((Self::slot_of(item)?, item)))
+	}
+
+	/// The items with an ID, such as `minecraft:diamond`, with their slots.
+	pub fn find<'a>(&'a self, id: &'a str) -> impl Iterator<Item = (i8, &'a ItemStack)> {
+		self.slots().filter(move |(_, item)| item.id == id)
+	}
+
+	/// The total count of the items with an ID.
+	pub fn count(&self, id: &str) -> i32 {
+		self.items.iter().filter(|item| item.id == id).map(|item| item.count).sum()
+	}
+
+	/// The first slot in `slots` that has no item.
+	pub fn first_empty(&self, mut slots: Range<i8>) -> Option<i8> {
+		slots.find(|&slot| self.position(slot).is_none())
+	}
+}
+
+impl DecodeNbt for Inventory {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		Ok(Self { items: codegen::list(nbt, ItemStack::decode_nbt)? })
+	}
+}
+
+impl EncodeNbt for Inventory {
+	fn encode_nbt(self) -> Tag {
+		codegen::encode_list(self.items.into_iter().map(ItemStack::encode_nbt))
+	}
+}
+
+/// What the player is allowed to do, which mostly depends on the game mode.
+#[derive(Clone, Debug)]
+pub struct Abilities {
+	pub flying: bool,
+	pub may_fly: bool,
+	/// Whether blocks break instantly and items aren't used up, as in creative mode.
+	pub instabuild: bool,
+	pub invulnerable: bool,
+	pub may_build: bool,
+	pub fly_speed: f32,
+	pub walk_speed: f32,
+	pub other: Map,
+}
+
+impl Default for Abilities {
+	/// The abilities of a player in survival mode.
+	fn default() -> Self {
+		Self {
+			flying: false,
+			may_fly: false,
+			instabuild: false,
+			invulnerable: false,
+			may_build: true,
+			fly_speed: 0.05,
+			walk_speed: 0.1,
+			other: Map::new(),
+		}
+	}
+}
+
+fn boolean(tag: Tag) -> Result<bool, DecodeError> {
+	codegen::value::<i8>(tag).map(|value| value != 0)
+}
+
+impl DecodeNbt for Abilities {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let default = Self::default();
+		Ok(Self {
+			flying: codegen::optional(&mut map, "flying", boolean)?.unwrap_or(default.flying),
+			may_fly: codegen::optional(&mut map, "mayfly", boolean)?.unwrap_or(default.may_fly),
+			instabuild: codegen::optional(&mut map, "instabuild", boolean)?.unwrap_or(default.instabuild),
+			invulnerable: codegen::optional(&mut map, "invulnerable", boolean)?.unwrap_or(default.invulnerable),
+			may_build: codegen::optional(&mut map, "mayBuild", boolean)?.unwrap_or(default.may_build),
+			fly_speed: codegen::optional(&mut map, "flySpeed", codegen::value)?.unwrap_or(default.fly_speed),
+			walk_speed: codegen::optional(&mut map, "walkSpeed", codegen::value)?.unwrap_or(default.walk_speed),
+			other: map,
+		})
+	}
+}
+
+impl EncodeNbt for Abilities {
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		for (key, value) in [("flying", self.flying), ("mayfly", self.may_fly), ("instabuild", self.instabuild),
+			("invulnerable", self.invulnerable), ("mayBuild", self.may_build)] {
+			map.insert(key.to_owned(), Tag::Byte(value as i8));
+		}
+		map.insert("flySpeed".to_owned(), Tag::Float(self.fly_speed));
+		map.insert("walkSpeed".to_owned(), Tag::Float(self.walk_speed));
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+/// The player's experience.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Experience {
+	pub level: i32,
+	/// The progress towards the next level, from 0 to 1.
+	pub progress: f32,
+	/// The experience collected in total, which is shown as the score on the death screen.
+	pub total: i32,
+}
+
+/// An attribute of the player, such as `minecraft:generic.max_health`, or `generic.maxHealth` before Minecraft 1.16.
+#[derive(Clone, Debug)]
+pub struct Attribute {
+	pub id: String,
+	pub base: f64,
+	/// The modifiers, whose keys were renamed along with the attribute's in Minecraft 1.20.5.
+	pub modifiers: Vec<Map>,
+	pub other: Map,
+}
+
+impl Attribute {
+	/// Decodes `{Name, Base, Modifiers}`, as used before Minecraft 1.20.5, or `{id, base, modifiers}`.
+	fn decode(tag: Tag, legacy: bool) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(tag)?;
+		let [id, base, modifiers] = match legacy {
+			true => ["Name", "Base", "Modifiers"],
+			false => ["id", "base", "modifiers"],
+		};
+		Ok(Self {
+			id: codegen::required(&mut map, id, codegen::value)?,
+			base: codegen::required(&mut map, base, codegen::value)?,
+			modifiers: codegen::optional(&mut map, modifiers, |tag| codegen::list(tag, codegen::compound))?.unwrap_or_default(),
+			other: map,
+		})
+	}
+
+	fn encode(self, legacy: bool) -> Tag {
+		let [id, base, modifiers] = match legacy {
+			true => ["Name", "Base", "Modifiers"],
+			false => ["id", "base", "modifiers"],
+		};
+		let mut map = Map::new();
+		map.insert(id.to_owned(), Tag::String(self.id));
+		map.insert(base.to_owned(), Tag::Double(self.base));
+		if !self.modifiers.is_empty() {
+			map.insert(modifiers.to_owned(), Tag::List(ListTag::from(self.modifiers)));
+		}
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+/// The dimensions that were stored as ints before Minecraft 1.16.
+const LEGACY_DIMENSIONS: [(i32, &str); 3] = [(-1, "minecraft:the_nether"), (0, "minecraft:overworld"), (1, "minecraft:the_end")];
+
+/// The data of a player.
+/// ```
+/// # use rustnbt::{player::{PlayerData, HOTBAR}, tag::{DecodeNbt, Tag}};
+/// let tag = Tag::parse("{Pos: [0.5d, 64d, 0.5d], Rotation: [0f, 0f], Dimension: -1, XpLevel: 30,
+///     Inventory: [{id: \"minecraft:diamond\", Count: 3b, Slot: 0b}]}").unwrap();
+/// let player = PlayerData::decode_nbt(tag).unwrap();
+/// assert_eq!(player.dimension, "minecraft:the_nether");
+/// assert_eq!(player.experience.level, 30);
+/// assert_eq!(player.inventory.find("minecraft:diamond").next().unwrap().0, 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PlayerData {
+	/// The data version, which is missing before Minecraft 1.9.
+	pub data_version: Option<i32>,
+	pub pos: [f64; 3],
+	/// The yaw and pitch in degrees.
+	pub rotation: [f32; 2],
+	/// The dimension, such as `minecraft:overworld`.
+	pub dimension: String,
+	pub inventory: Inventory,
+	pub ender_chest: Inventory,
+	pub abilities: Abilities,
+	pub experience: Experience,
+	pub attributes: Vec<Attribute>,
+	/// Keys that aren't modeled, such as `UUID`, `Health` and `playerGameType`.
+	pub other: Map,
+	/// Whether the dimension was stored as an int, as it was before Minecraft 1.16.
+	legacy_dimension: bool,
+	/// Whether the attributes were stored in `Attributes`, as they were before Minecraft 1.20.5.
+	legacy_attributes: bool,
+}
+
+fn array<T: NbtType + TryFrom<Tag, Error = ()>, const N: usize>(tag: Tag) -> Result<[T; N], DecodeError> {
+	let values = codegen::list(tag, codegen::value::<T>)?;
+	let len = values.len();
+	values.try_into().map_err(|_| DecodeError::invalid(format!("Expected {N} values, found {len}.")))
+}
+
+impl PlayerData {
+	/// Reads a player data file, which is compressed with gzip.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, PlayerDataError> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let (_, data) = compression::decompress(&data)?;
+		let (_, tag) = read_named_tag(&mut data.as_slice())?;
+		Ok(Self::decode_nbt(tag)?)
+	}
+
+	/// Writes a player data file compressed with gzip.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), PlayerDataError> {
+		let mut data = Vec::new();
+		write_named_tag(&mut data, &self.clone().encode_nbt(), "")?;
+		writer.write_all(&Compression::Gzip.compress(&data)?)?;
+		Ok(())
+	}
+
+	/// The player's UUID, which is also the name of the file.
+	pub fn uuid(&self) -> Option<Uuid> {
+		Uuid::read(&self.other, "UUID")
+	}
+
+	/// The item in the selected hotbar slot.
+	pub fn held_item(&self) -> Option<&ItemStack> {
+		match self.other.get("SelectedItemSlot") {
+			Some(&Tag::Int(slot)) => self.inventory.get(slot as i8),
+			_ => None,
+		}
+	}
+
+	pub fn attribute(&self, id: &str) -> Option<&Attribute> {
+		self.attributes.iter().find(|attribute| attribute.id == id)
+	}
+}
+
+impl DecodeNbt for PlayerData {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let data_version = codegen::optional(&mut map, "DataVersion", codegen::value)?;
+		let pos = codegen::required(&mut map, "Pos", array)?;
+		let rotation = codegen::required(&mut map, "Rotation", array)?;
+		let (dimension, legacy_dimension) = codegen::required(&mut map, "Dimension", |tag| match tag {
+			Tag::Int(id) => LEGACY_DIMENSIONS.iter()
+				.find(|(legacy, _)| *legacy == id)
+				.map(|(_, name)| ((*name).to_owned(), true))
+				.ok_or_else(|| DecodeError::invalid(format!("Unknown dimension {id}."))),
+			tag => Ok((codegen::value(tag)?, false)),
+		})?;
+		let inventory = codegen::optional(&mut map, "Inventory", Inventory::decode_nbt)?.unwrap_or_default();
+		let ender_chest = codegen::optional(&mut map, "EnderItems", Inventory::decode_nbt)?.unwrap_or_default();
+		let abilities = codegen::optional(&mut map, "abilities", Abilities::decode_nbt)?.unwrap_or_default();
+		let experience = Experience {
+			level: codegen::optional(&mut map, "XpLevel", codegen::value)?.unwrap_or_default(),
+			progress: codegen::optional(&mut map, "XpP", codegen::value)?.unwrap_or_default(),
+			total: codegen::optional(&mut map, "XpTotal", codegen::value)?.unwrap_or_default(),
+		};
+		let legacy_attributes = map.contains_key("Attributes");
+		let key = if legacy_attributes { "Attributes" } else { "attributes" };
+		let attributes = codegen::optional(&mut map, key, |tag| codegen::list(tag, |tag| Attribute::decode(tag, legacy_attributes)))?
+			.unwrap_or_default();
+		Ok(Self {
+			data_version,
+			pos,
+			rotation,
+			dimension,
+			inventory,
+			ender_chest,
+			abilities,
+			experience,
+			attributes,
+			other: map,
+			legacy_dimension,
+			legacy_attributes,
+		})
+	}
+}
+
+impl EncodeNbt for PlayerData {
+	/// Encodes the dimension and attributes in the form that they were decoded from.
+	fn encode_nbt(self) -> Tag {
+		let mut map = Map::new();
+		if let Some(data_version) = self.data_version {
+			map.insert("DataVersion".to_owned(), Tag::Int(data_version));
+		}
+		map.insert("Pos".to_owned(), Tag::List(ListTag::Double(self.pos.to_vec())));
+		map.insert("Rotation".to_owned(), Tag::List(ListTag::Float(self.rotation.to_vec())));
+		let legacy_dimension = LEGACY_DIMENSIONS.iter().find(|(_, name)| *name == self.dimension);
+		let dimension = match legacy_dimension {
+			Some(&(id, _)) if self.legacy_dimension => Tag::Int(id),
+			_ => Tag::String(self.dimension),
+		};
+		map.insert("Dimension".to_owned(), dimension);
+		map.insert("Inventory".to_owned(), self.inventory.encode_nbt());
+		map.insert("EnderItems".to_owned(), self.ender_chest.encode_nbt());
+		map.insert("abilities".to_owned(), self.abilities.encode_nbt());
+		map.insert("XpLevel".to_owned(), Tag::Int(self.experience.level));
+		map.insert("XpP".to_owned(), Tag::Float(self.experience.progress));
+		map.insert("XpTotal".to_owned(), Tag::Int(self.experience.total));
+		if !self.attributes.is_empty() {
+			let legacy = self.legacy_attributes;
+			let key = if legacy { "Attributes" } else { "attributes" };
+			map.insert(key.to_owned(), codegen::encode_list(self.attributes.into_iter().map(|attribute| attribute.encode(legacy))));
+		}
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+/// The statistics of a player, by category and then by statistic.
+/// ```
+/// # use rustnbt::player::Stats;
+/// let json = r#"{"stats": {"minecraft:mined": {"minecraft:stone": 12}}, "DataVersion": 3465}"#;
+/// let mut stats = Stats::from_json(json).unwrap();
+/// assert_eq!(stats.get("minecraft:mined", "minecraft:stone"), 12);
+/// stats.set("minecraft:custom", "minecraft:jump", 3);
+/// assert_eq!(Stats::from_json(&stats.to_json()).unwrap().get("minecraft:custom", "minecraft:jump"), 3);
+/// // The file is strict JSON, and statistics are ints.
+/// assert!(Stats::from_json(r#"{stats: {}}"#).is_err());
+/// assert!(Stats::from_json(r#"{"stats": {"minecraft:custom": {"minecraft:jump": 3000000000}}}"#).is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+	pub data_version: Option<i32>,
+	/// Categories such as `minecraft:mined` or `minecraft:custom`, with statistics such as `minecraft:stone`.
+	pub categories: MapType<String, MapType<String, i32>>,
+}
+
+impl Stats {
+	/// Parses a statistics file, which is JSON.
+	pub fn from_json(json: &str) -> Result<Self, PlayerDataError> {
+		let mut map = codegen::compound(json_tag(serde_json::from_str(json)?)?)?;
+		let data_version = codegen::optional(&mut map, "DataVersion", codegen::value)?;
+		let categories = codegen::optional(&mut map, "stats", |tag| {
+			codegen::compound(tag)?.into_iter().map(|(category, tag)| {
+				let stats = codegen::compound(tag)?.into_iter()
+					.map(|(stat, value)| Ok((stat.clone(), codegen::value(value).map_err(|error| error.within(PathSegment::Key(stat)))?)))
+					.collect::<Result<_, DecodeError>>();
+				Ok((category.clone(), stats.map_err(|error| error.within(PathSegment::Key(category)))?))
+			}).collect()
+		})?.unwrap_or_default();
+		Ok(Self { data_version, categories })
+	}
+
+	/// Writes the statistics as JSON.
+	pub fn to_json(&self) -> String {
+		let mut categories = Map::new();
+		for (category, stats) in &self.categories {
+			let stats = stats.iter().map(|(stat, &value)| (stat.clone(), Tag::Int(value))).collect();
+			categories.insert(category.clone(), Tag::Compound(stats));
+		}
+		let mut map = Map::new();
+		map.insert("stats".to_owned(), Tag::Compound(categories));
+		if let Some(data_version) = self.data_version {
+			map.insert("DataVersion".to_owned(), Tag::Int(data_version));
+		}
+		let mut json = String::new();
+		write_json(&mut json, &Tag::Compound(map)).expect("writing to a String doesn't fail");
+		json
+	}
+
+	/// The value of a statistic, which is 0 if it's missing.
+	pub fn get(&self, category: &str, stat: &str) -> i32 {
+		self.categories.get(category).and_then(|stats| stats.get(stat)).copied().unwrap_or_default()
+	}
+
+	pub fn set(&mut self, category: &str, stat: &str, value: i32) -> &mut Self {
+		self.categories.entry(category.to_owned()).or_default().insert(stat.to_owned(), value);
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::item::ItemLayout;
+
+	const PLAYER: &str = r#"{
+		DataVersion: 3953, Pos: [10.5d, 70d, -3.5d], Rotation: [90f, 10f], Dimension: "minecraft:overworld",
+		UUID: [I; 1, 2, 3, 4], SelectedItemSlot: 1, Health: 20f,
+		Inventory: [
+			{id: "minecraft:iron_pickaxe", count: 1, Slot: 1b, components: {"minecraft:damage": 40}},
+			{id: "minecraft:cobblestone", count: 64, Slot: 9b},
+			{id: "minecraft:cobblestone", count: 20, Slot: 10b},
+		],
+		EnderItems: [],
+		abilities: {flying: 0b, mayfly: 0b, instabuild: 0b, invulnerable: 0b, mayBuild: 1b, flySpeed: 0.05f, walkSpeed: 0.1f},
+		XpLevel: 12, XpP: 0.5f, XpTotal: 300,
+		attributes: [{id: "minecraft:generic.max_health", base: 20d}],
+	}"#;
+
+	#[test]
+	fn player_test() {
+		let mut player = PlayerData::decode_nbt(Tag::parse(PLAYER).unwrap()).unwrap();
+		assert_eq!(player.uuid(), Some(Uuid::from_ints([1, 2, 3, 4])));
+		assert_eq!(player.held_item().unwrap().id, "minecraft:iron_pickaxe");
+		assert_eq!(player.inventory.count("minecraft:cobblestone"), 84);
+		assert_eq!(player.inventory.first_empty(MAIN), Some(11));
+		assert_eq!(player.attribute("minecraft:generic.max_health").unwrap().base, 20.0);
+		assert_eq!(player.experience, Experience { level: 12, progress: 0.5, total: 300 });
+
+		// Moves the cobblestone into the ender chest.
+		for (slot, _) in player.inventory.find("minecraft:cobblestone").map(|(slot, item)| (slot, item.clone())).collect::<Vec<_>>() {
+			let item = player.inventory.remove(slot).unwrap();
+			let free = player.ender_chest.first_empty(ENDER_CHEST).unwrap();
+			player.ender_chest.set(free, item);
+		}
+		player.dimension = "minecraft:the_nether".to_owned();
+
+		let mut data = Vec::new();
+		player.write(&mut data).unwrap();
+		let read = PlayerData::read(&mut data.as_slice()).unwrap();
+		assert_eq!(read.inventory.items.len(), 1);
+		assert_eq!(read.ender_chest.get(1).unwrap().count, 20);
+		assert_eq!(read.dimension, "minecraft:the_nether");
+		assert!(read.other.contains_key("Health"));
+		let tag = read.encode_nbt();
+		assert!(matches!(crate::path::TagPath::root().key("attributes").index(0).key("base").get(&tag), Ok(Tag::Double(20.0))));
+	}
+
+	#[test]
+	fn legacy_test() {
+		let source = PLAYER.replace("\"minecraft:overworld\"", "0").replace("attributes: [{id:", "Attributes: [{Name:").replace("base:", "Base:");
+		let mut player = PlayerData::decode_nbt(Tag::parse(source).unwrap()).unwrap();
+		assert_eq!(player.dimension, "minecraft:overworld");
+		assert_eq!(player.attributes[0].base, 20.0);
+		player.inventory.set(2, ItemStack::new("minecraft:torch", 1, ItemLayout::Tag));
+		let tag = player.encode_nbt();
+		assert!(matches!(crate::path::TagPath::root().key("Dimension").get(&tag), Ok(Tag::Int(0))));
+		assert!(matches!(crate::path::TagPath::root().key("Attributes").index(0).key("Base").get(&tag), Ok(Tag::Double(_))));
+	}
+}
@@ -0,0 +1,641 @@
+// Region files (`.mca`), which store the chunks of a 32×32 chunk area of a dimension.
+// https://minecraft.wiki/w/Region_file_format
+//
+// A region file is made of 4 KiB sectors. The first sector holds the location of every chunk,
+// as a 3-byte sector offset and a 1-byte sector count, and the second holds the time each chunk was last saved.
+// Each chunk's sectors start with its length in bytes, then a compression type, then the compressed NBT.
+//
+// The compression types are 1 for gzip, 2 for zlib, 3 for none and 4 for LZ4 (since Minecraft 1.20.5).
+// Type 127 is a custom algorithm, whose namespaced name precedes the data as a string of a length and UTF-8 bytes.
+// Minecraft can't read it, but it lets tools store chunks with compression of their own (see [CompressionRegistry]).
+//
+// Chunks that need more than 255 sectors (about 1 MiB) are stored in a `c.<x>.<z>.mcc` file next to the region file,
+// named by the chunk's position in the world. Their compression type has bit 128 set, and their sectors hold no data.
+//
+// Region keeps the compressed payload of every chunk, so that writing a region only
+// compresses the chunks that were changed.
+
+use crate::{
+	MapType,
+	NbtError,
+	compression::Compression,
+	io::{read_named_tag, write_named_tag, NbtRead, NbtWrite},
+	maintenance::IssueKind,
+	tag::Tag,
+};
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The size of a sector of a region file.
+pub const SECTOR_SIZE: usize = 4096;
+/// The number of chunks along each side of a region.
+pub const REGION_WIDTH: usize = 32;
+/// The number of chunks in a region.
+pub const CHUNK_COUNT: usize = REGION_WIDTH * REGION_WIDTH;
+/// The most sectors that a chunk can have in the region file itself.
+pub const MAX_SECTORS: usize = 0xff;
+/// The bit of the compression type that marks a chunk as stored in a `.mcc` file.
+pub(crate) const EXTERNAL: u8 = 0x80;
+/// The compression type of custom algorithms.
+const CUSTOM: u8 = 127;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RegionError {
+	#[error("{0}")]
+	Io(#[from] std::io::Error),
+	#[error("{0}")]
+	Nbt(#[from] NbtError),
+	/// The file is too short to have a header.
+	#[error("The region file is {0} bytes, which is too short for its header.")]
+	Truncated(usize),
+	/// A chunk's location or length doesn't fit in the file.
+	#[error("Chunk ({x}, {z}) is outside the region file.")]
+	OutOfBounds { x: usize, z: usize },
+	#[error("Unknown compression type {0}.")]
+	UnknownCompression(u8),
+	/// A chunk uses a custom algorithm that isn't in the [CompressionRegistry].
+	#[error("Unknown custom compression algorithm `{0}`.")]
+	UnknownAlgorithm(String),
+	/// A chunk needs more sectors than its location can describe, and there is nowhere to store it externally.
+	#[error("Chunk ({x}, {z}) is {len} bytes, which is too long for a region file.")]
+	TooLong { x: usize, z: usize, len: usize },
+	/// A chunk is stored in a `.mcc` file, which can't be found without the region file's path.
+	#[error("Chunk ({x}, {z}) is stored in a separate .mcc file.")]
+	External { x: usize, z: usize },
+	/// A chunk has a problem that [crate::maintenance::check] reports as broken.
+	#[error("Chunk ({x}, {z}): {kind}")]
+	Broken { x: usize, z: usize, kind: IssueKind },
+	/// Writing the file would lose broken chunks whose data can't be found, which [crate::maintenance::repair_file] removes.
+	#[error("The region file wasn't written, because {0} broken chunks would be lost. Repair it first.")]
+	WouldLoseChunks(usize),
+}
+
+/// How the NBT of a chunk is compressed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChunkCompression {
+	/// One of the compression types that Minecraft supports.
+	Standard(Compression),
+	/// A custom algorithm, with its namespaced name.
+	Custom(String),
+}
+
+impl ChunkCompression {
+	/// The compression type in the region file.
+	pub fn id(&self) -> u8 {
+		match self {
+			ChunkCompression::Standard(Compression::Gzip) => 1,
+			ChunkCompression::Standard(Compression::Zlib) => 2,
+			ChunkCompression::Standard(Compression::None) => 3,
+			ChunkCompression::Standard(Compression::Lz4) => 4,
+			ChunkCompression::Custom(_) => CUSTOM,
+		}
+	}
+
+	/// Reads the compression type `id` from the start of a chunk's payload,
+	/// along with the name that follows it for custom algorithms.
+	pub(crate) fn read(id: u8, payload: &mut &[u8]) -> Result<Self, RegionError> {
+		match id {
+			1 => Ok(Compression::Gzip.into()),
+			2 => Ok(Compression::Zlib.into()),
+			3 => Ok(Compression::None.into()),
+			4 => Ok(Compression::Lz4.into()),
+			CUSTOM => Ok(ChunkCompression::Custom(String::nbt_read(payload)?)),
+			id => Err(RegionError::UnknownCompression(id)),
+		}
+	}
+
+	/// Writes the name of a custom algorithm, which precedes the data.
+	fn write_name(&self, payload: &mut Vec<u8>) -> Result<(), RegionError> {
+		if let ChunkCompression::Custom(name) = self {
+			name.nbt_write(payload)?;
+		}
+		Ok(())
+	}
+}
+
+impl From<Compression> for ChunkCompression {
+	fn from(value: Compression) -> Self {
+		ChunkCompression::Standard(value)
+	}
+}
+
+impl Display for ChunkCompression {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ChunkCompression::Standard(compression) => compression.fmt(f),
+			ChunkCompression::Custom(name) => f.write_str(name),
+		}
+	}
+}
+
+/// A custom compression algorithm for region chunks.
+pub trait ChunkCodec: Send + Sync {
+	fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>>;
+	fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>>;
+}
+
+/// The custom compression algorithms that chunks can be encoded and decoded with, by name.
+/// ```
+/// # use rustnbt::{region::{ChunkCodec, ChunkCompression, CompressionRegistry, RegionChunk}, tag::Tag};
+/// /// Reverses the bytes, which no one should do.
+/// struct Reverse;
+///
+/// impl ChunkCodec for Reverse {
+///     fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+///         Ok(data.iter().rev().copied().collect())
+///     }
+///
+///     fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+///         self.compress(data)
+///     }
+/// }
+///
+/// let mut registry = CompressionRegistry::new();
+/// registry.register("example:reverse", Reverse);
+/// let compression = ChunkCompression::Custom("example:reverse".to_owned());
+/// let chunk = RegionChunk::encode_with(&Tag::Int(5), compression, &registry).unwrap();
+/// assert!(matches!(chunk.decode_with(&registry), Ok(Tag::Int(5))));
+/// assert!(chunk.decode().is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct CompressionRegistry {
+	codecs: MapType<String, Arc<dyn ChunkCodec>>,
+}
+
+impl CompressionRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds an algorithm, replacing any algorithm with the same name.
+	pub fn register<S: Into<String>, C: ChunkCodec + 'static>(&mut self, name: S, codec: C) -> &mut Self {
+		self.codecs.insert(name.into(), Arc::new(codec));
+		self
+	}
+
+	pub fn get(&self, name: &str) -> Option<&dyn ChunkCodec> {
+		self.codecs.get(name).map(Arc::as_ref)
+	}
+
+	pub fn compress(&self, compression: &ChunkCompression, data: &[u8]) -> Result<Vec<u8>, RegionError> {
+		match compression {
+			ChunkCompression::Standard(compression) => Ok(compression.compress(data)?),
+			ChunkCompression::Custom(name) => Ok(self.codec(name)?.compress(data)?),
+		}
+	}
+
+	pub fn decompress(&self, compression: &ChunkCompression, data: &[u8]) -> Result<Vec<u8>, RegionError> {
+		match compression {
+			ChunkCompression::Standard(compression) => Ok(compression.decompress(data)?),
+			ChunkCompression::Custom(name) => Ok(self.codec(name)?.decompress(data)?),
+		}
+	}
+
+	fn codec(&self, name: &str) -> Result<&dyn ChunkCodec, RegionError> {
+		self.get(name).ok_or_else(|| RegionError::UnknownAlgorithm(name.to_owned()))
+	}
+}
+
+impl std::fmt::Debug for CompressionRegistry {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_set().entries(self.codecs.keys()).finish()
+	}
+}
+
+/// A chunk as stored in a region file, which is decoded on demand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionChunk {
+	/// When the chunk was last saved, in seconds since the Unix epoch.
+	pub timestamp: u32,
+	pub compression: ChunkCompression,
+	/// The compressed NBT, without the name of a custom algorithm.
+	pub data: Vec<u8>,
+}
+
+impl RegionChunk {
+	/// Compresses a chunk, with the current time as its timestamp.
+	pub fn encode<C: Into<ChunkCompression>>(tag: &Tag, compression: C) -> Result<Self, RegionError> {
+		Self::encode_with(tag, compression, &CompressionRegistry::new())
+	}
+
+	/// Compresses a chunk with an algorithm that may be in `registry`.
+	pub fn encode_with<C: Into<ChunkCompression>>(tag: &Tag, compression: C, registry: &CompressionRegistry) -> Result<Self, RegionError> {
+		let compression = compression.into();
+		let mut data = Vec::new();
+		write_named_tag(&mut data, tag, "")?;
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs() as u32).unwrap_or_default();
+		let data = registry.compress(&compression, &data)?;
+		Ok(Self { timestamp, compression, data })
+	}
+
+	/// Decompresses and reads the chunk's NBT.
+	pub fn decode(&self) -> Result<Tag, RegionError> {
+		self.decode_with(&CompressionRegistry::new())
+	}
+
+	/// Decompresses and reads the chunk's NBT with an algorithm that may be in `registry`.
+	pub fn decode_with(&self, registry: &CompressionRegistry) -> Result<Tag, RegionError> {
+		let data = registry.decompress(&self.compression, &self.data)?;
+		let (_, tag) = read_named_tag(&mut data.as_slice())?;
+		Ok(tag)
+	}
+
+	/// The data that follows the compression type: the name of a custom algorithm, then the compressed NBT.
+	fn payload(&self) -> Result<Vec<u8>, RegionError> {
+		let mut payload = Vec::with_capacity(self.data.len());
+		self.compression.write_name(&mut payload)?;
+		payload.extend_from_slice(&self.data);
+		Ok(payload)
+	}
+}
+
+/// The chunks of a region file, by their position within the region.
+/// ```
+/// # use rustnbt::{region::Region, tag::Tag};
+/// let mut region = Region::new();
+/// region.write_chunk(3, 31, &Tag::parse("{DataVersion: 3953, Status: \"minecraft:full\"}").unwrap()).unwrap();
+/// let mut file = Vec::new();
+/// region.write(&mut file).unwrap();
+/// let region = Region::read(&mut file.as_slice()).unwrap();
+/// assert_eq!(region.positions().collect::<Vec<_>>(), [(3, 31)]);
+/// assert!(region.read_chunk(3, 31).unwrap().is_some());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Region {
+	chunks: Vec<Option<RegionChunk>>,
+}
+
+impl Default for Region {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The index of a chunk in the header. Chunk coordinates are taken modulo 32,
+/// so either the position within the region or the position in the world can be used.
+fn index(x: usize, z: usize) -> usize {
+	(x % REGION_WIDTH) + (z % REGION_WIDTH) * REGION_WIDTH
+}
+
+/// Parses the position of a region from a file name such as `r.-1.2.mca`.
+pub fn region_pos(name: &str) -> Option<(i32, i32)> {
+	let (x, z) = name.strip_prefix("r.")?.strip_suffix(".mca")?.split_once('.')?;
+	Some((x.parse().ok()?, z.parse().ok()?))
+}
+
+/// The path of the `.mcc` file of a chunk, from the path of its region file.
+pub(crate) fn external_path(region: &Path, x: usize, z: usize) -> Option<PathBuf> {
+	let (region_x, region_z) = region_pos(&region.file_name()?.to_string_lossy())?;
+	let (x, z) = (region_x * REGION_WIDTH as i32 + x as i32, region_z * REGION_WIDTH as i32 + z as i32);
+	Some(region.with_file_name(format!("c.{x}.{z}.mcc")))
+}
+
+impl Region {
+	pub fn new() -> Self {
+		Self { chunks: vec![None; CHUNK_COUNT] }
+	}
+
+	/// Reads a region file. Fails for chunks that are stored in `.mcc` files, which [Region::open] reads.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, RegionError> {
+		Self::read_with(reader, |x, z| Err(RegionError::External { x, z }))
+	}
+
+	/// Reads a region file, with `load_external` reading the data of chunks that are stored externally,
+	/// given their position within the region.
+	pub fn read_with<R, F>(reader: &mut R, load_external: F) -> Result<Self, RegionError>
+	where
+		R: Read,
+		F: FnMut(usize, usize) -> Result<Vec<u8>, RegionError>,
+	{
+		let mut file = Vec::new();
+		reader.read_to_end(&mut file)?;
+		Self::from_bytes(&file, load_external)
+	}
+
+	fn from_bytes<F>(file: &[u8], mut load_external: F) -> Result<Self, RegionError>
+	where
+		F: FnMut(usize, usize) -> Result<Vec<u8>, RegionError>,
+	{
+		// Files of 0 bytes are left behind by Minecraft for regions that were never written.
+		if file.is_empty() {
+			return Ok(Self::new());
+		}
+		if file.len() < SECTOR_SIZE * 2 {
+			return Err(RegionError::Truncated(file.len()));
+		}
+		let mut region = Self::new();
+		for (index, slot) in region.chunks.iter_mut().enumerate() {
+			let (x, z) = (index % REGION_WIDTH, index / REGION_WIDTH);
+			let location = u32::from_be_bytes(file[index * 4..index * 4 + 4].try_into().unwrap());
+			if location == 0 {
+				continue;
+			}
+			let (start, sectors) = ((location >> 8) as usize, (location & 0xff) as usize);
+			let timestamp = u32::from_be_bytes(file[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4].try_into().unwrap());
+			let out_of_bounds = || RegionError::OutOfBounds { x, z };
+			// The first 2 sectors are the header.
+			if start < 2 {
+				return Err(out_of_bounds());
+			}
+			let offset = start * SECTOR_SIZE;
+			let header = file.get(offset..offset + 5).ok_or_else(out_of_bounds)?;
+			let len = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+			if len == 0 || len + 4 > sectors * SECTOR_SIZE {
+				return Err(out_of_bounds());
+			}
+			let payload = file.get(offset + 5..offset + 4 + len).ok_or_else(out_of_bounds)?;
+			let payload = match header[4] & EXTERNAL {
+				0 => payload.to_vec(),
+				_ => load_external(x, z)?,
+			};
+			let mut data = payload.as_slice();
+			let compression = ChunkCompression::read(header[4] & !EXTERNAL, &mut data)?;
+			*slot = Some(RegionChunk { timestamp, compression, data: data.to_vec() });
+		}
+		Ok(region)
+	}
+
+	/// Reads a region file from disk, along with its `.mcc` files.
+	/// The file must be named `r.<x>.<z>.mca` if it has chunks in `.mcc` files.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, RegionError> {
+		let path = path.as_ref();
+		Self::from_bytes(&std::fs::read(path)?, |x, z| match external_path(path, x, z) {
+			Some(external) => Ok(std::fs::read(external)?),
+			None => Err(RegionError::External { x, z }),
+		})
+	}
+
+	/// Writes a region file, with the chunks in order and without unused sectors.
+	/// Fails for chunks that need to be stored in `.mcc` files, which [Region::save] writes.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), RegionError> {
+		self.write_with(writer, |x, z, data| Err(RegionError::TooLong { x, z, len: data.len() + 1 }))
+	}
+
+	/// Writes a region file, with `store_external` storing the data of chunks that need more than
+	/// [MAX_SECTORS] sectors, given their position within the region.
+	pub fn write_with<W, F>(&self, writer: &mut W, mut store_external: F) -> Result<(), RegionError>
+	where
+		W: Write,
+		F: FnMut(usize, usize, &[u8]) -> Result<(), RegionError>,
+	{
+		let mut locations = vec![0u8; SECTOR_SIZE];
+		let mut timestamps = vec![0u8; SECTOR_SIZE];
+		let mut sectors = Vec::new();
+		for (index, chunk) in self.chunks.iter().enumerate() {
+			let Some(chunk) = chunk else {
+				continue;
+			};
+			let payload = chunk.payload()?;
+			let mut len = payload.len() + 1;
+			let mut compression = chunk.compression.id();
+			let mut data = payload.as_slice();
+			if (len + 4).div_ceil(SECTOR_SIZE) > MAX_SECTORS {
+				store_external(index % REGION_WIDTH, index / REGION_WIDTH, data)?;
+				(len, compression, data) = (1, compression | EXTERNAL, &[]);
+			}
+			let count = (len + 4).div_ceil(SECTOR_SIZE);
+			let offset = 2 + sectors.len() / SECTOR_SIZE;
+			locations[index * 4..index * 4 + 4].copy_from_slice(&((offset as u32) << 8 | count as u32).to_be_bytes());
+			timestamps[index * 4..index * 4 + 4].copy_from_slice(&chunk.timestamp.to_be_bytes());
+			sectors.extend_from_slice(&(len as u32).to_be_bytes());
+			sectors.push(compression);
+			sectors.extend_from_slice(data);
+			sectors.resize(sectors.len().next_multiple_of(SECTOR_SIZE), 0);
+		}
+		writer.write_all(&locations)?;
+		writer.write_all(&timestamps)?;
+		writer.write_all(&sectors)?;
+		Ok(())
+	}
+
+	/// Writes a region file to disk, replacing the file only once it has been written completely.
+	/// Oversized chunks are written to `.mcc` files, which also replace the old ones only once the region file has,
+	/// and `.mcc` files of chunks that no longer need them are removed.
+	/// The file must be named `r.<x>.<z>.mca` if it has oversized chunks.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RegionError> {
+		let path = path.as_ref();
+		let temporary = path.with_extension("mca.tmp");
+		// The temporary and final paths of every .mcc file.
+		let mut externals = Vec::new();
+		let written = self.write_temporary(path, &temporary, &mut externals)
+			.and_then(|()| Ok(std::fs::rename(&temporary, path)?));
+		if let Err(error) = written {
+			let _ = std::fs::remove_file(&temporary);
+			for (external_temporary, _) in externals {
+				let _ = std::fs::remove_file(external_temporary);
+			}
+			return Err(error);
+		}
+		let mut kept = Vec::with_capacity(externals.len());
+		for (external_temporary, external_path) in externals {
+			std::fs::rename(external_temporary, &external_path)?;
+			kept.push(external_path);
+		}
+		for index in 0..CHUNK_COUNT {
+			let stale = external_path(path, index % REGION_WIDTH, index / REGION_WIDTH).filter(|stale| !kept.contains(stale) && stale.exists());
+			if let Some(stale) = stale {
+				std::fs::remove_file(stale)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Writes the region file to `temporary` and oversized chunks to temporary `.mcc` files,
+	/// adding their temporary and final paths to `externals` as soon as they are created.
+	fn write_temporary(&self, path: &Path, temporary: &Path, externals: &mut Vec<(PathBuf, PathBuf)>) -> Result<(), RegionError> {
+		let mut file = std::io::BufWriter::new(std::fs::File::create(temporary)?);
+		self.write_with(&mut file, |x, z, data| {
+			let Some(external_path) = external_path(path, x, z) else {
+				return Err(RegionError::TooLong { x, z, len: data.len() + 1 });
+			};
+			let external_temporary = external_path.with_extension("mcc.tmp");
+			externals.push((external_temporary.clone(), external_path));
+			std::fs::write(external_temporary, data)?;
+			Ok(())
+		})?;
+		file.into_inner().map_err(|error| error.into_error())?.sync_all()?;
+		Ok(())
+	}
+
+	pub fn chunk(&self, x: usize, z: usize) -> Option<&RegionChunk> {
+		self.chunks[index(x, z)].as_ref()
+	}
+
+	pub fn set_chunk(&mut self, x: usize, z: usize, chunk: Option<RegionChunk>) -> Option<RegionChunk> {
+		std::mem::replace(&mut self.chunks[index(x, z)], chunk)
+	}
+
+	/// Decodes the chunk at a position, if there is one.
+	pub fn read_chunk(&self, x: usize, z: usize) -> Result<Option<Tag>, RegionError> {
+		self.chunk(x, z).map(RegionChunk::decode).transpose()
+	}
+
+	/// Encodes a chunk with zlib, which is what Minecraft uses by default.
+	pub fn write_chunk(&mut self, x: usize, z: usize, tag: &Tag) -> Result<(), RegionError> {
+		self.set_chunk(x, z, Some(RegionChunk::encode(tag, Compression::Zlib)?));
+		Ok(())
+	}
+
+	/// The positions within the region that have a chunk, row by row.
+	pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+		self.chunks.iter()
+			.enumerate()
+			.filter(|(_, chunk)| chunk.is_some())
+			.map(|(index, _)| (index % REGION_WIDTH, index / REGION_WIDTH))
+	}
+
+	pub fn len(&self) -> usize {
+		self.chunks.iter().filter(|chunk| chunk.is_some()).count()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::testutil::TempDir;
+
+	#[test]
+	fn round_trip_test() {
+		let mut region = Region::new();
+		for (x, z) in [(0, 0), (31, 0), (5, 17)] {
+			region.write_chunk(x, z, &Tag::parse(format!("{{xPos: {x}, zPos: {z}, Data: [I; {}]}}", "1, ".repeat(2000) + "1")).unwrap()).unwrap();
+		}
+		region.set_chunk(36, 1, Some(RegionChunk::encode(&Tag::parse("{}").unwrap(), Compression::Gzip).unwrap()));
+		let mut file = Vec::new();
+		region.write(&mut file).unwrap();
+		assert_eq!(file.len() % SECTOR_SIZE, 0);
+
+		let read = Region::read(&mut file.as_slice()).unwrap();
+		assert_eq!(read.positions().collect::<Vec<_>>(), [(0, 0), (31, 0), (4, 1), (5, 17)]);
+		assert_eq!(read.chunk(4, 1).unwrap().compression, Compression::Gzip.into());
+		assert_eq!(read.chunk(5, 17), region.chunk(5, 17));
+		let tag = read.read_chunk(31, 0).unwrap().unwrap();
+		assert!(matches!(crate::path::TagPath::root().key("xPos").get(&tag), Ok(Tag::Int(31))));
+		assert!(Region::read(&mut [].as_slice()).unwrap().is_empty());
+
+		// A chunk can't start in the header, even where the timestamps could pass for a chunk of 1 byte without compression.
+		let mut in_header = file.clone();
+		in_header[..4].copy_from_slice(&[0, 0, 1, 1]);
+		in_header[SECTOR_SIZE..SECTOR_SIZE + 5].copy_from_slice(&[0, 0, 0, 1, 3]);
+		assert!(matches!(Region::read(&mut in_header.as_slice()), Err(RegionError::OutOfBounds { x: 0, z: 0 })));
+
+		// The last chunk is cut off.
+		file.truncate(file.len() - SECTOR_SIZE);
+		assert!(matches!(Region::read(&mut file.as_slice()), Err(RegionError::OutOfBounds { x: 5, z: 17 })));
+	}
+
+	/// Stores data as it is, like type 3.
+	struct Identity;
+
+	impl ChunkCodec for Identity {
+		fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+			Ok(data.to_vec())
+		}
+
+		fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+			Ok(data.to_vec())
+		}
+	}
+
+	#[test]
+	fn compression_test() {
+		let mut registry = CompressionRegistry::new();
+		registry.register("test:identity", Identity);
+		let custom = ChunkCompression::Custom("test:identity".to_owned());
+		let compressions = [Compression::Gzip.into(), Compression::Zlib.into(), Compression::None.into(), Compression::Lz4.into(), custom.clone()];
+		let mut region = Region::new();
+		let tag = Tag::parse("{Status: \"minecraft:full\", sections: [{Y: 0b}]}").unwrap();
+		for (x, compression) in compressions.iter().enumerate() {
+			region.set_chunk(x, 0, Some(RegionChunk::encode_with(&tag, compression.clone(), &registry).unwrap()));
+		}
+		assert!(matches!(RegionChunk::encode(&tag, custom.clone()), Err(RegionError::UnknownAlgorithm(name)) if name == "test:identity"));
+
+		let mut file = Vec::new();
+		region.write(&mut file).unwrap();
+		let types: Vec<u8> = (0..5).map(|sector| file[SECTOR_SIZE * (2 + sector) + 4]).collect();
+		assert_eq!(types, [1, 2, 3, 4, 127]);
+		// The name of the algorithm follows the type.
+		assert_eq!(&file[SECTOR_SIZE * 6 + 5..SECTOR_SIZE * 6 + 20], b"\0\x0dtest:identity");
+
+		let read = Region::read(&mut file.as_slice()).unwrap();
+		for (x, compression) in compressions.iter().enumerate() {
+			let chunk = read.chunk(x, 0).unwrap();
+			assert_eq!(&chunk.compression, compression);
+			assert!(matches!(chunk.decode_with(&registry), Ok(Tag::Compound(_))));
+		}
+		assert!(matches!(read.read_chunk(4, 0), Err(RegionError::UnknownAlgorithm(_))));
+		file[SECTOR_SIZE * 2 + 4] = 5;
+		assert!(matches!(Region::read(&mut file.as_slice()), Err(RegionError::UnknownCompression(5))));
+	}
+
+	/// A chunk that is too big for a region file, because it is stored without compression.
+	fn oversized() -> RegionChunk {
+		let tag = Tag::ByteArray(vec![7; SECTOR_SIZE * MAX_SECTORS]);
+		RegionChunk::encode(&tag, Compression::None).unwrap()
+	}
+
+	#[test]
+	fn external_test() {
+		let mut region = Region::new();
+		region.set_chunk(2, 3, Some(oversized()));
+		region.write_chunk(4, 5, &Tag::Int(1)).unwrap();
+		let mut file = Vec::new();
+		assert!(matches!(region.write(&mut file), Err(RegionError::TooLong { x: 2, z: 3, .. })));
+
+		let mut stored = Vec::new();
+		file.clear();
+		region.write_with(&mut file, |x, z, data| {
+			stored.push((x, z, data.to_vec()));
+			Ok(())
+		}).unwrap();
+		// The oversized chunk only takes up one sector.
+		assert_eq!(file.len(), SECTOR_SIZE * 4);
+		assert_eq!(file[SECTOR_SIZE * 2 + 4], 0x83);
+		assert!(matches!(Region::read(&mut file.as_slice()), Err(RegionError::External { x: 2, z: 3 })));
+		let read = Region::read_with(&mut file.as_slice(), |x, z| {
+			assert_eq!((x, z), (2, 3));
+			Ok(stored[0].2.clone())
+		}).unwrap();
+		assert_eq!(read.chunk(2, 3), region.chunk(2, 3));
+		assert!(matches!(read.read_chunk(2, 3).unwrap(), Some(Tag::ByteArray(bytes)) if bytes.len() == SECTOR_SIZE * MAX_SECTORS));
+	}
+
+	#[test]
+	fn save_test() {
+		let directory = TempDir::new("region");
+		let path = directory.join("r.-1.2.mca");
+		let mut region = Region::new();
+		region.set_chunk(31, 0, Some(oversized()));
+		region.save(&path).unwrap();
+		let external = directory.join("c.-1.64.mcc");
+		assert!(external.exists() && !directory.join("c.-1.64.mcc.tmp").exists());
+		assert_eq!(Region::open(&path).unwrap().chunk(31, 0), region.chunk(31, 0));
+
+		// Shrinking the chunk removes its .mcc file.
+		region.write_chunk(31, 0, &Tag::Int(1)).unwrap();
+		region.save(&path).unwrap();
+		assert!(!external.exists());
+		assert!(matches!(Region::open(&path).unwrap().read_chunk(31, 0), Ok(Some(Tag::Int(1)))));
+
+		// Without a region file name, the position of the .mcc file is unknown.
+		region.set_chunk(0, 0, Some(oversized()));
+		assert!(matches!(region.save(directory.join("region.mca")), Err(RegionError::TooLong { x: 0, z: 0, .. })));
+		assert!(!directory.join("region.mca.tmp").exists());
+
+		// If the region file can't be replaced, its .mcc files aren't either.
+		let blocked = directory.join("r.0.0.mca");
+		std::fs::create_dir_all(blocked.join("in the way")).unwrap();
+		region.set_chunk(0, 0, None);
+		region.set_chunk(31, 0, Some(oversized()));
+		assert!(region.save(&blocked).is_err());
+		let left: Vec<_> = std::fs::read_dir(directory.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+		assert!(left.iter().all(|name| name != "c.31.0.mcc" && !name.to_string_lossy().ends_with(".tmp")), "{left:?}");
+	}
+}
@@ -0,0 +1,902 @@
+// Schemas describe the shape that an NBT tree is expected to have, so that item and entity
+// data coming from elsewhere can be checked before it is used.
+//
+// A schema can be built in Rust:
+//     Schema::compound()
+//         .required("id", Schema::string())
+//         .optional("count", Schema::int().range(1, 99))
+// or loaded from a description written in SNBT (or JSON, which is also valid SNBT):
+//     {
+//         type: "compound",
+//         fields: {
+//             id: "string",
+//             count: {type: "int", min: 1, max: 99, optional: true},
+//         },
+//     }
+// Schemas can also be inferred from sample data with Schema::infer.
+
+use crate::{
+	Map,
+	MapType,
+	path::TagPath,
+	snbt::ParseError,
+	tag::{
+		Tag,
+		TagID,
+		TagRef,
+		ListTag,
+	},
+};
+use std::fmt::Display;
+
+/// The expected shape of an NBT tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schema {
+	/// Accepts every tag.
+	Any,
+	Tag(TagSchema),
+	List(ListSchema),
+	Compound(CompoundSchema),
+	/// Matches tags that match any of these schemas.
+	OneOf(Vec<Schema>),
+}
+
+/// Inclusive bounds of a number or length. `None` means unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounds {
+	pub min: Option<f64>,
+	pub max: Option<f64>,
+}
+
+/// The expected type of a tag that isn't a List or Compound.<br>
+/// The bounds apply to the value of numbers, and to the length of strings and arrays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TagSchema {
+	id: TagID,
+	pub bounds: Bounds,
+}
+
+/// A list whose elements all match `element`. The bounds apply to its length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListSchema {
+	pub element: Box<Schema>,
+	pub bounds: Bounds,
+}
+
+/// The expected keys of a compound.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompoundSchema {
+	pub fields: MapType<String, Field>,
+	/// Whether keys that aren't in `fields` are allowed.
+	pub allow_unknown: bool,
+}
+
+/// A key of a [CompoundSchema].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+	pub schema: Schema,
+	pub required: bool,
+}
+
+/// A place where a tag doesn't match a [Schema].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+	/// The path of the offending tag. For missing keys, this is the path the key should have been at.
+	pub path: TagPath,
+	pub kind: ViolationKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViolationKind {
+	WrongType { expected: TagID, found: TagID },
+	/// The tag didn't match any schema of a [Schema::OneOf], and none of them were for its type.
+	NotOneOf { expected: Vec<TagID>, found: TagID },
+	OutOfRange { value: f64, bounds: Bounds },
+	WrongLength { len: usize, bounds: Bounds },
+	MissingKey,
+	UnknownKey,
+}
+
+/// Failure to load a [Schema] from a description.
+#[derive(thiserror::Error, Debug)]
+pub enum SchemaError {
+	#[error("{0}")]
+	Parse(#[from] ParseError),
+	/// `path` is the location within the description.
+	#[error("Invalid schema at `{path}`: {message}")]
+	Invalid { path: TagPath, message: String },
+}
+
+impl Bounds {
+	pub fn contains(&self, value: f64) -> bool {
+		self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+	}
+
+	pub fn is_unbounded(&self) -> bool {
+		self.min.is_none() && self.max.is_none()
+	}
+}
+
+impl Display for Bounds {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match (self.min, self.max) {
+			(Some(min), Some(max)) => write!(f, "between {min} and {max}"),
+			(Some(min), None) => write!(f, "at least {min}"),
+			(None, Some(max)) => write!(f, "at most {max}"),
+			(None, None) => write!(f, "unbounded"),
+		}
+	}
+}
+
+impl Display for Violation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.path.is_root() {
+			write!(f, "<root>: ")?;
+		} else {
+			write!(f, "{}: ", self.path)?;
+		}
+		match &self.kind {
+			ViolationKind::WrongType { expected, found } => write!(f, "Expected {}, found {}.", expected.title(), found.title()),
+			ViolationKind::NotOneOf { expected, found } => {
+				let expected: Vec<&str> = expected.iter().map(|id| id.title()).collect();
+				write!(f, "Expected one of {}, found {}.", expected.join(", "), found.title())
+			}
+			ViolationKind::OutOfRange { value, bounds } => write!(f, "{value} is out of range, it must be {bounds}."),
+			ViolationKind::WrongLength { len, bounds } => write!(f, "Length {len} is out of range, it must be {bounds}."),
+			ViolationKind::MissingKey => write!(f, "Required key is missing."),
+			ViolationKind::UnknownKey => write!(f, "Key is not allowed."),
+		}
+	}
+}
+
+impl TagSchema {
+	/// A schema for tags of type `id`, or `None` for List and Compound,
+	/// which have [ListSchema] and [CompoundSchema] instead.
+	pub fn new(id: TagID) -> Option<Self> {
+		match id {
+			TagID::List | TagID::Compound => None,
+			id => Some(Self { id, bounds: Bounds::default() }),
+		}
+	}
+
+	pub fn id(&self) -> TagID {
+		self.id
+	}
+}
+
+impl ListSchema {
+	/// A schema for lists whose elements match `element`.
+	pub fn new<S: Into<Schema>>(element: S) -> Self {
+		Self { element: Box::new(element.into()), bounds: Bounds::default() }
+	}
+}
+
+// TagSchema and ListSchema are built the same way.
+macro_rules! bounds_methods {
+	($($type:ty),+) => {
+		$(
+			impl $type {
+				/// Sets the inclusive lower bound of a number, or of the length of a string, array or list.
+				pub fn min<T: Into<f64>>(mut self, min: T) -> Self {
+					self.bounds.min = Some(min.into());
+					self
+				}
+
+				/// Sets the inclusive upper bound of a number, or of the length of a string, array or list.
+				pub fn max<T: Into<f64>>(mut self, max: T) -> Self {
+					self.bounds.max = Some(max.into());
+					self
+				}
+
+				/// Sets both inclusive bounds.
+				pub fn range<T: Into<f64>>(self, min: T, max: T) -> Self {
+					self.min(min).max(max)
+				}
+			}
+		)+
+	};
+}
+
+bounds_methods!(TagSchema, ListSchema);
+
+impl CompoundSchema {
+	/// Returns a schema for compounds with no known keys, that allows unknown keys.
+	pub fn new() -> Self {
+		Self { fields: MapType::new(), allow_unknown: true }
+	}
+
+	/// Adds a key that must be present.
+	pub fn required<K: Into<String>, S: Into<Schema>>(mut self, key: K, schema: S) -> Self {
+		self.fields.insert(key.into(), Field { schema: schema.into(), required: true });
+		self
+	}
+
+	/// Adds a key that may be absent.
+	pub fn optional<K: Into<String>, S: Into<Schema>>(mut self, key: K, schema: S) -> Self {
+		self.fields.insert(key.into(), Field { schema: schema.into(), required: false });
+		self
+	}
+
+	/// Rejects keys that weren't added with [CompoundSchema::required] or [CompoundSchema::optional].
+	pub fn deny_unknown(mut self) -> Self {
+		self.allow_unknown = false;
+		self
+	}
+}
+
+impl Default for CompoundSchema {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl From<TagSchema> for Schema {
+	fn from(schema: TagSchema) -> Self {
+		Schema::Tag(schema)
+	}
+}
+
+impl From<ListSchema> for Schema {
+	fn from(schema: ListSchema) -> Self {
+		Schema::List(schema)
+	}
+}
+
+impl From<CompoundSchema> for Schema {
+	fn from(schema: CompoundSchema) -> Self {
+		Schema::Compound(schema)
+	}
+}
+
+impl Schema {
+	/// A schema for tags of type `id`. Lists have elements of any type, and compounds allow any key.
+	pub fn of(id: TagID) -> Self {
+		match id {
+			TagID::List => Schema::list(Schema::Any).into(),
+			TagID::Compound => Schema::compound().into(),
+			id => Schema::Tag(Self::tag(id)),
+		}
+	}
+
+	fn tag(id: TagID) -> TagSchema {
+		TagSchema { id, bounds: Bounds::default() }
+	}
+
+	pub fn byte() -> TagSchema { Self::tag(TagID::Byte) }
+	pub fn short() -> TagSchema { Self::tag(TagID::Short) }
+	pub fn int() -> TagSchema { Self::tag(TagID::Int) }
+	pub fn long() -> TagSchema { Self::tag(TagID::Long) }
+	pub fn float() -> TagSchema { Self::tag(TagID::Float) }
+	pub fn double() -> TagSchema { Self::tag(TagID::Double) }
+	pub fn string() -> TagSchema { Self::tag(TagID::String) }
+	pub fn byte_array() -> TagSchema { Self::tag(TagID::ByteArray) }
+	pub fn int_array() -> TagSchema { Self::tag(TagID::IntArray) }
+	pub fn long_array() -> TagSchema { Self::tag(TagID::LongArray) }
+
+	/// A schema for lists whose elements match `element`.
+	pub fn list<S: Into<Schema>>(element: S) -> ListSchema {
+		ListSchema::new(element)
+	}
+
+	/// A schema for compounds with no known keys, that allows unknown keys.
+	/// Add keys with [CompoundSchema::required] and [CompoundSchema::optional].
+	pub fn compound() -> CompoundSchema {
+		CompoundSchema::new()
+	}
+
+	/// A schema for tags that match any of `options`.
+	pub fn one_of(options: Vec<Schema>) -> Self {
+		Schema::OneOf(options)
+	}
+
+	/// The type of tag this schema accepts, or `None` for [Schema::Any] and [Schema::OneOf].
+	pub fn id(&self) -> Option<TagID> {
+		match self {
+			Schema::Any | Schema::OneOf(_) => None,
+			Schema::Tag(schema) => Some(schema.id),
+			Schema::List(_) => Some(TagID::List),
+			Schema::Compound(_) => Some(TagID::Compound),
+		}
+	}
+
+	/// Checks `tag` against this schema, returning every [Violation] that was found.
+	/// An empty result means that `tag` matches.
+	/// ```
+	/// # use rustnbt::{compound, schema::Schema, tag::Tag};
+	/// let schema: Schema = Schema::compound()
+	///     .required("id", Schema::string())
+	///     .optional("count", Schema::int().range(1, 99))
+	///     .into();
+	/// assert!(schema.validate(&compound!{ ("id", "minecraft:stone") }).is_empty());
+	/// let violations = schema.validate(&compound!{ ("count", 100i32) });
+	/// assert_eq!(violations.len(), 2);
+	/// ```
+	pub fn validate(&self, tag: &Tag) -> Vec<Violation> {
+		let mut violations = Vec::new();
+		self.check(TagRef::from(tag), &TagPath::root(), &mut violations);
+		violations
+	}
+
+	/// Returns `true` if `tag` matches this schema.
+	pub fn is_valid(&self, tag: &Tag) -> bool {
+		self.validate(tag).is_empty()
+	}
+
+	fn check(&self, tag: TagRef, path: &TagPath, violations: &mut Vec<Violation>) {
+		if let Schema::OneOf(options) = self {
+			return Schema::check_one_of(options, tag, path, violations);
+		}
+		let mut violation = |kind| violations.push(Violation { path: path.clone(), kind });
+		let Some(expected) = self.id() else {
+			return;
+		};
+		if tag.id() != expected {
+			violation(ViolationKind::WrongType { expected, found: tag.id() });
+			return;
+		}
+		match (self, tag) {
+			(Schema::Tag(TagSchema { bounds, .. }), tag) => {
+				let value = match tag {
+					TagRef::Byte(value) => Some(value as f64),
+					TagRef::Short(value) => Some(value as f64),
+					TagRef::Int(value) => Some(value as f64),
+					TagRef::Long(value) => Some(value as f64),
+					TagRef::Float(value) => Some(value as f64),
+					TagRef::Double(value) => Some(value),
+					_ => None,
+				};
+				match value {
+					Some(value) if !bounds.contains(value) => violation(ViolationKind::OutOfRange { value, bounds: *bounds }),
+					Some(_) => (),
+					None => {
+						let len = match tag {
+							TagRef::ByteArray(array) => array.len(),
+							TagRef::String(value) => value.chars().count(),
+							TagRef::IntArray(array) => array.len(),
+							TagRef::LongArray(array) => array.len(),
+							_ => unreachable!("TagSchema is never for a List or Compound"),
+						};
+						if !bounds.contains(len as f64) {
+							violation(ViolationKind::WrongLength { len, bounds: *bounds });
+						}
+					}
+				}
+			}
+			(Schema::List(ListSchema { element, bounds }), TagRef::List(list)) => {
+				if !bounds.contains(list.len() as f64) {
+					violation(ViolationKind::WrongLength { len: list.len(), bounds: *bounds });
+				}
+				for (index, item) in list.iter_mixed().enumerate() {
+					element.check(item, &path.index(index as isize), violations);
+				}
+			}
+			(Schema::Compound(compound), TagRef::Compound(map)) => compound.check(map, path, violations),
+			_ => unreachable!("the tag type was checked"),
+		}
+	}
+
+	/// A tag matches a [Schema::OneOf] if it matches any option. Otherwise, the violations of
+	/// the first option for its type are reported.
+	fn check_one_of(options: &[Schema], tag: TagRef, path: &TagPath, violations: &mut Vec<Violation>) {
+		let mut first = None;
+		for option in options.iter().filter(|option| option.id().map_or(true, |id| id == tag.id())) {
+			let mut found = Vec::new();
+			option.check(tag, path, &mut found);
+			if found.is_empty() {
+				return;
+			}
+			first.get_or_insert(found);
+		}
+		match first {
+			Some(found) => violations.extend(found),
+			None => violations.push(Violation {
+				path: path.clone(),
+				kind: ViolationKind::NotOneOf { expected: options.iter().filter_map(Schema::id).collect(), found: tag.id() },
+			}),
+		}
+	}
+}
+
+impl CompoundSchema {
+	fn check(&self, map: &Map, path: &TagPath, violations: &mut Vec<Violation>) {
+		for (key, field) in self.fields.iter() {
+			match map.get(key) {
+				Some(tag) => field.schema.check(TagRef::from(tag), &path.key(key.as_str()), violations),
+				None if field.required => violations.push(Violation { path: path.key(key.as_str()), kind: ViolationKind::MissingKey }),
+				None => (),
+			}
+		}
+		if !self.allow_unknown {
+			for key in map.keys().filter(|key| !self.fields.contains_key(key.as_str())) {
+				violations.push(Violation { path: path.key(key.as_str()), kind: ViolationKind::UnknownKey });
+			}
+		}
+	}
+}
+
+/// The name of a type in schema descriptions.
+fn type_name(id: TagID) -> &'static str {
+	match id {
+		TagID::Byte => "byte",
+		TagID::Short => "short",
+		TagID::Int => "int",
+		TagID::Long => "long",
+		TagID::Float => "float",
+		TagID::Double => "double",
+		TagID::ByteArray => "byte_array",
+		TagID::String => "string",
+		TagID::List => "list",
+		TagID::Compound => "compound",
+		TagID::IntArray => "int_array",
+		TagID::LongArray => "long_array",
+	}
+}
+
+/// Finds the type with the given name. Accepts `int_array` as well as `IntArray`.
+fn type_by_name(name: &str) -> Option<TagID> {
+	let name = name.replace('_', "").to_ascii_lowercase();
+	(1..=12u8)
+		.filter_map(|id| TagID::try_from(id).ok())
+		.find(|id| id.title().to_ascii_lowercase() == name)
+}
+
+impl Schema {
+	/// Loads a schema from a description written in SNBT or JSON.
+	/// ```
+	/// # use rustnbt::{compound, schema::Schema};
+	/// let schema = Schema::parse(r#"{
+	///     "type": "compound",
+	///     "fields": {
+	///         "id": "string",
+	///         "count": {"type": "int", "min": 1, "max": 99, "optional": true}
+	///     }
+	/// }"#).unwrap();
+	/// assert!(schema.is_valid(&compound!{ ("id", "minecraft:stone"), ("count", 5i32) }));
+	/// ```
+	pub fn parse(source: &str) -> Result<Schema, SchemaError> {
+		Schema::from_tag(&Tag::parse(source)?)
+	}
+
+	/// Loads a schema from a description that was already parsed. See [Schema::parse].<br>
+	/// A description is either the name of a type (`"int"`, `"int_array"`, `"any"`, ...),
+	/// or a compound with a `type` key and these optional keys:
+	/// - `min` and `max`: bounds of numbers, or of the length of strings, arrays and lists.
+	/// - `element`: the description of the elements of a list.
+	/// - `fields`: a compound of key descriptions for a compound. Keys are required unless
+	///   their description has `optional: true`.
+	/// - `allow_unknown`: whether a compound allows keys that aren't in `fields`. Defaults to `true`.
+	pub fn from_tag(description: &Tag) -> Result<Schema, SchemaError> {
+		Schema::describe(description, &TagPath::root())
+	}
+
+	fn describe(description: &Tag, path: &TagPath) -> Result<Schema, SchemaError> {
+		let invalid = |path: &TagPath, message: String| SchemaError::Invalid { path: path.clone(), message };
+		let (name, map) = match description {
+			Tag::String(name) => (name.as_str(), None),
+			Tag::Compound(map) => match map.get("type") {
+				Some(Tag::String(name)) => (name.as_str(), Some(map)),
+				Some(other) => return Err(invalid(&path.key("type"), format!("Expected a String, found {}.", other.id().title()))),
+				None => return Err(invalid(path, "Missing `type`.".to_owned())),
+			},
+			other => return Err(invalid(path, format!("Expected a String or Compound, found {}.", other.id().title()))),
+		};
+		let mut schema = match name {
+			"any" => Schema::Any,
+			"one_of" => Schema::OneOf(Vec::new()),
+			name => Schema::of(type_by_name(name).ok_or_else(|| invalid(path, format!("Unknown type `{name}`.")))?),
+		};
+		let Some(map) = map else {
+			if let Schema::OneOf(_) = schema {
+				return Err(invalid(path, "`one_of` schemas need `options`.".to_owned()));
+			}
+			return Ok(schema);
+		};
+		let number = |key: &str| match map.get(key) {
+			None => Ok(None),
+			Some(Tag::Byte(value)) => Ok(Some(*value as f64)),
+			Some(Tag::Short(value)) => Ok(Some(*value as f64)),
+			Some(Tag::Int(value)) => Ok(Some(*value as f64)),
+			Some(Tag::Long(value)) => Ok(Some(*value as f64)),
+			Some(Tag::Float(value)) => Ok(Some(*value as f64)),
+			Some(Tag::Double(value)) => Ok(Some(*value)),
+			Some(other) => Err(invalid(&path.key(key), format!("Expected a number, found {}.", other.id().title()))),
+		};
+		let boolean = |key: &str, default: bool| match map.get(key) {
+			None => Ok(default),
+			Some(Tag::Byte(value)) => Ok(*value != 0),
+			Some(other) => Err(invalid(&path.key(key), format!("Expected a boolean, found {}.", other.id().title()))),
+		};
+		let mut allowed = vec!["type"];
+		if let Schema::Tag(TagSchema { bounds, .. }) | Schema::List(ListSchema { bounds, .. }) = &mut schema {
+			allowed.extend(["min", "max"]);
+			*bounds = Bounds { min: number("min")?, max: number("max")? };
+		}
+		if let Schema::List(ListSchema { element, .. }) = &mut schema {
+			allowed.push("element");
+			if let Some(description) = map.get("element") {
+				**element = Schema::describe(description, &path.key("element"))?;
+			}
+		}
+		if let Schema::OneOf(options) = &mut schema {
+			allowed.push("options");
+			match map.get("options") {
+				None => return Err(invalid(path, "`one_of` schemas need `options`.".to_owned())),
+				Some(Tag::List(list)) => for (index, description) in list.clone().into_mixed().iter().enumerate() {
+					options.push(Schema::describe(description, &path.key("options").index(index as isize))?);
+				},
+				Some(other) => return Err(invalid(&path.key("options"), format!("Expected a List, found {}.", other.id().title()))),
+			}
+		}
+		if let Schema::Compound(compound) = &mut schema {
+			allowed.extend(["fields", "allow_unknown"]);
+			compound.allow_unknown = boolean("allow_unknown", true)?;
+			match map.get("fields") {
+				None => (),
+				Some(Tag::Compound(fields)) => for (key, description) in fields.iter() {
+					let path = path.key("fields").key(key.as_str());
+					let required = match description {
+						Tag::Compound(field) => match field.get("optional") {
+							None => true,
+							Some(Tag::Byte(value)) => *value == 0,
+							Some(other) => return Err(invalid(&path.key("optional"), format!("Expected a boolean, found {}.", other.id().title()))),
+						},
+						_ => true,
+					};
+					let schema = match description {
+						Tag::Compound(field) if field.contains_key("optional") => {
+							let mut field = field.clone();
+							field.remove("optional");
+							Schema::describe(&Tag::Compound(field), &path)?
+						}
+						description => Schema::describe(description, &path)?,
+					};
+					compound.fields.insert(key.clone(), Field { schema, required });
+				},
+				Some(other) => return Err(invalid(&path.key("fields"), format!("Expected a Compound, found {}.", other.id().title()))),
+			}
+		}
+		if let Some(key) = map.keys().find(|key| !allowed.contains(&key.as_str())) {
+			return Err(invalid(&path.key(key.as_str()), format!("`{key}` doesn't apply to `{name}` schemas.")));
+		}
+		Ok(schema)
+	}
+
+	/// Writes this schema as a description that [Schema::from_tag] accepts.
+	/// Schemas without any details are written as just the name of their type.
+	pub fn to_tag(&self) -> Tag {
+		let mut map = Map::new();
+		let bounds = |map: &mut Map, bounds: &Bounds| {
+			if let Some(min) = bounds.min {
+				map.insert("min".to_owned(), number_tag(min));
+			}
+			if let Some(max) = bounds.max {
+				map.insert("max".to_owned(), number_tag(max));
+			}
+		};
+		match self {
+			Schema::Any => return Tag::string("any"),
+			Schema::OneOf(options) => {
+				map.insert("type".to_owned(), Tag::string("one_of"));
+				let options = options.iter().map(Schema::to_tag).collect();
+				map.insert("options".to_owned(), Tag::List(ListTag::from_mixed(options)));
+			}
+			Schema::Tag(TagSchema { id, bounds: range }) => {
+				if range.is_unbounded() {
+					return Tag::string(type_name(*id));
+				}
+				map.insert("type".to_owned(), Tag::string(type_name(*id)));
+				bounds(&mut map, range);
+			}
+			Schema::List(ListSchema { element, bounds: range }) => {
+				if range.is_unbounded() && **element == Schema::Any {
+					return Tag::string("list");
+				}
+				map.insert("type".to_owned(), Tag::string("list"));
+				bounds(&mut map, range);
+				if **element != Schema::Any {
+					map.insert("element".to_owned(), element.to_tag());
+				}
+			}
+			Schema::Compound(compound) => {
+				map.insert("type".to_owned(), Tag::string("compound"));
+				if !compound.allow_unknown {
+					map.insert("allow_unknown".to_owned(), Tag::Byte(0));
+				}
+				let fields = compound.fields.iter().map(|(key, field)| {
+					let mut description = field.schema.to_tag();
+					if !field.required {
+						let mut map = match description {
+							Tag::Compound(map) => map,
+							name => Map::from([("type".to_owned(), name)]),
+						};
+						map.insert("optional".to_owned(), Tag::Byte(1));
+						description = Tag::Compound(map);
+					}
+					(key.clone(), description)
+				}).collect();
+				map.insert("fields".to_owned(), Tag::Compound(fields));
+			}
+		}
+		Tag::Compound(map)
+	}
+}
+
+/// Bounds that are whole numbers are written as Longs, everything else as Doubles.
+fn number_tag(value: f64) -> Tag {
+	if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+		Tag::Long(value as i64)
+	} else {
+		Tag::Double(value)
+	}
+}
+
+impl Display for Schema {
+	/// Formats the description of this schema as SNBT. See [Schema::to_tag].
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.to_tag())
+	}
+}
+
+/// Infers a [Schema] from sample tags, one sample at a time.<br>
+/// Keys that appear in every sample of a compound are required, and the others are optional.
+/// Numbers are bounded by the smallest and largest values that were seen, and places where
+/// samples had different types become [Schema::OneOf].
+/// Compounds allow unknown keys, since the samples may not have every key.
+#[derive(Clone, Debug, Default)]
+pub struct Inference {
+	root: Observed,
+}
+
+/// What was seen at one place in the samples.
+#[derive(Clone, Debug, Default)]
+struct Observed {
+	/// One entry per type that was seen, in the order that they were first seen.
+	kinds: Vec<Kind>,
+}
+
+#[derive(Clone, Debug)]
+enum Kind {
+	/// Numbers keep the range of their values. `min` > `max` until a value that isn't NaN is seen.
+	Number { id: TagID, min: f64, max: f64 },
+	Value(TagID),
+	List(Observed),
+	Compound { samples: usize, fields: MapType<String, (usize, Observed)> },
+}
+
+impl Kind {
+	fn id(&self) -> TagID {
+		match self {
+			Kind::Number { id, .. } => *id,
+			Kind::Value(id) => *id,
+			Kind::List(_) => TagID::List,
+			Kind::Compound { .. } => TagID::Compound,
+		}
+	}
+}
+
+impl Observed {
+	fn add(&mut self, tag: TagRef) {
+		let id = tag.id();
+		let index = match self.kinds.iter().position(|kind| kind.id() == id) {
+			Some(index) => index,
+			None => {
+				self.kinds.push(match id {
+					TagID::Byte | TagID::Short | TagID::Int | TagID::Long | TagID::Float | TagID::Double => {
+						Kind::Number { id, min: f64::INFINITY, max: f64::NEG_INFINITY }
+					}
+					TagID::List => Kind::List(Observed::default()),
+					TagID::Compound => Kind::Compound { samples: 0, fields: MapType::new() },
+					id => Kind::Value(id),
+				});
+				self.kinds.len() - 1
+			}
+		};
+		match (&mut self.kinds[index], tag) {
+			(Kind::Number { min, max, .. }, tag) => {
+				let value = match tag {
+					TagRef::Byte(value) => value as f64,
+					TagRef::Short(value) => value as f64,
+					TagRef::Int(value) => value as f64,
+					TagRef::Long(value) => value as f64,
+					TagRef::Float(value) => value as f64,
+					TagRef::Double(value) => value,
+					_ => unreachable!("numbers are only observed for numeric tags"),
+				};
+				*min = min.min(value);
+				*max = max.max(value);
+			}
+			(Kind::List(element), TagRef::List(list)) => list.iter_mixed().for_each(|item| element.add(item)),
+			(Kind::Compound { samples, fields }, TagRef::Compound(map)) => {
+				*samples += 1;
+				for (key, tag) in map.iter() {
+					let (count, observed) = fields.entry(key.clone()).or_default();
+					*count += 1;
+					observed.add(TagRef::from(tag));
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn schema(&self) -> Schema {
+		let mut options: Vec<Schema> = self.kinds.iter().map(|kind| match kind {
+			&Kind::Number { id, min, max } => Schema::Tag(TagSchema {
+				id,
+				bounds: if min <= max { Bounds { min: Some(min), max: Some(max) } } else { Bounds::default() },
+			}),
+			Kind::Value(id) => Schema::of(*id),
+			Kind::List(element) => Schema::list(element.schema()).into(),
+			Kind::Compound { samples, fields } => Schema::Compound(CompoundSchema {
+				fields: fields.iter()
+					.map(|(key, (count, observed))| (key.clone(), Field { schema: observed.schema(), required: count == samples }))
+					.collect(),
+				allow_unknown: true,
+			}),
+		}).collect();
+		match options.len() {
+			0 => Schema::Any,
+			1 => options.pop().expect("there is one option"),
+			_ => Schema::OneOf(options),
+		}
+	}
+}
+
+impl Inference {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a sample.
+	pub fn add(&mut self, tag: &Tag) {
+		self.root.add(TagRef::from(tag));
+	}
+
+	/// Returns a schema that every sample so far matches.
+	/// This is [Schema::Any] if there were no samples.
+	pub fn schema(&self) -> Schema {
+		self.root.schema()
+	}
+}
+
+impl Schema {
+	/// Infers a schema that all of `samples` match. See [Inference].
+	/// ```
+	/// # use rustnbt::{schema::{Field, Schema}, tag::Tag};
+	/// let samples = ["{id: \"a\", count: 1}", "{id: \"b\", count: 64, damage: 2}"]
+	///     .map(|snbt| Tag::parse(snbt).unwrap());
+	/// let schema = Schema::infer(&samples);
+	/// assert_eq!(schema, Schema::compound()
+	///     .required("id", Schema::string())
+	///     .required("count", Schema::int().range(1, 64))
+	///     .optional("damage", Schema::int().range(2, 2))
+	///     .into());
+	/// ```
+	pub fn infer<'a, I: IntoIterator<Item = &'a Tag>>(samples: I) -> Schema {
+		let mut inference = Inference::new();
+		samples.into_iter().for_each(|tag| inference.add(tag));
+		inference.schema()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item() -> Schema {
+		Schema::compound()
+			.required("id", Schema::string().min(1))
+			.required("count", Schema::int().range(1, 99))
+			.optional("components", Schema::compound()
+				.optional("damage", Schema::int().min(0))
+				.optional("lore", Schema::list(Schema::string()).max(256)))
+			.deny_unknown()
+			.into()
+	}
+
+	fn violations(schema: &Schema, snbt: &str) -> Vec<String> {
+		schema.validate(&Tag::parse(snbt).unwrap()).iter().map(ToString::to_string).collect()
+	}
+
+	#[test]
+	fn validate_test() {
+		let schema = item();
+		assert!(violations(&schema, r#"{id: "minecraft:stone", count: 64}"#).is_empty());
+		assert!(violations(&schema, r#"{id: "a", count: 1, components: {damage: 3, lore: ["x"], extra: 1b}}"#).is_empty());
+		let mut found = violations(&schema, r#"{id: "", count: 100, components: {damage: -1s, lore: ["x", 1]}, slot: 3b}"#);
+		found.sort();
+		let mut expected = vec![
+			"count: 100 is out of range, it must be between 1 and 99.",
+			"components.damage: Expected Int, found Short.",
+			"id: Length 0 is out of range, it must be at least 1.",
+			"slot: Key is not allowed.",
+		];
+		expected.sort();
+		expected.push("components.lore[1]: Expected String, found Int.");
+		expected.sort();
+		assert_eq!(found, expected);
+		let mut found = violations(&schema, "{}");
+		found.sort();
+		assert_eq!(found, ["count: Required key is missing.", "id: Required key is missing."]);
+		assert_eq!(violations(&schema, "[]"), ["<root>: Expected Compound, found List."]);
+		let nested = Schema::list(Schema::list(Schema::byte().max(1))).into();
+		assert_eq!(violations(&nested, "[[0b, 1b], [2b]]"), ["[1][0]: 2 is out of range, it must be at most 1."]);
+		assert!(Schema::Any.is_valid(&Tag::Int(1)));
+		assert_eq!(TagSchema::new(TagID::List), None);
+		assert_eq!(TagSchema::new(TagID::Int).map(Schema::from), Some(Schema::int().into()));
+	}
+
+	#[test]
+	fn description_test() {
+		let description = r#"{
+			type: "compound",
+			allow_unknown: false,
+			fields: {
+				id: {type: "string", min: 1},
+				count: {type: "int", min: 1, max: 99},
+				components: {
+					type: "compound",
+					optional: true,
+					fields: {
+						damage: {type: "int", min: 0, optional: true},
+						lore: {type: "list", element: "string", max: 256, optional: true},
+					},
+				},
+			},
+		}"#;
+		assert_eq!(Schema::parse(description).unwrap(), item());
+		assert_eq!(Schema::from_tag(&item().to_tag()).unwrap(), item());
+		let json = r#"{"type": "list", "element": {"type": "IntArray", "min": 2.5}, "max": 4}"#;
+		assert_eq!(Schema::parse(json).unwrap(), Schema::list(Schema::int_array().min(2.5)).max(4).into());
+		let one_of = r#"{type: "list", element: {type: "one_of", options: ["string", {type: "int", min: 0}]}}"#;
+		let schema = Schema::list(Schema::one_of(vec![Schema::string().into(), Schema::int().min(0).into()])).into();
+		assert_eq!(Schema::parse(one_of).unwrap(), schema);
+		assert_eq!(Schema::from_tag(&schema.to_tag()).unwrap(), schema);
+		assert_eq!(violations(&schema, r#"["a", 1, -1, 2b]"#), [
+			"[2]: -1 is out of range, it must be at least 0.",
+			"[3]: Expected one of String, Int, found Byte.",
+		]);
+		for (invalid, at) in [
+			(r#"{type: "one_of"}"#, ""),
+			(r#"{type: "one_of", options: ["int", "bool"]}"#, "options[1]"),
+			(r#"{fields: {}}"#, ""),
+			(r#""integer""#, ""),
+			(r#"{type: "int", element: "int"}"#, "element"),
+			(r#"{type: "list", element: {type: "int", max: "x"}}"#, "element.max"),
+			(r#"{type: "compound", fields: {a: {type: "int", optional: 2s}}}"#, "fields.a.optional"),
+		] {
+			match Schema::parse(invalid) {
+				Err(SchemaError::Invalid { path, .. }) => assert_eq!(path.to_string(), at, "{invalid}"),
+				other => panic!("{invalid}: {other:?}"),
+			}
+		}
+	}
+
+	#[test]
+	fn infer_test() {
+		let samples = [
+			r#"{DataVersion: 3953, Pos: [0.5d, 64.0d], Inventory: [{Slot: 0b, id: "stone"}], Tags: []}"#,
+			r#"{DataVersion: 4189, Pos: [-3.0d, 70.0d], Inventory: [{Slot: 8b, id: "dirt", count: 2}, {Slot: 3b, id: "air"}], Mode: 1b}"#,
+			r#"{DataVersion: 4189, Pos: [1.0d, 2.0d], Inventory: [], Mode: "creative", Mixed: [1, "a", {b: 1b}]}"#,
+		].map(|snbt| Tag::parse(snbt).unwrap());
+		let schema = Schema::infer(&samples);
+		let expected = Schema::compound()
+			.required("DataVersion", Schema::int().range(3953, 4189))
+			.required("Pos", Schema::list(Schema::double().range(-3, 70)))
+			.required("Inventory", Schema::list(Schema::compound()
+				.required("Slot", Schema::byte().range(0, 8))
+				.required("id", Schema::string())
+				.optional("count", Schema::int().range(2, 2))))
+			.optional("Tags", Schema::list(Schema::Any))
+			.optional("Mode", Schema::one_of(vec![Schema::byte().range(1, 1).into(), Schema::string().into()]))
+			.optional("Mixed", Schema::list(Schema::one_of(vec![
+				Schema::int().range(1, 1).into(),
+				Schema::string().into(),
+				Schema::compound().required("b", Schema::byte().range(1, 1)).into(),
+			])))
+			.into();
+		assert_eq!(schema, expected);
+		assert!(samples.iter().all(|sample| schema.is_valid(sample)));
+		assert_eq!(Schema::from_tag(&schema.to_tag()).unwrap(), schema);
+		assert_eq!(Schema::infer([]), Schema::Any);
+	}
+}
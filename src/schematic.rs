@@ -0,0 +1,559 @@
+// Sponge schematics (.schem), versions 2 and 3.
+// https://github.com/SpongePowered/Schematic-Specification
+//
+// Version 2 keeps everything in the root compound, which is named `Schematic`.
+// Version 3 moves it into a `Schematic` compound, groups blocks and biomes with their palettes,
+// and nests the data of block entities and entities under `Data`.
+// Blocks are palette indices encoded as varints in a ByteArray, ordered by y, then z, then x.
+
+use crate::{
+	Map,
+	NbtError,
+	chunk::{BlockState, InvalidBlockState},
+	codegen::{self, DecodeError},
+	compression::{self, Compression},
+	io::{read_named_tag, write_named_tag},
+	path::PathSegment,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+	},
+};
+use std::io::{Read, Write};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SchematicError {
+	#[error("{0}")]
+	Io(#[from] std::io::Error),
+	#[error("{0}")]
+	Nbt(#[from] NbtError),
+	#[error("{0}")]
+	Decode(#[from] DecodeError),
+}
+
+/// A block entity, such as the contents of a chest.
+#[derive(Clone, Debug)]
+pub struct BlockEntity {
+	/// The position of the block, relative to the schematic.
+	pub pos: [i32; 3],
+	pub id: String,
+	/// The rest of the block entity's NBT.
+	pub data: Map,
+	/// Keys next to `Data` that aren't part of the format. Always empty in version 2.
+	pub other: Map,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entity {
+	/// The position of the entity, relative to the schematic.
+	pub pos: [f64; 3],
+	pub id: String,
+	/// The rest of the entity's NBT.
+	pub data: Map,
+	/// Keys next to `Data` that aren't part of the format. Always empty in version 2.
+	pub other: Map,
+}
+
+/// Biomes as indices into a palette of biome names.
+/// In version 2 there is one biome per column, ordered by z, then x.
+/// In version 3 there is one biome per block, in the same order as blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Biomes {
+	pub palette: Vec<String>,
+	pub data: Vec<u32>,
+}
+
+/// A box of blocks, with the block entities, entities and biomes in it.
+/// ```
+/// # use rustnbt::{chunk::BlockState, schematic::Schematic};
+/// let mut schematic = Schematic::new(2, 3, 4, 3465);
+/// schematic.set_block(1, 2, 3, BlockState::new("minecraft:stone"));
+/// assert_eq!(schematic.block_at(1, 2, 3).unwrap().name, "minecraft:stone");
+/// assert_eq!(schematic.block_at(0, 0, 0), Some(&BlockState::air()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Schematic {
+	/// The format version, which is 2 or 3.
+	pub version: i32,
+	pub data_version: i32,
+	width: u16,
+	height: u16,
+	length: u16,
+	/// Where the schematic is placed relative to the position it is pasted at.
+	pub offset: [i32; 3],
+	/// Information about the schematic, such as its `Name`, `Author` and `Date`.
+	pub metadata: Option<Map>,
+	palette: Vec<BlockState>,
+	/// Indices into `palette`.
+	blocks: Vec<u32>,
+	pub block_entities: Vec<BlockEntity>,
+	pub entities: Vec<Entity>,
+	pub biomes: Option<Biomes>,
+	/// Keys that aren't part of the specification, such as extensions added by other tools.
+	pub other: Map,
+}
+
+impl Schematic {
+	/// Creates a version 3 schematic filled with air.
+	pub fn new(width: u16, height: u16, length: u16, data_version: i32) -> Self {
+		Self {
+			version: 3,
+			data_version,
+			width,
+			height,
+			length,
+			offset: [0; 3],
+			metadata: None,
+			palette: vec![BlockState::air()],
+			blocks: vec![0; width as usize * height as usize * length as usize],
+			block_entities: Vec::new(),
+			entities: Vec::new(),
+			biomes: None,
+			other: Map::new(),
+		}
+	}
+
+	/// Reads a schematic file, which is usually compressed with gzip.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, SchematicError> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let (_, data) = compression::decompress(&data)?;
+		let (_, tag) = read_named_tag(&mut data.as_slice())?;
+		Ok(Self::decode_nbt(tag)?)
+	}
+
+	/// Writes a schematic file compressed with gzip.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), SchematicError> {
+		let name = if self.version >= 3 { "" } else { "Schematic" };
+		let mut data = Vec::new();
+		write_named_tag(&mut data, &self.clone().encode_nbt(), name)?;
+		writer.write_all(&Compression::Gzip.compress(&data)?)?;
+		Ok(())
+	}
+
+	/// The size along the x axis.
+	pub fn width(&self) -> u16 {
+		self.width
+	}
+
+	/// The size along the y axis.
+	pub fn height(&self) -> u16 {
+		self.height
+	}
+
+	/// The size along the z axis.
+	pub fn length(&self) -> u16 {
+		self.length
+	}
+
+	/// The block states that blocks refer to. Some of them may be unused.
+	pub fn palette(&self) -> &[BlockState] {
+		&self.palette
+	}
+
+	fn index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+		let (width, length) = (self.width as usize, self.length as usize);
+		(x < width && y < self.height as usize && z < length).then(|| (y * length + z) * width + x)
+	}
+
+	/// Returns the block at a position relative to the schematic, or `None` if it is outside.
+	pub fn block_at(&self, x: usize, y: usize, z: usize) -> Option<&BlockState> {
+		let index = self.index(x, y, z)?;
+		Some(&self.palette[self.blocks[index] as usize])
+	}
+
+	/// Replaces the block at a position relative to the schematic, returning the old one.
+	/// # Panics
+	/// Panics if the position is outside the schematic.
+	pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: BlockState) -> BlockState {
+		let index = self.index(x, y, z)
+			.unwrap_or_else(|| panic!("({x}, {y}, {z}) is outside a schematic of size {}×{}×{}", self.width, self.height, self.length));
+		let palette_index = match self.palette.iter().position(|entry| entry == &state) {
+			Some(palette_index) => palette_index,
+			None => {
+				self.palette.push(state);
+				self.palette.len() - 1
+			}
+		};
+		let old = std::mem::replace(&mut self.blocks[index], palette_index as u32);
+		self.palette[old as usize].clone()
+	}
+
+	/// Every position in the schematic with its block, ordered by y, then z, then x.
+	pub fn blocks(&self) -> impl Iterator<Item = ([usize; 3], &BlockState)> + '_ {
+		let (width, length) = (self.width as usize, self.length as usize);
+		self.blocks.iter().enumerate().map(move |(index, &block)| {
+			([index % width, index / (width * length), index / width % length], &self.palette[block as usize])
+		})
+	}
+}
+
+/// An error in a varint-encoded ByteArray.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+enum VarintError {
+	#[error("Varint {0} doesn't fit in 32 bits.")]
+	Overflow(usize),
+	#[error("The last varint is incomplete.")]
+	Incomplete,
+	#[error("Expected {expected} entries, found {found}.")]
+	Count { expected: usize, found: usize },
+}
+
+/// Decodes unsigned LEB128 varints, which must be exactly `count`.
+fn read_varints(bytes: &[i8], count: usize) -> Result<Vec<u32>, VarintError> {
+	// Every varint takes at least one byte, so don't trust `count` beyond that.
+	let mut values = Vec::with_capacity(count.min(bytes.len()));
+	let mut value = 0u32;
+	let mut shift = 0;
+	for &byte in bytes {
+		let byte = byte as u8;
+		// The fifth byte only has room for the top 4 bits, and can't continue.
+		if shift == 28 && byte > 0x0f {
+			return Err(VarintError::Overflow(values.len()));
+		}
+		value |= ((byte & 0x7f) as u32) << shift;
+		if byte & 0x80 == 0 {
+			values.push(value);
+			value = 0;
+			shift = 0;
+		} else {
+			shift += 7;
+		}
+	}
+	if shift != 0 {
+		return Err(VarintError::Incomplete);
+	}
+	if values.len() != count {
+		return Err(VarintError::Count { expected: count, found: values.len() });
+	}
+	Ok(values)
+}
+
+fn write_varints(values: &[u32]) -> Vec<i8> {
+	let mut bytes = Vec::with_capacity(values.len());
+	for &value in values {
+		let mut value = value;
+		while value >= 0x80 {
+			bytes.push((value as u8 & 0x7f | 0x80) as i8);
+			value >>= 7;
+		}
+		bytes.push(value as i8);
+	}
+	bytes
+}
+
+/// Decodes a palette of names to indices, returning the names ordered by index.
+fn decode_palette(tag: Tag) -> Result<Vec<String>, DecodeError> {
+	let map = codegen::compound(tag)?;
+	let mut entries = map.into_iter()
+		.map(|(name, index)| {
+			let index: i32 = codegen::value(index).map_err(|error| error.within(PathSegment::Key(name.clone())))?;
+			Ok((index, name))
+		})
+		.collect::<Result<Vec<_>, DecodeError>>()?;
+	entries.sort();
+	for (expected, (index, name)) in entries.iter().enumerate() {
+		if *index != expected as i32 {
+			let message = format!("Palette indices must count up from 0, but the index after {} is {index}.", expected as i32 - 1);
+			return Err(DecodeError::invalid(message).within(PathSegment::Key(name.clone())));
+		}
+	}
+	Ok(entries.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Decodes a palette of block state strings.
+fn decode_block_palette(tag: Tag) -> Result<Vec<BlockState>, DecodeError> {
+	decode_palette(tag)?.into_iter()
+		.map(|name| name.parse().map_err(|error: InvalidBlockState| {
+			DecodeError::invalid(error.to_string()).within(PathSegment::Key(name.clone()))
+		}))
+		.collect()
+}
+
+fn encode_palette<I: IntoIterator<Item = String>>(names: I) -> Tag {
+	Tag::Compound(names.into_iter().enumerate().map(|(index, name)| (name, Tag::Int(index as i32))).collect())
+}
+
+/// Decodes varint palette indices, which must all be in the palette.
+fn decode_indices(tag: Tag, count: usize, palette_len: usize) -> Result<Vec<u32>, DecodeError> {
+	let bytes: Vec<i8> = codegen::value(tag)?;
+	let indices = read_varints(&bytes, count).map_err(|error| DecodeError::invalid(error.to_string()))?;
+	if let Some((index, value)) = indices.iter().enumerate().find(|&(_, &value)| value as usize >= palette_len) {
+		return Err(DecodeError::invalid(format!("Entry {index} is {value}, but the palette only has {palette_len} entries.")));
+	}
+	Ok(indices)
+}
+
+/// Decodes a list of block entities or entities, whose data is nested under `Data` since version 3.
+/// Returns the position, id, data and, since version 3, the other keys of each.
+fn decode_objects<P>(tag: Tag, version: i32, pos: fn(Tag) -> Result<P, DecodeError>) -> Result<Vec<(P, String, Map, Map)>, DecodeError> {
+	codegen::list(tag, |tag| {
+		let mut map = codegen::compound(tag)?;
+		let position = codegen::required(&mut map, "Pos", pos)?;
+		let id = codegen::required(&mut map, "Id", codegen::value)?;
+		let (data, other) = match version {
+			2 => (map, Map::new()),
+			_ => (codegen::optional(&mut map, "Data", codegen::compound)?.unwrap_or_default(), map),
+		};
+		Ok((position, id, data, other))
+	})
+}
+
+fn encode_objects(objects: Vec<(Tag, String, Map, Map)>, version: i32) -> Tag {
+	let maps: Vec<Map> = objects.into_iter().map(|(pos, id, data, other)| {
+		let mut map = Map::new();
+		map.insert("Pos".to_owned(), pos);
+		map.insert("Id".to_owned(), Tag::String(id));
+		match version {
+			2 => map.extend(data),
+			_ => {
+				map.insert("Data".to_owned(), Tag::Compound(data));
+			}
+		}
+		map.extend(other);
+		map
+	}).collect();
+	Tag::List(ListTag::from(maps))
+}
+
+fn decode_block_pos(tag: Tag) -> Result<[i32; 3], DecodeError> {
+	let pos: Vec<i32> = codegen::value(tag)?;
+	pos.try_into().map_err(|pos: Vec<i32>| DecodeError::invalid(format!("Expected 3 coordinates, found {}.", pos.len())))
+}
+
+fn decode_entity_pos(tag: Tag) -> Result<[f64; 3], DecodeError> {
+	let pos = codegen::list(tag, codegen::value::<f64>)?;
+	pos.try_into().map_err(|pos: Vec<f64>| DecodeError::invalid(format!("Expected 3 coordinates, found {}.", pos.len())))
+}
+
+impl DecodeNbt for Schematic {
+	type Error = DecodeError;
+
+	/// Decodes the root compound of a schematic file, in either version.
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		// Version 3 wraps everything in a `Schematic` compound.
+		if !map.contains_key("Version") && map.contains_key("Schematic") {
+			return codegen::required(&mut map, "Schematic", Schematic::decode_nbt);
+		}
+		let version = codegen::required(&mut map, "Version", |tag| match codegen::value::<i32>(tag)? {
+			version @ (2 | 3) => Ok(version),
+			version => Err(DecodeError::invalid(format!("Version {version} isn't supported. Only versions 2 and 3 are."))),
+		})?;
+		let data_version = codegen::required(&mut map, "DataVersion", codegen::value)?;
+		let metadata = codegen::optional(&mut map, "Metadata", codegen::compound)?;
+		// Sizes are unsigned shorts.
+		let size = |map: &mut Map, key| codegen::required(map, key, codegen::value::<i16>).map(|size| size as u16);
+		let (width, height, length) = (size(&mut map, "Width")?, size(&mut map, "Height")?, size(&mut map, "Length")?);
+		let offset = codegen::optional(&mut map, "Offset", decode_block_pos)?.unwrap_or_default();
+		let volume = width as usize * height as usize * length as usize;
+
+		let (palette, blocks, block_entities) = if version == 2 {
+			let palette = codegen::required(&mut map, "Palette", decode_block_palette)?;
+			let blocks = codegen::required(&mut map, "BlockData", |tag| decode_indices(tag, volume, palette.len()))?;
+			// `PaletteMax` is the size of the palette, which is written again from the palette.
+			codegen::optional(&mut map, "PaletteMax", codegen::any)?;
+			let block_entities = codegen::optional(&mut map, "BlockEntities", |tag| decode_objects(tag, version, decode_block_pos))?;
+			(palette, blocks, block_entities)
+		} else {
+			codegen::optional(&mut map, "Blocks", |tag| {
+				let mut container = codegen::compound(tag)?;
+				let palette = codegen::required(&mut container, "Palette", decode_block_palette)?;
+				let blocks = codegen::required(&mut container, "Data", |tag| decode_indices(tag, volume, palette.len()))?;
+				let block_entities = codegen::optional(&mut container, "BlockEntities", |tag| decode_objects(tag, version, decode_block_pos))?;
+				Ok((palette, blocks, block_entities))
+			})?.unwrap_or_else(|| (vec![BlockState::air()], vec![0; volume], None))
+		};
+		let biomes = if version == 2 {
+			match codegen::optional(&mut map, "BiomePalette", decode_palette)? {
+				Some(palette) => {
+					let data = codegen::required(&mut map, "BiomeData", |tag| decode_indices(tag, width as usize * length as usize, palette.len()))?;
+					codegen::optional(&mut map, "BiomePaletteMax", codegen::any)?;
+					Some(Biomes { palette, data })
+				}
+				None => None,
+			}
+		} else {
+			codegen::optional(&mut map, "Biomes", |tag| {
+				let mut container = codegen::compound(tag)?;
+				let palette = codegen::required(&mut container, "Palette", decode_palette)?;
+				let data = codegen::required(&mut container, "Data", |tag| decode_indices(tag, volume, palette.len()))?;
+				Ok(Biomes { palette, data })
+			})?
+		};
+		let entities = codegen::optional(&mut map, "Entities", |tag| decode_objects(tag, version, decode_entity_pos))?;
+
+		Ok(Self {
+			version,
+			data_version,
+			width,
+			height,
+			length,
+			offset,
+			metadata,
+			palette,
+			blocks,
+			block_entities: block_entities.unwrap_or_default().into_iter()
+				.map(|(pos, id, data, other)| BlockEntity { pos, id, data, other })
+				.collect(),
+			entities: entities.unwrap_or_default().into_iter()
+				.map(|(pos, id, data, other)| Entity { pos, id, data, other })
+				.collect(),
+			biomes,
+			other: map,
+		})
+	}
+}
+
+impl EncodeNbt for Schematic {
+	/// Encodes the root compound of a schematic file in [Schematic::version].
+	fn encode_nbt(self) -> Tag {
+		let version = self.version;
+		let mut map = Map::new();
+		map.insert("Version".to_owned(), Tag::Int(version));
+		map.insert("DataVersion".to_owned(), Tag::Int(self.data_version));
+		if let Some(metadata) = self.metadata {
+			map.insert("Metadata".to_owned(), Tag::Compound(metadata));
+		}
+		map.insert("Width".to_owned(), Tag::Short(self.width as i16));
+		map.insert("Height".to_owned(), Tag::Short(self.height as i16));
+		map.insert("Length".to_owned(), Tag::Short(self.length as i16));
+		map.insert("Offset".to_owned(), Tag::IntArray(self.offset.to_vec()));
+
+		let palette_len = self.palette.len();
+		let palette = encode_palette(self.palette.iter().map(BlockState::to_string));
+		let blocks = Tag::ByteArray(write_varints(&self.blocks));
+		let block_entities = encode_objects(self.block_entities.into_iter()
+			.map(|entity| (Tag::IntArray(entity.pos.to_vec()), entity.id, entity.data, entity.other))
+			.collect(), version);
+		if version == 2 {
+			map.insert("PaletteMax".to_owned(), Tag::Int(palette_len as i32));
+			map.insert("Palette".to_owned(), palette);
+			map.insert("BlockData".to_owned(), blocks);
+			map.insert("BlockEntities".to_owned(), block_entities);
+		} else {
+			let mut container = Map::new();
+			container.insert("Palette".to_owned(), palette);
+			container.insert("Data".to_owned(), blocks);
+			container.insert("BlockEntities".to_owned(), block_entities);
+			map.insert("Blocks".to_owned(), Tag::Compound(container));
+		}
+
+		if let Some(biomes) = self.biomes {
+			let palette_len = biomes.palette.len();
+			let palette = encode_palette(biomes.palette);
+			let data = Tag::ByteArray(write_varints(&biomes.data));
+			if version == 2 {
+				map.insert("BiomePaletteMax".to_owned(), Tag::Int(palette_len as i32));
+				map.insert("BiomePalette".to_owned(), palette);
+				map.insert("BiomeData".to_owned(), data);
+			} else {
+				let mut container = Map::new();
+				container.insert("Palette".to_owned(), palette);
+				container.insert("Data".to_owned(), data);
+				map.insert("Biomes".to_owned(), Tag::Compound(container));
+			}
+		}
+		let entities = self.entities.into_iter()
+			.map(|entity| (Tag::List(ListTag::from(entity.pos.to_vec())), entity.id, entity.data, entity.other))
+			.collect();
+		map.insert("Entities".to_owned(), encode_objects(entities, version));
+		map.extend(self.other);
+
+		if version == 2 {
+			return Tag::Compound(map);
+		}
+		let mut root = Map::new();
+		root.insert("Schematic".to_owned(), Tag::Compound(map));
+		Tag::Compound(root)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn varint_test() {
+		let values = [0, 1, 127, 128, 300, u32::MAX];
+		let bytes = write_varints(&values);
+		assert_eq!(bytes[..6], [0, 1, 127, -128, 1, -84]);
+		assert_eq!(read_varints(&bytes, values.len()), Ok(values.to_vec()));
+		assert_eq!(read_varints(&bytes, 7), Err(VarintError::Count { expected: 7, found: 6 }));
+		assert_eq!(read_varints(&[-128], 1), Err(VarintError::Incomplete));
+		// u32::MAX plus one more bit in the fifth byte.
+		assert_eq!(read_varints(&[0, -1, -1, -1, -1, 0x1f], 2), Err(VarintError::Overflow(1)));
+		assert_eq!(read_varints(&[-1, -1, -1, -1, -113, 0], 2), Err(VarintError::Overflow(0)));
+	}
+
+	fn sample() -> Schematic {
+		let mut schematic = Schematic::new(3, 2, 2, 3465);
+		let chest: BlockState = "minecraft:chest[facing=west]".parse().unwrap();
+		schematic.set_block(2, 1, 0, chest);
+		schematic.set_block(0, 0, 1, BlockState::new("minecraft:stone"));
+		schematic.offset = [-1, 0, 4];
+		schematic.metadata = Some(crate::compound!{ ("Name", "hut") }.try_into().unwrap());
+		schematic.block_entities.push(BlockEntity { pos: [2, 1, 0], id: "minecraft:chest".to_owned(), data: Map::new(), other: Map::new() });
+		schematic.entities.push(Entity { pos: [0.5, 1.0, 0.5], id: "minecraft:pig".to_owned(), data: Map::new(), other: Map::new() });
+		schematic.other.insert("WorldEdit".to_owned(), Tag::Int(7));
+		schematic
+	}
+
+	#[test]
+	fn round_trip_test() {
+		for version in [2, 3] {
+			let mut schematic = sample();
+			schematic.version = version;
+			schematic.block_entities[0].data.insert("Items".to_owned(), Tag::List(ListTag::from(Vec::<Map>::new())));
+			if version == 3 {
+				schematic.entities[0].other.insert("Note".to_owned(), Tag::String("kept".to_owned()));
+			}
+			let mut file = Vec::new();
+			schematic.write(&mut file).unwrap();
+			assert_eq!(Compression::detect(&file), Compression::Gzip);
+			let (_, data) = compression::decompress(&file).unwrap();
+			let (name, tag) = read_named_tag(&mut data.as_slice()).unwrap();
+			assert_eq!(name, if version == 2 { "Schematic" } else { "" });
+			if version == 3 {
+				assert!(matches!(crate::path::TagPath::root().key("Schematic").key("Blocks").key("Data").get(&tag), Ok(Tag::ByteArray(_))));
+			}
+
+			let read = Schematic::read(&mut file.as_slice()).unwrap();
+			assert_eq!(read.version, version);
+			assert_eq!((read.width(), read.height(), read.length(), read.offset), (3, 2, 2, [-1, 0, 4]));
+			assert_eq!(read.block_at(2, 1, 0).unwrap().to_string(), "minecraft:chest[facing=west]");
+			assert_eq!(read.block_at(0, 0, 1).unwrap().name, "minecraft:stone");
+			assert_eq!(read.block_at(0, 0, 0), Some(&BlockState::air()));
+			assert_eq!(read.block_at(3, 0, 0), None);
+			assert_eq!(read.blocks().filter(|(_, state)| **state != BlockState::air()).map(|(pos, _)| pos).collect::<Vec<_>>(), [[0, 0, 1], [2, 1, 0]]);
+			assert!(read.block_entities[0].data.contains_key("Items"));
+			assert_eq!(read.entities[0].pos, [0.5, 1.0, 0.5]);
+			assert_eq!(read.entities[0].other.contains_key("Note"), version == 3);
+			assert!(read.metadata.unwrap().contains_key("Name"));
+			assert!(read.other.contains_key("WorldEdit"));
+		}
+	}
+
+	#[test]
+	fn decode_test() {
+		let v2 = r#"{
+			Version: 2, DataVersion: 2586, Width: 2s, Height: 1s, Length: 1s, PaletteMax: 2,
+			Palette: {"minecraft:air": 0, "minecraft:stone": 1}, BlockData: [B; 1b, 0b],
+			BlockEntities: [{Pos: [I; 0, 0, 0], Id: "minecraft:sign", Text1: "hi"}],
+			BiomePalette: {"minecraft:plains": 0}, BiomeData: [B; 0b, 0b],
+		}"#;
+		let schematic = Schematic::decode_nbt(Tag::parse(v2).unwrap()).unwrap();
+		assert_eq!(schematic.block_at(0, 0, 0).unwrap().name, "minecraft:stone");
+		assert!(schematic.block_entities[0].data.contains_key("Text1"));
+		assert_eq!(schematic.biomes, Some(Biomes { palette: vec!["minecraft:plains".to_owned()], data: vec![0, 0] }));
+		assert!(schematic.other.is_empty());
+
+		let error = |source: &str| Schematic::decode_nbt(Tag::parse(source).unwrap()).unwrap_err().to_string();
+		assert_eq!(error(&v2.replace("1b, 0b]", "2b, 0b]")), "BlockData: Entry 0 is 2, but the palette only has 2 entries.");
+		assert_eq!(error(&v2.replace("\"minecraft:stone\": 1", "\"minecraft:stone\": 2")),
+			"Palette.minecraft:stone: Palette indices must count up from 0, but the index after 0 is 2.");
+		assert_eq!(error("{Schematic: {Version: 4}}"), "Schematic.Version: Version 4 isn't supported. Only versions 2 and 3 are.");
+	}
+}
@@ -4,10 +4,10 @@ Minecraft SNBT, and will not cover the Tag type extensions.
 
 | Tag Type        | Syntax                                                                      |
 |-----------------|-----------------------------------------------------------------------------|
-|[Tag::Byte]      | `<number>b` or `<number>B`
-|[Tag::Short]     | `<number>s` or `<number>S`
-|[Tag::Int]       | `<integer_number>`
-|[Tag::Long]      | `<number>l` or `<number>L`
+|[Tag::Byte]      | `<integer>b`, `<integer>B`, `true` or `false`
+|[Tag::Short]     | `<integer>s` or `<integer>S`
+|[Tag::Int]       | `<integer>` or `<integer>i`
+|[Tag::Long]      | `<integer>l` or `<integer>L`
 |[Tag::Float]     | `<number>f` or `<number>F`
 |[Tag::Double]    | `<decimal_number>`, `<number>d` or `<number>D`
 |[Tag::ByteArray] | `[B; 0b, 1b, 2b]`
@@ -19,21 +19,72 @@ Minecraft SNBT, and will not cover the Tag type extensions.
 
 Note: Identifiers can include the following characters: [a-zA-Z0-9+-._].
 For [Tag::List], the tag type for the list is determined by the type of the first tag.
+An empty list (`[]`) is always read as [ListTag::Empty].
+
+[Tag::Float] and [Tag::Double] values that are NaN or infinite are written and read as
+`NaNF`, `InfinityF`, `-InfinityF` (or with the `D` suffix for [Tag::Double]). The suffix is required.
+
+### Dialects
+
+Minecraft 1.21.5 extended the SNBT grammar. [Dialect::Modern] (the default) accepts the
+extended grammar, and [Dialect::Legacy] only accepts the grammar from before 1.21.5.
+The following is only available in [Dialect::Modern]:
+- Hexadecimal (`0x1F`) and binary (`0b101`) integers.
+- Underscores between digits (`1_000_000`).
+- Signedness in integer suffixes: `s` (signed) or `u` (unsigned) followed by the type, such as `200ub` or `-5sl`.
+  Unsigned values are stored with the same bits as the unsigned number, so `255ub` is stored as `-1`.
+  Decimal integers are signed by default, hexadecimal and binary integers may use either range.
+- The `i` suffix for [Tag::Int].
+- Array elements without a type suffix, such as `[B; 1, 2, 3]`.
+- The escape sequences `\s`, `\xXX`, `\UXXXXXXXX` and `\N{<unicode name>}`.
+- Lists with elements of different types. These are stored as a list of compounds, where each
+  element that isn't a compound is wrapped as `{"": <element>}`.
+- The `bool(<number>)` and `uuid(<string>)` operations.
+
+### Parsers
+
+[Tag::parse] and [Tag::parse_dialect] read the source in a single pass without building a list of
+tokens first. [Tag::parse_tokenized] tokenizes the whole source into [Token]s and then parses the
+tokens. It accepts the same grammar, but it is much slower and reports every error instead of only
+the first. Run `cargo bench --bench snbt` to compare them.
+
+### Errors
+
+When parsing fails, the [ParseError] holds a [Diagnostic] for each problem with the line and
+column where it was found. Displaying the [ParseError] shows each problem with an excerpt of the
+source, what was expected, and a hint when one is available.
+
+[Tag::parse_recovering] doesn't stop at the first error. It returns a best-effort [Tag] along
+with every [Diagnostic], which is useful for editors that need a tree while SNBT is being typed.
 "#]
 
 use crate::*;
 use crate::tag::*;
 use chumsky::prelude::*;
+use chumsky::text;
 use chumsky::primitive::{
 	Container,
 	OneOf,
 	NoneOf,
 };
+use chumsky::error::SimpleReason;
 use chumsky::Error;
+use chumsky::Stream;
 use std::collections::HashSet;
+use std::hash::Hash;
 use std::fmt::{Write, Display};
 use std::str::FromStr;
 
+/// The version of the SNBT grammar to parse.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+pub enum Dialect {
+	/// The grammar used by Minecraft 1.21.5 and later.
+	#[default]
+	Modern,
+	/// The grammar used before Minecraft 1.21.5.
+	Legacy,
+}
+
 #[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
 pub enum Token {
 	Comma,
@@ -43,8 +94,10 @@ pub enum Token {
 	CloseBracket,
 	OpenBrace,
 	CloseBrace,
+	OpenParen,
+	CloseParen,
 	Boolean(bool),
-	Integer(String, IntegerType),
+	Integer(IntegerLiteral),
 	Decimal(String, DecimalType),
 	Identifier(String),
 	StringLiteral(String),
@@ -57,7 +110,7 @@ pub enum ArrayType {
 	Long = 'L' as isize,
 }
 
-#[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
+#[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Copy, Hash, Debug)]
 pub enum IntegerType {
 	Byte,
 	Int,
@@ -65,7 +118,89 @@ pub enum IntegerType {
 	Long,
 }
 
+impl IntegerType {
+	/// The name of the type, as used in error messages.
+	pub(crate) const fn name(self) -> &'static str {
+		match self {
+			IntegerType::Byte => "Byte",
+			IntegerType::Short => "Short",
+			IntegerType::Int => "Int",
+			IntegerType::Long => "Long",
+		}
+	}
+
+	/// The width of this integer type in bits.
+	pub const fn bits(self) -> u32 {
+		match self {
+			IntegerType::Byte => 8,
+			IntegerType::Short => 16,
+			IntegerType::Int => 32,
+			IntegerType::Long => 64,
+		}
+	}
+}
+
+/// The signedness written in an integer suffix, such as the `u` in `255ub`.
+#[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Copy, Hash, Debug)]
+pub enum Signedness {
+	/// No signedness was given.
+	Unspecified,
+	Signed,
+	Unsigned,
+}
+
+/// An integer literal as it was written in the source.
 #[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
+pub struct IntegerLiteral {
+	pub negative: bool,
+	pub radix: u32,
+	/// The digits of the number without the sign, radix prefix, or underscores.
+	pub digits: String,
+	pub signedness: Signedness,
+	/// The type from the suffix, or `None` if there was no suffix.
+	pub suffix: Option<IntegerType>,
+}
+
+impl IntegerLiteral {
+	/// The type of the integer. Integers without a suffix are [IntegerType::Int].
+	pub fn kind(&self) -> IntegerType {
+		self.suffix.unwrap_or(IntegerType::Int)
+	}
+
+	/// Returns the value of this literal with the width of `kind`, or `None` if the value is
+	/// out of range. Unsigned values are returned with the same bits as the unsigned number
+	/// (so `255ub` is `-1`).
+	pub fn value(&self, kind: IntegerType) -> Option<i64> {
+		integer_value_of(self.negative, self.radix, &self.digits, self.signedness, kind)
+	}
+}
+
+/// Returns the value of an integer with the given parts, or `None` if it doesn't fit in `kind`.
+/// Underscores in `digits` are ignored. See [IntegerLiteral::value].
+pub(crate) fn integer_value_of(negative: bool, radix: u32, digits: &str, signedness: Signedness, kind: IntegerType) -> Option<i64> {
+	let bits = kind.bits();
+	let mut magnitude = 0u64;
+	for c in digits.chars().filter(|&c| c != '_') {
+		magnitude = magnitude.checked_mul(radix as u64)?.checked_add(c.to_digit(radix)? as u64)?;
+	}
+	let magnitude = magnitude as i128;
+	let value = if negative { -magnitude } else { magnitude };
+	let signed_min = -(1i128 << (bits - 1));
+	let signed_max = (1i128 << (bits - 1)) - 1;
+	let unsigned_max = (1i128 << bits) - 1;
+	let (min, max) = match signedness {
+		Signedness::Signed => (signed_min, signed_max),
+		Signedness::Unsigned => (0, unsigned_max),
+		Signedness::Unspecified if radix == 10 => (signed_min, signed_max),
+		Signedness::Unspecified => (signed_min, unsigned_max),
+	};
+	if value < min || value > max {
+		return None;
+	}
+	Some(if value > signed_max { value - (1i128 << bits) } else { value } as i64)
+}
+
+#[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Copy, Hash, Debug)]
 pub enum DecimalType {
 	Float,
 	Double,
@@ -79,31 +214,68 @@ pub enum DecimalType {
 // First you have the name that you want to apply to the function, then "=>", then a block
 // for the parser:
 //     name => { /* parser initialization */ }
+// The name before the first `;` is the name of the [Dialect] argument that is available
+// inside of every block.
 macro_rules! token_parse_functions {
-	($($name:ident => $block:block)+) => {
+	($dialect:ident; $($name:ident => $block:block)+) => {
 		impl Token {
 			$(
-				pub fn $name() -> impl Parser<char, Token, Error = Simple<char>>
+				pub fn $name($dialect: Dialect) -> impl Parser<char, Token, Error = SnbtError<char>>
 				$block
 			)+
 
-			pub fn parse<S: AsRef<str>>(source: S) -> Result<Vec<Token>, Vec<Simple<char>>> {
+			/// Tokenizes `source` using [Dialect::Modern].
+			pub fn parse<S: AsRef<str>>(source: S) -> Result<Vec<Token>, Vec<SnbtError<char>>> {
+				Self::parse_dialect(source, Dialect::default())
+			}
+
+			/// Tokenizes `source` using the grammar of `dialect`.
+			pub fn parse_dialect<S: AsRef<str>>(source: S, dialect: Dialect) -> Result<Vec<Token>, Vec<SnbtError<char>>> {
+				Self::parse_spanned(source, dialect)
+					.map(|tokens| tokens.into_iter().map(|(token, _)| token).collect())
+			}
+
+			/// Tokenizes `source` using the grammar of `dialect`, keeping the [Span] of each token.
+			pub fn parse_spanned<S: AsRef<str>>(source: S, dialect: Dialect) -> Result<Vec<(Token, Span)>, Vec<SnbtError<char>>> {
 				choice((
 					$(
-						Self::$name(),
+						Self::$name(dialect),
 					)+
 				))
+				.map_with_span(|token, span| (token, span))
 				.padded() // each token may be padded with whitespace
 				.repeated().at_least(1)
 				.then_ignore(end()) // Force read until end.
-				.collect::<Vec<Token>>()
+				.collect::<Vec<(Token, Span)>>()
 				.parse(source.as_ref())
 			}
+
+			/// Tokenizes as much of `source` as possible, skipping characters that can't start a
+			/// token. Returns the tokens along with every error that was found.
+			fn parse_spanned_recovering(source: &str, dialect: Dialect) -> (Vec<(Token, Span)>, Vec<SnbtError<char>>) {
+				let (tokens, errors) = choice((
+					$(
+						Self::$name(dialect),
+					)+
+				))
+				.map_with_span(|token, span| (token, span))
+				.recover_with(skip_then_retry_until([]))
+				.padded()
+				.repeated()
+				.then_ignore(any().repeated().validate(|rest: Vec<char>, span, emit| {
+					if !rest.is_empty() {
+						emit(SnbtError::custom(span, "Skipped invalid characters."));
+					}
+				}))
+				.parse_recovery(source);
+				(tokens.unwrap_or_default(), errors)
+			}
 		}
 	};
 }
 
 token_parse_functions!{
+	dialect;
 	comma => { just(',').to(Token::Comma).labelled("Comma") }
 	colon => { just(':').to(Token::Colon).labelled("Colon") }
 	array_start => {
@@ -123,6 +295,16 @@ token_parse_functions!{
 	close_bracket => { just(']').to(Token::CloseBracket).labelled("Close Bracket") }
 	open_brace => { just('{').to(Token::OpenBrace).labelled("Open Brace") }
 	close_brace => { just('}').to(Token::CloseBrace).labelled("Close Brace") }
+	open_paren => {
+		just('(').to(Token::OpenParen)
+			.try_map(move |token, span| modern_only(dialect, token, span, "Operations require the modern dialect."))
+			.labelled("Open Parenthesis")
+	}
+	close_paren => {
+		just(')').to(Token::CloseParen)
+			.try_map(move |token, span| modern_only(dialect, token, span, "Operations require the modern dialect."))
+			.labelled("Close Parenthesis")
+	}
 	boolean => {
 		choice((
 			text::keyword("true").to(Token::Boolean(true)),
@@ -130,62 +312,91 @@ token_parse_functions!{
 		))
 		.labelled("Boolean")
 	}
-	// If I want, I can add binary and hex literals.
 	integer => {
-		just::<char, _, Simple<char>>('-')
+		let number = choice((
+			just('0').ignore_then(one_of("xX")).ignore_then(digit_run(16, dialect)).map(|digits| (16u32, digits)),
+			just('0').ignore_then(one_of("bB")).ignore_then(digit_run(2, dialect)).map(|digits| (2u32, digits)),
+			digit_run(10, dialect).map(|digits| (10u32, digits)),
+		))
+		.try_map(move |(radix, digits), span| {
+			if dialect == Dialect::Legacy && radix != 10 {
+				Err(SnbtError::custom(span, "Hexadecimal and binary integers require the modern dialect."))
+			} else if dialect == Dialect::Legacy && digits.len() > 1 && digits.starts_with('0') {
+				Err(SnbtError::custom(span, "Leading zeros are not allowed."))
+			} else {
+				Ok((radix, digits))
+			}
+		});
+		one_of::<char, _, SnbtError<char>>("+-")
 			.or_not()
-			.chain::<char, _, _>(text::int(10))
-			.collect::<String>()
+			.then(number)
 			.then(
-				choice((
-					keyword("b", true).to(IntegerType::Byte),
-					keyword("s", true).to(IntegerType::Short),
-					keyword("l", true).to(IntegerType::Long),
-				))
-				.or_not()
-				.map(|opt| opt.unwrap_or(IntegerType::Int))
+				identifier()
+					.try_map(move |text, span| {
+						integer_suffix(&text, dialect)
+							.ok_or_else(|| SnbtError::custom(span, format!("Invalid integer suffix: {text}")))
+					})
+					.or_not()
 			)
-			.then_ignore(choice((
-				filter(|c: &char| {
-					!c.is_alphanumeric() && !['_', '+','-','.'].contains(c)
-				}),
-				end().to('\0')
-			)).rewind())
-			.map(|(int_text, int_type)| Token::Integer(int_text, int_type))
+			.then_ignore(not_followed_by_identifier())
+			.map(|((sign, (radix, digits)), suffix)| {
+				let (signedness, suffix) = suffix.unwrap_or((Signedness::Unspecified, None));
+				Token::Integer(IntegerLiteral {
+					negative: sign == Some('-'),
+					radix,
+					digits,
+					signedness,
+					suffix,
+				})
+			})
 			.labelled("Integer")
 	}
 	decimal => {
-		just::<char, _, Simple<char>>('-').or_not()
+		let suffix = || choice((
+			keyword("d", true).to(DecimalType::Double),
+			keyword("f", true).to(DecimalType::Float),
+		));
+		let exponent = one_of::<char, _, SnbtError<char>>("eE")
+			.ignore_then(one_of("+-").or_not())
+			.then(digit_run(10, dialect))
+			.map(|(sign, digits)| format!("e{}{digits}", sign.map(String::from).unwrap_or_default()));
+		// NaN and infinity are not part of Minecraft's grammar, but they can be stored in
+		// a Float or Double tag, so they need a representation that can be read back.
+		// They require a suffix so that they can't be mistaken for an identifier.
+		let special = just::<char, _, SnbtError<char>>('-').or_not()
 			.chain::<char,_,_>(
-				choice((
-					text::int(10)
-						.chain::<char,_,_>(just('.'))
-						.chain::<char,_,_>(text::digits(10))
-						.collect::<String>(),
-					text::int(10)
-						.then_ignore(
-							choice((
-								keyword("d", true),
-								keyword("f", true),
-							)).rewind()
-						),
-				))
+				choice((just("NaN"), just("Infinity")))
+					.map(|word: &str| word.chars().collect::<Vec<char>>())
 			)
 			.collect::<String>()
-			.then(
-				choice((
-					keyword("d", true).to(DecimalType::Double),
-					keyword("f", true).to(DecimalType::Float),
+			.then(suffix());
+		// `1.5`, `1.`, `.5` or `1` (`1` requires an exponent or a suffix).
+		let mantissa = choice((
+			digit_run(10, dialect)
+				.then_ignore(just('.'))
+				.then(digit_run(10, dialect).or_not())
+				.map(|(whole, fraction)| (format!("{whole}.{}", fraction.unwrap_or_default()), true)),
+			just('.')
+				.ignore_then(digit_run(10, dialect))
+				.map(|fraction| (format!("0.{fraction}"), true)),
+			digit_run(10, dialect).map(|whole| (whole, false)),
+		));
+		let finite = one_of::<char, _, SnbtError<char>>("+-").or_not()
+			.then(mantissa)
+			.then(exponent.or_not())
+			.then(suffix().or_not())
+			.try_map(|(((sign, (mantissa, has_point)), exponent), suffix), span| {
+				if !has_point && exponent.is_none() && suffix.is_none() {
+					return Err(SnbtError::custom(span, "Expected a decimal number."));
+				}
+				let sign = if sign == Some('-') { "-" } else { "" };
+				Ok((
+					format!("{sign}{mantissa}{}", exponent.unwrap_or_default()),
+					suffix.unwrap_or(DecimalType::Double),
 				))
-				.or_not()
-				.map(|opt| opt.unwrap_or(DecimalType::Double))
-			)
-			.then_ignore(choice((
-				filter(|c: &char| {
-					!c.is_alphanumeric() && !['_', '+','-','.'].contains(c)
-				}),
-				end().to('\0')
-			)).rewind())
+			});
+		special.or(finite)
+			.then_ignore(not_followed_by_identifier())
 			.map(|(dec_str, dec_type)| Token::Decimal(dec_str, dec_type))
 			.labelled("Decimal")
 	}
@@ -200,7 +411,30 @@ token_parse_functions!{
 		.labelled("Identifier")
 	}
 	string_literal => {
-		let escape = just::<_,_,Simple<char>>('\\').ignore_then(
+		let hex = |count: usize| filter(char::is_ascii_hexdigit)
+			.repeated().exactly(count)
+			.collect::<String>()
+			.try_map(|digits, span| {
+				u32::from_str_radix(&digits, 16).ok()
+					.and_then(char::from_u32)
+					.ok_or_else(|| SnbtError::custom(span, "Invalid unicode escape."))
+			});
+		let modern_escape = choice((
+			just('s').to(' '),
+			just('x').ignore_then(hex(2)),
+			just('U').ignore_then(hex(8)),
+			just('N').ignore_then(
+				none_of("}").repeated().at_least(1)
+					.collect::<String>()
+					.delimited_by(just('{'), just('}'))
+					.try_map(|name, span| {
+						unicode_names2::character(&name)
+							.ok_or_else(|| SnbtError::custom(span, format!("Unknown unicode character name: {name}")))
+					})
+			),
+		))
+		.try_map(move |c, span| modern_only(dialect, c, span, "This escape sequence requires the modern dialect."));
+		let escape = just::<_,_,SnbtError<char>>('\\').ignore_then(
 			just('\\')
 				.or(just('/'))
 				.or(just('"'))
@@ -210,9 +444,11 @@ token_parse_functions!{
 				.or(just('n').to('\n'))
 				.or(just('r').to('\r'))
 				.or(just('t').to('\t'))
+				.or(just('u').ignore_then(hex(4)))
+				.or(modern_escape)
 		);
-		Token::identifier().or(
-			choice::<_,Simple<char>>((
+		Token::identifier(dialect).or(
+			choice::<_,SnbtError<char>>((
 				just('"')
 					.ignore_then(
 						none_of("\\\"").or(escape.clone()).repeated()
@@ -221,7 +457,7 @@ token_parse_functions!{
 					.collect::<String>(),
 				just('\'')
 					.ignore_then(
-						none_of("\\'").or(escape.clone()).repeated()
+						none_of("\\'").or(escape).repeated()
 					)
 					.then_ignore(just('\''))
 					.collect::<String>(),
@@ -230,141 +466,496 @@ token_parse_functions!{
 	}
 }
 
+/// Fails with `message` if `dialect` is not [Dialect::Modern].
+fn modern_only<T>(dialect: Dialect, value: T, span: Span, message: &str) -> Result<T, SnbtError<char>> {
+	if dialect == Dialect::Modern {
+		Ok(value)
+	} else {
+		Err(SnbtError::custom(span, message))
+	}
+}
+
+/// A run of digits in the given radix. In [Dialect::Modern], underscores are allowed between
+/// digits. The underscores are removed from the result.
+fn digit_run(radix: u32, dialect: Dialect) -> impl Parser<char, String, Error = SnbtError<char>> + Clone {
+	filter(move |c: &char| c.is_digit(radix))
+		.chain::<char, _, _>(filter(move |c: &char| c.is_digit(radix) || *c == '_').repeated())
+		.collect::<String>()
+		.try_map(move |digits: String, span| {
+			if !digits.contains('_') {
+				Ok(digits)
+			} else if dialect == Dialect::Legacy {
+				Err(SnbtError::custom(span, "Underscores in numbers require the modern dialect."))
+			} else if digits.ends_with('_') {
+				Err(SnbtError::custom(span, "Numbers can't end with an underscore."))
+			} else {
+				Ok(digits.replace('_', ""))
+			}
+		})
+}
+
+/// Succeeds without consuming input if the next character can't be part of an identifier.
+fn not_followed_by_identifier() -> impl Parser<char, char, Error = SnbtError<char>> + Clone {
+	choice((
+		filter(|c: &char| {
+			!c.is_alphanumeric() && !['_', '+','-','.'].contains(c)
+		}),
+		end().to('\0')
+	)).rewind()
+}
+
+/// Parses an integer suffix such as `b`, `L` or `ub` (case insensitive).
+pub(crate) fn integer_suffix(text: &str, dialect: Dialect) -> Option<(Signedness, Option<IntegerType>)> {
+	let kind = |c: u8| match c.to_ascii_lowercase() {
+		b'b' => Some(IntegerType::Byte),
+		b's' => Some(IntegerType::Short),
+		b'i' if dialect == Dialect::Modern => Some(IntegerType::Int),
+		b'l' => Some(IntegerType::Long),
+		_ => None,
+	};
+	match *text.as_bytes() {
+		[c] => Some((Signedness::Unspecified, Some(kind(c)?))),
+		[sign, c] if dialect == Dialect::Modern => {
+			let signedness = match sign.to_ascii_lowercase() {
+				b's' => Signedness::Signed,
+				b'u' => Signedness::Unsigned,
+				_ => return None,
+			};
+			Some((signedness, Some(kind(c)?)))
+		}
+		_ => None,
+	}
+}
+
+/// Parses a UUID in the form `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` into the four integers
+/// that Minecraft uses to store UUIDs (most significant first).
+pub(crate) fn parse_uuid(text: &str) -> Option<[i32; 4]> {
+	let groups: Vec<&str> = text.split('-').collect();
+	let lengths = [8, 4, 4, 4, 12];
+	if groups.len() != lengths.len()
+	|| groups.iter().zip(lengths).any(|(group, len)| group.len() != len || !group.bytes().all(|b| b.is_ascii_hexdigit())) {
+		return None;
+	}
+	let value = u128::from_str_radix(&groups.concat(), 16).ok()?;
+	Some([
+		(value >> 96) as u32 as i32,
+		(value >> 64) as u32 as i32,
+		(value >> 32) as u32 as i32,
+		value as u32 as i32,
+	])
+}
+
+/// Returns the message and hint for an integer that doesn't fit in `kind`. The hint gives the
+/// valid range, and suggests an unsigned suffix if the value would fit as an unsigned integer.
+pub(crate) fn out_of_range_text(negative: bool, radix: u32, digits: &str, signedness: Signedness, kind: IntegerType, modern: bool) -> (String, String) {
+	let bits = kind.bits();
+	let name = kind.name();
+	let (min, max) = match signedness {
+		Signedness::Unsigned => (0, (1i128 << bits) - 1),
+		_ => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+	};
+	let suffix = match kind {
+		IntegerType::Byte => 'b',
+		IntegerType::Short => 's',
+		IntegerType::Int => 'i',
+		IntegerType::Long => 'l',
+	};
+	let mut hint = format!("{name} values must be between {min} and {max}.");
+	if modern
+	&& signedness == Signedness::Unspecified
+	&& integer_value_of(negative, radix, digits, Signedness::Unsigned, kind).is_some() {
+		hint.push_str(&format!(" Use the `u{suffix}` suffix for an unsigned value."));
+	}
+	(format!("Integer out of range for {name}."), hint)
+}
+
+/// Builds the error for an integer that doesn't fit in `kind`, with a hint giving the valid range.
+fn out_of_range(literal: &IntegerLiteral, kind: IntegerType, span: Span, modern: bool) -> SnbtError<Token> {
+	let (message, hint) = out_of_range_text(literal.negative, literal.radix, &literal.digits, literal.signedness, kind, modern);
+	SnbtError::custom(span, message).with_hint(hint)
+}
+
+pub(crate) const MIXED_LIST_HINT: &str = "All elements of a list must have the same type. Lists with mixed types require the modern dialect.";
+
+/// Adds a hint to errors inside of a list where an element of a different type than the
+/// first element was found, since only the modern dialect allows mixed lists.
+fn mixed_list_hint(error: SnbtError<Token>) -> SnbtError<Token> {
+	let found_value = error.simple().found().is_some_and(starts_value);
+	if found_value && error.hint().is_none() {
+		error.with_hint(MIXED_LIST_HINT)
+	} else {
+		error
+	}
+}
+
+/// A value produced by the parser, with the spans of the placeholders that were inserted
+/// into it while recovering from errors.
+type WithPlaceholders<T> = (T, Vec<Span>);
+
+fn no_placeholders<T>(value: T) -> WithPlaceholders<T> {
+	(value, Vec::new())
+}
+
+/// Applies `f` to a value produced by the parser, keeping its placeholders.
+fn keep_placeholders<T, U>(f: impl Fn(T) -> U + Clone) -> impl Fn(WithPlaceholders<T>) -> WithPlaceholders<U> + Clone {
+	move |(value, placeholders)| (f(value), placeholders)
+}
+
+/// Collects the values of `elements`, along with all of their placeholders.
+fn split_placeholders<T>(elements: impl IntoIterator<Item = WithPlaceholders<T>>) -> WithPlaceholders<Vec<T>> {
+	let mut placeholders = Vec::new();
+	let values = elements.into_iter()
+		.map(|(value, spans)| {
+			placeholders.extend(spans);
+			value
+		})
+		.collect();
+	(values, placeholders)
+}
+
+/// Returns the value of an integer literal, or emits an error and returns `0` as a
+/// placeholder if the value is out of range for `kind`.
+fn integer_value(literal: &IntegerLiteral, kind: IntegerType, span: Span, modern: bool, emit: &mut dyn FnMut(SnbtError<Token>)) -> WithPlaceholders<i64> {
+	match literal.value(kind) {
+		Some(value) => no_placeholders(value),
+		None => {
+			emit(out_of_range(literal, kind, span.clone(), modern));
+			(0, vec![span])
+		}
+	}
+}
+
+/// The span of an empty parse runs from the start of the next token back to the end of the
+/// previous one, so this returns the empty span at the start of the next token instead.
+fn insertion_point(span: Span) -> Span {
+	span.start..span.start
+}
+
+/// Returns `true` for tokens that can start a value.
+fn starts_value(token: &Token) -> bool {
+	matches!(token,
+		Token::Integer(_) | Token::Decimal(_, _) | Token::Boolean(_) | Token::StringLiteral(_)
+		| Token::Identifier(_) | Token::OpenBrace | Token::OpenBracket | Token::ArrayStart(_)
+	)
+}
+
+/// Returns `true` for tokens that open or close a compound, list or array.
+fn is_delimiter(token: &Token) -> bool {
+	matches!(token, Token::OpenBrace | Token::CloseBrace | Token::OpenBracket | Token::ArrayStart(_) | Token::CloseBracket)
+}
+
+/// Returns `true` for tokens that end an element of a compound, list or array.
+fn ends_element(token: &Token) -> bool {
+	matches!(token, Token::Comma | Token::CloseBrace | Token::CloseBracket)
+}
+
+/// Matches a compound, list or array, including anything nested inside, without checking
+/// what is inside. Used to skip over malformed elements.
+fn balanced_group() -> impl Parser<Token, (), Error = SnbtError<Token>> + Clone {
+	recursive(|group| {
+		let inner = group
+			.or(filter(|token| !is_delimiter(token)).ignored())
+			.repeated();
+		choice((
+			inner.clone().delimited_by(just(Token::OpenBrace), just(Token::CloseBrace)),
+			inner.delimited_by(filter(|token| matches!(token, Token::OpenBracket | Token::ArrayStart(_))), just(Token::CloseBracket)),
+		)).ignored()
+	})
+}
+
+/// Parses comma separated `item`s followed by `close`, recovering from malformed input:
+/// - Elements that don't start with a token accepted by `starts_item` are skipped up to the
+///   next `,` or closing delimiter.
+/// - Missing commas between elements are inserted.
+/// - A missing `close` is inserted.
+///
+/// Each recovery emits an error, so the result is only used by [Tag::parse_recovering].
+/// `item` is expected to recover from its own errors once it has started. Skipping only
+/// elements that `item` can't start keeps the alternatives apart, since chumsky prefers the
+/// alternative with the fewest errors rather than the first one that succeeds.
+fn recovering_sequence<T, P, S>(item: P, starts_item: S, close: Token) -> impl Parser<Token, Vec<T>, Error = SnbtError<Token>> + Clone
+where
+	T: Clone,
+	P: Parser<Token, T, Error = SnbtError<Token>> + Clone,
+	S: Fn(&Token) -> bool + Clone,
+{
+	let skipped = filter(move |token| !starts_item(token) && !ends_element(token))
+		.rewind()
+		.ignore_then(
+			balanced_group()
+				.or(filter(|token| !ends_element(token)).ignored())
+				.repeated().at_least(1)
+		)
+		.validate(|_, span, emit| emit(SnbtError::custom(span, "Skipped malformed element.")));
+	let missing_element = filter(|token| *token == Token::Comma)
+		.rewind()
+		.validate(|_, span, emit| emit(SnbtError::custom(span, "Missing element before `,`.")));
+	let element = item.map(Some)
+		.or(skipped.to(None))
+		.or(missing_element.to(None));
+	let missing_comma = filter(|token| !ends_element(token))
+		.rewind()
+		.validate(|_, span, emit| emit(SnbtError::custom(span, "Missing `,` before this element.")));
+	let missing_close = {
+		let close = close.clone();
+		empty().validate(move |_, span, emit| {
+			emit(SnbtError::custom(insertion_point(span), format!("Missing {}.", close.describe())))
+		})
+	};
+	element
+		.separated_by(just(Token::Comma).ignored().or(missing_comma.ignored()))
+		.allow_trailing()
+		.then_ignore(just(close).ignored().or(missing_close))
+		.map(|elements| elements.into_iter().flatten().collect())
+}
+
 /// Returns a parser that takes [Token] as input and returns a [Tag].
-fn parser() -> impl Parser<Token, Tag, Error = Simple<Token>> {
+/// When `recover` is `true`, the parser recovers from errors as described in [Tag::parse_recovering].
+fn parser(dialect: Dialect, recover: bool) -> impl Parser<Token, WithPlaceholders<Tag>, Error = SnbtError<Token>> {
+	let modern = dialect == Dialect::Modern;
 	// Macros rule!
-	macro_rules! num_parsers {
-		($(let $name:ident = Token::$token_type:ident($subtype:path) => $type:ty;)+) => {
+	macro_rules! int_parsers {
+		($(let $name:ident = IntegerType::$kind:ident => $type:ty;)+) => {
+			$(
+				let $name = filter::<Token,_,SnbtError<Token>>(|token| matches!(token, Token::Integer(literal) if literal.kind() == IntegerType::$kind))
+					.validate(move |token, span, emit| {
+						match token {
+							Token::Integer(literal) => keep_placeholders(|value| value as $type)(integer_value(&literal, IntegerType::$kind, span, modern, emit)),
+							_ => panic!("Impossible state."),
+						}
+					});
+			)+
+		};
+	}
+	macro_rules! decimal_parsers {
+		($(let $name:ident = DecimalType::$kind:ident => $type:ty;)+) => {
 			$(
-				let $name = filter::<Token,_,Simple<Token>>(|token| matches!(token, Token::$token_type(_, $subtype)))
+				let $name = filter::<Token,_,SnbtError<Token>>(|token| matches!(token, Token::Decimal(_, DecimalType::$kind)))
 					.try_map(|token, span| {
 						match token {
-							Token::$token_type(digits, $subtype) => {
-								digits.parse::<$type>().map_err(|_| Simple::custom(span, concat!("Failed to parse.")))
+							Token::Decimal(digits, _) => {
+								digits.parse::<$type>().map_err(|_| SnbtError::custom(span, "Failed to parse."))
 							},
-							_ => Err(Simple::custom(span, "Invalid token.")),
+							_ => Err(SnbtError::custom(span, "Invalid token.")),
 						}
 					});
 			)+
 		};
 	}
-	num_parsers!{
-		let byte = Token::Integer(IntegerType::Byte) => i8;
-		let short = Token::Integer(IntegerType::Short) => i16;
-		let int = Token::Integer(IntegerType::Int) => i32;
-		let long = Token::Integer(IntegerType::Long) => i64;
-		let float = Token::Decimal(DecimalType::Float) => f32;
-		let double = Token::Decimal(DecimalType::Double) => f64;
+	int_parsers!{
+		let byte = IntegerType::Byte => i8;
+		let short = IntegerType::Short => i16;
+		let int = IntegerType::Int => i32;
+		let long = IntegerType::Long => i64;
 	};
-	let byte = byte.or(
-		choice((
-			filter(|token| matches!(token, Token::Boolean(true))).to(1i8),
-			filter(|token| matches!(token, Token::Boolean(false))).to(0i8),
-		))
-	);
+	decimal_parsers!{
+		let float = DecimalType::Float => f32;
+		let double = DecimalType::Double => f64;
+	};
+	let boolean = filter::<Token,_,SnbtError<Token>>(|token| matches!(token, Token::Boolean(_)))
+		.map(|token| match token {
+			Token::Boolean(true) => 1i8,
+			_ => 0i8,
+		});
+	let byte = byte.or(boolean.map(no_placeholders));
+	// In the modern dialect, array elements may be written without a suffix.
 	macro_rules! array_parsers {
-		($(let $name:ident = [$type:ident; $item:expr];)+) => {
+		($(let $name:ident = [$type:ident; IntegerType::$kind:ident => $item:ty];)+) => {
 			$(
-				let $name = ($item)
-					.separated_by(just(Token::Comma))
-					.delimited_by(just(Token::ArrayStart(ArrayType::$type)), just(Token::CloseBracket));
+				let starts_element = move |token: &Token| match token {
+					Token::Integer(literal) => literal.kind() == IntegerType::$kind || (modern && literal.suffix.is_none()),
+					Token::Boolean(_) => true,
+					_ => false,
+				};
+				let $name = filter::<Token,_,SnbtError<Token>>(move |token| matches!(token, Token::Integer(_)) && starts_element(token))
+					.validate(move |token, span, emit| {
+						match token {
+							Token::Integer(literal) => keep_placeholders(|value| value as $item)(integer_value(&literal, IntegerType::$kind, span, modern, emit)),
+							_ => panic!("Impossible state."),
+						}
+					})
+					.or(boolean.map(|value| no_placeholders(value as $item)));
+				let $name = match recover {
+					false => $name
+						.separated_by(just(Token::Comma))
+						.delimited_by(just(Token::ArrayStart(ArrayType::$type)), just(Token::CloseBracket))
+						.boxed(),
+					true => just(Token::ArrayStart(ArrayType::$type))
+						.ignore_then(recovering_sequence($name, starts_element, Token::CloseBracket))
+						.boxed(),
+				}.map(split_placeholders).labelled(concat!(stringify!($type), " Array"));
 			)+
 		};
 	}
 	array_parsers!{
-		let bytearray = [Byte; byte.clone()];
-		let intarray = [Int; int.clone()];
-		let longarray = [Long; long.clone()];
+		let bytearray = [Byte; IntegerType::Byte => i8];
+		let intarray = [Int; IntegerType::Int => i32];
+		let longarray = [Long; IntegerType::Long => i64];
 	}
-	let byte = byte.or(
-		filter::<Token,_,Simple<Token>>(|token| matches!(token, Token::Boolean(_)))
-			.map(|token| match token {
-				Token::Boolean(true) => 1i8,
-				_ => 0i8,
-			})
-	);
 	// converts Token::StringLiteral and Token::Identifier into String.
 	// This is because these tokens may mean different things in different contexts.
-	let string = filter::<Token,_,Simple<Token>>(|token| matches!(token, Token::StringLiteral(_) | Token::Identifier(_)))
+	let string = filter::<Token,_,SnbtError<Token>>(|token| matches!(token, Token::StringLiteral(_) | Token::Identifier(_)))
 		.map(|token| match token {
 			Token::StringLiteral(data) => data,
 			Token::Identifier(data) => data,
 			_ => panic!("Impossible state.")
 		});
+	// Keys may also be written as `true` or `false`.
+	let key = string.or(
+		filter::<Token,_,SnbtError<Token>>(|token| matches!(token, Token::Boolean(_)))
+			.map(|token| match token {
+				Token::Boolean(true) => String::from("true"),
+				_ => String::from("false"),
+			})
+	);
+
+	// `bool(<number>)` and `uuid(<string>)`. The tokenizer only produces parentheses
+	// in the modern dialect, so these are unreachable in the legacy dialect.
+	let operation = |name: &'static str| just(Token::Identifier(name.to_owned()))
+		.ignore_then(just(Token::OpenParen));
+	let bool_operation = operation("bool")
+		.ignore_then(choice((
+			byte.map(keep_placeholders(|value| value != 0)),
+			short.map(keep_placeholders(|value| value != 0)),
+			int.map(keep_placeholders(|value| value != 0)),
+			long.map(keep_placeholders(|value| value != 0)),
+			float.map(|value| no_placeholders(value != 0.0)),
+			double.map(|value| no_placeholders(value != 0.0)),
+		)))
+		.then_ignore(just(Token::CloseParen))
+		.map(keep_placeholders(|value| value as i8));
+	let uuid_operation = operation("uuid")
+		.ignore_then(string)
+		.then_ignore(just(Token::CloseParen))
+		.try_map(|text, span| {
+			parse_uuid(&text)
+				.map(Vec::from)
+				.ok_or_else(|| SnbtError::custom(span, format!("Invalid UUID: {text}")))
+		});
 
 	let mut list = Recursive::declare();
 	let mut compound = Recursive::declare();
 
 	let tag_match = choice((
-		compound.clone().map(Tag::Compound),
-		list.clone().map(Tag::List),
-		byte.clone().map(Tag::Byte),
-		short.clone().map(Tag::Short),
-		int.clone().map(Tag::Int),
-		long.clone().map(Tag::Long),
-		float.clone().map(Tag::Float),
-		double.clone().map(Tag::Double),
-		bytearray.clone().map(Tag::ByteArray),
-		intarray.clone().map(Tag::IntArray),
-		longarray.clone().map(Tag::LongArray),
-		string.clone().map(Tag::String)
+		compound.clone().map(keep_placeholders(Tag::Compound)),
+		list.clone().map(keep_placeholders(Tag::List)),
+		bool_operation.map(keep_placeholders(Tag::Byte)),
+		uuid_operation.clone().map(Tag::IntArray).map(no_placeholders),
+		byte.map(keep_placeholders(Tag::Byte)),
+		short.map(keep_placeholders(Tag::Short)),
+		int.map(keep_placeholders(Tag::Int)),
+		long.map(keep_placeholders(Tag::Long)),
+		float.map(Tag::Float).map(no_placeholders),
+		double.map(Tag::Double).map(no_placeholders),
+		bytearray.clone().map(keep_placeholders(Tag::ByteArray)),
+		intarray.clone().map(keep_placeholders(Tag::IntArray)),
+		longarray.clone().map(keep_placeholders(Tag::LongArray)),
+		string.map(Tag::String).map(no_placeholders)
 	));
 
 	macro_rules! list_maker {
 		($([$pattern:expr]),+) => {
-			choice::<_,Simple<Token>>((
+			choice::<_,SnbtError<Token>>((
 				$(
 					($pattern)
 						.separated_by(just(Token::Comma))
 						.allow_trailing()
 						.delimited_by(just(Token::OpenBracket), just(Token::CloseBracket))
-						.map(ListTag::from),
+						.map(split_placeholders)
+						.map(keep_placeholders(ListTag::from)),
 				)+
 			))
 		};
 	}
 
-	list.define(
-		list_maker!{
-			[byte.clone()],
-			[short.clone()],
-			[int.clone()],
-			[long.clone()],
-			[float.clone()],
-			[double.clone()],
-			[bytearray.clone()],
-			[string.clone()],
-			[list.clone()],
-			[compound.clone()],
-			[intarray.clone()],
-			[longarray.clone()]
-		}
-	);
+	// `[]` has no element to infer the type from, so it is always read as [ListTag::Empty].
+	let empty_list = just(Token::OpenBracket)
+		.then(just(Token::CloseBracket))
+		.to(no_placeholders(ListTag::Empty));
+
+	let legacy_list = empty_list.or(list_maker!{
+		[byte],
+		[short],
+		[int],
+		[long],
+		[float.map(no_placeholders)],
+		[double.map(no_placeholders)],
+		[bytearray.clone()],
+		[string.map(no_placeholders)],
+		[list.clone()],
+		[compound.clone()],
+		[intarray.clone()],
+		[longarray.clone()]
+	}).map_err(mixed_list_hint);
+
+	let modern_list = tag_match.clone()
+		.separated_by(just(Token::Comma))
+		.allow_trailing()
+		.delimited_by(just(Token::OpenBracket), just(Token::CloseBracket))
+		.map(split_placeholders)
+		.map(keep_placeholders(ListTag::from_mixed));
+
+	let starts_key = |token: &Token| matches!(token, Token::StringLiteral(_) | Token::Identifier(_) | Token::Boolean(_));
+	// Recovering lists are read like modern lists. In the legacy dialect, elements that don't
+	// have the same type as the first element are dropped.
+	let recovering_list = just(Token::OpenBracket)
+		.ignore_then(recovering_sequence(tag_match.clone().map_with_span(|tag, span| (tag, span)), starts_value, Token::CloseBracket))
+		.validate(move |elements, _, emit| {
+			if dialect == Dialect::Legacy {
+				let id = elements.first().map(|((tag, _), _): &(WithPlaceholders<Tag>, Span)| tag.id());
+				let (tags, placeholders) = split_placeholders(elements.into_iter().filter_map(|(element, span)| {
+					if Some(element.0.id()) == id {
+						Some(element)
+					} else {
+						emit(SnbtError::custom(span, "Element has a different type than the first element of the list.")
+							.with_hint(MIXED_LIST_HINT)
+							.with_label("List"));
+						None
+					}
+				}));
+				(ListTag::from_tags(tags).unwrap_or(ListTag::Empty), placeholders)
+			} else {
+				keep_placeholders(ListTag::from_mixed)(split_placeholders(elements.into_iter().map(|(element, _)| element)))
+			}
+		});
+
+	list.define(match (dialect, recover) {
+		(_, true) => recovering_list.boxed(),
+		(Dialect::Modern, false) => modern_list.boxed(),
+		(Dialect::Legacy, false) => legacy_list.boxed(),
+	}.labelled("List"));
 
-	compound.define(
-		string.clone()
+	// A missing `:` or value in a recovering compound is inserted, with a placeholder value.
+	let recovering_entry = key
+		.then_ignore(just(Token::Colon).ignored().or(
+			empty().validate(|_, span, emit| emit(SnbtError::custom(insertion_point(span), "Missing `:`.")))
+		))
+		.then(tag_match.clone().or_not().validate(|tag, span, emit| {
+			tag.unwrap_or_else(|| {
+				let span = insertion_point(span);
+				emit(SnbtError::custom(span.clone(), "Missing value."));
+				(Tag::String(String::new()), vec![span])
+			})
+		}));
+
+	compound.define(match recover {
+		false => key
 			.then_ignore(just(Token::Colon))
 			.then(tag_match.clone())
 			.separated_by(just(Token::Comma))
 			.allow_trailing()
 			.delimited_by(just(Token::OpenBrace), just(Token::CloseBrace))
-			.map(crate::Map::from_iter)
+			.boxed(),
+		true => just(Token::OpenBrace)
+			.ignore_then(recovering_sequence(recovering_entry, starts_key, Token::CloseBrace))
+			.boxed(),
+	}
+		.map(|entries| split_placeholders(entries.into_iter().map(|(key, (tag, placeholders))| ((key, tag), placeholders))))
+		.map(keep_placeholders(crate::Map::from_iter))
+		.labelled("Compound")
 	);
 
-	choice((
-		compound.clone().map(Tag::Compound),
-		list.clone().map(Tag::List),
-		byte.clone().map(Tag::Byte),
-		short.clone().map(Tag::Short),
-		int.clone().map(Tag::Int),
-		long.clone().map(Tag::Long),
-		float.clone().map(Tag::Float),
-		double.clone().map(Tag::Double),
-		bytearray.clone().map(Tag::ByteArray),
-		intarray.clone().map(Tag::IntArray),
-		longarray.clone().map(Tag::LongArray),
-		string.clone().map(Tag::String)
-	))
+	tag_match
 }
 
 impl Tag {
@@ -386,20 +977,20 @@ impl Tag {
 	///     double2 : 4.5d,
 	///     double3 : 5.1,
 	///     "bytearray" : [B;
-	/// 		true,
-	/// 		false,
-	/// 		5b
-	/// 	],
+	///         true,
+	///         false,
+	///         5b
+	///     ],
 	///     intarray : [I;
-	/// 		3,
-	/// 		5,
-	/// 		1
-	/// 	],
+	///         3,
+	///         5,
+	///         1
+	///     ],
 	///     longarray : [L;
-	/// 		3l,
-	/// 		4l,
-	/// 		5l
-	/// 	],
+	///         3l,
+	///         4l,
+	///         5l
+	///     ],
 	///     lists : [
 	///         ["one", "two", 'three', 'four\\nnewline']
 	///     ],
@@ -415,18 +1006,86 @@ impl Tag {
 	/// }
 	/// ```
 	pub fn parse<S: AsRef<str>>(source: S) -> Result<Tag, ParseError> {
-		match Token::parse(source) {
-			Ok(tokens) => {
-				match parser().parse(tokens) {
-					Ok(tag) => Ok(tag),
-					Err(errors) => Err(ParseError::ParseFailure(errors)),
+		Tag::parse_dialect(source, Dialect::default())
+	}
+
+	/// Attempt to parse SNBT into an NBT [Tag] using the grammar of `dialect`.
+	pub fn parse_dialect<S: AsRef<str>>(source: S, dialect: Dialect) -> Result<Tag, ParseError> {
+		crate::snbtreader::parse(source.as_ref(), dialect)
+	}
+
+	/// Parses SNBT by tokenizing all of `source` into [Token]s, then parsing the tokens.
+	/// This accepts the same grammar as [Tag::parse_dialect], but is slower and uses more memory.
+	/// It reports every error that it finds instead of only the first.
+	pub fn parse_tokenized<S: AsRef<str>>(source: S, dialect: Dialect) -> Result<Tag, ParseError> {
+		let source = source.as_ref();
+		let tokens = Token::parse_spanned(source, dialect)
+			.map_err(|errors| ParseError::TokenizeError(Diagnostic::from_errors(source, errors)))?;
+		let end_of_input = source.chars().count();
+		parser(dialect, false)
+			.then_ignore(end())
+			.parse(Stream::from_iter(end_of_input..end_of_input, tokens.into_iter()))
+			.map(|(tag, _)| tag)
+			.map_err(|errors| ParseError::ParseFailure(Diagnostic::from_errors(source, errors)))
+	}
+
+	/// Parses as much of `source` as possible, for tools such as editors that need a tree for
+	/// SNBT that is incomplete or malformed. Instead of stopping at the first error, the parser:
+	/// - skips characters that can't start a token, and elements that can't be parsed,
+	/// - inserts missing commas, colons and closing delimiters,
+	/// - inserts a placeholder for missing values and integers that are out of range.
+	///   Missing values are replaced with an empty [Tag::String], and integers with `0`.
+	///
+	/// In the legacy dialect, list elements that don't match the type of the first element are dropped.
+	/// ### Example
+	/// ```
+	/// # use rustnbt::{tag::*, snbt::*};
+	/// let recovered = Tag::parse_recovering("{name: \"Steve\" health: , items: [1, 2", Dialect::Modern);
+	/// let Some(Tag::Compound(player)) = &recovered.tag else { panic!() };
+	/// assert_eq!(player.len(), 3);
+	/// assert_eq!(recovered.placeholders.len(), 1);
+	/// assert!(!recovered.diagnostics.is_empty());
+	/// ```
+	pub fn parse_recovering<S: AsRef<str>>(source: S, dialect: Dialect) -> Recovered {
+		let source = source.as_ref();
+		let (tokens, token_errors) = Token::parse_spanned_recovering(source, dialect);
+		let end_of_input = source.chars().count();
+		let (parsed, errors) = parser(dialect, true)
+			.then_ignore(any().repeated().validate(|rest: Vec<Token>, span, emit| {
+				if !rest.is_empty() {
+					emit(SnbtError::custom(span, "Skipped unexpected input after the value."));
 				}
-			},
-			Err(errors) => Err(ParseError::TokenizeError(errors)),
+			}))
+			.parse_recovery(Stream::from_iter(end_of_input..end_of_input, tokens.into_iter()));
+		let (tag, placeholders) = parsed.unzip();
+		let mut diagnostics = Diagnostic::from_errors(source, token_errors);
+		diagnostics.extend(Diagnostic::from_errors(source, errors));
+		Recovered {
+			tag,
+			diagnostics,
+			placeholders: placeholders.unwrap_or_default(),
 		}
 	}
 }
 
+/// The result of [Tag::parse_recovering].
+#[derive(Debug, Clone)]
+pub struct Recovered {
+	/// The best-effort [Tag], or `None` if nothing that looks like a value was found.
+	pub tag: Option<Tag>,
+	/// Every problem that was found, including the ones that the parser recovered from.
+	pub diagnostics: Vec<Diagnostic>,
+	/// The spans of placeholder values that were inserted by the parser.
+	pub placeholders: Vec<Span>,
+}
+
+impl Recovered {
+	/// Returns `true` if the source was parsed without any problems.
+	pub fn is_clean(&self) -> bool {
+		self.diagnostics.is_empty()
+	}
+}
+
 impl FromStr for Tag {
 	type Err = ParseError;
 
@@ -457,13 +1116,13 @@ fn strcmp(ignore_case: bool, lhs: &str, rhs: &str, ) -> bool {
 	}
 }
 
-fn keyword<S: AsRef<str>>(word: S, ignore_case: bool) -> impl Parser<char, (), Error = Simple<char>> {
+fn keyword<S: AsRef<str>>(word: S, ignore_case: bool) -> impl Parser<char, (), Error = SnbtError<char>> {
 	identifier()
 		.try_map(move |text, span| {
 			if strcmp(ignore_case, word.as_ref(), &text) {
 				Ok(())
 			} else {
-				Err(Simple::custom(span, format!("Expected keyword: {}, found {}", word.as_ref(), text)))
+				Err(SnbtError::custom(span, format!("Expected keyword: {}, found {}", word.as_ref(), text)))
 			}
 		})
 }
@@ -488,12 +1147,259 @@ fn none_of_nc<C: Container<char>, E: Error<char>>(chars: C) -> NoneOf<char,HashS
 	none_of(no_case(chars))
 }
 
+/// A range of character (not byte) offsets into SNBT source text.
+pub type Span = std::ops::Range<usize>;
+
+/// The error type used by the SNBT tokenizer (with `I` = [char]) and parser (with `I` = [Token]).
+/// This is chumsky's [Simple] error with an optional hint for how to fix the problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnbtError<I: Hash + Eq> {
+	// Boxed, since [Simple] is large and the parsers return `Result`s of this type everywhere.
+	error: Box<Simple<I>>,
+	hint: Option<String>,
+}
+
+impl<I: Hash + Eq> SnbtError<I> {
+	/// Creates an error with a custom message.
+	pub fn custom<M: ToString>(span: Span, message: M) -> Self {
+		Self {
+			error: Box::new(Simple::custom(span, message)),
+			hint: None,
+		}
+	}
+
+	/// Attaches a hint to the error, replacing any previous hint.
+	pub fn with_hint<H: Into<String>>(mut self, hint: H) -> Self {
+		self.hint = Some(hint.into());
+		self
+	}
+
+	pub fn hint(&self) -> Option<&str> {
+		self.hint.as_deref()
+	}
+
+	pub fn span(&self) -> Span {
+		self.error.span()
+	}
+
+	/// The underlying chumsky error.
+	pub fn simple(&self) -> &Simple<I> {
+		&self.error
+	}
+}
+
+impl<I: Hash + Eq> chumsky::Error<I> for SnbtError<I> {
+	type Span = Span;
+	type Label = &'static str;
+
+	fn expected_input_found<Iter: IntoIterator<Item = Option<I>>>(span: Span, expected: Iter, found: Option<I>) -> Self {
+		Self {
+			error: Box::new(Simple::expected_input_found(span, expected, found)),
+			hint: None,
+		}
+	}
+
+	fn unclosed_delimiter(unclosed_span: Span, unclosed: I, span: Span, expected: I, found: Option<I>) -> Self {
+		Self {
+			error: Box::new(Simple::unclosed_delimiter(unclosed_span, unclosed, span, expected, found)),
+			hint: None,
+		}
+	}
+
+	fn with_label(mut self, label: Self::Label) -> Self {
+		self.error = Box::new((*self.error).with_label(label));
+		self
+	}
+
+	fn merge(self, other: Self) -> Self {
+		Self {
+			error: Box::new((*self.error).merge(*other.error)),
+			hint: self.hint.or(other.hint),
+		}
+	}
+}
+
+/// Describes tokenizer and parser input in diagnostics.
+trait Describe {
+	fn describe(&self) -> String;
+}
+
+impl Describe for char {
+	fn describe(&self) -> String {
+		format!("{self:?}")
+	}
+}
+
+impl Describe for Token {
+	fn describe(&self) -> String {
+		match self {
+			Token::Comma => String::from("`,`"),
+			Token::Colon => String::from("`:`"),
+			Token::ArrayStart(array_type) => format!("`[{};`", array_type.clone() as u8 as char),
+			Token::OpenBracket => String::from("`[`"),
+			Token::CloseBracket => String::from("`]`"),
+			Token::OpenBrace => String::from("`{`"),
+			Token::CloseBrace => String::from("`}`"),
+			Token::OpenParen => String::from("`(`"),
+			Token::CloseParen => String::from("`)`"),
+			Token::Boolean(value) => format!("`{value}`"),
+			Token::Integer(_) => String::from("integer"),
+			Token::Decimal(_, _) => String::from("decimal number"),
+			Token::Identifier(name) => format!("`{name}`"),
+			Token::StringLiteral(_) => String::from("string"),
+		}
+	}
+}
+
+/// A single problem found while parsing SNBT, with enough information to point at the
+/// offending source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	/// The location of the problem.
+	pub span: Span,
+	/// 1-based line number of the start of the span.
+	pub line: usize,
+	/// 1-based column (in characters) of the start of the span.
+	pub column: usize,
+	/// The text of the line that the span starts on.
+	pub line_text: String,
+	pub message: String,
+	/// Descriptions of the input that would have been accepted.
+	pub expected: Vec<String>,
+	/// The syntactic structure that was being parsed, such as `Compound`.
+	pub context: Option<&'static str>,
+	pub hint: Option<String>,
+}
+
+impl Diagnostic {
+	fn from_errors<I: Hash + Eq + Describe>(source: &str, errors: Vec<SnbtError<I>>) -> Vec<Diagnostic> {
+		errors.into_iter()
+			.map(|error| Diagnostic::new(source, error))
+			.collect()
+	}
+
+	fn new<I: Hash + Eq + Describe>(source: &str, error: SnbtError<I>) -> Diagnostic {
+		let span = error.span();
+		let message = match error.error.reason() {
+			SimpleReason::Custom(message) => message.clone(),
+			SimpleReason::Unclosed { delimiter, .. } => format!("Unclosed delimiter {}.", delimiter.describe()),
+			SimpleReason::Unexpected => Diagnostic::unexpected(source, &span),
+		};
+		let mut expected: Vec<String> = error.error.expected()
+			.map(|expected| expected.as_ref().map_or_else(|| String::from("end of input"), Describe::describe))
+			.collect();
+		expected.sort();
+		expected.dedup();
+		Diagnostic::at(source, span, message, expected, error.error.label(), error.hint)
+	}
+
+	/// Creates a diagnostic for the problem at `span` in `source`.
+	pub(crate) fn at(source: &str, span: Span, message: String, expected: Vec<String>, context: Option<&'static str>, hint: Option<String>) -> Diagnostic {
+		let (line, column, line_text) = locate(source, span.start);
+		Diagnostic {
+			span,
+			line,
+			column,
+			line_text,
+			message,
+			expected,
+			context,
+			hint,
+		}
+	}
+
+	/// The message for unexpected input at `span`, such as "Unexpected `,`.".
+	pub(crate) fn unexpected(source: &str, span: &Span) -> String {
+		let found_text: String = source.chars().skip(span.start).take(span.len()).collect();
+		if span.is_empty() || found_text.is_empty() {
+			String::from("Unexpected end of input.")
+		} else {
+			format!("Unexpected `{found_text}`.")
+		}
+	}
+}
+
+/// Finds the 1-based line and column of the character at `offset`, and the text of that line.
+fn locate(source: &str, offset: usize) -> (usize, usize, String) {
+	let mut line = 1;
+	let mut line_start = 0;
+	for (index, c) in source.chars().enumerate().take(offset) {
+		if c == '\n' {
+			line += 1;
+			line_start = index + 1;
+		}
+	}
+	let line_text: String = source.chars()
+		.skip(line_start)
+		.take_while(|&c| c != '\n')
+		.collect();
+	(line, offset - line_start + 1, line_text.trim_end_matches('\r').to_owned())
+}
+
+impl Display for Diagnostic {
+	/// Renders the diagnostic with an excerpt of the source and a caret pointing at the problem:
+	/// ```text
+	/// error: Integer out of range for Byte.
+	///  --> 2:9
+	///   |
+	/// 2 |     count: 300b,
+	///   |            ^^^^
+	///   = while parsing Compound
+	///   = hint: Byte values must be between -128 and 127.
+	/// ```
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let line_number = self.line.to_string();
+		let gutter = " ".repeat(line_number.len());
+		writeln!(f, "error: {}", self.message)?;
+		writeln!(f, "{gutter}--> {}:{}", self.line, self.column)?;
+		writeln!(f, "{gutter} |")?;
+		writeln!(f, "{line_number} | {}", self.line_text)?;
+		// Copy the tabs from the line so that the caret lines up with the text.
+		let padding: String = self.line_text.chars()
+			.take(self.column - 1)
+			.map(|c| if c == '\t' { '\t' } else { ' ' })
+			.collect();
+		let remaining = self.line_text.chars().count().saturating_sub(self.column - 1);
+		let carets = "^".repeat(self.span.len().min(remaining).max(1));
+		write!(f, "{gutter} | {padding}{carets}")?;
+		if let Some(context) = self.context {
+			write!(f, "\n{gutter} = while parsing {context}")?;
+		}
+		if !self.expected.is_empty() {
+			write!(f, "\n{gutter} = expected {}", self.expected.join(", "))?;
+		}
+		if let Some(hint) = &self.hint {
+			write!(f, "\n{gutter} = hint: {hint}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Writes each diagnostic on its own line(s), separated by a blank line.
+struct Diagnostics<'a>(&'a [Diagnostic]);
+
+impl Display for Diagnostics<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.iter().try_for_each(|diagnostic| write!(f, "\n{diagnostic}\n"))
+	}
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ParseError {
-	#[error("Found invalid token(s).")]
-	TokenizeError(Vec<Simple<char>>),
-	#[error("Failed to parse SNBT.")]
-	ParseFailure(Vec<Simple<Token>>),
+	#[error("Found invalid token(s).{}", Diagnostics(.0))]
+	TokenizeError(Vec<Diagnostic>),
+	#[error("Failed to parse SNBT.{}", Diagnostics(.0))]
+	ParseFailure(Vec<Diagnostic>),
+}
+
+impl ParseError {
+	/// Every problem that was found in the source.
+	pub fn diagnostics(&self) -> &[Diagnostic] {
+		match self {
+			ParseError::TokenizeError(diagnostics) => diagnostics,
+			ParseError::ParseFailure(diagnostics) => diagnostics,
+		}
+	}
 }
 
 #[cfg(test)]
@@ -639,4 +1545,182 @@ mod tests {
 		"#);
 	}
 
+	fn parse_value(source: &str) -> super::Tag {
+		super::Tag::parse(source).unwrap_or_else(|err| panic!("Failed to parse {source:?}: {err:?}"))
+	}
+
+	#[test]
+	fn modern_integers_test() {
+		use super::*;
+		macro_rules! check {
+			($($source:literal => $pattern:pat),+ $(,)?) => {
+				$(
+					let tag = parse_value($source);
+					assert!(matches!(tag, $pattern), "{} => {:?}", $source, tag);
+				)+
+			};
+		}
+		check!{
+			"0x1F" => Tag::Int(31),
+			"0XffL" => Tag::Long(255),
+			"0b101" => Tag::Int(5),
+			"0b101b" => Tag::Byte(5),
+			"0b" => Tag::Byte(0),
+			"1_000_000" => Tag::Int(1_000_000),
+			"10ub" => Tag::Byte(10),
+			"255ub" => Tag::Byte(-1),
+			"-128sb" => Tag::Byte(-128),
+			"0xFFub" => Tag::Byte(-1),
+			"0xFFFFFFFF" => Tag::Int(-1),
+			"65535us" => Tag::Short(-1),
+			"+5" => Tag::Int(5),
+			"7i" => Tag::Int(7),
+			"1e3" => Tag::Double(_),
+			"1.5e-3f" => Tag::Float(_),
+			".5" => Tag::Double(_),
+			"5." => Tag::Double(_),
+			"1_0.2_5" => Tag::Double(_),
+		}
+		assert!(Tag::parse("200sb").is_err());
+		assert!(Tag::parse("-1ub").is_err());
+		assert!(Tag::parse("128b").is_err());
+		assert!(Tag::parse("1_").is_ok_and(|tag| matches!(tag, Tag::String(_))));
+	}
+
+	#[test]
+	fn modern_strings_test() {
+		use super::*;
+		let tag = parse_value(r#""a\sb\x41\u00e9\U0001F388\N{SNOWMAN}""#);
+		assert!(matches!(tag, Tag::String(text) if text == "a bA\u{e9}\u{1F388}\u{2603}"));
+		assert!(Tag::parse(r#""\N{NOT A REAL CHARACTER NAME}""#).is_err());
+	}
+
+	#[test]
+	fn modern_collections_test() {
+		use super::*;
+		let Tag::Compound(map) = parse_value(r#"{
+			true: 1,
+			false: false,
+			bytes: [B; 1, 2, true],
+			longs: [L; 1, 2L],
+			mixed: [1, "two", {three: 3}, {"": 4}],
+			same: [1b, true],
+			flag: bool(5),
+			off: bool(0.0),
+			id: uuid(f81d4fae-7dec-11d0-a765-00a0c91e6bf6),
+		}"#) else {
+			panic!();
+		};
+		assert!(matches!(map.get("true"), Some(Tag::Int(1))));
+		assert!(matches!(map.get("false"), Some(Tag::Byte(0))));
+		assert!(matches!(map.get("bytes"), Some(Tag::ByteArray(bytes)) if bytes == &[1, 2, 1]));
+		assert!(matches!(map.get("longs"), Some(Tag::LongArray(longs)) if longs == &[1, 2]));
+		assert!(matches!(map.get("same"), Some(Tag::List(ListTag::Byte(_)))));
+		assert!(matches!(map.get("flag"), Some(Tag::Byte(1))));
+		assert!(matches!(map.get("off"), Some(Tag::Byte(0))));
+		assert!(matches!(map.get("id"), Some(Tag::IntArray(ints)) if ints == &[-132296786, 2112623056, -1486552928, -920753162]));
+		let Some(Tag::List(ListTag::Compound(mixed))) = map.get("mixed") else {
+			panic!("{:?}", map.get("mixed"));
+		};
+		assert_eq!(mixed.len(), 4);
+		assert!(matches!(mixed[0].get(""), Some(Tag::Int(1))));
+		assert!(matches!(mixed[1].get(""), Some(Tag::String(_))));
+		assert!(matches!(mixed[2].get("three"), Some(Tag::Int(3))));
+		// A compound that looks like a wrapper is wrapped again.
+		assert!(matches!(mixed[3].get(""), Some(Tag::Compound(_))));
+	}
+
+	#[test]
+	fn legacy_dialect_test() {
+		use super::*;
+		let legacy = |source: &str| Tag::parse_dialect(source, Dialect::Legacy);
+		assert!(matches!(legacy("0x1F"), Ok(Tag::String(_))));
+		assert!(matches!(legacy("1_000"), Ok(Tag::String(_))));
+		assert!(matches!(legacy("10b"), Ok(Tag::Byte(10))));
+		// Unknown suffixes make the literal an unquoted string, as they did before 1.21.5.
+		assert!(matches!(legacy("10ub"), Ok(Tag::String(_))));
+		assert!(legacy("[1, \"two\"]").is_err());
+		assert!(legacy("[B; 1, 2]").is_err());
+		let error = Tag::parse_tokenized(r#""\x41""#, Dialect::Legacy).unwrap_err();
+		assert_eq!(error.diagnostics()[0].message, "This escape sequence requires the modern dialect.");
+		assert!(legacy("bool(1)").is_err());
+		assert!(matches!(legacy("{true: [1b, true]}"), Ok(Tag::Compound(_))));
+	}
+
+	#[test]
+	fn diagnostics_test() {
+		use super::*;
+		let error = Tag::parse("{\n\tcount: 300b,\n}").unwrap_err();
+		let [diagnostic] = error.diagnostics() else {
+			panic!("Expected one diagnostic: {error}");
+		};
+		assert_eq!((diagnostic.line, diagnostic.column), (2, 9));
+		assert_eq!(diagnostic.span, 10..14);
+		assert_eq!(diagnostic.line_text, "\tcount: 300b,");
+		assert_eq!(diagnostic.hint.as_deref(), Some("Byte values must be between -128 and 127."));
+		let rendered = diagnostic.to_string();
+		assert!(rendered.contains("2 | \tcount: 300b,\n  | \t       ^^^^\n"), "{rendered}");
+		// Values that fit in the unsigned range suggest the unsigned suffix.
+		let error = Tag::parse("[B; 200]").unwrap_err();
+		assert!(error.diagnostics()[0].hint.as_ref().unwrap().contains("`ub`"), "{error}");
+
+		let error = Tag::parse("{a: 1, b 2}").unwrap_err();
+		let diagnostic = &error.diagnostics()[0];
+		assert_eq!((diagnostic.line, diagnostic.column), (1, 10));
+		assert_eq!(diagnostic.expected, ["`:`"]);
+		assert_eq!(diagnostic.context, Some("Compound"));
+
+		let error = Tag::parse_dialect("[1b, 2s]", Dialect::Legacy).unwrap_err();
+		assert!(error.diagnostics()[0].hint.as_ref().unwrap().contains("mixed types"), "{error}");
+
+		let error = Tag::parse(r#"{a: "\q"}"#).unwrap_err();
+		assert!(matches!(error, ParseError::TokenizeError(_)));
+		assert_eq!(error.diagnostics()[0].column, 7);
+	}
+
+	#[test]
+	fn recovering_test() {
+		use super::*;
+		let compound = |recovered: &Recovered| match &recovered.tag {
+			Some(Tag::Compound(map)) => map.clone(),
+			other => panic!("Expected a compound: {other:?}"),
+		};
+		let recovered = Tag::parse_recovering("{a: 1, b: [2, 3]}", Dialect::Modern);
+		assert!(recovered.is_clean());
+		assert!(recovered.placeholders.is_empty());
+
+		// Missing comma, value, and closing delimiters.
+		let recovered = Tag::parse_recovering(r#"{name: "Steve" health: , items: [1, 2"#, Dialect::Modern);
+		let map = compound(&recovered);
+		assert!(matches!(map.get("name"), Some(Tag::String(name)) if name == "Steve"));
+		assert!(matches!(map.get("health"), Some(Tag::String(text)) if text.is_empty()));
+		assert!(matches!(map.get("items"), Some(Tag::List(ListTag::Int(items))) if items == &[1, 2]));
+		assert_eq!(recovered.placeholders, vec![Span { start: 23, end: 23 }]);
+		let messages: Vec<&str> = recovered.diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+		assert_eq!(messages, ["Missing `,` before this element.", "Missing value.", "Missing `]`.", "Missing `}`."]);
+
+		// Malformed entries and array elements are skipped, out of range values are replaced.
+		let recovered = Tag::parse_recovering("{a: 1, : [x, 2], b 3, c: [B; 1, 300, x]}", Dialect::Modern);
+		let map = compound(&recovered);
+		assert_eq!(map.len(), 3);
+		assert!(matches!(map.get("b"), Some(Tag::Int(3))));
+		assert!(matches!(map.get("c"), Some(Tag::ByteArray(bytes)) if bytes == &[1, 0]));
+		assert_eq!(recovered.placeholders, vec![Span { start: 32, end: 35 }]);
+		assert_eq!(recovered.diagnostics.len(), 4);
+
+		// Legacy lists keep the elements with the type of the first element.
+		let recovered = Tag::parse_recovering("[1b, 2s, 3b]", Dialect::Legacy);
+		assert!(matches!(recovered.tag, Some(Tag::List(ListTag::Byte(bytes))) if bytes == [1, 3]));
+		assert!(recovered.diagnostics[0].hint.is_some());
+
+		// Invalid characters are skipped by the tokenizer.
+		let recovered = Tag::parse_recovering("{a: 1, b: \"open", Dialect::Modern);
+		assert_eq!(compound(&recovered).len(), 2);
+		assert!(!recovered.is_clean());
+
+		let recovered = Tag::parse_recovering("", Dialect::Modern);
+		assert!(recovered.tag.is_none());
+		assert_eq!(recovered.diagnostics.len(), 1);
+	}
+
 }
\ No newline at end of file
@@ -0,0 +1,764 @@
+#![doc = "
+A single-pass SNBT parser that builds a [Tag] directly from the source text.
+
+[Tag::parse] and [Tag::parse_dialect] use this parser. Tokens are read one at a time as the
+parser needs them, and borrow from the source text where they can, so there is no intermediate
+token list and no copy of the source. It accepts exactly the same grammar as the two-stage
+parser in [crate::snbt] ([Tag::parse_tokenized]), and reports the first error that it finds.
+"]
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::Map;
+use crate::snbt::{
+	self,
+	ArrayType,
+	DecimalType,
+	Diagnostic,
+	Dialect,
+	IntegerType,
+	ParseError,
+	Signedness,
+};
+use crate::tag::{ListTag, Tag};
+
+/// Parses `source` into a [Tag] using the grammar of `dialect`.
+pub(crate) fn parse(source: &str, dialect: Dialect) -> Result<Tag, ParseError> {
+	let mut reader = Reader {
+		source,
+		pos: 0,
+		dialect,
+		context: Vec::new(),
+		peeked: None,
+	};
+	reader.document().map_err(|error| error.into_parse_error(source))
+}
+
+/// Whether an error was found while reading a token or while reading the structure of the
+/// document. These become [ParseError::TokenizeError] and [ParseError::ParseFailure].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stage {
+	Token,
+	Structure,
+}
+
+struct ReadError {
+	stage: Stage,
+	/// Byte offsets into the source.
+	span: Range<usize>,
+	/// `None` for unexpected input, which gets a message describing the input.
+	message: Option<String>,
+	expected: Vec<String>,
+	context: Option<&'static str>,
+	hint: Option<String>,
+}
+
+impl ReadError {
+	fn into_parse_error(self, source: &str) -> ParseError {
+		// Diagnostics count characters, not bytes.
+		let start = source[..self.span.start].chars().count();
+		let span = start..start + source[self.span.clone()].chars().count();
+		let message = self.message.unwrap_or_else(|| Diagnostic::unexpected(source, &span));
+		let diagnostics = vec![Diagnostic::at(source, span, message, self.expected, self.context, self.hint)];
+		match self.stage {
+			Stage::Token => ParseError::TokenizeError(diagnostics),
+			Stage::Structure => ParseError::ParseFailure(diagnostics),
+		}
+	}
+}
+
+type ReadResult<T> = Result<T, Box<ReadError>>;
+
+/// The tokens of [snbt::Token], borrowing from the source text.
+enum Token<'a> {
+	Comma,
+	Colon,
+	ArrayStart(ArrayType),
+	OpenBracket,
+	CloseBracket,
+	OpenBrace,
+	CloseBrace,
+	OpenParen,
+	CloseParen,
+	Boolean(bool),
+	Integer(Integer<'a>),
+	/// The number without the suffix. Underscores have not been removed.
+	Decimal(&'a str, DecimalType),
+	Identifier(&'a str),
+	StringLiteral(Cow<'a, str>),
+}
+
+/// The parts of an integer literal. See [snbt::IntegerLiteral].
+struct Integer<'a> {
+	negative: bool,
+	radix: u32,
+	/// The digits without the sign or radix prefix. Underscores have not been removed.
+	digits: &'a str,
+	signedness: Signedness,
+	suffix: Option<IntegerType>,
+}
+
+impl Integer<'_> {
+	fn kind(&self) -> IntegerType {
+		self.suffix.unwrap_or(IntegerType::Int)
+	}
+}
+
+fn is_ident_byte(b: u8) -> bool {
+	b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'+' | b'.')
+}
+
+struct Reader<'a> {
+	source: &'a str,
+	/// Byte offset of the next character to read.
+	pos: usize,
+	dialect: Dialect,
+	/// The structures that are being parsed, innermost last. Used for error messages.
+	context: Vec<&'static str>,
+	peeked: Option<(Token<'a>, Range<usize>)>,
+}
+
+impl<'a> Reader<'a> {
+	fn modern(&self) -> bool {
+		self.dialect == Dialect::Modern
+	}
+
+	fn byte(&self, at: usize) -> Option<u8> {
+		self.source.as_bytes().get(at).copied()
+	}
+
+	fn char_at(&self, at: usize) -> Option<char> {
+		self.source[at..].chars().next()
+	}
+
+	fn token_error(&self, span: Range<usize>, message: Option<String>, expected: Vec<String>) -> Box<ReadError> {
+		Box::new(ReadError {
+			stage: Stage::Token,
+			span,
+			message,
+			expected,
+			context: None,
+			hint: None,
+		})
+	}
+
+	fn error(&self, span: Range<usize>, message: Option<String>, expected: &[&str]) -> Box<ReadError> {
+		Box::new(ReadError {
+			stage: Stage::Structure,
+			span,
+			message,
+			expected: expected.iter().map(|expected| expected.to_string()).collect(),
+			context: self.context.last().copied(),
+			hint: None,
+		})
+	}
+
+	/// The span of the character at `at`, or an empty span at the end of the input.
+	fn char_span(&self, at: usize) -> Range<usize> {
+		at..at + self.char_at(at).map_or(0, char::len_utf8)
+	}
+
+	// Tokens
+
+	fn skip_whitespace(&mut self) {
+		while let Some(c) = self.char_at(self.pos) {
+			if !c.is_whitespace() {
+				break;
+			}
+			self.pos += c.len_utf8();
+		}
+	}
+
+	fn peek(&mut self) -> ReadResult<Option<&(Token<'a>, Range<usize>)>> {
+		if self.peeked.is_none() {
+			self.peeked = self.read_token()?;
+		}
+		Ok(self.peeked.as_ref())
+	}
+
+	fn next(&mut self) -> ReadResult<Option<(Token<'a>, Range<usize>)>> {
+		match self.peeked.take() {
+			Some(token) => Ok(Some(token)),
+			None => self.read_token(),
+		}
+	}
+
+	fn read_token(&mut self) -> ReadResult<Option<(Token<'a>, Range<usize>)>> {
+		self.skip_whitespace();
+		let start = self.pos;
+		let Some(first) = self.byte(start) else {
+			return Ok(None);
+		};
+		let (token, end) = match first {
+			b',' => (Token::Comma, start + 1),
+			b':' => (Token::Colon, start + 1),
+			b'[' => match self.array_start(start) {
+				Some(token) => token,
+				None => (Token::OpenBracket, start + 1),
+			},
+			b']' => (Token::CloseBracket, start + 1),
+			b'{' => (Token::OpenBrace, start + 1),
+			b'}' => (Token::CloseBrace, start + 1),
+			b'(' | b')' if !self.modern() => {
+				return Err(self.token_error(start..start + 1, Some(String::from("Operations require the modern dialect.")), Vec::new()));
+			}
+			b'(' => (Token::OpenParen, start + 1),
+			b')' => (Token::CloseParen, start + 1),
+			b'"' | b'\'' => self.string_literal(start)?,
+			_ => match self.boolean(start)
+				.or_else(|| self.integer(start))
+				.or_else(|| self.decimal(start)) {
+				Some(token) => token,
+				None => {
+					let end = self.ident_end(start);
+					if end == start {
+						return Err(self.token_error(self.char_span(start), None, Vec::new()));
+					}
+					(Token::Identifier(&self.source[start..end]), end)
+				}
+			},
+		};
+		self.pos = end;
+		Ok(Some((token, start..end)))
+	}
+
+	/// The end of the run of identifier characters starting at `at`.
+	fn ident_end(&self, at: usize) -> usize {
+		let bytes = self.source.as_bytes();
+		at + bytes[at..].iter().take_while(|&&b| is_ident_byte(b)).count()
+	}
+
+	/// `true` if the character at `at` can't be part of an identifier.
+	fn ends_identifier(&self, at: usize) -> bool {
+		self.char_at(at).map_or(true, |c| !c.is_alphanumeric() && !matches!(c, '_' | '+' | '-' | '.'))
+	}
+
+	/// The end of a run of digits in `radix` starting at `at`, which may contain underscores
+	/// between digits in the modern dialect.
+	fn digit_run(&self, at: usize, radix: u32) -> Option<usize> {
+		let bytes = self.source.as_bytes();
+		if !bytes.get(at).is_some_and(|&b| (b as char).is_digit(radix)) {
+			return None;
+		}
+		let end = at + bytes[at..].iter().take_while(|&&b| (b as char).is_digit(radix) || b == b'_').count();
+		let digits = &bytes[at..end];
+		if digits.contains(&b'_') && (!self.modern() || digits.ends_with(b"_")) {
+			return None;
+		}
+		Some(end)
+	}
+
+	/// `[B;`, `[I;` or `[L;`.
+	fn array_start(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		let end = self.ident_end(at + 1);
+		if end != at + 2 || self.byte(end) != Some(b';') {
+			return None;
+		}
+		let array_type = match self.byte(at + 1)?.to_ascii_uppercase() {
+			b'B' => ArrayType::Byte,
+			b'I' => ArrayType::Int,
+			b'L' => ArrayType::Long,
+			_ => return None,
+		};
+		Some((Token::ArrayStart(array_type), end + 1))
+	}
+
+	/// `true` or `false`, not followed by a letter, digit or `_`.
+	fn boolean(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		let bytes = self.source.as_bytes();
+		let end = at + bytes[at..].iter().take_while(|b| b.is_ascii_alphanumeric() || **b == b'_').count();
+		match &self.source[at..end] {
+			"true" => Some((Token::Boolean(true), end)),
+			"false" => Some((Token::Boolean(false), end)),
+			_ => None,
+		}
+	}
+
+	fn integer(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		let mut pos = at;
+		let negative = self.byte(pos) == Some(b'-');
+		if matches!(self.byte(pos), Some(b'+' | b'-')) {
+			pos += 1;
+		}
+		let prefixed = |letters: &[u8], radix: u32| {
+			if self.byte(pos) == Some(b'0') && self.byte(pos + 1).is_some_and(|b| letters.contains(&b)) {
+				self.digit_run(pos + 2, radix).map(|end| (radix, pos + 2, end))
+			} else {
+				None
+			}
+		};
+		let (radix, digits_start, digits_end) = prefixed(b"xX", 16)
+			.or_else(|| prefixed(b"bB", 2))
+			.or_else(|| self.digit_run(pos, 10).map(|end| (10, pos, end)))?;
+		let digits = &self.source[digits_start..digits_end];
+		if !self.modern() && (radix != 10 || (digits.len() > 1 && digits.starts_with('0'))) {
+			return None;
+		}
+		let suffix_end = self.ident_end(digits_end);
+		let (signedness, suffix, end) = match snbt::integer_suffix(&self.source[digits_end..suffix_end], self.dialect) {
+			Some((signedness, suffix)) if suffix_end > digits_end => (signedness, suffix, suffix_end),
+			_ => (Signedness::Unspecified, None, digits_end),
+		};
+		if !self.ends_identifier(end) {
+			return None;
+		}
+		Some((Token::Integer(Integer {
+			negative,
+			radix,
+			digits,
+			signedness,
+			suffix,
+		}), end))
+	}
+
+	/// A `d` or `f` suffix (case insensitive) that makes up the whole identifier at `at`.
+	fn decimal_suffix(&self, at: usize) -> Option<DecimalType> {
+		if self.ident_end(at) != at + 1 {
+			return None;
+		}
+		match self.byte(at)?.to_ascii_lowercase() {
+			b'd' => Some(DecimalType::Double),
+			b'f' => Some(DecimalType::Float),
+			_ => None,
+		}
+	}
+
+	fn decimal(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		self.special_decimal(at).or_else(|| self.finite_decimal(at))
+	}
+
+	/// `NaN` or `Infinity` with an optional `-` and a required suffix.
+	fn special_decimal(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		let mut pos = at;
+		if self.byte(pos) == Some(b'-') {
+			pos += 1;
+		}
+		let rest = &self.source[pos..];
+		pos += if rest.starts_with("NaN") {
+			3
+		} else if rest.starts_with("Infinity") {
+			8
+		} else {
+			return None;
+		};
+		let decimal_type = self.decimal_suffix(pos)?;
+		self.ends_identifier(pos + 1)
+			.then(|| (Token::Decimal(&self.source[at..pos], decimal_type), pos + 1))
+	}
+
+	/// `1.5`, `1.`, `.5` or `1`, with an optional sign, exponent and suffix.
+	/// `1` requires an exponent or a suffix.
+	fn finite_decimal(&self, at: usize) -> Option<(Token<'a>, usize)> {
+		let mut pos = at;
+		if matches!(self.byte(pos), Some(b'+' | b'-')) {
+			pos += 1;
+		}
+		let mut has_point = true;
+		pos = match self.digit_run(pos, 10) {
+			Some(end) if self.byte(end) == Some(b'.') => self.digit_run(end + 1, 10).unwrap_or(end + 1),
+			_ if self.byte(pos) == Some(b'.') && self.digit_run(pos + 1, 10).is_some() => self.digit_run(pos + 1, 10)?,
+			Some(end) => {
+				has_point = false;
+				end
+			}
+			None => return None,
+		};
+		let mut has_exponent = false;
+		if matches!(self.byte(pos), Some(b'e' | b'E')) {
+			let sign = usize::from(matches!(self.byte(pos + 1), Some(b'+' | b'-')));
+			if let Some(end) = self.digit_run(pos + 1 + sign, 10) {
+				pos = end;
+				has_exponent = true;
+			}
+		}
+		let number_end = pos;
+		let suffix = self.decimal_suffix(pos);
+		if suffix.is_some() {
+			pos += 1;
+		}
+		if !has_point && !has_exponent && suffix.is_none() {
+			return None;
+		}
+		self.ends_identifier(pos)
+			.then(|| (Token::Decimal(&self.source[at..number_end], suffix.unwrap_or(DecimalType::Double)), pos))
+	}
+
+	/// A quoted string starting at `at`. Strings without escape sequences borrow from the source.
+	fn string_literal(&mut self, at: usize) -> ReadResult<(Token<'a>, usize)> {
+		let quote = self.source.as_bytes()[at];
+		let mut owned: Option<String> = None;
+		let mut segment_start = at + 1;
+		let mut pos = at + 1;
+		loop {
+			let Some(offset) = self.source.as_bytes()[pos..].iter().position(|&b| b == quote || b == b'\\') else {
+				let end = self.source.len();
+				let mut error = self.token_error(end..end, None, vec![format!("{:?}", quote as char), String::from("'\\\\'")]);
+				error.context = Some("String Literal");
+				return Err(error);
+			};
+			pos += offset;
+			let segment = &self.source[segment_start..pos];
+			if self.source.as_bytes()[pos] == quote {
+				let text = match owned {
+					Some(mut text) => {
+						text.push_str(segment);
+						Cow::Owned(text)
+					}
+					None => Cow::Borrowed(segment),
+				};
+				return Ok((Token::StringLiteral(text), pos + 1));
+			}
+			let text = owned.get_or_insert_with(String::new);
+			text.push_str(segment);
+			let (c, end) = self.escape(pos + 1).map_err(|mut error| {
+				error.context = Some("String Literal");
+				error
+			})?;
+			text.push(c);
+			pos = end;
+			segment_start = end;
+		}
+	}
+
+	/// The escape sequence after a `\` at `at`. Returns the character and the end of the sequence.
+	fn escape(&self, at: usize) -> ReadResult<(char, usize)> {
+		let Some(c) = self.char_at(at) else {
+			return Err(self.token_error(at..at, None, Vec::new()));
+		};
+		let simple = match c {
+			'\\' | '/' | '"' | '\'' => Some(c),
+			'b' => Some('\x08'),
+			'f' => Some('\x0C'),
+			'n' => Some('\n'),
+			'r' => Some('\r'),
+			't' => Some('\t'),
+			's' if self.modern() => Some(' '),
+			_ => None,
+		};
+		if let Some(simple) = simple {
+			return Ok((simple, at + 1));
+		}
+		let (c, end) = match c {
+			'u' => self.hex_escape(at + 1, 4)?,
+			'x' | 'U' | 'N' | 's' if !self.modern() => {
+				return Err(self.token_error(self.char_span(at), Some(String::from("This escape sequence requires the modern dialect.")), Vec::new()));
+			}
+			'x' => self.hex_escape(at + 1, 2)?,
+			'U' => self.hex_escape(at + 1, 8)?,
+			'N' => self.named_escape(at + 1)?,
+			_ => {
+				let mut expected = vec!['"', '/', '\'', '\\', 'b', 'f', 'n', 'r', 't', 'u'];
+				if self.modern() {
+					expected.extend(['N', 'U', 's', 'x']);
+				}
+				expected.sort();
+				let expected = expected.into_iter().map(|c| format!("{c:?}")).collect();
+				return Err(self.token_error(self.char_span(at), None, expected));
+			}
+		};
+		Ok((c, end))
+	}
+
+	/// `count` hex digits starting at `at`.
+	fn hex_escape(&self, at: usize, count: usize) -> ReadResult<(char, usize)> {
+		let bytes = self.source.as_bytes();
+		let digits = bytes[at..].iter().take(count).take_while(|b| b.is_ascii_hexdigit()).count();
+		if digits < count {
+			return Err(self.token_error(self.char_span(at + digits), None, Vec::new()));
+		}
+		let end = at + count;
+		u32::from_str_radix(&self.source[at..end], 16).ok()
+			.and_then(char::from_u32)
+			.map(|c| (c, end))
+			.ok_or_else(|| self.token_error(at..end, Some(String::from("Invalid unicode escape.")), Vec::new()))
+	}
+
+	/// `{<unicode name>}` starting at `at`.
+	fn named_escape(&self, at: usize) -> ReadResult<(char, usize)> {
+		if self.byte(at) != Some(b'{') {
+			return Err(self.token_error(self.char_span(at), None, vec![String::from("'{'")]));
+		}
+		let name_start = at + 1;
+		let name_end = match self.source[name_start..].find('}') {
+			Some(0) => return Err(self.token_error(self.char_span(name_start), None, Vec::new())),
+			Some(length) => name_start + length,
+			None => return Err(self.token_error(self.source.len()..self.source.len(), None, vec![String::from("'}'")])),
+		};
+		let name = &self.source[name_start..name_end];
+		unicode_names2::character(name)
+			.map(|c| (c, name_end + 1))
+			.ok_or_else(|| self.token_error(at..name_end + 1, Some(format!("Unknown unicode character name: {name}")), Vec::new()))
+	}
+
+	// Structure
+
+	fn document(&mut self) -> ReadResult<Tag> {
+		let tag = self.value()?;
+		match self.next()? {
+			None => Ok(tag),
+			Some((_, span)) => Err(self.error(span, None, &["end of input"])),
+		}
+	}
+
+	/// The span at the end of the input, for errors about missing input.
+	fn end_span(&self) -> Range<usize> {
+		self.source.len()..self.source.len()
+	}
+
+	/// Reads the next token, which must be `expected`.
+	fn expect(&mut self, expected: &str, matches: fn(&Token) -> bool) -> ReadResult<()> {
+		match self.next()? {
+			Some((token, _)) if matches(&token) => Ok(()),
+			Some((_, span)) => Err(self.error(span, None, &[expected])),
+			None => Err(self.error(self.end_span(), None, &[expected])),
+		}
+	}
+
+	fn value(&mut self) -> ReadResult<Tag> {
+		let Some((token, span)) = self.next()? else {
+			return Err(self.error(self.end_span(), None, &["value"]));
+		};
+		Ok(match token {
+			Token::OpenBrace => Tag::Compound(self.compound()?),
+			Token::OpenBracket => Tag::List(self.list()?),
+			Token::ArrayStart(ArrayType::Byte) => Tag::ByteArray(self.array(IntegerType::Byte, "Byte Array")?.into_iter().map(|value| value as i8).collect()),
+			Token::ArrayStart(ArrayType::Int) => Tag::IntArray(self.array(IntegerType::Int, "Int Array")?.into_iter().map(|value| value as i32).collect()),
+			Token::ArrayStart(ArrayType::Long) => Tag::LongArray(self.array(IntegerType::Long, "Long Array")?),
+			Token::Integer(integer) => {
+				let value = self.integer_value(&integer, integer.kind(), span)?;
+				match integer.kind() {
+					IntegerType::Byte => Tag::Byte(value as i8),
+					IntegerType::Short => Tag::Short(value as i16),
+					IntegerType::Int => Tag::Int(value as i32),
+					IntegerType::Long => Tag::Long(value),
+				}
+			}
+			Token::Decimal(text, DecimalType::Float) => Tag::Float(self.decimal_value(text, span)?),
+			Token::Decimal(text, DecimalType::Double) => Tag::Double(self.decimal_value(text, span)?),
+			Token::Boolean(value) => Tag::Byte(value as i8),
+			Token::Identifier(name @ ("bool" | "uuid")) if matches!(self.peek()?, Some((Token::OpenParen, _))) => {
+				self.next()?;
+				let tag = if name == "bool" {
+					Tag::Byte(self.bool_argument()? as i8)
+				} else {
+					Tag::IntArray(self.uuid_argument()?)
+				};
+				self.expect("`)`", |token| matches!(token, Token::CloseParen))?;
+				tag
+			}
+			Token::Identifier(text) => Tag::String(text.to_owned()),
+			Token::StringLiteral(text) => Tag::String(text.into_owned()),
+			_ => return Err(self.error(span, None, &["value"])),
+		})
+	}
+
+	fn integer_value(&self, integer: &Integer, kind: IntegerType, span: Range<usize>) -> ReadResult<i64> {
+		snbt::integer_value_of(integer.negative, integer.radix, integer.digits, integer.signedness, kind).ok_or_else(|| {
+			let (message, hint) = snbt::out_of_range_text(integer.negative, integer.radix, integer.digits, integer.signedness, kind, self.modern());
+			let mut error = self.error(span, Some(message), &[]);
+			error.hint = Some(hint);
+			error
+		})
+	}
+
+	fn decimal_value<F: std::str::FromStr>(&self, text: &str, span: Range<usize>) -> ReadResult<F> {
+		let parsed = if text.contains('_') {
+			text.replace('_', "").parse()
+		} else {
+			text.parse()
+		};
+		parsed.map_err(|_| self.error(span, Some(String::from("Failed to parse.")), &[]))
+	}
+
+	fn bool_argument(&mut self) -> ReadResult<bool> {
+		match self.next()? {
+			Some((Token::Integer(integer), span)) => Ok(self.integer_value(&integer, integer.kind(), span)? != 0),
+			Some((Token::Decimal(text, _), span)) => Ok(self.decimal_value::<f64>(text, span)? != 0.0),
+			Some((Token::Boolean(value), _)) => Ok(value),
+			Some((_, span)) => Err(self.error(span, None, &["number"])),
+			None => Err(self.error(self.end_span(), None, &["number"])),
+		}
+	}
+
+	fn uuid_argument(&mut self) -> ReadResult<Vec<i32>> {
+		let (text, span) = match self.next()? {
+			Some((Token::Identifier(text), span)) => (Cow::Borrowed(text), span),
+			Some((Token::StringLiteral(text), span)) => (text, span),
+			Some((_, span)) => return Err(self.error(span, None, &["string"])),
+			None => return Err(self.error(self.end_span(), None, &["string"])),
+		};
+		snbt::parse_uuid(&text)
+			.map(Vec::from)
+			.ok_or_else(|| self.error(span, Some(format!("Invalid UUID: {text}")), &[]))
+	}
+
+	/// The elements of a compound, after the `{`.
+	fn compound(&mut self) -> ReadResult<Map> {
+		self.context.push("Compound");
+		let mut map = Map::new();
+		if matches!(self.peek()?, Some((Token::CloseBrace, _))) {
+			self.next()?;
+			self.context.pop();
+			return Ok(map);
+		}
+		loop {
+			let key = match self.next()? {
+				Some((Token::Identifier(key), _)) => key.to_owned(),
+				Some((Token::StringLiteral(key), _)) => key.into_owned(),
+				Some((Token::Boolean(key), _)) => key.to_string(),
+				Some((_, span)) => return Err(self.error(span, None, &["string"])),
+				None => return Err(self.error(self.end_span(), None, &["string"])),
+			};
+			self.expect("`:`", |token| matches!(token, Token::Colon))?;
+			let value = self.value()?;
+			map.insert(key, value);
+			match self.next()? {
+				Some((Token::Comma, _)) => {
+					if matches!(self.peek()?, Some((Token::CloseBrace, _))) {
+						self.next()?;
+						break;
+					}
+				}
+				Some((Token::CloseBrace, _)) => break,
+				Some((_, span)) => return Err(self.error(span, None, &["`,`", "`}`"])),
+				None => return Err(self.error(self.end_span(), None, &["`,`", "`}`"])),
+			}
+		}
+		self.context.pop();
+		Ok(map)
+	}
+
+	/// The elements of a list, after the `[`.
+	fn list(&mut self) -> ReadResult<ListTag> {
+		self.context.push("List");
+		let mut tags = Vec::new();
+		if matches!(self.peek()?, Some((Token::CloseBracket, _))) {
+			self.next()?;
+			self.context.pop();
+			return Ok(ListTag::Empty);
+		}
+		loop {
+			// The legacy dialect requires every element to have the type of the first element.
+			let start = match self.peek()? {
+				Some((_, span)) => span.clone(),
+				None => self.end_span(),
+			};
+			let tag = self.value()?;
+			if !self.modern() && tags.first().is_some_and(|first: &Tag| first.id() != tag.id()) {
+				let mut error = self.error(start, None, &["`]`"]);
+				error.hint = Some(String::from(snbt::MIXED_LIST_HINT));
+				return Err(error);
+			}
+			tags.push(tag);
+			match self.next()? {
+				Some((Token::Comma, _)) => {
+					if matches!(self.peek()?, Some((Token::CloseBracket, _))) {
+						self.next()?;
+						break;
+					}
+				}
+				Some((Token::CloseBracket, _)) => break,
+				Some((_, span)) => return Err(self.error(span, None, &["`,`", "`]`"])),
+				None => return Err(self.error(self.end_span(), None, &["`,`", "`]`"])),
+			}
+		}
+		self.context.pop();
+		Ok(ListTag::from_mixed(tags))
+	}
+
+	/// The elements of an array of `kind`, after the `[B;`, `[I;` or `[L;`.
+	fn array(&mut self, kind: IntegerType, context: &'static str) -> ReadResult<Vec<i64>> {
+		self.context.push(context);
+		let mut values = Vec::new();
+		if matches!(self.peek()?, Some((Token::CloseBracket, _))) {
+			self.next()?;
+			self.context.pop();
+			return Ok(values);
+		}
+		loop {
+			match self.next()? {
+				Some((Token::Integer(integer), span)) if integer.kind() == kind || (self.modern() && integer.suffix.is_none()) => {
+					values.push(self.integer_value(&integer, kind, span)?);
+				}
+				Some((Token::Boolean(value), _)) => values.push(value as i64),
+				Some((_, span)) => return Err(self.error(span, None, &["integer"])),
+				None => return Err(self.error(self.end_span(), None, &["integer"])),
+			}
+			match self.next()? {
+				Some((Token::Comma, _)) => {}
+				Some((Token::CloseBracket, _)) => break,
+				Some((_, span)) => return Err(self.error(span, None, &["`,`", "`]`"])),
+				None => return Err(self.error(self.end_span(), None, &["`,`", "`]`"])),
+			}
+		}
+		self.context.pop();
+		Ok(values)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::tests::same_tag;
+	use proptest::prelude::*;
+
+	/// Checks that both parsers accept `source`, and produce the same [Tag], or that both reject it.
+	fn same_result(source: &str, dialect: Dialect) -> Result<(), String> {
+		match (Tag::parse_dialect(source, dialect), Tag::parse_tokenized(source, dialect)) {
+			(Ok(fast), Ok(tokenized)) if same_tag(&fast, &tokenized) => Ok(()),
+			(Err(_), Err(_)) => Ok(()),
+			(fast, tokenized) => Err(format!("{source:?} ({dialect:?}): {fast:?} != {tokenized:?}")),
+		}
+	}
+
+	#[test]
+	fn matches_tokenized_test() {
+		let sources = [
+			"{a: 1b, b: [1, 2], c: [B; 1b, 2b], d: \"text\", e: 'single', f: 1.5f, g: .5, h: 1e3}",
+			"[I; 1, 2, 3]", "[L; 1l, -2L]", "[B;]", "[]", "[1,]", "{}", "{a:1,}", "[B; 1,]",
+			"0x1F", "0b101", "0b", "0b1b", "0x", "01", "1_000", "1_", "0xFFub", "-128b", "255ub", "-1ub",
+			"1.", "-.5e-3d", "1e", "1ef", "NaNF", "-InfinityD", "NaN", "1.5.3", "1b2", "true", "truex",
+			"true.x", "+", "-", "+5", "bool(1)", "bool(0.0)", "bool", "uuid(\"f81d4fae-7dec-11d0-a765-00a0c91e6bf6\")",
+			"uuid(x)", "\"\\x41\\u0042\\N{LATIN SMALL LETTER C}\\s\"", "\"\\q\"", "'\\'\"'", "\"open",
+			"[1b, 2s]", "[1, \"two\", {a: 1}]", "{true: false}", "{1: 2}", "{a 1}", "@", "a\u{e9}", "1\u{e9}",
+			"{\"k\"\t:\n1}",
+		];
+		for dialect in [Dialect::Modern, Dialect::Legacy] {
+			for source in sources {
+				if let Err(message) = same_result(source, dialect) {
+					panic!("{message}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn errors_test() {
+		let error = Tag::parse("{a: [1, 2,, 3]}").unwrap_err();
+		let [diagnostic] = error.diagnostics() else { panic!() };
+		assert!(matches!(error, ParseError::ParseFailure(_)));
+		assert_eq!((diagnostic.line, diagnostic.column), (1, 11));
+		assert_eq!(diagnostic.message, "Unexpected `,`.");
+		assert_eq!(diagnostic.context, Some("List"));
+		// Columns count characters rather than bytes.
+		let error = Tag::parse("{\"\u{e9}\u{e9}\": 1b, b: 300b}").unwrap_err();
+		assert!(matches!(error, ParseError::ParseFailure(_)));
+		assert_eq!(error.diagnostics()[0].column, 15);
+		let error = Tag::parse("{a: \"\\q\"}").unwrap_err();
+		assert!(matches!(error, ParseError::TokenizeError(_)));
+		assert_eq!(error.diagnostics()[0].column, 7);
+		assert_eq!(error.diagnostics()[0].context, Some("String Literal"));
+		let error = Tag::parse_dialect(r#""\x41""#, Dialect::Legacy).unwrap_err();
+		assert_eq!(error.diagnostics()[0].message, "This escape sequence requires the modern dialect.");
+	}
+
+	proptest! {
+		#[test]
+		fn matches_tokenized(source in r#"[\[\]{}:,; "'\\()0-9abdefilnsuxyBDEFILNSUX.+_-]{0,16}"#) {
+			for dialect in [Dialect::Modern, Dialect::Legacy] {
+				prop_assert!(same_result(&source, dialect).is_ok(), "{}", same_result(&source, dialect).unwrap_err());
+			}
+		}
+	}
+}
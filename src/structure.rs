@@ -0,0 +1,411 @@
+// Structure templates (.nbt), as saved by structure blocks and used by world generation.
+// https://minecraft.wiki/w/Structure_file
+//
+// Blocks refer to a palette of block states by index. Structures with random variants,
+// such as shipwrecks, have several palettes of the same length under `palettes`,
+// and one of them is chosen when the structure is placed.
+
+use crate::{
+	Map,
+	codegen::{self, DecodeError},
+	path::PathSegment,
+	chunk::BlockState,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		NamedTag,
+		NbtType,
+		Tag,
+	},
+};
+use std::collections::HashMap;
+
+/// A block in a [StructureTemplate].
+#[derive(Clone, Debug)]
+pub struct StructureBlock {
+	pub pos: [i32; 3],
+	/// The index of the block's state in the palettes.
+	pub state: usize,
+	/// The block entity data, without the position.
+	pub nbt: Option<Map>,
+	/// Keys next to `nbt` that aren't modeled.
+	pub extra: Map,
+}
+
+/// An entity in a [StructureTemplate].
+#[derive(Clone, Debug)]
+pub struct StructureEntity {
+	pub pos: [f64; 3],
+	/// The block that the entity is in.
+	pub block_pos: [i32; 3],
+	pub nbt: Map,
+	/// Keys next to `nbt` that aren't modeled.
+	pub extra: Map,
+}
+
+/// The palettes of a [StructureTemplate] don't all have the same length.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Palettes must have {expected} entries, found {found}.")]
+pub struct PaletteLengthMismatch {
+	pub expected: usize,
+	pub found: usize,
+}
+
+/// The contents of a structure file.
+/// ```
+/// # use rustnbt::{chunk::BlockState, structure::StructureTemplate};
+/// let mut structure = StructureTemplate::new(3465);
+/// structure
+///     .place_block([0, 0, 0], BlockState::new("minecraft:stone"))
+///     .place_block([1, 2, 0], BlockState::new("minecraft:stone"));
+/// assert_eq!(structure.size, [2, 3, 1]);
+/// assert_eq!(structure.palette(0).len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StructureTemplate {
+	/// The name of the root tag, which is usually empty.
+	pub name: String,
+	pub data_version: i32,
+	/// The size of the structure, which grows to fit placed blocks.
+	pub size: [i32; 3],
+	/// One palette per variant, all of the same length.
+	palettes: Vec<Vec<BlockState>>,
+	/// Whether the palettes are stored under `palettes` even if there is only one.
+	multiple: bool,
+	blocks: Vec<StructureBlock>,
+	/// The index in `blocks` of every position.
+	positions: HashMap<[i32; 3], usize>,
+	pub entities: Vec<StructureEntity>,
+	/// Keys that aren't modeled, such as `author` in old structures.
+	pub other: Map,
+}
+
+impl StructureTemplate {
+	/// Creates an empty structure with one palette.
+	pub fn new(data_version: i32) -> Self {
+		Self {
+			name: String::new(),
+			data_version,
+			size: [0; 3],
+			palettes: vec![Vec::new()],
+			multiple: false,
+			blocks: Vec::new(),
+			positions: HashMap::new(),
+			entities: Vec::new(),
+			other: Map::new(),
+		}
+	}
+
+	/// The palette of `variant`.
+	/// # Panics
+	/// Panics if there is no such variant.
+	pub fn palette(&self, variant: usize) -> &[BlockState] {
+		&self.palettes[variant]
+	}
+
+	/// The palette of `variant`, whose block states can be replaced but not added or removed.
+	/// # Panics
+	/// Panics if there is no such variant.
+	pub fn palette_mut(&mut self, variant: usize) -> &mut [BlockState] {
+		&mut self.palettes[variant]
+	}
+
+	pub fn palettes(&self) -> &[Vec<BlockState>] {
+		&self.palettes
+	}
+
+	/// Adds a variant, returning its index.
+	pub fn add_palette(&mut self, palette: Vec<BlockState>) -> Result<usize, PaletteLengthMismatch> {
+		let expected = self.palettes[0].len();
+		if palette.len() != expected {
+			return Err(PaletteLengthMismatch { expected, found: palette.len() });
+		}
+		self.palettes.push(palette);
+		Ok(self.palettes.len() - 1)
+	}
+
+	/// The index of `state` in the palettes, adding it to every palette if it's new.
+	/// In variants, the state is found by its place in the first palette.
+	pub fn palette_index(&mut self, state: BlockState) -> usize {
+		if let Some(index) = self.palettes[0].iter().position(|entry| entry == &state) {
+			return index;
+		}
+		for palette in &mut self.palettes {
+			palette.push(state.clone());
+		}
+		self.palettes[0].len() - 1
+	}
+
+	pub fn blocks(&self) -> &[StructureBlock] {
+		&self.blocks
+	}
+
+	/// Returns the block at `pos` with its state in the palette of `variant`.
+	pub fn block_at(&self, pos: [i32; 3], variant: usize) -> Option<(&BlockState, Option<&Map>)> {
+		let block = &self.blocks[*self.positions.get(&pos)?];
+		Some((&self.palettes.get(variant)?[block.state], block.nbt.as_ref()))
+	}
+
+	/// Places a block, replacing any block at the same position.
+	/// # Panics
+	/// Panics if a coordinate is negative.
+	pub fn place_block(&mut self, pos: [i32; 3], state: BlockState) -> &mut Self {
+		self.place(pos, state, None)
+	}
+
+	/// Places a block with block entity data, such as the items in a chest.
+	/// # Panics
+	/// Panics if a coordinate is negative.
+	pub fn place_block_entity(&mut self, pos: [i32; 3], state: BlockState, nbt: Map) -> &mut Self {
+		self.place(pos, state, Some(nbt))
+	}
+
+	fn place(&mut self, pos: [i32; 3], state: BlockState, nbt: Option<Map>) -> &mut Self {
+		assert!(pos.iter().all(|&coordinate| coordinate >= 0), "block position {pos:?} is outside the structure");
+		let state = self.palette_index(state);
+		let block = StructureBlock { pos, state, nbt, extra: Map::new() };
+		match self.positions.get(&pos) {
+			Some(&index) => self.blocks[index] = block,
+			None => {
+				self.positions.insert(pos, self.blocks.len());
+				self.blocks.push(block);
+			}
+		}
+		for (size, coordinate) in self.size.iter_mut().zip(pos) {
+			*size = (*size).max(coordinate + 1);
+		}
+		self
+	}
+
+	/// Removes the block at `pos`, leaving nothing there when the structure is placed.
+	pub fn remove_block(&mut self, pos: [i32; 3]) -> Option<StructureBlock> {
+		let index = self.positions.remove(&pos)?;
+		let block = self.blocks.swap_remove(index);
+		if let Some(moved) = self.blocks.get(index) {
+			self.positions.insert(moved.pos, index);
+		}
+		Some(block)
+	}
+
+	/// Places an entity, whose NBT should include its `id`.
+	pub fn place_entity(&mut self, pos: [f64; 3], nbt: Map) -> &mut Self {
+		let block_pos = pos.map(|coordinate| coordinate.floor() as i32);
+		self.entities.push(StructureEntity { pos, block_pos, nbt, extra: Map::new() });
+		self
+	}
+
+	/// Removes palette entries that no block uses.
+	pub fn compact_palettes(&mut self) {
+		let mut used = vec![false; self.palettes[0].len()];
+		for block in &self.blocks {
+			used[block.state] = true;
+		}
+		let mut count = 0;
+		let remap: Vec<Option<usize>> = used.iter().map(|&used| used.then(|| {
+			count += 1;
+			count - 1
+		})).collect();
+		for palette in &mut self.palettes {
+			let mut index = 0;
+			palette.retain(|_| {
+				index += 1;
+				used[index - 1]
+			});
+		}
+		for block in &mut self.blocks {
+			block.state = remap[block.state].expect("every used state was remapped");
+		}
+	}
+}
+
+fn triple<T: NbtType + TryFrom<Tag, Error = ()>>(tag: Tag) -> Result<[T; 3], DecodeError> {
+	let values = codegen::list(tag, codegen::value::<T>)?;
+	let len = values.len();
+	values.try_into().map_err(|_| DecodeError::invalid(format!("Expected 3 coordinates, found {len}.")))
+}
+
+fn decode_palette(tag: Tag) -> Result<Vec<BlockState>, DecodeError> {
+	codegen::list(tag, BlockState::decode_nbt)
+}
+
+fn encode_palette(palette: Vec<BlockState>) -> Tag {
+	codegen::encode_list(palette.into_iter().map(BlockState::encode_nbt))
+}
+
+impl DecodeNbt for StructureTemplate {
+	type Error = DecodeError;
+
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = codegen::compound(nbt)?;
+		let data_version = codegen::required(&mut map, "DataVersion", codegen::value)?;
+		let size = codegen::required(&mut map, "size", triple)?;
+		let (palettes, multiple) = match codegen::optional(&mut map, "palettes", |tag| codegen::list(tag, decode_palette))? {
+			Some(palettes) => (palettes, true),
+			None => (vec![codegen::optional(&mut map, "palette", decode_palette)?.unwrap_or_default()], false),
+		};
+		if palettes.is_empty() {
+			return Err(DecodeError::invalid("There must be at least one palette.").within(PathSegment::Key("palettes".to_owned())));
+		}
+		let palette_len = palettes[0].len();
+		if let Some(index) = palettes.iter().position(|palette| palette.len() != palette_len) {
+			let error = PaletteLengthMismatch { expected: palette_len, found: palettes[index].len() };
+			return Err(DecodeError::invalid(error.to_string())
+				.within(PathSegment::Index(index as isize))
+				.within(PathSegment::Key("palettes".to_owned())));
+		}
+		let blocks = codegen::optional(&mut map, "blocks", |tag| codegen::list(tag, |tag| {
+			let mut block = codegen::compound(tag)?;
+			let pos = codegen::required(&mut block, "pos", triple)?;
+			let state = codegen::required(&mut block, "state", |tag| match codegen::value::<i32>(tag)? {
+				state if state >= 0 && (state as usize) < palette_len => Ok(state as usize),
+				state => Err(DecodeError::invalid(format!("State {state} isn't in the palette of {palette_len} entries."))),
+			})?;
+			let nbt = codegen::optional(&mut block, "nbt", codegen::compound)?;
+			Ok(StructureBlock { pos, state, nbt, extra: block })
+		}))?.unwrap_or_default();
+		let entities = codegen::optional(&mut map, "entities", |tag| codegen::list(tag, |tag| {
+			let mut entity = codegen::compound(tag)?;
+			let pos = codegen::required(&mut entity, "pos", triple)?;
+			let block_pos = codegen::required(&mut entity, "blockPos", triple)?;
+			let nbt = codegen::required(&mut entity, "nbt", codegen::compound)?;
+			Ok(StructureEntity { pos, block_pos, nbt, extra: entity })
+		}))?.unwrap_or_default();
+		let positions = blocks.iter().enumerate().map(|(index, block)| (block.pos, index)).collect();
+		Ok(Self {
+			name: String::new(),
+			data_version,
+			size,
+			palettes,
+			multiple,
+			blocks,
+			positions,
+			entities,
+			other: map,
+		})
+	}
+}
+
+impl EncodeNbt for StructureTemplate {
+	fn encode_nbt(self) -> Tag {
+		let coordinates = |values: Vec<i32>| Tag::List(ListTag::from(values));
+		let mut map = Map::new();
+		map.insert("DataVersion".to_owned(), Tag::Int(self.data_version));
+		map.insert("size".to_owned(), coordinates(self.size.to_vec()));
+		if self.multiple || self.palettes.len() > 1 {
+			let palettes = self.palettes.into_iter().map(encode_palette);
+			map.insert("palettes".to_owned(), codegen::encode_list(palettes));
+		} else {
+			let palette = self.palettes.into_iter().next().expect("there is always a palette");
+			map.insert("palette".to_owned(), encode_palette(palette));
+		}
+		let blocks = self.blocks.into_iter().map(|block| {
+			let mut map = Map::new();
+			map.insert("pos".to_owned(), coordinates(block.pos.to_vec()));
+			map.insert("state".to_owned(), Tag::Int(block.state as i32));
+			if let Some(nbt) = block.nbt {
+				map.insert("nbt".to_owned(), Tag::Compound(nbt));
+			}
+			map.extend(block.extra);
+			Tag::Compound(map)
+		});
+		map.insert("blocks".to_owned(), codegen::encode_list(blocks));
+		let entities = self.entities.into_iter().map(|entity| {
+			let mut map = Map::new();
+			map.insert("pos".to_owned(), Tag::List(ListTag::from(entity.pos.to_vec())));
+			map.insert("blockPos".to_owned(), coordinates(entity.block_pos.to_vec()));
+			map.insert("nbt".to_owned(), Tag::Compound(entity.nbt));
+			map.extend(entity.extra);
+			Tag::Compound(map)
+		});
+		map.insert("entities".to_owned(), codegen::encode_list(entities));
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+impl TryFrom<NamedTag> for StructureTemplate {
+	type Error = DecodeError;
+
+	fn try_from(value: NamedTag) -> Result<Self, Self::Error> {
+		let name = value.name().to_owned();
+		let mut structure = StructureTemplate::decode_nbt(value.take_tag())?;
+		structure.name = name;
+		Ok(structure)
+	}
+}
+
+impl From<StructureTemplate> for NamedTag {
+	fn from(value: StructureTemplate) -> Self {
+		let name = value.name.clone();
+		NamedTag::with_name(name, value.encode_nbt())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::diff::{diff, DiffOptions};
+
+	fn state(name: &str) -> BlockState {
+		BlockState::new(name)
+	}
+
+	#[test]
+	fn builder_test() {
+		let mut structure = StructureTemplate::new(3465);
+		let mut chest = Map::new();
+		chest.insert("LootTable".to_owned(), Tag::string("minecraft:chests/shipwreck_supply"));
+		structure
+			.place_block([0, 0, 0], state("minecraft:oak_planks"))
+			.place_block([1, 0, 0], state("minecraft:oak_planks"))
+			.place_block_entity([1, 1, 0], state("minecraft:chest"), chest)
+			.place_entity([0.5, 1.0, 0.5], Map::new());
+		assert_eq!(structure.size, [2, 2, 1]);
+		assert_eq!(structure.palette(0), [state("minecraft:oak_planks"), state("minecraft:chest")]);
+		assert_eq!(structure.entities[0].block_pos, [0, 1, 0]);
+
+		// Variants replace block states without moving blocks.
+		let variant = structure.add_palette(structure.palette(0).to_vec()).unwrap();
+		structure.palette_mut(variant)[0] = state("minecraft:spruce_planks");
+		assert_eq!(structure.block_at([1, 0, 0], variant).unwrap().0, &state("minecraft:spruce_planks"));
+		assert_eq!(structure.block_at([1, 0, 0], 0).unwrap().0, &state("minecraft:oak_planks"));
+		assert!(structure.block_at([1, 1, 0], 0).unwrap().1.unwrap().contains_key("LootTable"));
+		assert_eq!(structure.add_palette(Vec::new()), Err(PaletteLengthMismatch { expected: 2, found: 0 }));
+
+		// Replacing and removing blocks, then dropping unused states.
+		structure.place_block([0, 0, 0], state("minecraft:stone"));
+		assert_eq!(structure.palette(variant).len(), 3);
+		structure.remove_block([1, 1, 0]).unwrap();
+		assert!(structure.block_at([1, 1, 0], 0).is_none());
+		assert_eq!(structure.block_at([1, 0, 0], 0).unwrap().0, &state("minecraft:oak_planks"));
+		structure.compact_palettes();
+		assert_eq!(structure.palette(0), [state("minecraft:oak_planks"), state("minecraft:stone")]);
+		assert_eq!(structure.block_at([0, 0, 0], 1).unwrap().0, &state("minecraft:stone"));
+		assert_eq!(structure.block_at([1, 0, 0], 1).unwrap().0, &state("minecraft:spruce_planks"));
+	}
+
+	#[test]
+	fn named_tag_test() {
+		let source = r#"{
+			DataVersion: 3465, size: [2, 1, 1], author: "someone",
+			palettes: [[{Name: "minecraft:stone"}, {Name: "minecraft:chest", Properties: {facing: "west"}}]],
+			blocks: [{pos: [0, 0, 0], state: 0, note: "kept"}, {pos: [1, 0, 0], state: 1, nbt: {id: "minecraft:chest", Items: []}}],
+			entities: [{pos: [0.5d, 0.0d, 0.5d], blockPos: [0, 0, 0], nbt: {id: "minecraft:armor_stand"}, note: "kept"}],
+		}"#;
+		let tag = Tag::parse(source).unwrap();
+		let structure = StructureTemplate::try_from(NamedTag::with_name("shipwreck", tag.clone())).unwrap();
+		assert_eq!(structure.name, "shipwreck");
+		assert_eq!(structure.block_at([1, 0, 0], 0).unwrap().0.property("facing"), Some("west"));
+		assert!(structure.other.contains_key("author"));
+		assert!(structure.blocks()[0].extra.contains_key("note"));
+		assert!(structure.entities[0].extra.contains_key("note"));
+		let named = NamedTag::from(structure);
+		assert_eq!(named.name(), "shipwreck");
+		let options = DiffOptions { ignore_order: true, ..Default::default() };
+		assert!(diff(&tag, named.tag(), &options).is_empty());
+
+		let error = StructureTemplate::decode_nbt(Tag::parse(source.replace("state: 1,", "state: 2,")).unwrap()).unwrap_err();
+		assert_eq!(error.to_string(), "blocks[1].state: State 2 isn't in the palette of 2 entries.");
+	}
+}
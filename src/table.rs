@@ -8,6 +8,7 @@
 ///     NonBytePrimitive: Marker that combines the Primitive and NonByte marker.
 /// Usage:
 /// ```no_run
+/// # use rustnbt::tag_info_table;
 /// macro_rules! read_table {
 ///     //             $id: The tag ID that is written to file to mark a type. These are distinct integer values.
 ///     //                  Typically the ID will be a single 8-bit value, but as the extensions become more advanced, this may change.
@@ -203,6 +203,220 @@ impl ListTag {
 			ListTag::Empty => 0,
 		}
 	}
+
+	#[doc = "Returns `true` if the list has no elements."]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Creates a [ListTag] from tags that all have the same [TagID].<br>
+	/// Returns [ListTag::Empty] if `tags` is empty, and gives back `tags` unchanged
+	/// if they aren't all the same type.
+	pub fn from_tags(tags: Vec<Tag>) -> Result<ListTag, Vec<Tag>> {
+		let Some(id) = tags.first().map(Tag::id) else {
+			return Ok(ListTag::Empty);
+		};
+		if tags.iter().any(|tag| tag.id() != id) {
+			return Err(tags);
+		}
+		macro_rules! collect_list {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match id {
+					$(
+						TagID::$title => ListTag::$title(
+							tags.into_iter()
+								.filter_map(|tag| if let Tag::$title(value) = tag { Some(value) } else { None })
+								.collect()
+						),
+					)+
+				}
+			};
+		}
+		Ok(tag_info_table!(collect_list))
+	}
+
+	/// Returns a copy of the element at `index` as a [Tag], or `None` if `index` is out of bounds.
+	pub fn get(&self, index: usize) -> Option<Tag> {
+		macro_rules! get_element {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match self {
+					ListTag::Empty => None,
+					$(
+						ListTag::$title(list) => list.get(index).cloned().map(Tag::$title),
+					)+
+				}
+			};
+		}
+		tag_info_table!(get_element)
+	}
+
+	/// Replaces the element at `index` with `tag`, returning the previous element.<br>
+	/// Gives back `tag` if `index` is out of bounds or `tag` is not the element type of this list.
+	pub fn replace(&mut self, index: usize, tag: Tag) -> Result<Tag, Tag> {
+		macro_rules! replace_element {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match (self, tag) {
+					$(
+						(ListTag::$title(list), Tag::$title(value)) if index < list.len() => {
+							Ok(Tag::$title(std::mem::replace(&mut list[index], value)))
+						}
+					)+
+					(_, tag) => Err(tag),
+				}
+			};
+		}
+		tag_info_table!(replace_element)
+	}
+
+	/// Returns a borrowed view of the element at `index`, or `None` if `index` is out of bounds.
+	pub fn get_ref(&self, index: usize) -> Option<TagRef<'_>> {
+		match self {
+			ListTag::Empty => None,
+			ListTag::Byte(list) => list.get(index).map(|&value| TagRef::Byte(value)),
+			ListTag::Short(list) => list.get(index).map(|&value| TagRef::Short(value)),
+			ListTag::Int(list) => list.get(index).map(|&value| TagRef::Int(value)),
+			ListTag::Long(list) => list.get(index).map(|&value| TagRef::Long(value)),
+			ListTag::Float(list) => list.get(index).map(|&value| TagRef::Float(value)),
+			ListTag::Double(list) => list.get(index).map(|&value| TagRef::Double(value)),
+			ListTag::ByteArray(list) => list.get(index).map(|array| TagRef::ByteArray(array)),
+			ListTag::String(list) => list.get(index).map(|value| TagRef::String(value)),
+			ListTag::List(list) => list.get(index).map(TagRef::List),
+			ListTag::Compound(list) => list.get(index).map(TagRef::Compound),
+			ListTag::IntArray(list) => list.get(index).map(|array| TagRef::IntArray(array)),
+			ListTag::LongArray(list) => list.get(index).map(|array| TagRef::LongArray(array)),
+		}
+	}
+
+	/// Iterates over borrowed views of the elements.
+	pub fn iter(&self) -> impl Iterator<Item = TagRef<'_>> + '_ {
+		(0..self.len()).filter_map(|index| self.get_ref(index))
+	}
+
+	/// Creates a [ListTag] from tags of any type. Lists with elements of different types are
+	/// stored the same way that Minecraft stores them: as a list of compounds, where every
+	/// element that isn't a compound (or that is a compound that could be mistaken for a wrapper)
+	/// is wrapped as `{"": <element>}`.
+	pub fn from_mixed(tags: Vec<Tag>) -> ListTag {
+		ListTag::from_tags(tags).unwrap_or_else(|tags| {
+			ListTag::Compound(tags.into_iter().map(|tag| match tag {
+				Tag::Compound(map) if !is_wrapper(&map) => map,
+				other => Map::from_iter([(String::new(), other)]),
+			}).collect())
+		})
+	}
+
+	/// Returns the elements as [Tag]s, unwrapping the `{"": <element>}` wrappers of lists that
+	/// were created by [ListTag::from_mixed].
+	pub fn into_mixed(self) -> Vec<Tag> {
+		macro_rules! into_tags {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match self {
+					ListTag::Empty => Vec::new(),
+					ListTag::Compound(maps) => maps.into_iter().map(|mut map| {
+						if is_wrapper(&map) {
+							map.remove("").expect("wrappers have an empty key")
+						} else {
+							Tag::Compound(map)
+						}
+					}).collect(),
+					$(
+						ListTag::$title(list) => list.into_iter().map(Tag::$title).collect(),
+					)+
+				}
+			};
+		}
+		tag_info_table!(into_tags)
+	}
+
+	/// Iterates over borrowed views of the elements, unwrapping the `{"": <element>}` wrappers of
+	/// lists that were created by [ListTag::from_mixed].
+	pub fn iter_mixed(&self) -> impl Iterator<Item = TagRef<'_>> + '_ {
+		self.iter().map(|element| match element {
+			TagRef::Compound(map) if is_wrapper(map) => TagRef::from(&map[""]),
+			element => element,
+		})
+	}
+}
+
+/// Returns `true` if `map` is the wrapper of an element of a heterogeneous list.
+fn is_wrapper(map: &Map) -> bool {
+	map.len() == 1 && map.contains_key("")
+}
+
+/// A borrowed view of a [Tag], which can also refer to an element of a [ListTag].<br>
+/// Elements of a list aren't stored as [Tag]s, so this is how code that walks
+/// an NBT tree can treat them the same way as other tags without cloning them.
+#[derive(Clone, Copy, Debug)]
+pub enum TagRef<'a> {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(&'a [i8]),
+	String(&'a str),
+	List(&'a ListTag),
+	Compound(&'a Map),
+	IntArray(&'a [i32]),
+	LongArray(&'a [i64]),
+}
+
+impl TagRef<'_> {
+	#[doc = "Returns the NBT type ID."]
+	pub fn id(&self) -> TagID {
+		match self {
+			TagRef::Byte(_) => TagID::Byte,
+			TagRef::Short(_) => TagID::Short,
+			TagRef::Int(_) => TagID::Int,
+			TagRef::Long(_) => TagID::Long,
+			TagRef::Float(_) => TagID::Float,
+			TagRef::Double(_) => TagID::Double,
+			TagRef::ByteArray(_) => TagID::ByteArray,
+			TagRef::String(_) => TagID::String,
+			TagRef::List(_) => TagID::List,
+			TagRef::Compound(_) => TagID::Compound,
+			TagRef::IntArray(_) => TagID::IntArray,
+			TagRef::LongArray(_) => TagID::LongArray,
+		}
+	}
+
+	/// Clones the viewed tag.
+	pub fn to_tag(&self) -> Tag {
+		match *self {
+			TagRef::Byte(value) => Tag::Byte(value),
+			TagRef::Short(value) => Tag::Short(value),
+			TagRef::Int(value) => Tag::Int(value),
+			TagRef::Long(value) => Tag::Long(value),
+			TagRef::Float(value) => Tag::Float(value),
+			TagRef::Double(value) => Tag::Double(value),
+			TagRef::ByteArray(array) => Tag::ByteArray(array.to_vec()),
+			TagRef::String(value) => Tag::String(value.to_owned()),
+			TagRef::List(list) => Tag::List(list.clone()),
+			TagRef::Compound(map) => Tag::Compound(map.clone()),
+			TagRef::IntArray(array) => Tag::IntArray(array.to_vec()),
+			TagRef::LongArray(array) => Tag::LongArray(array.to_vec()),
+		}
+	}
+}
+
+impl<'a> From<&'a Tag> for TagRef<'a> {
+	fn from(tag: &'a Tag) -> Self {
+		match tag {
+			Tag::Byte(value) => TagRef::Byte(*value),
+			Tag::Short(value) => TagRef::Short(*value),
+			Tag::Int(value) => TagRef::Int(*value),
+			Tag::Long(value) => TagRef::Long(*value),
+			Tag::Float(value) => TagRef::Float(*value),
+			Tag::Double(value) => TagRef::Double(*value),
+			Tag::ByteArray(array) => TagRef::ByteArray(array),
+			Tag::String(value) => TagRef::String(value),
+			Tag::List(list) => TagRef::List(list),
+			Tag::Compound(map) => TagRef::Compound(map),
+			Tag::IntArray(array) => TagRef::IntArray(array),
+			Tag::LongArray(array) => TagRef::LongArray(array),
+		}
+	}
 }
 
 impl TryFrom<u8> for TagID {
@@ -213,6 +427,7 @@ impl TryFrom<u8> for TagID {
 	- [NbtError::End]
 	- [NbtError::Unsupported] { id_encountered }
 	"]
+	#[allow(clippy::zero_prefixed_literal)]
 	fn try_from(value: u8) -> Result<Self,Self::Error> {
 		match value {
 			01 => Ok(TagID::Byte),
@@ -540,7 +755,7 @@ impl Tag {
 
 	/// Create a [Tag::List].
 	pub fn list<T: NbtType, IT: IntoIterator<Item = T>>(it: IT) -> Tag where Vec<T>: Into<ListTag> {
-		Tag::List(ListTag::from(it.into_iter().collect::<Vec<T>>().into()))
+		Tag::List(it.into_iter().collect::<Vec<T>>().into())
 	}
 
 	/// Create a [Tag::Compound].
@@ -629,6 +844,7 @@ impl Display for NamedTag {
 mod tests {
 
 	#[test]
+	#[allow(clippy::approx_constant)]
 	fn value_tests(){
 		use crate::tag::*;
 		let named: (String, Tag) = NamedTag::with_name("test", Tag::Byte(13)).into();
@@ -0,0 +1,36 @@
+// Fixtures shared by the tests of modules that work with region files and worlds.
+
+use crate::tag::Tag;
+use std::path::{Path, PathBuf};
+
+/// A directory for the files of one test, which is removed when the test ends, even if it fails.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+	/// Creates an empty directory named after `name` and the process, so that test runs don't collide.
+	pub(crate) fn new(name: &str) -> Self {
+		let path = std::env::temp_dir().join(format!("rustnbt-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		std::fs::create_dir_all(&path).unwrap();
+		Self(path)
+	}
+
+	pub(crate) fn path(&self) -> &Path {
+		&self.0
+	}
+
+	pub(crate) fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+		self.0.join(path)
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+	}
+}
+
+/// A minimal chunk at chunk coordinates `x` and `z`.
+pub(crate) fn chunk(x: i32, z: i32) -> Tag {
+	Tag::parse(format!("{{xPos: {x}, zPos: {z}, Status: \"minecraft:full\"}}")).unwrap()
+}
@@ -0,0 +1,476 @@
+// Text components, which are the rich text of item names, signs, books and chat.
+// https://minecraft.wiki/w/Text_component_format
+//
+// Until Minecraft 1.21.5, NBT stores text components as JSON strings. Since then, they are stored as NBT.
+// JSON is converted to NBT first, so both are decoded from a Tag.
+
+use crate::{
+	Map,
+	codegen::{self, DecodeError, DecodeErrorKind},
+	path::PathSegment,
+	tag::{
+		DecodeNbt,
+		EncodeNbt,
+		ListTag,
+		Tag,
+		TagID,
+	},
+};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::value::RawValue;
+use std::fmt::Write;
+
+#[derive(thiserror::Error, Debug)]
+pub enum TextError {
+	#[error("{0}")]
+	Json(#[from] serde_json::Error),
+	#[error("{0}")]
+	Decode(#[from] DecodeError),
+}
+
+/// What a component shows, before its `extra` components.
+#[derive(Clone, Debug)]
+pub enum Content {
+	Text(String),
+	/// A translated string, with `%s` replaced by the components in `with`.
+	Translate { key: String, fallback: Option<String>, with: Vec<TextComponent> },
+	/// The key bound to a control, such as `key.jump`.
+	Keybind(String),
+	/// A score, selector or NBT lookup, whose keys are left in [TextComponent::other].
+	Other,
+}
+
+/// The formatting of a component, which its `extra` components inherit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+	/// A color name such as `gold`, or a hex color such as `#ff8000`.
+	pub color: Option<String>,
+	pub bold: Option<bool>,
+	pub italic: Option<bool>,
+	pub underlined: Option<bool>,
+	pub strikethrough: Option<bool>,
+	pub obfuscated: Option<bool>,
+	pub font: Option<String>,
+	/// Text that is inserted into chat when the component is shift-clicked.
+	pub insertion: Option<String>,
+}
+
+/// A text component.
+/// ```
+/// # use rustnbt::text::TextComponent;
+/// let name = TextComponent::text("Excalibur").color("gold").italic(false);
+/// let json = name.to_json();
+/// let read = TextComponent::from_json(&json).unwrap();
+/// assert_eq!(read.plain_text(), "Excalibur");
+/// assert_eq!(read.style.color.as_deref(), Some("gold"));
+/// assert_eq!(TextComponent::from_json("\"plain\"").unwrap().plain_text(), "plain");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TextComponent {
+	pub content: Content,
+	pub style: Style,
+	/// Components that follow this one.
+	pub extra: Vec<TextComponent>,
+	/// Keys that aren't modeled, such as `clickEvent` and `hoverEvent`.
+	pub other: Map,
+}
+
+impl TextComponent {
+	fn new(content: Content) -> Self {
+		Self { content, style: Style::default(), extra: Vec::new(), other: Map::new() }
+	}
+
+	pub fn text<S: Into<String>>(text: S) -> Self {
+		Self::new(Content::Text(text.into()))
+	}
+
+	pub fn translate<S: Into<String>>(key: S, with: Vec<TextComponent>) -> Self {
+		Self::new(Content::Translate { key: key.into(), fallback: None, with })
+	}
+
+	pub fn keybind<S: Into<String>>(key: S) -> Self {
+		Self::new(Content::Keybind(key.into()))
+	}
+
+	pub fn color<S: Into<String>>(mut self, color: S) -> Self {
+		self.style.color = Some(color.into());
+		self
+	}
+
+	pub fn bold(mut self, bold: bool) -> Self {
+		self.style.bold = Some(bold);
+		self
+	}
+
+	/// Sets whether the component is italic. Custom item names are italic unless this is `false`.
+	pub fn italic(mut self, italic: bool) -> Self {
+		self.style.italic = Some(italic);
+		self
+	}
+
+	pub fn append(mut self, component: TextComponent) -> Self {
+		self.extra.push(component);
+		self
+	}
+
+	/// Parses a component from JSON, as stored in NBT before Minecraft 1.21.5.
+	pub fn from_json(json: &str) -> Result<Self, TextError> {
+		Ok(Self::decode_nbt(component_tag(serde_json::from_str(json)?)?)?)
+	}
+
+	/// Writes the component as JSON, as stored in NBT before Minecraft 1.21.5.
+	pub fn to_json(&self) -> String {
+		let mut json = String::new();
+		write_json(&mut json, &self.clone().encode_nbt()).expect("writing to a String doesn't fail");
+		json
+	}
+
+	/// The text of the component and its `extra` components, without formatting.
+	/// Translated and keybind components show their keys.
+	pub fn plain_text(&self) -> String {
+		let mut text = match &self.content {
+			Content::Text(text) => text.clone(),
+			Content::Translate { key, .. } | Content::Keybind(key) => key.clone(),
+			Content::Other => String::new(),
+		};
+		for extra in &self.extra {
+			text.push_str(&extra.plain_text());
+		}
+		text
+	}
+
+	/// Whether the component can be written as a plain string.
+	fn is_plain(&self) -> bool {
+		matches!(self.content, Content::Text(_)) && self.style == Style::default() && self.extra.is_empty() && self.other.is_empty()
+	}
+}
+
+/// A JSON object's members in order, with their values left unparsed.
+struct Members<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de> Deserialize<'de> for Members<'de> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct MembersVisitor;
+
+		impl<'de> Visitor<'de> for MembersVisitor {
+			type Value = Members<'de>;
+
+			fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+				formatter.write_str("a JSON object")
+			}
+
+			fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Members<'de>, A::Error> {
+				let mut members = Vec::new();
+				while let Some(member) = access.next_entry()? {
+					members.push(member);
+				}
+				Ok(Members(members))
+			}
+		}
+
+		deserializer.deserialize_map(MembersVisitor)
+	}
+}
+
+/// A JSON value whose elements and members are left unparsed, so that numbers keep the text they were written with.
+enum Json<'a> {
+	Null,
+	Bool(bool),
+	Number(&'a str),
+	String(String),
+	Array(Vec<&'a RawValue>),
+	Object(Members<'a>),
+}
+
+impl<'a> Json<'a> {
+	fn parse(json: &'a RawValue) -> Self {
+		let source = json.get();
+		let parsed = match source.as_bytes()[0] {
+			b'n' => Ok(Json::Null),
+			b't' | b'f' => serde_json::from_str(source).map(Json::Bool),
+			b'"' => serde_json::from_str(source).map(Json::String),
+			b'[' => serde_json::from_str(source).map(Json::Array),
+			b'{' => serde_json::from_str(source).map(Json::Object),
+			_ => Ok(Json::Number(source)),
+		};
+		parsed.expect("raw values are valid JSON")
+	}
+}
+
+/// Converts JSON to NBT. Booleans are bytes, and numbers are ints, longs or doubles, whichever holds them.
+/// Members that are null are left out, as Minecraft does, but null anywhere else is an error.
+pub(crate) fn json_tag(json: &RawValue) -> Result<Tag, DecodeError> {
+	match Json::parse(json) {
+		Json::Null => Err(DecodeError::invalid("NBT has no null.")),
+		Json::Bool(value) => Ok(Tag::Byte(value as i8)),
+		Json::Number(number) => match (number.parse::<i64>(), number.parse::<f64>()) {
+			(Ok(value), _) => Ok(i32::try_from(value).map_or(Tag::Long(value), Tag::Int)),
+			(Err(_), Ok(value)) if value.is_finite() => Ok(Tag::Double(value)),
+			_ => Err(DecodeError::invalid(format!("{number} doesn't fit in a double."))),
+		},
+		Json::String(value) => Ok(Tag::String(value)),
+		Json::Array(values) => json_list(values, json_tag),
+		Json::Object(members) => json_compound(members, false),
+	}
+}
+
+/// Converts JSON where a component goes. Numbers and booleans are text, as they are written in the JSON.
+fn component_tag(json: &RawValue) -> Result<Tag, DecodeError> {
+	match Json::parse(json) {
+		Json::Bool(_) | Json::Number(_) => Ok(Tag::String(json.get().to_owned())),
+		Json::Array(values) => json_list(values, component_tag),
+		Json::Object(members) => json_compound(members, true),
+		_ => json_tag(json),
+	}
+}
+
+fn json_list(values: Vec<&RawValue>, element: fn(&RawValue) -> Result<Tag, DecodeError>) -> Result<Tag, DecodeError> {
+	let tags = values.into_iter().enumerate()
+		.map(|(index, value)| element(value).map_err(|error| error.within(PathSegment::Index(index as isize))))
+		.collect::<Result<_, _>>()?;
+	Ok(Tag::List(ListTag::from_mixed(tags)))
+}
+
+/// Converts a JSON object. In a component, `extra` and `with` hold components.
+fn json_compound(members: Members, component: bool) -> Result<Tag, DecodeError> {
+	let mut map = Map::new();
+	for (key, value) in members.0 {
+		let element = match component && matches!(key.as_str(), "extra" | "with") {
+			true => component_tag,
+			false => json_tag,
+		};
+		let tag = match Json::parse(value) {
+			Json::Null => continue,
+			Json::Array(values) => json_list(values, element),
+			_ => json_tag(value),
+		};
+		map.insert(key.clone(), tag.map_err(|error| error.within(PathSegment::Key(key)))?);
+	}
+	Ok(Tag::Compound(map))
+}
+
+/// Writes a float as a JSON number. JSON has no NaN or infinities, so they are written as the strings
+/// `"NaN"`, `"Infinity"` and `"-Infinity"`, which lenient readers such as Minecraft's accept.
+fn write_float(json: &mut String, value: f64, finite: impl std::fmt::Display) -> std::fmt::Result {
+	match value {
+		value if value.is_nan() => json.push_str("\"NaN\""),
+		f64::INFINITY => json.push_str("\"Infinity\""),
+		f64::NEG_INFINITY => json.push_str("\"-Infinity\""),
+		_ => write!(json, "{finite}")?,
+	}
+	Ok(())
+}
+
+/// Writes a tag as JSON. Bytes of 0 and 1 are booleans, since that is how booleans are stored in NBT.
+pub(crate) fn write_json(json: &mut String, tag: &Tag) -> std::fmt::Result {
+	let list = |json: &mut String, tags: Vec<Tag>| {
+		json.push('[');
+		for (index, tag) in tags.iter().enumerate() {
+			if index > 0 {
+				json.push(',');
+			}
+			write_json(json, tag)?;
+		}
+		json.push(']');
+		Ok(())
+	};
+	match tag {
+		Tag::Byte(0) => json.push_str("false"),
+		Tag::Byte(1) => json.push_str("true"),
+		Tag::Byte(value) => write!(json, "{value}")?,
+		Tag::Short(value) => write!(json, "{value}")?,
+		Tag::Int(value) => write!(json, "{value}")?,
+		Tag::Long(value) => write!(json, "{value}")?,
+		Tag::Float(value) => write_float(json, *value as f64, value)?,
+		Tag::Double(value) => write_float(json, *value, value)?,
+		Tag::String(value) => crate::format::write_string(json, value)?,
+		Tag::ByteArray(values) => list(json, values.iter().map(|&value| Tag::Int(value as i32)).collect())?,
+		Tag::IntArray(values) => list(json, values.iter().map(|&value| Tag::Int(value)).collect())?,
+		Tag::LongArray(values) => list(json, values.iter().map(|&value| Tag::Long(value)).collect())?,
+		Tag::List(tags) => list(json, tags.clone().into_mixed())?,
+		Tag::Compound(map) => {
+			json.push('{');
+			for (index, (key, value)) in map.iter().enumerate() {
+				if index > 0 {
+					json.push(',');
+				}
+				crate::format::write_string(json, key)?;
+				json.push(':');
+				write_json(json, value)?;
+			}
+			json.push('}');
+		}
+	}
+	Ok(())
+}
+
+fn boolean(tag: Tag) -> Result<bool, DecodeError> {
+	codegen::value::<i8>(tag).map(|value| value != 0)
+}
+
+impl DecodeNbt for TextComponent {
+	type Error = DecodeError;
+
+	/// Decodes a string, a compound, or a list whose first component is followed by the rest.
+	/// Numbers and booleans are text, as in JSON, where bytes of 0 and 1 are booleans.
+	fn decode_nbt(nbt: Tag) -> Result<Self, DecodeError> {
+		let mut map = match nbt {
+			Tag::String(text) => return Ok(Self::text(text)),
+			Tag::Byte(0) => return Ok(Self::text("false")),
+			Tag::Byte(1) => return Ok(Self::text("true")),
+			Tag::Byte(value) => return Ok(Self::text(value.to_string())),
+			Tag::Short(value) => return Ok(Self::text(value.to_string())),
+			Tag::Int(value) => return Ok(Self::text(value.to_string())),
+			Tag::Long(value) => return Ok(Self::text(value.to_string())),
+			Tag::Float(value) => return Ok(Self::text(value.to_string())),
+			Tag::Double(value) => return Ok(Self::text(value.to_string())),
+			Tag::List(_) => {
+				let mut components = codegen::list(nbt, TextComponent::decode_nbt)?.into_iter();
+				let mut first = components.next().ok_or_else(|| DecodeError::invalid("A list of components can't be empty."))?;
+				first.extra.extend(components);
+				return Ok(first);
+			}
+			Tag::Compound(map) => map,
+			other => return Err(DecodeError::new(DecodeErrorKind::NotOneOf {
+				expected: vec![TagID::String, TagID::List, TagID::Compound],
+				found: other.id(),
+			})),
+		};
+		let content = if let Some(text) = codegen::optional(&mut map, "text", codegen::value)? {
+			Content::Text(text)
+		} else if let Some(key) = codegen::optional(&mut map, "translate", codegen::value)? {
+			let fallback = codegen::optional(&mut map, "fallback", codegen::value)?;
+			let with = codegen::optional(&mut map, "with", |tag| codegen::list(tag, TextComponent::decode_nbt))?.unwrap_or_default();
+			Content::Translate { key, fallback, with }
+		} else if let Some(key) = codegen::optional(&mut map, "keybind", codegen::value)? {
+			Content::Keybind(key)
+		} else {
+			Content::Other
+		};
+		let style = Style {
+			color: codegen::optional(&mut map, "color", codegen::value)?,
+			bold: codegen::optional(&mut map, "bold", boolean)?,
+			italic: codegen::optional(&mut map, "italic", boolean)?,
+			underlined: codegen::optional(&mut map, "underlined", boolean)?,
+			strikethrough: codegen::optional(&mut map, "strikethrough", boolean)?,
+			obfuscated: codegen::optional(&mut map, "obfuscated", boolean)?,
+			font: codegen::optional(&mut map, "font", codegen::value)?,
+			insertion: codegen::optional(&mut map, "insertion", codegen::value)?,
+		};
+		let extra = codegen::optional(&mut map, "extra", |tag| codegen::list(tag, TextComponent::decode_nbt))?.unwrap_or_default();
+		Ok(Self { content, style, extra, other: map })
+	}
+}
+
+impl EncodeNbt for TextComponent {
+	/// Encodes a plain string if the component has no formatting, and a compound otherwise.
+	fn encode_nbt(self) -> Tag {
+		if self.is_plain() {
+			let Content::Text(text) = self.content else {
+				unreachable!("plain components are text");
+			};
+			return Tag::String(text);
+		}
+		let mut map = Map::new();
+		match self.content {
+			Content::Text(text) => {
+				map.insert("text".to_owned(), Tag::String(text));
+			}
+			Content::Translate { key, fallback, with } => {
+				map.insert("translate".to_owned(), Tag::String(key));
+				if let Some(fallback) = fallback {
+					map.insert("fallback".to_owned(), Tag::String(fallback));
+				}
+				if !with.is_empty() {
+					map.insert("with".to_owned(), codegen::encode_list(with.into_iter().map(TextComponent::encode_nbt)));
+				}
+			}
+			Content::Keybind(key) => {
+				map.insert("keybind".to_owned(), Tag::String(key));
+			}
+			Content::Other => {}
+		}
+		let Style { color, bold, italic, underlined, strikethrough, obfuscated, font, insertion } = self.style;
+		for (key, value) in [("color", color), ("font", font), ("insertion", insertion)] {
+			if let Some(value) = value {
+				map.insert(key.to_owned(), Tag::String(value));
+			}
+		}
+		for (key, value) in [("bold", bold), ("italic", italic), ("underlined", underlined), ("strikethrough", strikethrough), ("obfuscated", obfuscated)] {
+			if let Some(value) = value {
+				map.insert(key.to_owned(), Tag::Byte(value as i8));
+			}
+		}
+		if !self.extra.is_empty() {
+			map.insert("extra".to_owned(), codegen::encode_list(self.extra.into_iter().map(TextComponent::encode_nbt)));
+		}
+		map.extend(self.other);
+		Tag::Compound(map)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn json_test() {
+		let json = r#"[{"text": "Sword of ", "bold": true, "clickEvent": {"action": "open_url", "value": "https://example.com"}},
+			"the \"Ancients\"", {"translate": "item.minecraft.diamond", "with": [1.5, {"keybind": "key.jump"}, true], "italic": false}]"#;
+		let component = TextComponent::from_json(json).unwrap();
+		assert_eq!(component.plain_text(), "Sword of the \"Ancients\"item.minecraft.diamond");
+		assert_eq!((component.style.bold, component.extra[1].style.italic), (Some(true), Some(false)));
+		assert!(component.other.contains_key("clickEvent"));
+		let Content::Translate { with, .. } = &component.extra[1].content else {
+			panic!("expected a translated component");
+		};
+		assert!(matches!(&with[1].content, Content::Keybind(key) if key == "key.jump"));
+		// Numbers and booleans are text.
+		assert_eq!((with[0].plain_text(), with[2].plain_text()), ("1.5".to_owned(), "true".to_owned()));
+
+		let written = component.to_json();
+		assert!(written.contains(r#""bold":true"#) && written.contains(r#""the \"Ancients\"""#));
+		let read = TextComponent::from_json(&written).unwrap();
+		assert_eq!(read.plain_text(), component.plain_text());
+		assert_eq!(read.extra.len(), 2);
+		assert_eq!(TextComponent::text("plain").to_json(), r#""plain""#);
+	}
+
+	#[test]
+	fn nbt_test() {
+		// Since 1.21.5, components are NBT, where booleans are bytes.
+		let nbt = Tag::parse(r#"{translate: "chat.type.text", with: [{text: "Steve", bold: 1b}, 42, 0b], extra: ["!"]}"#).unwrap();
+		let component = TextComponent::decode_nbt(nbt).unwrap();
+		assert_eq!(component.plain_text(), "chat.type.text!");
+		let Content::Translate { with, .. } = &component.content else {
+			panic!("expected a translated component");
+		};
+		assert_eq!(with[0].style.bold, Some(true));
+		assert_eq!((with[1].plain_text(), with[2].plain_text()), ("42".to_owned(), "false".to_owned()));
+		assert!(matches!(crate::path::TagPath::root().key("with").index(0).key("bold").get(&component.clone().encode_nbt()), Ok(Tag::Byte(1))));
+		assert!(TextComponent::decode_nbt(Tag::IntArray(vec![1])).is_err());
+
+		// JSON has no NaN or infinities.
+		let mut component = TextComponent::text("x");
+		component.other.insert("scale".to_owned(), Tag::Float(f32::NAN));
+		component.other.insert("size".to_owned(), Tag::Double(f64::NEG_INFINITY));
+		let json = component.to_json();
+		assert!(json.contains(r#""scale":"NaN""#) && json.contains(r#""size":"-Infinity""#));
+		assert!(TextComponent::from_json(&json).is_ok());
+	}
+
+	#[test]
+	fn json_numbers_test() {
+		// Numbers are text as written, whatever their size.
+		let component = TextComponent::from_json(r#"[1e5, {"text": "", "extra": [3000000000, 1.0, -0]}]"#).unwrap();
+		assert_eq!(component.plain_text(), "1e530000000001.0-0");
+		// Elsewhere, they are the narrowest number that holds them, and null members are left out.
+		let component = TextComponent::from_json(r#"{"text": "x", "page": 3000000000, "scale": 1e5, "font": null}"#).unwrap();
+		assert!(matches!(component.other.get("page"), Some(Tag::Long(3000000000))));
+		assert!(matches!(component.other.get("scale"), Some(Tag::Double(value)) if *value == 100000.0));
+		assert_eq!(component.style.font, None);
+		for invalid in ["null", "[null]", "'single'", "007", "{text: \"unquoted key\"}", "\"a\" \"b\""] {
+			assert!(TextComponent::from_json(invalid).is_err(), "{invalid}");
+		}
+	}
+}
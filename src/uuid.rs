@@ -0,0 +1,138 @@
+// UUIDs, which Minecraft has stored in three ways:
+// an IntArray of four ints since 1.16, a pair of longs named `<key>Most` and `<key>Least` before that,
+// and a hyphenated string in a few places such as `Owner` before 1.16.
+
+use crate::{
+	Map,
+	snbt::parse_uuid,
+	tag::Tag,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid UUID: {0}")]
+pub struct InvalidUuid(pub String);
+
+/// A UUID, which converts between the forms that NBT stores it in.
+/// ```
+/// # use rustnbt::{uuid::Uuid, tag::Tag};
+/// let uuid: Uuid = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6".parse().unwrap();
+/// assert!(matches!(uuid.to_tag(), Tag::IntArray(ints) if ints[0] == 0xf81d4faeu32 as i32));
+/// assert_eq!(Uuid::from_longs(uuid.most(), uuid.least()), uuid);
+/// assert_eq!(uuid.to_string(), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+	/// Converts four ints, most significant first, as stored in an IntArray.
+	pub fn from_ints(ints: [i32; 4]) -> Self {
+		Self(ints.iter().fold(0, |value, &int| value << 32 | int as u32 as u128))
+	}
+
+	pub fn to_ints(self) -> [i32; 4] {
+		[96, 64, 32, 0].map(|shift| (self.0 >> shift) as u32 as i32)
+	}
+
+	/// Converts the most and least significant halves, as stored in `UUIDMost` and `UUIDLeast`.
+	pub fn from_longs(most: i64, least: i64) -> Self {
+		Self((most as u64 as u128) << 64 | least as u64 as u128)
+	}
+
+	/// The most significant half.
+	pub fn most(self) -> i64 {
+		(self.0 >> 64) as i64
+	}
+
+	/// The least significant half.
+	pub fn least(self) -> i64 {
+		self.0 as i64
+	}
+
+	/// Converts an IntArray of four ints or a hyphenated string.
+	pub fn from_tag(tag: &Tag) -> Option<Self> {
+		match tag {
+			Tag::IntArray(ints) => Some(Self::from_ints(ints.as_slice().try_into().ok()?)),
+			Tag::String(text) => text.parse().ok(),
+			_ => None,
+		}
+	}
+
+	/// An IntArray of four ints, as stored since Minecraft 1.16.
+	pub fn to_tag(self) -> Tag {
+		Tag::IntArray(self.to_ints().to_vec())
+	}
+
+	/// Reads `key` in any of its forms: `key` as an IntArray or string, or `<key>Most` and `<key>Least`.
+	pub fn read(map: &Map, key: &str) -> Option<Self> {
+		if let Some(uuid) = map.get(key).and_then(Self::from_tag) {
+			return Some(uuid);
+		}
+		match (map.get(&format!("{key}Most")), map.get(&format!("{key}Least"))) {
+			(Some(&Tag::Long(most)), Some(&Tag::Long(least))) => Some(Self::from_longs(most, least)),
+			_ => None,
+		}
+	}
+
+	/// Writes `key` as an IntArray, removing `<key>Most` and `<key>Least`.
+	pub fn write(self, map: &mut Map, key: &str) {
+		map.remove(&format!("{key}Most"));
+		map.remove(&format!("{key}Least"));
+		map.insert(key.to_owned(), self.to_tag());
+	}
+
+	/// Writes `<key>Most` and `<key>Least`, as stored before Minecraft 1.16, removing `key`.
+	pub fn write_legacy(self, map: &mut Map, key: &str) {
+		map.remove(key);
+		map.insert(format!("{key}Most"), Tag::Long(self.most()));
+		map.insert(format!("{key}Least"), Tag::Long(self.least()));
+	}
+}
+
+impl Display for Uuid {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let hex = format!("{:032x}", self.0);
+		write!(f, "{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+	}
+}
+
+impl FromStr for Uuid {
+	type Err = InvalidUuid;
+
+	/// Parses a hyphenated UUID, such as `f81d4fae-7dec-11d0-a765-00a0c91e6bf6`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_uuid(s).map(Self::from_ints).ok_or_else(|| InvalidUuid(s.to_owned()))
+	}
+}
+
+impl From<Uuid> for Tag {
+	fn from(value: Uuid) -> Self {
+		value.to_tag()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn forms_test() {
+		let uuid = Uuid(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+		assert_eq!(uuid.to_ints(), [0x01234567, 0x89abcdefu32 as i32, 0xfedcba98u32 as i32, 0x76543210]);
+		assert_eq!(Uuid::from_ints(uuid.to_ints()), uuid);
+		assert_eq!((uuid.most(), uuid.least()), (0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210u64 as i64));
+		assert_eq!(uuid.to_string().parse(), Ok(uuid));
+		assert_eq!("0123".parse::<Uuid>(), Err(InvalidUuid("0123".to_owned())));
+		assert_eq!(Uuid::from_tag(&Tag::IntArray(vec![1, 2, 3])), None);
+
+		let mut map = Map::new();
+		uuid.write_legacy(&mut map, "UUID");
+		assert_eq!(Uuid::read(&map, "UUID"), Some(uuid));
+		uuid.write(&mut map, "UUID");
+		assert_eq!(map.len(), 1);
+		assert_eq!(Uuid::read(&map, "UUID"), Some(uuid));
+		map.insert("Owner".to_owned(), Tag::String(uuid.to_string()));
+		assert_eq!(Uuid::read(&map, "Owner"), Some(uuid));
+	}
+}
@@ -0,0 +1,387 @@
+// Save directories, which hold level.dat, player data and the region files of every dimension.
+// https://minecraft.wiki/w/Java_Edition_level_format
+//
+// The overworld is stored in the save directory itself, the Nether in `DIM-1` and the End in `DIM1`.
+// Datapack dimensions are stored in `dimensions/<namespace>/<path>`.
+// Each dimension has up to three kinds of region files: block data in `region`,
+// entities in `entities` (since Minecraft 1.17) and points of interest in `poi`.
+
+use crate::{
+	level::{LevelDat, LevelDatError},
+	maintenance,
+	region::{region_pos, CompressionRegistry, Region, RegionChunk, RegionError, REGION_WIDTH},
+	tag::Tag,
+};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The kind of data that a region file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+	/// Chunks of blocks, in `region`.
+	Region,
+	/// Chunks of entities, in `entities`.
+	Entities,
+	/// Points of interest, such as beds and job sites, in `poi`.
+	Poi,
+}
+
+impl RegionKind {
+	pub const ALL: [RegionKind; 3] = [RegionKind::Region, RegionKind::Entities, RegionKind::Poi];
+
+	/// The name of the directory that the region files are in.
+	pub const fn directory(self) -> &'static str {
+		match self {
+			RegionKind::Region => "region",
+			RegionKind::Entities => "entities",
+			RegionKind::Poi => "poi",
+		}
+	}
+}
+
+/// A dimension of a world.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dimension {
+	/// The dimension's ID, such as `minecraft:the_nether`.
+	pub id: String,
+	/// The directory that holds the dimension's region directories.
+	pub path: PathBuf,
+}
+
+/// A region file of a dimension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionFile {
+	pub path: PathBuf,
+	pub dimension: String,
+	pub kind: RegionKind,
+	/// The position of the region, from the file name `r.<x>.<z>.mca`.
+	pub x: i32,
+	pub z: i32,
+}
+
+impl RegionFile {
+	/// The position in the world of a chunk of this region.
+	pub fn chunk_pos(&self, x: usize, z: usize) -> (i32, i32) {
+		(self.x * REGION_WIDTH as i32 + x as i32, self.z * REGION_WIDTH as i32 + z as i32)
+	}
+}
+
+/// Where a chunk that is being processed is.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkContext<'a> {
+	pub dimension: &'a str,
+	pub kind: RegionKind,
+	/// The position of the chunk in the world.
+	pub x: i32,
+	pub z: i32,
+}
+
+/// How far [World::process] has got, which is reported after every region file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+	pub regions_done: usize,
+	pub regions_total: usize,
+	/// The number of chunks processed so far.
+	pub chunks: usize,
+	/// The number of chunks that were modified so far.
+	pub modified: usize,
+}
+
+/// A region file or chunk that couldn't be read or written.
+#[derive(thiserror::Error, Debug)]
+#[error("{}{}: {error}", path.display(), chunk.map(|(x, z)| format!(" chunk ({x}, {z})")).unwrap_or_default())]
+pub struct ProcessError {
+	pub path: PathBuf,
+	/// The position of the chunk in the world, or `None` if the whole file failed.
+	pub chunk: Option<(i32, i32)>,
+	pub error: RegionError,
+}
+
+/// The result of [World::process].
+#[derive(Debug, Default)]
+pub struct Summary {
+	pub regions: usize,
+	pub chunks: usize,
+	pub modified: usize,
+	/// Files and chunks that were skipped because of an error. Processing continues past them.
+	pub errors: Vec<ProcessError>,
+}
+
+/// A save directory.
+/// ```no_run
+/// # use rustnbt::{world::{World, RegionKind}, tag::Tag};
+/// let world = World::open("saves/New World").unwrap();
+/// // Removes the `Heightmaps` of every chunk, so that Minecraft recalculates them.
+/// let summary = world.process(&[RegionKind::Region], |_, chunk| match chunk {
+///     Tag::Compound(map) => map.remove("Heightmaps").is_some(),
+///     _ => false,
+/// }, |progress| eprintln!("{}/{} regions", progress.regions_done, progress.regions_total)).unwrap();
+/// println!("Modified {} of {} chunks", summary.modified, summary.chunks);
+/// ```
+#[derive(Clone, Debug)]
+pub struct World {
+	root: PathBuf,
+	registry: CompressionRegistry,
+}
+
+impl World {
+	/// Opens a save directory, which must exist.
+	pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+		let root = path.as_ref().to_path_buf();
+		if !root.is_dir() {
+			return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} isn't a directory.", root.display())));
+		}
+		Ok(Self { root, registry: CompressionRegistry::new() })
+	}
+
+	/// Sets the custom compression algorithms that chunks are decoded and encoded with.
+	pub fn with_registry(mut self, registry: CompressionRegistry) -> Self {
+		self.registry = registry;
+		self
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	pub fn level_dat(&self) -> Result<LevelDat, LevelDatError> {
+		LevelDat::read(&mut std::io::BufReader::new(std::fs::File::open(self.root.join("level.dat"))?))
+	}
+
+	/// The dimensions that have at least one region directory, with the vanilla dimensions first.
+	pub fn dimensions(&self) -> std::io::Result<Vec<Dimension>> {
+		let has_regions = |path: &Path| RegionKind::ALL.iter().any(|kind| path.join(kind.directory()).is_dir());
+		let mut dimensions = Vec::new();
+		for (id, directory) in [("minecraft:overworld", ""), ("minecraft:the_nether", "DIM-1"), ("minecraft:the_end", "DIM1")] {
+			let path = self.root.join(directory);
+			if has_regions(&path) {
+				dimensions.push(Dimension { id: id.to_owned(), path });
+			}
+		}
+		let datapack = self.root.join("dimensions");
+		let vanilla = dimensions.len();
+		if datapack.is_dir() {
+			for namespace in std::fs::read_dir(&datapack)? {
+				let namespace = namespace?;
+				if !namespace.file_type()?.is_dir() {
+					continue;
+				}
+				// Dimension paths can have slashes, so every directory with region directories is a dimension.
+				let mut pending = vec![namespace.path()];
+				while let Some(directory) = pending.pop() {
+					if has_regions(&directory) {
+						let path = directory.strip_prefix(namespace.path()).expect("the directory is in the namespace");
+						let path = path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+						let id = format!("{}:{path}", namespace.file_name().to_string_lossy());
+						if !dimensions.iter().any(|dimension: &Dimension| dimension.id == id) {
+							dimensions.push(Dimension { id, path: directory.clone() });
+						}
+					}
+					for entry in std::fs::read_dir(&directory)? {
+						let entry = entry?;
+						let name = entry.file_name();
+						if entry.file_type()?.is_dir() && !RegionKind::ALL.iter().any(|kind| name == kind.directory()) {
+							pending.push(entry.path());
+						}
+					}
+				}
+			}
+		}
+		dimensions[vanilla..].sort_by(|a, b| a.id.cmp(&b.id));
+		Ok(dimensions)
+	}
+
+	/// The region files of a kind in a dimension, ordered by position.
+	pub fn region_files(&self, dimension: &Dimension, kind: RegionKind) -> std::io::Result<Vec<RegionFile>> {
+		let directory = dimension.path.join(kind.directory());
+		if !directory.is_dir() {
+			return Ok(Vec::new());
+		}
+		let mut files = Vec::new();
+		for entry in std::fs::read_dir(directory)? {
+			let entry = entry?;
+			if let Some((x, z)) = region_pos(&entry.file_name().to_string_lossy()) {
+				files.push(RegionFile { path: entry.path(), dimension: dimension.id.clone(), kind, x, z });
+			}
+		}
+		files.sort_by_key(|file| (file.z, file.x));
+		Ok(files)
+	}
+
+	/// The region files of the given kinds in every dimension.
+	pub fn all_region_files(&self, kinds: &[RegionKind]) -> std::io::Result<Vec<RegionFile>> {
+		let mut files = Vec::new();
+		for dimension in self.dimensions()? {
+			for &kind in kinds {
+				files.extend(self.region_files(&dimension, kind)?);
+			}
+		}
+		Ok(files)
+	}
+
+	/// The positions in the world of the chunks in a region file.
+	pub fn chunks(&self, file: &RegionFile) -> Result<Vec<(i32, i32)>, RegionError> {
+		Ok(Region::open(&file.path)?.positions().map(|(x, z)| file.chunk_pos(x, z)).collect())
+	}
+
+	/// Runs `process` over every chunk in region files of the given kinds, with region files processed in parallel.
+	///
+	/// `process` returns whether it modified the chunk. Only modified chunks are encoded again,
+	/// and only region files with modified chunks are written. `progress` is called after every region file.
+	/// Chunks and files that fail to read or write are reported in the [Summary] instead of stopping the other files.
+	/// Broken chunks, as [crate::maintenance::check] finds them, are reported and kept as they are.
+	/// Files with broken chunks whose data can't be found aren't written, since those chunks would be lost.
+	pub fn process<F, P>(&self, kinds: &[RegionKind], process: F, progress: P) -> std::io::Result<Summary>
+	where
+		F: Fn(&ChunkContext, &mut Tag) -> bool + Sync,
+		P: Fn(Progress) + Sync,
+	{
+		let files = self.all_region_files(kinds)?;
+		let regions_done = AtomicUsize::new(0);
+		let chunks = AtomicUsize::new(0);
+		let modified = AtomicUsize::new(0);
+		let errors = files.par_iter().flat_map_iter(|file| {
+			let mut errors = Vec::new();
+			let error = |chunk, error| ProcessError { path: file.path.clone(), chunk, error };
+			match maintenance::read_lenient(&file.path, &self.registry) {
+				Ok((mut region, issues)) => {
+					let mut broken = Vec::new();
+					for issue in issues {
+						let (x, z) = issue.chunk.expect("broken issues belong to chunks");
+						errors.push(error(Some(file.chunk_pos(x, z)), RegionError::Broken { x, z, kind: issue.kind }));
+						if !broken.contains(&(x, z)) {
+							broken.push((x, z));
+						}
+					}
+					let lost = broken.iter().filter(|&&(x, z)| region.chunk(x, z).is_none()).count();
+					let mut changed = false;
+					for (x, z) in region.positions().collect::<Vec<_>>() {
+						if broken.contains(&(x, z)) {
+							continue;
+						}
+						let (chunk_x, chunk_z) = file.chunk_pos(x, z);
+						let chunk = region.chunk(x, z).expect("the position has a chunk");
+						let mut tag = match chunk.decode_with(&self.registry) {
+							Ok(tag) => tag,
+							Err(cause) => {
+								errors.push(error(Some((chunk_x, chunk_z)), cause));
+								continue;
+							}
+						};
+						chunks.fetch_add(1, Ordering::Relaxed);
+						let context = ChunkContext { dimension: &file.dimension, kind: file.kind, x: chunk_x, z: chunk_z };
+						if !process(&context, &mut tag) {
+							continue;
+						}
+						match RegionChunk::encode_with(&tag, chunk.compression.clone(), &self.registry) {
+							Ok(chunk) => {
+								region.set_chunk(x, z, Some(chunk));
+								modified.fetch_add(1, Ordering::Relaxed);
+								changed = true;
+							}
+							Err(cause) => errors.push(error(Some((chunk_x, chunk_z)), cause)),
+						}
+					}
+					if changed {
+						let saved = match lost {
+							0 => region.save(&file.path),
+							lost => Err(RegionError::WouldLoseChunks(lost)),
+						};
+						if let Err(cause) = saved {
+							errors.push(error(None, cause));
+						}
+					}
+				}
+				Err(cause) => errors.push(error(None, cause)),
+			}
+			progress(Progress {
+				regions_done: regions_done.fetch_add(1, Ordering::Relaxed) + 1,
+				regions_total: files.len(),
+				chunks: chunks.load(Ordering::Relaxed),
+				modified: modified.load(Ordering::Relaxed),
+			});
+			errors
+		}).collect();
+		Ok(Summary {
+			regions: files.len(),
+			chunks: chunks.into_inner(),
+			modified: modified.into_inner(),
+			errors,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::region::SECTOR_SIZE;
+	use crate::testutil::{chunk, TempDir};
+
+	#[test]
+	fn process_test() {
+		let root = TempDir::new("world");
+		for (directory, rx, rz) in [("region", 0, 0), ("region", -1, 2), ("DIM-1/region", 0, 0), ("dimensions/test/sky/islands/entities", 3, 3)] {
+			std::fs::create_dir_all(root.join(directory)).unwrap();
+			let mut region = Region::new();
+			region.write_chunk(1, 2, &chunk(rx * 32 + 1, rz * 32 + 2)).unwrap();
+			region.write_chunk(30, 0, &chunk(rx * 32 + 30, rz * 32)).unwrap();
+			region.save(root.join(directory).join(format!("r.{rx}.{rz}.mca"))).unwrap();
+		}
+		std::fs::write(root.join("region/r.5.5.mca"), [1, 2, 3]).unwrap();
+		// Corrupts the data of chunk (1, 2), which is in the second sector after the header.
+		let mut file = std::fs::read(root.join("region/r.0.0.mca")).unwrap();
+		file[SECTOR_SIZE * 3 + 10] ^= 0xff;
+		std::fs::write(root.join("region/r.0.0.mca"), &file).unwrap();
+		// Cuts off the data of chunk (1, 0), which can't be written back.
+		let mut region = Region::new();
+		region.write_chunk(0, 0, &chunk(32, 0)).unwrap();
+		region.write_chunk(1, 0, &chunk(33, 0)).unwrap();
+		let mut file = Vec::new();
+		region.write(&mut file).unwrap();
+		std::fs::write(root.join("region/r.1.0.mca"), &file[..SECTOR_SIZE * 3]).unwrap();
+		std::fs::write(root.join("region/notes.txt"), "not a region").unwrap();
+
+		let world = World::open(root.path()).unwrap();
+		let ids: Vec<_> = world.dimensions().unwrap().into_iter().map(|dimension| dimension.id).collect();
+		assert_eq!(ids, ["minecraft:overworld", "minecraft:the_nether", "test:sky/islands"]);
+		let files = world.all_region_files(&RegionKind::ALL).unwrap();
+		assert_eq!(files.len(), 6);
+		let file = files.iter().find(|file| file.path.ends_with("r.-1.2.mca")).unwrap();
+		assert_eq!(world.chunks(file).unwrap(), [(-2, 64), (-31, 66)]);
+
+		// Marks the overworld chunks whose position matches the region's, leaving the others untouched.
+		let before = std::fs::read(root.join("DIM-1/region/r.0.0.mca")).unwrap();
+		let reports = std::sync::Mutex::new(Vec::new());
+		let summary = world.process(&[RegionKind::Region], |context, tag| {
+			let Tag::Compound(map) = tag else { return false };
+			assert!(matches!(map.get("xPos"), Some(Tag::Int(x)) if *x == context.x));
+			if context.dimension != "minecraft:overworld" {
+				return false;
+			}
+			map.insert("Marked".to_owned(), Tag::Byte(1));
+			true
+		}, |progress| reports.lock().unwrap().push(progress)).unwrap();
+		assert_eq!((summary.regions, summary.chunks, summary.modified), (5, 6, 4));
+		let mut errors: Vec<_> = summary.errors.iter()
+			.map(|error| (error.path.file_name().unwrap().to_str().unwrap(), error.chunk, error.error.to_string()))
+			.collect();
+		errors.sort();
+		assert_eq!(errors.len(), 4);
+		assert!(matches!(&errors[0], ("r.0.0.mca", Some((1, 2)), message) if message.starts_with("Chunk (1, 2): The chunk can't be read")));
+		assert_eq!(errors[1], ("r.1.0.mca", None, "The region file wasn't written, because 1 broken chunks would be lost. Repair it first.".to_owned()));
+		assert_eq!(errors[2], ("r.1.0.mca", Some((33, 0)), "Chunk (1, 0): The chunk goes past the end of the file.".to_owned()));
+		assert!(errors[3].0 == "r.5.5.mca" && errors[3].1.is_none());
+		assert_eq!(reports.lock().unwrap().len(), 5);
+		// The good chunk of a region with a broken one is processed, and the broken one is written back as it was.
+		let report = maintenance::check_file(root.join("region/r.0.0.mca"), &CompressionRegistry::new()).unwrap();
+		assert!(matches!(&report.issues[..], [maintenance::Issue { chunk: Some((1, 2)), kind: maintenance::IssueKind::Unreadable(_) }]));
+		let region = Region::open(root.join("region/r.0.0.mca")).unwrap();
+		assert!(matches!(crate::path::TagPath::root().key("Marked").get(&region.read_chunk(30, 0).unwrap().unwrap()), Ok(Tag::Byte(1))));
+		assert_eq!(std::fs::read(root.join("region/r.1.0.mca")).unwrap().len(), SECTOR_SIZE * 3);
+		assert_eq!(std::fs::read(root.join("DIM-1/region/r.0.0.mca")).unwrap(), before);
+		let region = Region::open(root.join("region/r.-1.2.mca")).unwrap();
+		let tag = region.read_chunk(30, 0).unwrap().unwrap();
+		assert!(matches!(crate::path::TagPath::root().key("Marked").get(&tag), Ok(Tag::Byte(1))));
+	}
+}
@@ -0,0 +1,183 @@
+// Tests for the formatnbt command-line tool. Run with `cargo test --features cli`.
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const PLAYER: &str = r#"{name: "Steve", health: 20.0f, items: [{id: "minecraft:stone", count: 3}]}"#;
+
+/// A directory for the files of one test, which is removed when the test ends.
+struct TempDir(PathBuf);
+
+impl TempDir {
+	fn new(name: &str) -> Self {
+		let path = std::env::temp_dir().join(format!("rustnbt-cli-{name}-{}", std::process::id()));
+		std::fs::create_dir_all(&path).unwrap();
+		Self(path)
+	}
+
+	/// Writes `contents` to the file `name`, returning its path.
+	fn file(&self, name: &str, contents: &str) -> PathBuf {
+		let path = self.0.join(name);
+		std::fs::write(&path, contents).unwrap();
+		path
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+	}
+}
+
+fn formatnbt(args: &[&str], stdin: &str) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_formatnbt"))
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+	child.wait_with_output().unwrap()
+}
+
+fn run(args: &[&str]) -> Output {
+	formatnbt(args, "")
+}
+
+fn path(path: &Path) -> &str {
+	path.to_str().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+	String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+	String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn print_test() {
+	let output = formatnbt(&["print", "--compact"], PLAYER);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output), "{ name : \"Steve\", health : 20.0F, items : [{ id : \"minecraft:stone\", count : 3 }] }\n");
+
+	let output = formatnbt(&["print"], "{a: }");
+	assert_eq!(output.status.code(), Some(2));
+	assert!(stderr(&output).starts_with("error: <stdin>:"));
+	assert_eq!(run(&["print", "missing.nbt"]).status.code(), Some(2));
+}
+
+#[test]
+fn compile_test() {
+	let dir = TempDir::new("compile");
+	let source = dir.file("player.snbt", PLAYER);
+	let binary = dir.0.join("player.dat");
+	let output = run(&["compile", path(&source), "--output", path(&binary), "--name", "Data"]);
+	assert_eq!(output.status.code(), Some(0));
+	// Compressed with gzip by default.
+	assert_eq!(std::fs::read(&binary).unwrap()[..2], [0x1f, 0x8b]);
+	assert_eq!(stdout(&run(&["print", path(&binary), "--compact"])), stdout(&formatnbt(&["print", "--compact"], PLAYER)));
+
+	let output = formatnbt(&["compile", "--compression", "none"], "{a: 1b}");
+	assert_eq!(output.stdout, [10, 0, 0, 1, 0, 1, b'a', 1, 0]);
+}
+
+#[test]
+fn get_set_test() {
+	let dir = TempDir::new("get-set");
+	let file = dir.file("player.snbt", PLAYER);
+	let output = run(&["get", "items[0].id", path(&file)]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output), "\"minecraft:stone\"\n");
+
+	assert_eq!(run(&["set", "items[0].count", "64", path(&file)]).status.code(), Some(0));
+	assert_eq!(stdout(&run(&["get", "items[0].count", path(&file)])), "64\n");
+	// The file is rewritten as SNBT, like it was read.
+	assert!(std::fs::read_to_string(&file).unwrap().starts_with('{'));
+
+	let output = run(&["get", "items[1]", path(&file)]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(stderr(&output).contains("out of bounds"));
+	assert_eq!(run(&["set", "name", "{", path(&file)]).status.code(), Some(2));
+}
+
+#[test]
+fn validate_stats_test() {
+	let dir = TempDir::new("validate");
+	let good = dir.file("good.snbt", PLAYER);
+	let bad = dir.file("bad.snbt", "{a: }");
+	let output = run(&["validate", path(&good)]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(stdout(&output), format!("{}: ok (SNBT, none compression)\n", path(&good)));
+
+	let output = run(&["validate", path(&good), path(&bad)]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).starts_with(&format!("error: {}:", path(&bad))));
+
+	let output = run(&["stats", path(&good)]);
+	assert_eq!(output.status.code(), Some(0));
+	let report = stdout(&output);
+	assert!(report.contains("tags:          7\n"));
+	assert!(report.contains("largest:       items (List, 1 elements)\n"));
+}
+
+#[test]
+fn diff_test() {
+	let dir = TempDir::new("diff");
+	let before = dir.file("before.snbt", "{a: 1, b: [1, 2], c: 1.0d, d: \"same\", e: 5}");
+	let after = dir.file("after.snbt", "{a: 2, b: [1, 2, 3], c: 1.001d, d: \"same\", e: 5}");
+	let output = run(&["diff", path(&before), path(&before)]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(output.stdout.is_empty());
+
+	let output = run(&["diff", path(&before), path(&after), "--color", "never", "--ignore", "a"]);
+	assert_eq!(output.status.code(), Some(1));
+	let expected = format!("--- {}\n+++ {}\n", path(&before), path(&after)) + concat!(
+		"@@ -2,9 +2,10 @@ b[1]\n",
+		" \ta : 1,\n",
+		" \tb : [\n",
+		" \t\t1,\n",
+		"-\t\t2\n",
+		"+\t\t2,\n",
+		"+\t\t3\n",
+		" \t],\n",
+		"-\tc : 1.0D,\n",
+		"+\tc : 1.001D,\n",
+		" \td : \"same\",\n",
+		" \te : 5\n",
+		" }\n",
+	);
+	assert_eq!(stdout(&output), expected);
+
+	let output = run(&["diff", path(&before), path(&after), "--color", "never", "--ignore", "a", "--ignore", "b", "--epsilon", "0.01"]);
+	assert_eq!(output.status.code(), Some(0));
+
+	// Large files with nothing in common.
+	let list = |values: std::ops::Range<i32>| format!("[{}]", values.map(|value| value.to_string()).collect::<Vec<_>>().join(", "));
+	let before = dir.file("large-before.snbt", &list(0..20_000));
+	let after = dir.file("large-after.snbt", &list(20_000..40_000));
+	let output = run(&["diff", path(&before), path(&after), "--color", "never"]);
+	assert_eq!(output.status.code(), Some(1));
+	let report = stdout(&output);
+	assert!(report.contains("\n-\t19999\n") && report.contains("\n+\t39999\n"), "{}", &report[..200]);
+}
+
+#[test]
+fn codegen_test() {
+	let dir = TempDir::new("codegen");
+	let file = dir.file("player.snbt", PLAYER);
+	let output = run(&["codegen", path(&file)]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).contains("pub struct Player {"));
+
+	let output = run(&["infer", path(&file)]);
+	assert_eq!(output.status.code(), Some(0));
+	let schema = dir.file("schema.snbt", &stdout(&output));
+	let output = run(&["codegen", "--schema", "--name", "Save", path(&schema)]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).contains("pub struct Save {"));
+}